@@ -0,0 +1,76 @@
+#[macro_use]
+extern crate criterion;
+#[macro_use]
+extern crate xml_rpc;
+
+use criterion::Criterion;
+use std::hint::black_box;
+use xml_rpc::{stream, Call, Encoder, ParseOptions, Response, ToXml, Value};
+
+/// A ROS master `getSystemState` reply listing publishers, subscribers and services.
+fn topic_list() -> Response {
+    let entries = |kind: &str| {
+        Value::Array(
+            (0..200)
+                .map(|i| {
+                    xmlrpc_value!([
+                        (format!("/robot/{}/topic_{}", kind, i)),
+                        [(format!("/node_{}", i)), "/rosout", "/tf <&> listener"]
+                    ])
+                })
+                .collect(),
+        )
+    };
+    Ok(vec![xmlrpc_value!([
+        1,
+        "current system state",
+        [(entries("pub")), (entries("sub")), (entries("srv"))]
+    ])])
+}
+
+fn large_base64() -> Call {
+    Call {
+        name: "upload".into(),
+        params: vec![Value::Base64((0..=255).cycle().take(4 << 20).collect())],
+    }
+}
+
+fn deep_struct() -> Response {
+    let mut value = Value::Int(0);
+    for depth in 0..50 {
+        value = xmlrpc_value!({
+            "depth": depth,
+            "name": "level with \"quotes\" & ampersands",
+            "ratio": 0.5,
+            "flag": true,
+            "child": value,
+        });
+    }
+    Ok(vec![value])
+}
+
+fn bench_payload<T: ToXml>(c: &mut Criterion, name: &str, payload: &T, call: bool) {
+    let mut encoder = Encoder::new();
+    c.bench_function(&format!("encode {}", name), |b| {
+        b.iter(|| black_box(encoder.encode(payload).len()))
+    });
+    let data = payload.to_xml();
+    c.bench_function(&format!("decode {}", name), |b| {
+        b.iter(|| {
+            let mut reader =
+                stream::ParamReader::with_options(data.as_bytes(), ParseOptions::default())
+                    .unwrap();
+            assert_eq!(call, matches!(*reader.header(), stream::Header::Call(_)));
+            black_box(reader.collect_params().unwrap())
+        })
+    });
+}
+
+fn codec(c: &mut Criterion) {
+    bench_payload(c, "topic list", &topic_list(), false);
+    bench_payload(c, "large base64", &large_base64(), true);
+    bench_payload(c, "deep struct", &deep_struct(), false);
+}
+
+criterion_group!(benches, codec);
+criterion_main!(benches);
@@ -0,0 +1,17 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xml_rpc::{parse, ParseOptions, ToXml};
+
+fuzz_target!(|data: &[u8]| {
+    let lenient = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    for options in &[ParseOptions::default(), lenient] {
+        if let Ok(call) = parse::call_with(data, options) {
+            // Whatever is read must be writable again.
+            let _ = call.to_xml();
+        }
+    }
+});
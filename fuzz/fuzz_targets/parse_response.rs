@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xml_rpc::{from_value, parse, ParseOptions, ToXml, Value};
+
+fuzz_target!(|data: &[u8]| {
+    let lenient = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    for options in &[ParseOptions::default(), lenient] {
+        if let Ok(response) = parse::response_with(data, options) {
+            let _ = response.to_xml();
+            for value in response.unwrap_or_default() {
+                let _ = from_value::<i64>(value.clone());
+                let _ = from_value::<f64>(value.clone());
+                let _ = from_value::<Value>(value);
+            }
+        }
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use xml_rpc::datetime::DateTime;
+use xml_rpc::{parse, ToXml, Value};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(value) = parse::xml(data) {
+        let _ = value.to_xml();
+        let _ = value.to_string();
+        if let Value::DateTime(ref v) = value {
+            let _ = DateTime::parse(v).map(|v| v.to_string());
+        }
+    }
+});
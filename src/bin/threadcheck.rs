@@ -32,27 +32,19 @@ fn main() {
     std::thread::sleep(Duration::from_secs(1));
 
     let t1 = std::thread::spawn(|| {
-        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "foo", ())
-            .unwrap()
-            .unwrap();
+        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "foo", ()).unwrap();
     });
 
     let t2 = std::thread::spawn(|| {
-        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "bar", ())
-            .unwrap()
-            .unwrap();
+        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "bar", ()).unwrap();
     });
 
     let t3 = std::thread::spawn(|| {
-        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "foo", ())
-            .unwrap()
-            .unwrap();
+        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "foo", ()).unwrap();
     });
 
     let t4 = std::thread::spawn(|| {
-        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "bar", ())
-            .unwrap()
-            .unwrap();
+        call::<_, _, ()>(&"http://127.0.0.1:5000".parse().unwrap(), "bar", ()).unwrap();
     });
 
     t1.join().unwrap();
@@ -0,0 +1,59 @@
+extern crate serde_json;
+extern crate xml_rpc;
+
+use std::convert::TryFrom;
+use std::{env, process};
+use xml_rpc::{Client, Value};
+
+const USAGE: &str = "Usage: xml-rpc-call <url> <method> [param...]
+
+Each param is JSON, like 3, true, [1, 2] or {\"a\": \"b\"}, and anything that isn't is a string.
+Datetimes and base64 are written as {\"$dateTime.iso8601\": \"...\"} and {\"$base64\": \"...\"}.";
+
+fn parse_param(arg: &str) -> Result<Value, String> {
+    match serde_json::from_str::<serde_json::Value>(arg) {
+        Ok(json) => Value::try_from(json).map_err(|err| format!("Invalid param {}: {}", arg, err)),
+        Err(_) => Ok(Value::String(arg.into())),
+    }
+}
+
+fn print_value(value: Value) {
+    match serde_json::Value::try_from(value.clone()) {
+        Ok(json) => println!("{:#}", json),
+        // Like non-finite doubles, which JSON can't hold.
+        Err(_) => println!("{}", value),
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 {
+        return Err(USAGE.into());
+    }
+    let uri = args[0]
+        .parse()
+        .map_err(|err| format!("Invalid URL {}: {}", args[0], err))?;
+    let params = args[2..]
+        .iter()
+        .map(|arg| parse_param(arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    let response = Client::new()
+        .and_then(|mut client| client.call_value(&uri, args[1].as_str(), params))
+        .map_err(|err| format!("Call failed: {}", err))?;
+    match response {
+        Ok(params) => {
+            for param in params {
+                print_value(param);
+            }
+            Ok(())
+        }
+        Err(fault) => Err(format!("Fault {}: {}", fault.code, fault.message)),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
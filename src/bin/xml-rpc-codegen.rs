@@ -0,0 +1,36 @@
+extern crate xml_rpc;
+
+use std::{env, process};
+use xml_rpc::{codegen, Client};
+
+const USAGE: &str = "Usage: xml-rpc-codegen <url> > module.rs
+
+Writes a Rust module with a function for each method the server lists in system.listMethods.";
+
+fn run(args: &[String]) -> Result<String, String> {
+    if args.len() != 1 {
+        return Err(USAGE.into());
+    }
+    let uri = args[0]
+        .parse()
+        .map_err(|err| format!("Invalid URL {}: {}", args[0], err))?;
+    let methods = Client::new()
+        .and_then(|mut client| codegen::introspect(&mut client, &uri))
+        .map_err(|err| format!("Introspection failed: {}", err))?;
+    Ok(format!(
+        "// Generated by xml-rpc-codegen from {}\n\n{}",
+        uri,
+        codegen::generate(&methods)
+    ))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => print!("{}", code),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
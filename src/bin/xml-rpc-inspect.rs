@@ -0,0 +1,174 @@
+extern crate xml_rpc;
+
+use std::io::{self, Read};
+use std::{env, fs, process};
+use xml_rpc::datetime::DateTime;
+use xml_rpc::{parse, ParseOptions, Value, ValueType};
+
+const USAGE: &str = "Usage: xml-rpc-inspect [file...]
+
+Reads captured XML-RPC calls and responses from the files, or from stdin without any, and prints
+the values in them with their types, followed by anything peers might read differently. HTTP
+headers in front of a payload, like in a capture of the whole request, are skipped.";
+
+/// The payload of a capture, after any HTTP request or status line and headers.
+fn body(data: &[u8]) -> &[u8] {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    if data[start..].starts_with(b"<") || data[start..].starts_with("\u{feff}".as_bytes()) {
+        return data;
+    }
+    [&b"\r\n\r\n"[..], &b"\n\n"[..]]
+        .iter()
+        .filter_map(|separator| {
+            data.windows(separator.len())
+                .position(|window| window == *separator)
+                .map(|end| end + separator.len())
+        })
+        .min()
+        .map_or(data, |end| &data[end..])
+}
+
+fn scalar(value: &Value) -> String {
+    match *value {
+        Value::DateTime(ref v) | Value::Base64Text(ref v) => v.clone(),
+        Value::Base64(ref v) => format!("({} bytes)", v.len()),
+        _ => value.to_string(),
+    }
+}
+
+struct Inspector {
+    warnings: Vec<String>,
+}
+
+impl Inspector {
+    fn value(&mut self, path: &str, label: &str, value: &Value, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let name = ValueType::of(value).name();
+        match *value {
+            Value::Array(ref items) => {
+                println!("{}{}: {} ({} items)", indent, label, name, items.len());
+                for (index, item) in items.iter().enumerate() {
+                    let path = format!("{}[{}]", path, index);
+                    self.value(&path, &format!("[{}]", index), item, depth + 1);
+                }
+            }
+            Value::Struct(ref members) => {
+                println!("{}{}: {} ({} members)", indent, label, name, members.len());
+                let mut members = members.iter().collect::<Vec<_>>();
+                members.sort_by(|a, b| a.0.cmp(b.0));
+                for (key, member) in members {
+                    let path = format!("{}.{}", path, key);
+                    self.value(&path, &format!("{:?}", key), member, depth + 1);
+                }
+            }
+            _ => {
+                println!("{}{}: {} {}", indent, label, name, scalar(value));
+                self.check(path, value);
+            }
+        }
+    }
+
+    fn check(&mut self, path: &str, value: &Value) {
+        let warning = match *value {
+            Value::DateTime(ref v) => match DateTime::parse(v) {
+                Ok(ref datetime) if datetime.is_naive() => {
+                    "has no timezone, so peers may read it in different ones"
+                }
+                Ok(_) => return,
+                Err(_) => "isn't a valid dateTime.iso8601",
+            },
+            Value::I8(_) | Value::Nil => "is an extension that some peers reject",
+            Value::Double(v) if !v.is_finite() => "isn't finite, which the spec doesn't allow",
+            _ => return,
+        };
+        self.warnings.push(format!("{} {}", path, warning));
+    }
+
+    fn params(&mut self, params: &[Value]) {
+        for (index, param) in params.iter().enumerate() {
+            let label = format!("param {}", index);
+            self.value(&format!("params[{}]", index), &label, param, 1);
+        }
+    }
+}
+
+fn inspect(source: &str, data: &[u8]) -> Result<(), String> {
+    let data = body(data);
+    let options = ParseOptions {
+        lenient: true,
+        ..ParseOptions::default()
+    };
+    let is_response = data.windows(15).any(|window| window == b"<methodResponse");
+    let mut inspector = Inspector {
+        warnings: Vec::new(),
+    };
+    let warnings = if is_response {
+        let (response, warnings) =
+            parse::response_with_warnings(data, &options).map_err(|err| err.to_string())?;
+        match response {
+            Ok(params) => {
+                println!("{}: response with {} params", source, params.len());
+                inspector.params(&params);
+            }
+            Err(fault) => println!("{}: fault {}: {:?}", source, fault.code, fault.message),
+        }
+        warnings
+    } else {
+        let (call, warnings) =
+            parse::call_with_warnings(data, &options).map_err(|err| err.to_string())?;
+        println!(
+            "{}: call to {:?} with {} params",
+            source,
+            call.name,
+            call.params.len()
+        );
+        inspector.params(&call.params);
+        warnings
+    };
+    for warning in warnings {
+        println!("  warning: {}", warning);
+    }
+    for warning in inspector.warnings {
+        println!("  warning: {}", warning);
+    }
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return Err(USAGE.into());
+    }
+    let mut failed = false;
+    let mut report = |source: &str, data: &[u8]| {
+        if let Err(err) = inspect(source, data) {
+            println!("{}: invalid: {}", source, err);
+            failed = true;
+        }
+    };
+    if args.is_empty() {
+        let mut data = Vec::new();
+        io::stdin()
+            .read_to_end(&mut data)
+            .map_err(|err| format!("Failed to read stdin: {}", err))?;
+        report("stdin", &data);
+    }
+    for path in args {
+        let data = fs::read(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+        report(path, &data);
+    }
+    if failed {
+        return Err("Some payloads are invalid".into());
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,85 @@
+extern crate serde;
+extern crate serde_json;
+extern crate xml_rpc;
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+use std::{env, fs, process, thread};
+use xml_rpc::{Fault, Server, Value};
+
+const USAGE: &str = "Usage: xml-rpc-mock <config.json> [address]
+
+Serves canned responses on the address, 127.0.0.1:8080 by default. The config maps method names
+to a JSON result or a fault, optionally answered after a delay:
+
+{
+  \"getVersion\": { \"result\": \"1.0\" },
+  \"upload\": { \"fault\": { \"code\": 403, \"message\": \"Read only\" } },
+  \"slowSum\": { \"result\": 42, \"delay_ms\": 500 }
+}";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Method {
+    result: Option<serde_json::Value>,
+    fault: Option<FaultConfig>,
+    #[serde(default)]
+    delay_ms: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FaultConfig {
+    code: i32,
+    message: String,
+}
+
+fn register(server: &mut Server, name: String, method: Method) -> Result<(), String> {
+    let response = match (method.result, method.fault) {
+        (Some(result), None) => Ok(vec![Value::try_from(result)
+            .map_err(|err| format!("Invalid result of {}: {}", name, err))?]),
+        (None, Some(fault)) => Err(Fault::new(fault.code, fault.message)),
+        _ => return Err(format!("{} needs either a result or a fault", name)),
+    };
+    let delay = Duration::from_millis(method.delay_ms);
+    server.register_value(name, move |_| {
+        thread::sleep(delay);
+        response.clone()
+    });
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let address = match args.get(1) {
+        Some(address) => address
+            .parse()
+            .map_err(|err| format!("Invalid address {}: {}", address, err))?,
+        None => ([127, 0, 0, 1], 8080).into(),
+    };
+    let config =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    let methods: HashMap<String, Method> =
+        serde_json::from_str(&config).map_err(|err| format!("Invalid config {}: {}", path, err))?;
+
+    let mut server = Server::new();
+    for (name, method) in methods {
+        register(&mut server, name, method)?;
+    }
+    let bound_server = server
+        .bind(&address)
+        .map_err(|err| format!("Failed to bind {}: {}", address, err))?;
+    println!("Serving on {}", bound_server.local_addr());
+    bound_server.run();
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
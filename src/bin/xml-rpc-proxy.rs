@@ -0,0 +1,126 @@
+extern crate serde;
+extern crate serde_json;
+extern crate xml_rpc;
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::{env, fs, process};
+use xml_rpc::proxy::Proxy;
+use xml_rpc::{Fault, Server, Value};
+
+const USAGE: &str = "Usage: xml-rpc-proxy <upstream url> [address] [rules.json]
+
+Forwards every call made on the address, 127.0.0.1:8080 by default, to the upstream server, and
+prints each call and its response. The optional rules rewrite calls to some methods, by renaming
+them, and replace what upstream responds with a JSON result or a fault:
+
+{
+  \"getVersion\": { \"result\": \"2.0\" },
+  \"oldName\": { \"rename\": \"newName\" },
+  \"upload\": { \"fault\": { \"code\": 403, \"message\": \"Read only\" } }
+}";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rule {
+    rename: Option<String>,
+    result: Option<serde_json::Value>,
+    fault: Option<FaultConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FaultConfig {
+    code: i32,
+    message: String,
+}
+
+fn add_rules(proxy: &mut Proxy, path: &str) -> Result<(), String> {
+    let config =
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    let rules: HashMap<String, Rule> =
+        serde_json::from_str(&config).map_err(|err| format!("Invalid rules {}: {}", path, err))?;
+    let mut renames = HashMap::new();
+    let mut responses = HashMap::new();
+    for (name, rule) in rules {
+        let response = match (rule.result, rule.fault) {
+            (Some(result), None) => Some(Ok(vec![Value::try_from(result)
+                .map_err(|err| format!("Invalid result of {}: {}", name, err))?])),
+            (None, Some(fault)) => Some(Err(Fault::new(fault.code, fault.message))),
+            (None, None) => None,
+            _ => return Err(format!("{} can't have both a result and a fault", name)),
+        };
+        // Responses are looked up by the name calls are forwarded under.
+        let forwarded = rule.rename.clone().unwrap_or_else(|| name.clone());
+        if let Some(response) = response {
+            responses.insert(forwarded, response);
+        }
+        if let Some(rename) = rule.rename {
+            renames.insert(name, rename);
+        }
+    }
+    proxy.rewrite_calls(move |call| {
+        if let Some(rename) = renames.get(&call.name) {
+            call.name = rename.clone();
+        }
+    });
+    proxy.rewrite_responses(move |call, response| {
+        if let Some(replacement) = responses.get(&call.name) {
+            *response = replacement.clone();
+        }
+    });
+    Ok(())
+}
+
+fn print_exchange(name: &str, params: &[Value], response: &xml_rpc::Response) {
+    let params = params
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    println!("-> {}({})", name, params);
+    match *response {
+        Ok(ref params) => {
+            for param in params {
+                println!("<- {}", param);
+            }
+        }
+        Err(ref fault) => println!("<- fault {}: {:?}", fault.code, fault.message),
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let upstream = args.first().ok_or(USAGE)?;
+    let upstream = upstream
+        .parse()
+        .map_err(|err| format!("Invalid URL {}: {}", upstream, err))?;
+    let address = match args.get(1) {
+        Some(address) => address
+            .parse()
+            .map_err(|err| format!("Invalid address {}: {}", address, err))?,
+        None => ([127, 0, 0, 1], 8080).into(),
+    };
+
+    let mut proxy = Proxy::new(upstream).map_err(|err| err.to_string())?;
+    if let Some(path) = args.get(2) {
+        add_rules(&mut proxy, path)?;
+    }
+    proxy.on_exchange(|call, response| print_exchange(&call.name, &call.params, response));
+    let mut server = Server::new();
+    proxy.serve(&mut server);
+    let bound_server = server
+        .bind(&address)
+        .map_err(|err| format!("Failed to bind {}: {}", address, err))?;
+    println!("Proxying on {}", bound_server.local_addr());
+    bound_server.run();
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
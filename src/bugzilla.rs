@@ -0,0 +1,236 @@
+//! Typed calls for the Bugzilla WebService API, served at `/xmlrpc.cgi`.
+//!
+//! Every Bugzilla method takes a single struct, and authenticates with members of that
+//! struct: `Bugzilla_api_key` with API keys, or `Bugzilla_token` with the token `User.login`
+//! hands out. `Bugzilla` merges these into each call's struct.
+
+use super::client::CallResultExt;
+use super::error::Result;
+use super::xmlfmt::{
+    to_value_with, DeserializeOptions, Fault, IntegerPolicy, OptionRepresentation,
+    SerializeOptions, Value,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std;
+use {datetime, Client, Secret, Url};
+
+/// A bug as returned by `Bug.get` and `Bug.search`.
+///
+/// Members other than the ID are missing when left out of `include_fields`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Bug {
+    pub id: i32,
+    #[serde(default)]
+    pub alias: Option<Vec<String>>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub resolution: Option<String>,
+    #[serde(default)]
+    pub is_open: Option<bool>,
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub component: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub assigned_to: Option<String>,
+    #[serde(default)]
+    pub creator: Option<String>,
+    #[serde(default)]
+    pub cc: Option<Vec<String>>,
+    #[serde(default)]
+    pub keywords: Option<Vec<String>>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub whiteboard: Option<String>,
+    #[serde(default, with = "datetime::option")]
+    pub creation_time: Option<String>,
+    #[serde(default, with = "datetime::option")]
+    pub last_change_time: Option<String>,
+}
+
+/// Criteria of `Bug.search`, all of which have to match, with members left out when `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct BugSearch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<Vec<String>>,
+    /// Matches bugs whose summary contains this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Matches bugs changed at or after this time.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "datetime::option::serialize"
+    )]
+    pub last_change_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i32>,
+    /// Bug members to return, like `["id", "summary"]`, instead of all of them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_fields: Option<Vec<String>>,
+}
+
+/// A bug to file with `Bug.create`, with members left out when `None`.
+///
+/// Installations may require more members, and default others.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct NewBug {
+    pub product: String,
+    pub component: String,
+    pub summary: String,
+    pub version: String,
+    /// The first comment on the bug.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub op_sys: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+struct Login<'a> {
+    login: &'a str,
+    password: &'a str,
+}
+
+#[derive(Deserialize)]
+struct LoggedIn {
+    id: i32,
+    token: String,
+}
+
+#[derive(Serialize)]
+struct Ids<'a> {
+    ids: &'a [i32],
+}
+
+#[derive(Deserialize)]
+struct Bugs {
+    bugs: Vec<Bug>,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: i32,
+}
+
+pub struct Bugzilla {
+    client: Client,
+    uri: Url,
+    serialize_options: SerializeOptions,
+    auth: Option<(&'static str, Secret)>,
+}
+
+impl Bugzilla {
+    pub fn new(uri: Url) -> Result<Bugzilla> {
+        let serialize_options = SerializeOptions {
+            integers: IntegerPolicy::IntWhenFits,
+            options: OptionRepresentation::Omit,
+            ..SerializeOptions::default()
+        };
+        let mut client = Client::new()?;
+        client.set_deserialize_options(DeserializeOptions {
+            options: OptionRepresentation::Omit,
+            ..DeserializeOptions::default()
+        });
+        Ok(Bugzilla {
+            client,
+            uri,
+            serialize_options,
+            auth: None,
+        })
+    }
+
+    pub fn set_api_key<T: Into<String>>(&mut self, api_key: T) {
+        self.auth = Some(("Bugzilla_api_key", Secret::new(api_key.into())));
+    }
+
+    pub fn set_token<T: Into<String>>(&mut self, token: T) {
+        self.auth = Some(("Bugzilla_token", Secret::new(token.into())));
+    }
+
+    /// Calls `User.login` and authenticates later calls with the token it returns.
+    ///
+    /// Returns the user's ID.
+    pub fn login(
+        &mut self,
+        login: &str,
+        password: &str,
+    ) -> Result<std::result::Result<i32, Fault>> {
+        self.auth = None;
+        let response: std::result::Result<LoggedIn, Fault> =
+            self.call("User.login", &Login { login, password })?;
+        Ok(response.map(|logged_in| {
+            self.set_token(logged_in.token);
+            logged_in.id
+        }))
+    }
+
+    pub fn get_bugs(&mut self, ids: &[i32]) -> Result<std::result::Result<Vec<Bug>, Fault>> {
+        let response: std::result::Result<Bugs, Fault> = self.call("Bug.get", &Ids { ids })?;
+        Ok(response.map(|bugs| bugs.bugs))
+    }
+
+    pub fn search(&mut self, search: &BugSearch) -> Result<std::result::Result<Vec<Bug>, Fault>> {
+        let response: std::result::Result<Bugs, Fault> = self.call("Bug.search", search)?;
+        Ok(response.map(|bugs| bugs.bugs))
+    }
+
+    /// Returns the new bug's ID.
+    pub fn create(&mut self, bug: &NewBug) -> Result<std::result::Result<i32, Fault>> {
+        let response: std::result::Result<Created, Fault> = self.call("Bug.create", bug)?;
+        Ok(response.map(|created| created.id))
+    }
+
+    /// Calls any method, passing `params` as its struct along with the credentials.
+    pub fn call<Treq, Tres>(
+        &mut self,
+        name: &str,
+        params: &Treq,
+    ) -> Result<std::result::Result<Tres, Fault>>
+    where
+        Treq: Serialize,
+        Tres: DeserializeOwned,
+    {
+        let mut params = to_value_with(params, &self.serialize_options)?;
+        if let (Value::Struct(members), Some((key, value))) = (&mut params, &self.auth) {
+            members.insert(key.to_string(), Value::String(value.expose().clone()));
+        }
+        self.client.call(&self.uri, name, (params,)).nested()
+    }
+}
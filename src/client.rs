@@ -1,12 +1,42 @@
-use super::error::{Result, ResultExt};
-use super::xmlfmt::{from_params, into_params, parse, Call, Fault, Params, Response};
+use super::error::{Error, ErrorKind, Result, ResultExt};
+use super::xmlfmt::{
+    charset, from_value_with, into_params_with, multicall, parse, Call, DeserializeOptions, Fault,
+    IntoParams, MethodNamePolicy, MulticallResponse, Params, ParseOptions, Profile, Response,
+    SerializeOptions, Value, WriteOptions,
+};
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hyper::client::pool::Pool;
+use hyper::net::{HttpConnector, NetworkConnector, NetworkStream};
 use hyper::{self, Client as HyperClient};
-use serde::{Deserialize, Serialize};
+use runtime::{Blocking, Runtime};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use std;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+#[cfg(feature = "async")]
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::{Duration, Instant};
+use trace::TraceContext;
 use Url;
 
-use hyper::header::Headers;
-header! { (ContentType, "ContentType") => [String] }
+use hyper::header::{
+    qitem, AcceptEncoding, Authorization, Bearer, ContentEncoding, Encoding, Headers,
+};
+use hyper::status::StatusCode;
+header! { (ContentType, "Content-Type") => [String] }
 
 pub fn call_value<Tkey>(uri: &Url, name: Tkey, params: Params) -> Result<Response>
 where
@@ -15,54 +45,757 @@ where
     Client::new()?.call_value(uri, name, params)
 }
 
-pub fn call<'a, Tkey, Treq, Tres>(
-    uri: &Url,
-    name: Tkey,
-    req: Treq,
-) -> Result<std::result::Result<Tres, Fault>>
+pub fn call<'a, Tkey, Treq, Tres>(uri: &Url, name: Tkey, req: Treq) -> CallResult<Tres>
 where
     Tkey: Into<String>,
-    Treq: Serialize,
+    Treq: IntoParams,
     Tres: Deserialize<'a>,
 {
     Client::new()?.call(uri, name, req)
 }
 
+pub type CallResult<T> = std::result::Result<T, CallError>;
+
+/// Why a typed call failed.
+#[derive(Debug)]
+pub enum CallError {
+    /// The server answered with a fault.
+    Fault(Fault),
+    /// The call didn't reach the server, or its response didn't make it back.
+    Transport(Error),
+    /// The params couldn't be encoded, or the response couldn't be read into the expected type.
+    Decode(Error),
+}
+
+impl CallError {
+    pub fn fault(&self) -> Option<&Fault> {
+        match *self {
+            CallError::Fault(ref fault) => Some(fault),
+            _ => None,
+        }
+    }
+}
+
+/// Sorts errors by `Error::is_transport`.
+impl From<Error> for CallError {
+    fn from(err: Error) -> CallError {
+        match err {
+            Error(ErrorKind::Fault(fault), _) => CallError::Fault(fault),
+            err if err.is_transport() => CallError::Transport(err),
+            err => CallError::Decode(err),
+        }
+    }
+}
+
+impl From<Fault> for CallError {
+    fn from(fault: Fault) -> CallError {
+        CallError::Fault(fault)
+    }
+}
+
+/// Faults become `ErrorKind::Fault`.
+impl From<CallError> for Error {
+    fn from(err: CallError) -> Error {
+        match err {
+            CallError::Fault(fault) => fault.into(),
+            CallError::Transport(err) | CallError::Decode(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CallError::Fault(ref fault) => write!(f, "Server returned a fault: {}", fault),
+            CallError::Transport(ref err) | CallError::Decode(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            CallError::Fault(_) => None,
+            CallError::Transport(ref err) | CallError::Decode(ref err) => Some(err),
+        }
+    }
+}
+
+/// Turns a typed call's result back into the nested shape calls used to return, with faults
+/// separate from all other errors.
+pub trait CallResultExt<T> {
+    fn nested(self) -> Result<std::result::Result<T, Fault>>;
+}
+
+impl<T> CallResultExt<T> for CallResult<T> {
+    fn nested(self) -> Result<std::result::Result<T, Fault>> {
+        match self {
+            Ok(value) => Ok(Ok(value)),
+            Err(CallError::Fault(fault)) => Ok(Err(fault)),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
 pub struct Client {
-    client: HyperClient,
+    transport: Transport,
+    serialize_options: SerializeOptions,
+    deserialize_options: DeserializeOptions,
+    parse_options: ParseOptions,
+    write_options: WriteOptions,
+    method_names: MethodNamePolicy,
+    compression: bool,
+    request_compression: bool,
+    uncompressed_hosts: HashSet<(String, u16)>,
+    content_type: String,
+    cached_methods: HashSet<String>,
+    cache: ResponseCache,
+    fallbacks: HashMap<Url, Vec<Url>>,
+    last_endpoint: Option<Url>,
+}
+
+/// Supplies access tokens, sent as `Authorization: Bearer` headers with every call.
+pub trait TokenSource: Send + Sync {
+    fn token(&self) -> Result<String>;
+
+    /// Called when a server rejects the token with `401 Unauthorized`, before the call is
+    /// retried once with a new one.
+    fn invalidate(&self) {}
+}
+
+/// How connections to a single host have been used, from `Client::pool_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Connections open right now, whether in use or idle in the pool.
+    pub open: usize,
+    /// Connections opened so far.
+    pub handshakes: u64,
+    /// Requests sent so far, including retries and calls to fallbacks.
+    pub requests: u64,
+}
+
+impl PoolStats {
+    /// The share of requests sent over a connection that was already open.
+    pub fn reuse_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.requests.saturating_sub(self.handshakes) as f64 / self.requests as f64
+    }
+}
+
+/// Caps how many calls run at once, across all clients and threads sharing a clone of it.
+#[derive(Clone, Debug)]
+pub struct ConcurrencyLimit {
+    running: Arc<(Mutex<usize>, Condvar)>,
+    /// Wakes calls waiting with `acquire_async`, which the condvar can't.
+    #[cfg(feature = "async")]
+    freed_async: Arc<::event_listener::Event>,
+    max: usize,
+    timeout: Option<Duration>,
+}
+
+impl ConcurrencyLimit {
+    /// Calls over the limit wait for a running one to finish, for at most `timeout` if given,
+    /// and then fail with `ConcurrencyLimitTimeout`. A limit of 0 lets one call run at a time.
+    pub fn new(max: usize, timeout: Option<Duration>) -> ConcurrencyLimit {
+        ConcurrencyLimit {
+            running: Arc::new((Mutex::new(0), Condvar::new())),
+            #[cfg(feature = "async")]
+            freed_async: Arc::new(::event_listener::Event::new()),
+            max: max.max(1),
+            timeout,
+        }
+    }
+
+    /// Calls running right now.
+    pub fn running(&self) -> usize {
+        *self.running.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Blocks until there's room under the limit, and holds it until the permit is dropped.
+    pub fn acquire(&self) -> Result<Permit> {
+        let (ref lock, ref freed) = *self.running;
+        let running = lock.lock().unwrap_or_else(|err| err.into_inner());
+        let mut running = match self.timeout {
+            Some(timeout) => {
+                let (running, wait) = freed
+                    .wait_timeout_while(running, timeout, |running| *running >= self.max)
+                    .unwrap_or_else(|err| err.into_inner());
+                if wait.timed_out() {
+                    bail!(ErrorKind::ConcurrencyLimitTimeout(self.max));
+                }
+                running
+            }
+            None => freed
+                .wait_while(running, |running| *running >= self.max)
+                .unwrap_or_else(|err| err.into_inner()),
+        };
+        *running += 1;
+        Ok(Permit(self.clone()))
+    }
+
+    /// Like `acquire`, but waits without blocking the thread.
+    #[cfg(feature = "async")]
+    pub fn acquire_async(&self) -> Acquire {
+        Acquire {
+            limit: self.clone(),
+            listener: None,
+            timer: match self.timeout {
+                Some(timeout) => ::async_io::Timer::after(timeout),
+                None => ::async_io::Timer::never(),
+            },
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn try_acquire(&self) -> Option<Permit> {
+        let mut running = self.running.0.lock().unwrap_or_else(|err| err.into_inner());
+        if *running >= self.max {
+            return None;
+        }
+        *running += 1;
+        Some(Permit(self.clone()))
+    }
+}
+
+/// A place under a `ConcurrencyLimit`, given back when dropped.
+pub struct Permit(ConcurrencyLimit);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        let (ref lock, ref freed) = *self.0.running;
+        *lock.lock().unwrap_or_else(|err| err.into_inner()) -= 1;
+        freed.notify_one();
+        #[cfg(feature = "async")]
+        self.0.freed_async.notify_additional(1);
+    }
+}
+
+/// A future of a place under a `ConcurrencyLimit`, from `ConcurrencyLimit::acquire_async`.
+#[cfg(feature = "async")]
+pub struct Acquire {
+    limit: ConcurrencyLimit,
+    listener: Option<::event_listener::EventListener>,
+    timer: ::async_io::Timer,
+}
+
+#[cfg(feature = "async")]
+impl Future for Acquire {
+    type Output = Result<Permit>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<Permit>> {
+        let acquire = self.get_mut();
+        loop {
+            if let Some(permit) = acquire.limit.try_acquire() {
+                return Poll::Ready(Ok(permit));
+            }
+            // Listening before checking again, so a permit dropped in between isn't missed.
+            match acquire.listener {
+                Some(ref mut listener) => {
+                    if Pin::new(listener).poll(cx).is_pending() {
+                        break;
+                    }
+                    acquire.listener = None;
+                }
+                None => acquire.listener = Some(acquire.limit.freed_async.listen()),
+            }
+        }
+        if Pin::new(&mut acquire.timer).poll(cx).is_ready() {
+            return Poll::Ready(Err(
+                ErrorKind::ConcurrencyLimitTimeout(acquire.limit.max).into()
+            ));
+        }
+        Poll::Pending
+    }
+}
+
+/// Everything calls need to be sent, shared with hedged calls' threads.
+#[derive(Clone)]
+struct Transport {
+    client: Arc<HyperClient>,
+    pre_send: Option<PreSendHook>,
+    tokens: Option<Arc<dyn TokenSource>>,
+    stats: pool::Stats,
+    limit: Option<ConcurrencyLimit>,
+}
+
+type PreSendHook = Arc<dyn Fn(&Url, &[u8], &mut Headers) + Send + Sync>;
+
+/// A response along with the validators to check it's still fresh with.
+struct CachedResponse {
+    etag: Option<Vec<u8>>,
+    last_modified: Option<Vec<u8>>,
+    /// Until when `Cache-Control: max-age` lets the response be used without asking.
+    fresh_until: Option<Instant>,
+    params: Params,
+    /// When the response was last used, in `ResponseCache::clock` ticks.
+    used: u64,
+}
+
+/// Identifies a call by hashes of its URL and body, so the body doesn't have to be kept.
+type CacheKey = (u64, u64);
+
+/// Responses of the methods passed to `Client::cache_method`, dropping the least recently used
+/// one once there are `capacity` of them.
+struct ResponseCache {
+    capacity: usize,
+    entries: HashMap<CacheKey, CachedResponse>,
+    clock: u64,
+}
+
+impl ResponseCache {
+    fn new(capacity: usize) -> ResponseCache {
+        ResponseCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn key(uri: &Url, body: &[u8]) -> CacheKey {
+        let hash = |seed: u8| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            uri.as_str().hash(&mut hasher);
+            body.hash(&mut hasher);
+            hasher.finish()
+        };
+        (hash(0), hash(1))
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<&mut CachedResponse> {
+        self.clock += 1;
+        let cached = self.entries.get_mut(key)?;
+        cached.used = self.clock;
+        Some(cached)
+    }
+
+    fn insert(&mut self, key: CacheKey, mut cached: CachedResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        cached.used = self.clock;
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            self.remove_oldest();
+        }
+        self.entries.insert(key, cached);
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        self.entries.remove(key);
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.remove_oldest();
+        }
+    }
+
+    fn remove_oldest(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, cached)| cached.used)
+            .map(|(key, _)| *key);
+        if let Some(oldest) = oldest {
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+/// What a response's `Cache-Control` header allows.
+#[derive(Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<Duration>,
+}
+
+impl CacheControl {
+    fn of(headers: &Headers) -> CacheControl {
+        let mut control = CacheControl::default();
+        let values = headers.get_raw("Cache-Control").unwrap_or(&[]);
+        let directives = values
+            .iter()
+            .flat_map(|value| value.split(|&b| b == b','))
+            .filter_map(|directive| std::str::from_utf8(directive).ok())
+            .map(str::trim);
+        for directive in directives {
+            let (name, value) = match directive.find('=') {
+                Some(at) => (
+                    &directive[..at],
+                    Some(directive[at + 1..].trim_matches('"')),
+                ),
+                None => (directive, None),
+            };
+            if name.eq_ignore_ascii_case("no-store") {
+                control.no_store = true;
+            } else if name.eq_ignore_ascii_case("no-cache") {
+                control.no_cache = true;
+            } else if name.eq_ignore_ascii_case("max-age") {
+                control.max_age = value
+                    .and_then(|value| value.parse().ok())
+                    .map(Duration::from_secs);
+            }
+        }
+        control
+    }
+
+    fn fresh_until(&self) -> Option<Instant> {
+        match self.max_age {
+            Some(age) if !self.no_cache && age > Duration::from_secs(0) => {
+                Some(Instant::now() + age)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Client {
     pub fn new() -> Result<Client> {
-        let client = HyperClient::new();
-        Ok(Client { client })
+        Ok(Client::with_connector(HttpConnector))
+    }
+
+    /// Sends every call over the Unix domain socket at `path`, whatever the host in the URL.
+    ///
+    /// URLs still need an HTTP host, so servers on sockets are called like `http://localhost/RPC2`.
+    #[cfg(unix)]
+    pub fn with_unix_socket<P>(path: P) -> Result<Client>
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        let stats = pool::Stats::default();
+        let connector = pool::Counting::new(unix::Connector(path.into()), &stats);
+        Ok(Client::with_hyper(
+            HyperClient::with_connector(connector),
+            stats,
+        ))
+    }
+
+    /// Sends calls through a fully configured hyper client, like one with a proxy, timeouts or
+    /// its own connector.
+    ///
+    /// Pool statistics then only count requests, since connections are up to that client.
+    pub fn with_http_client(client: HyperClient) -> Client {
+        Client::with_hyper(client, pool::Stats::default())
+    }
+
+    /// Keeps connections made by `connector` alive between calls.
+    fn with_connector<C>(connector: C) -> Client
+    where
+        C: NetworkConnector + Send + Sync + 'static,
+        C::Stream: NetworkStream + Send,
+    {
+        let stats = pool::Stats::default();
+        let connector = pool::Counting::new(connector, &stats);
+        let client =
+            HyperClient::with_connector(Pool::with_connector(Default::default(), connector));
+        Client::with_hyper(client, stats)
+    }
+
+    fn with_hyper(client: HyperClient, stats: pool::Stats) -> Client {
+        Client {
+            transport: Transport {
+                client: Arc::new(client),
+                pre_send: None,
+                tokens: None,
+                stats,
+                limit: None,
+            },
+            serialize_options: SerializeOptions::default(),
+            deserialize_options: DeserializeOptions::default(),
+            parse_options: ParseOptions::default(),
+            write_options: WriteOptions::default(),
+            method_names: MethodNamePolicy::default(),
+            compression: true,
+            request_compression: false,
+            uncompressed_hosts: HashSet::new(),
+            content_type: "text/xml".into(),
+            cached_methods: HashSet::new(),
+            cache: ResponseCache::new(256),
+            fallbacks: HashMap::new(),
+            last_endpoint: None,
+        }
+    }
+
+    pub fn set_serialize_options(&mut self, options: SerializeOptions) {
+        self.serialize_options = options;
+    }
+
+    pub fn set_deserialize_options(&mut self, options: DeserializeOptions) {
+        self.deserialize_options = options;
+    }
+
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+    }
+
+    pub fn set_write_options(&mut self, options: WriteOptions) {
+        self.write_options = options;
+    }
+
+    /// Sets the options of every step of a call at once.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.serialize_options = profile.serialize;
+        self.deserialize_options = profile.deserialize;
+        self.parse_options = profile.parse;
+        self.write_options = profile.write;
+    }
+
+    /// Method names are checked before sending, to catch markup smuggled into them.
+    pub fn set_method_name_policy(&mut self, policy: MethodNamePolicy) {
+        self.method_names = policy;
+    }
+
+    /// Asks for responses compressed with gzip or deflate, which are decompressed when servers
+    /// use either. Enabled by default.
+    pub fn set_compression(&mut self, enabled: bool) {
+        self.compression = enabled;
+    }
+
+    /// Sends calls compressed with gzip. Disabled by default, since not all servers take them.
+    ///
+    /// Calls a server rejects with `415 Unsupported Media Type` or `400 Bad Request` are sent
+    /// again uncompressed, and so are all later calls to the same host.
+    pub fn set_request_compression(&mut self, enabled: bool) {
+        self.request_compression = enabled;
+    }
+
+    /// Sent with calls, `text/xml` unless changed, like to `application/xml` or
+    /// `text/xml; charset=utf-8` for peers that insist on either.
+    pub fn set_content_type<T: Into<String>>(&mut self, content_type: T) {
+        self.content_type = content_type.into();
+    }
+
+    /// Caches responses of the method, which has to be idempotent, when servers send an `ETag`
+    /// or `Last-Modified` header with them, or a `Cache-Control: max-age`.
+    ///
+    /// Later calls to the same URL with the same params are answered from the cache for as long
+    /// as `max-age` allows, and after that ask the server whether the response changed, with a
+    /// `304 Not Modified` reply answered from the cache. Responses with `Cache-Control:
+    /// no-store` aren't cached.
+    pub fn cache_method<K: Into<String>>(&mut self, name: K) {
+        self.cached_methods.insert(name.into());
+    }
+
+    /// Most responses kept by `cache_method`, 256 unless changed. The least recently used one
+    /// is dropped to make room for another.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.cache.set_capacity(capacity);
+    }
+
+    pub fn clear_cache(&mut self) {
+        self.cache.entries.clear();
+    }
+
+    /// Runs right before each request is sent, including retries against other URLs, with the
+    /// URL, the encoded call and the headers, which it can add to, like a signature of the call.
+    pub fn set_pre_send_hook<T>(&mut self, hook: T)
+    where
+        T: Fn(&Url, &[u8], &mut Headers) + Send + Sync + 'static,
+    {
+        self.transport.pre_send = Some(Arc::new(hook));
+    }
+
+    /// Authenticates calls with tokens from `tokens`, like `oauth2::ClientCredentials`.
+    pub fn set_token_source<T: TokenSource + 'static>(&mut self, tokens: T) {
+        self.transport.tokens = Some(Arc::new(tokens));
+    }
+
+    /// Calls to `uri` that fail to go through are retried against each of the fallbacks in
+    /// turn. Faults are returned as they are.
+    ///
+    /// Calls that failed while being sent may have reached the server, so a method could run
+    /// more than once.
+    pub fn set_fallbacks(&mut self, uri: Url, fallbacks: Vec<Url>) {
+        self.fallbacks.insert(uri, fallbacks);
+    }
+
+    /// The URL that answered the last call, which differs from the one called when a fallback
+    /// or another replica of a hedged call answered.
+    pub fn last_endpoint(&self) -> Option<&Url> {
+        self.last_endpoint.as_ref()
+    }
+
+    /// Makes calls wait for their turn under `limit`, which can be shared with other clients.
+    pub fn set_concurrency_limit(&mut self, limit: ConcurrencyLimit) {
+        self.transport.limit = Some(limit);
+    }
+
+    /// How connections have been used so far, by `host:port`.
+    pub fn pool_stats(&self) -> HashMap<String, PoolStats> {
+        self.transport
+            .stats
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
     }
 
     pub fn call_value<Tkey>(&mut self, uri: &Url, name: Tkey, params: Params) -> Result<Response>
     where
         Tkey: Into<String>,
     {
-        use super::xmlfmt::value::ToXml;
-        let body_str = Call {
+        let call = Call {
             name: name.into(),
             params,
+        };
+        self.send_call(uri, &call)
+    }
+
+    /// Like `call_value`, but borrows the call, for callers that still need it afterwards.
+    pub fn send_call(&mut self, uri: &Url, call: &Call) -> Result<Response> {
+        let cacheable = self.cached_methods.contains(&call.name);
+        trace!("Calling {} on {}", call.name, uri);
+        let body = self.encode(call)?;
+        // Calls are cached by their whole body, which holds the method name and params.
+        let key = if cacheable {
+            Some(ResponseCache::key(uri, &body))
+        } else {
+            None
+        };
+
+        let mut headers = self.headers();
+        if let Some(cached) = key.as_ref().and_then(|key| self.cache.get(key)) {
+            if cached
+                .fresh_until
+                .is_some_and(|until| Instant::now() < until)
+            {
+                return Ok(Ok(cached.params.clone()));
+            }
+            if let Some(ref etag) = cached.etag {
+                headers.set_raw("If-None-Match", vec![etag.clone()]);
+            }
+            if let Some(ref last_modified) = cached.last_modified {
+                headers.set_raw("If-Modified-Since", vec![last_modified.clone()]);
+            }
         }
-        .to_xml();
-        let bytes: &[u8] = body_str.as_bytes();
-        let body = hyper::client::Body::BufBody(bytes, bytes.len());
+        let _permit = self.transport.acquire()?;
+        let response = self.post_compressed(uri, &body, headers)?;
 
-        let mut headers = Headers::new();
-        headers.set(ContentType("xml".to_owned()));
+        let control = CacheControl::of(&response.headers);
+        if response.status == StatusCode::NotModified {
+            if let Some(cached) = key.as_ref().and_then(|key| self.cache.get(key)) {
+                cached.fresh_until = control.fresh_until();
+                return Ok(Ok(cached.params.clone()));
+            }
+        }
+        let validator = |name: &str| {
+            response
+                .headers
+                .get_raw(name)
+                .and_then(|values| values.first())
+                .cloned()
+        };
+        let etag = validator("ETag");
+        let last_modified = validator("Last-Modified");
 
-        let response = self
-            .client
-            .post(uri.as_ref())
-            .headers(headers)
-            .body(body)
-            .send()
-            .chain_err(|| "Failed to run the HTTP request within hyper.")?;
+        let result = read_response(response, &self.parse_options).map_err(|err| {
+            debug!("Failed to parse the response from {}: {}", uri, err);
+            err
+        })?;
+        if let (Some(key), Ok(params)) = (key, &result) {
+            let fresh_until = control.fresh_until();
+            if control.no_store {
+                self.cache.remove(&key);
+            } else if etag.is_some() || last_modified.is_some() || fresh_until.is_some() {
+                let cached = CachedResponse {
+                    etag,
+                    last_modified,
+                    fresh_until,
+                    params: params.clone(),
+                    used: 0,
+                };
+                self.cache.insert(key, cached);
+            }
+        }
+        Ok(result)
+    }
 
-        parse::response(response).map_err(Into::into)
+    /// Sends the call to the first URL, and to each next one in turn whenever `delay` passes
+    /// without a response, or when all calls sent so far failed.
+    ///
+    /// Returns the first response, and faults count as responses. Calls still running are
+    /// left to finish in the background. Responses aren't cached.
+    pub fn call_value_hedged<Tkey>(
+        &mut self,
+        uris: &[Url],
+        delay: Duration,
+        name: Tkey,
+        params: Params,
+    ) -> Result<Response>
+    where
+        Tkey: Into<String>,
+    {
+        let call = Call {
+            name: name.into(),
+            params,
+        };
+        let body = Arc::new(self.encode(&call)?);
+        let (sender, receiver) = mpsc::channel();
+        let mut pending = 0;
+        let mut error = None;
+        for (index, uri) in uris.iter().enumerate() {
+            let transport = self.transport.clone();
+            let uri = uri.clone();
+            let body = Arc::clone(&body);
+            let headers = self.headers();
+            let options = self.parse_options.clone();
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let result = transport.acquire().and_then(|_permit| {
+                    transport
+                        .post(&uri, &body, headers)
+                        .and_then(|response| read_response(response, &options))
+                });
+                let _ = sender.send((index, result));
+            });
+            pending += 1;
+            let deadline = Instant::now() + delay;
+            while pending > 0 {
+                let (answered, result) = if index + 1 < uris.len() {
+                    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(result) => result,
+                        Err(_) => break,
+                    }
+                } else {
+                    receiver.recv().expect("Calls always send a result")
+                };
+                pending -= 1;
+                match result {
+                    Ok(response) => {
+                        self.last_endpoint = Some(uris[answered].clone());
+                        return Ok(response);
+                    }
+                    Err(err) => error = Some(err),
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| "No URLs to send the call to.".into()))
+    }
+
+    /// Makes all of `calls` in a single `system.multicall` request. Faults of each call are
+    /// in the response, while the outer result is a fault if the multicall failed as a whole.
+    pub fn multicall(
+        &mut self,
+        uri: &Url,
+        calls: Vec<Call>,
+    ) -> Result<std::result::Result<MulticallResponse, Fault>> {
+        let names = calls.iter().map(|call| call.name.clone()).collect();
+        let response = self.call_value(uri, multicall::METHOD, multicall::params(calls))?;
+        let mut results = match response {
+            Ok(results) => results,
+            Err(fault) => return Ok(Err(fault)),
+        };
+        let results = match (results.pop(), results.is_empty()) {
+            (Some(results), true) => results,
+            _ => Value::Nil,
+        };
+        // Anything but a single array of results fails to be read.
+        Ok(Ok(MulticallResponse::from_value_named(names, results)?))
     }
 
     pub fn call<'a, Tkey, Treq, Tres>(
@@ -70,16 +803,495 @@ impl Client {
         uri: &Url,
         name: Tkey,
         req: Treq,
-    ) -> Result<std::result::Result<Tres, Fault>>
+    ) -> CallResult<Tres>
     where
         Tkey: Into<String>,
-        Treq: Serialize,
+        Treq: IntoParams,
+        Tres: Deserialize<'a>,
+    {
+        let params = into_params_with(&req, &self.serialize_options).map_err(decode_error)?;
+        let response = self.call_value(uri, name, params)?;
+        self.decode(response)
+    }
+
+    /// Like `call_value_hedged`, with params and the response converted like in `call`.
+    pub fn call_hedged<'a, Tkey, Treq, Tres>(
+        &mut self,
+        uris: &[Url],
+        delay: Duration,
+        name: Tkey,
+        req: Treq,
+    ) -> CallResult<Tres>
+    where
+        Tkey: Into<String>,
+        Treq: IntoParams,
+        Tres: Deserialize<'a>,
+    {
+        let params = into_params_with(&req, &self.serialize_options).map_err(decode_error)?;
+        let response = self.call_value_hedged(uris, delay, name, params)?;
+        self.decode(response)
+    }
+
+    /// Compresses the call when the host hasn't rejected compressed calls before, and sends it
+    /// again uncompressed when it does now.
+    fn post_compressed(
+        &mut self,
+        uri: &Url,
+        body: &[u8],
+        headers: Headers,
+    ) -> Result<hyper::client::Response> {
+        let host = match (uri.host_str(), uri.port_or_known_default()) {
+            (Some(host), Some(port)) => Some((host.to_string(), port)),
+            _ => None,
+        };
+        let compress = self.request_compression
+            && !host
+                .as_ref()
+                .is_some_and(|host| self.uncompressed_hosts.contains(host));
+        if !compress {
+            return self.post_with_fallbacks(uri, body, headers);
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(body)
+            .chain_err(|| "Failed to compress the call.")?;
+        let compressed = encoder
+            .finish()
+            .chain_err(|| "Failed to compress the call.")?;
+        let mut compressed_headers = headers.clone();
+        compressed_headers.set(ContentEncoding(vec![Encoding::Gzip]));
+        let response = self.post_with_fallbacks(uri, &compressed, compressed_headers)?;
+        match response.status {
+            StatusCode::UnsupportedMediaType | StatusCode::BadRequest => {
+                debug!(
+                    "{} rejected a compressed call, sending it uncompressed",
+                    uri
+                );
+                self.uncompressed_hosts.extend(host);
+                self.post_with_fallbacks(uri, body, headers)
+            }
+            _ => Ok(response),
+        }
+    }
+
+    fn post_with_fallbacks(
+        &mut self,
+        uri: &Url,
+        body: &[u8],
+        headers: Headers,
+    ) -> Result<hyper::client::Response> {
+        let fallbacks = self.fallbacks.get(uri).cloned().unwrap_or_default();
+        let mut error = None;
+        for endpoint in Some(uri).into_iter().chain(&fallbacks) {
+            match self.transport.post(endpoint, body, headers.clone()) {
+                Ok(response) => {
+                    self.last_endpoint = Some(endpoint.clone());
+                    return Ok(response);
+                }
+                Err(err) => {
+                    debug!("Call to {} failed: {}", endpoint, err);
+                    error = Some(err);
+                }
+            }
+        }
+        Err(error.expect("The called URL is always tried"))
+    }
+
+    fn encode(&self, call: &Call) -> Result<Vec<u8>> {
+        use super::xmlfmt::value::{check_method_name, ToXml};
+        check_method_name(&call.name, self.method_names)?;
+        let mut body = Vec::with_capacity(self.write_options.write_buffer_size);
+        call.write_xml_with(&mut body, &self.write_options)
+            .chain_err(|| "Failed to encode the XML-RPC call.")?;
+        Ok(body)
+    }
+
+    fn headers(&self) -> Headers {
+        let mut headers = Headers::new();
+        headers.set(ContentType(self.content_type.clone()));
+        if self.compression {
+            headers.set(AcceptEncoding(vec![
+                qitem(Encoding::Gzip),
+                qitem(Encoding::Deflate),
+            ]));
+        }
+        if let Some(trace) = TraceContext::current() {
+            headers.set_raw("traceparent", vec![trace.traceparent().into_bytes()]);
+            if let Some(state) = trace.state {
+                headers.set_raw("tracestate", vec![state.into_bytes()]);
+            }
+        }
+        headers
+    }
+
+    fn decode<'a, Tres>(&self, response: Response) -> CallResult<Tres>
+    where
         Tres: Deserialize<'a>,
     {
-        match self.call_value(uri, name, into_params(&req)?) {
-            Ok(Ok(v)) => from_params(v).map(Ok).map_err(Into::into),
-            Ok(Err(v)) => Ok(Err(v)),
-            Err(v) => Err(v),
+        let mut v = response.map_err(CallError::Fault)?;
+        let value = if v.len() == 1 {
+            v.pop().unwrap()
+        } else {
+            Value::Array(v)
+        };
+        from_value_with(value, &self.deserialize_options).map_err(decode_error)
+    }
+}
+
+impl Transport {
+    /// Holds a place under the concurrency limit until the returned permit is dropped.
+    fn acquire(&self) -> Result<Option<Permit>> {
+        self.limit
+            .as_ref()
+            .map(ConcurrencyLimit::acquire)
+            .transpose()
+    }
+
+    fn post(&self, uri: &Url, body: &[u8], headers: Headers) -> Result<hyper::client::Response> {
+        let response = self.send(uri, body, headers.clone())?;
+        match self.tokens {
+            // Tokens can be revoked before they expire.
+            Some(ref tokens) if response.status == StatusCode::Unauthorized => {
+                debug!("{} rejected the token, retrying with a new one", uri);
+                tokens.invalidate();
+                self.send(uri, body, headers)
+            }
+            _ => Ok(response),
+        }
+    }
+
+    fn send(
+        &self,
+        uri: &Url,
+        body: &[u8],
+        mut headers: Headers,
+    ) -> Result<hyper::client::Response> {
+        if let Some(ref tokens) = self.tokens {
+            let token = tokens.token()?;
+            headers.set(Authorization(Bearer { token }));
+        }
+        if let Some(ref pre_send) = self.pre_send {
+            pre_send(uri, body, &mut headers);
+        }
+        if let (Some(host), Some(port)) = (uri.host_str(), uri.port_or_known_default()) {
+            pool::update(&self.stats, host, port, |stats| stats.requests += 1);
+        }
+        self.client
+            .post(uri.as_ref())
+            .headers(headers)
+            .body(hyper::client::Body::BufBody(body, body.len()))
+            .send()
+            .chain_err(|| "Failed to run the HTTP request within hyper.")
+    }
+}
+
+/// A client bound to a server, making calls the way `R` does, so the same code makes blocking
+/// calls with `Endpoint<Blocking>` and async ones with `Endpoint<runtime::Async>`.
+///
+/// Clones share the client, and calls through them take turns on it. Calls wait for their turn
+/// under the client's concurrency limit before they're run, async ones without holding a
+/// thread, and calls answered from the cache take a turn too.
+pub struct Endpoint<R: Runtime = Blocking> {
+    client: Arc<Mutex<Client>>,
+    uri: Url,
+    /// Taken from the client, to be acquired before its calls are run.
+    limit: Option<ConcurrencyLimit>,
+    runtime: PhantomData<R>,
+}
+
+impl<R: Runtime> Endpoint<R> {
+    pub fn new(uri: Url) -> Result<Endpoint<R>> {
+        Ok(Endpoint::with_client(Client::new()?, uri))
+    }
+
+    pub fn with_client(mut client: Client, uri: Url) -> Endpoint<R> {
+        let limit = client.transport.limit.take();
+        Endpoint {
+            client: Arc::new(Mutex::new(client)),
+            uri,
+            limit,
+            runtime: PhantomData,
+        }
+    }
+
+    pub fn uri(&self) -> &Url {
+        &self.uri
+    }
+
+    pub fn call_value<Tkey>(&self, name: Tkey, params: Params) -> R::Output<Result<Response>>
+    where
+        Tkey: Into<String>,
+    {
+        let client = Arc::clone(&self.client);
+        let uri = self.uri.clone();
+        let name = name.into();
+        R::run_limited(self.limit.clone(), move || {
+            lock(&client).call_value(&uri, name, params)
+        })
+    }
+
+    /// Params are converted right away, and the response once it arrives.
+    pub fn call<Tkey, Treq, Tres>(&self, name: Tkey, req: Treq) -> R::Output<CallResult<Tres>>
+    where
+        Tkey: Into<String>,
+        Treq: IntoParams,
+        Tres: DeserializeOwned + Send + 'static,
+    {
+        let params = into_params_with(&req, &lock(&self.client).serialize_options);
+        let client = Arc::clone(&self.client);
+        let uri = self.uri.clone();
+        let name = name.into();
+        R::run_limited(self.limit.clone(), move || {
+            let mut client = lock(&client);
+            let response = client.call_value(&uri, name, params.map_err(decode_error)?)?;
+            client.decode(response)
+        })
+    }
+}
+
+impl<R: Runtime> Clone for Endpoint<R> {
+    fn clone(&self) -> Self {
+        Endpoint {
+            client: Arc::clone(&self.client),
+            uri: self.uri.clone(),
+            limit: self.limit.clone(),
+            runtime: PhantomData,
+        }
+    }
+}
+
+fn lock(client: &Mutex<Client>) -> std::sync::MutexGuard<'_, Client> {
+    client.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn decode_error<E: Into<Error>>(err: E) -> CallError {
+    CallError::Decode(err.into())
+}
+
+fn read_response(response: hyper::client::Response, options: &ParseOptions) -> Result<Response> {
+    let options = ParseOptions {
+        charset: response
+            .headers
+            .get_raw("Content-Type")
+            .and_then(|values| values.first())
+            .and_then(|value| std::str::from_utf8(value).ok())
+            .and_then(charset::from_content_type)
+            .map(Into::into),
+        ..options.clone()
+    };
+    let encodings = response
+        .headers
+        .get::<ContentEncoding>()
+        .map(|encodings| encodings.0.clone())
+        .unwrap_or_default();
+    let body = decompress(response, &encodings, options.read_buffer_size)?;
+    parse::response_with(body, &options).map_err(Into::into)
+}
+
+/// Undoes the encodings in reverse of the order they were applied in.
+fn decompress<'a, R: Read + 'a>(
+    body: R,
+    encodings: &[Encoding],
+    buffer_size: usize,
+) -> Result<Box<dyn Read + 'a>> {
+    let mut body: Box<dyn Read + 'a> = Box::new(body);
+    for encoding in encodings.iter().rev() {
+        body = match *encoding {
+            Encoding::Identity => body,
+            Encoding::Gzip => Box::new(MultiGzDecoder::new(body)),
+            Encoding::EncodingExt(ref name) if name.eq_ignore_ascii_case("x-gzip") => {
+                Box::new(MultiGzDecoder::new(body))
+            }
+            Encoding::Deflate => inflate(body, buffer_size)?,
+            ref encoding => bail!("Unsupported Content-Encoding: {}", encoding),
+        };
+    }
+    Ok(body)
+}
+
+/// Deflate is meant to be zlib wrapped, but some servers send raw deflate data instead.
+fn inflate<'a>(body: Box<dyn Read + 'a>, buffer_size: usize) -> Result<Box<dyn Read + 'a>> {
+    let mut body = BufReader::with_capacity(buffer_size.max(1), body);
+    let zlib = match *body
+        .fill_buf()
+        .chain_err(|| "Failed to read the HTTP response.")?
+    {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0,
+        _ => true,
+    };
+    Ok(if zlib {
+        Box::new(ZlibDecoder::new(body))
+    } else {
+        Box::new(DeflateDecoder::new(body))
+    })
+}
+
+#[cfg(unix)]
+mod unix {
+    use hyper;
+    use hyper::net::{NetworkConnector, NetworkStream};
+    use std::io::{self, Read, Write};
+    use std::net::{Shutdown, SocketAddr};
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    pub struct Connector(pub PathBuf);
+
+    impl NetworkConnector for Connector {
+        type Stream = Stream;
+
+        fn connect(&self, _host: &str, _port: u16, _scheme: &str) -> hyper::Result<Stream> {
+            Ok(Stream(UnixStream::connect(&self.0)?))
+        }
+    }
+
+    pub struct Stream(UnixStream);
+
+    impl Read for Stream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Stream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    impl NetworkStream for Stream {
+        fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+            Err(io::Error::other("Unix domain sockets have no IP address"))
+        }
+
+        fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+            self.0.set_read_timeout(dur)
+        }
+
+        fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+            self.0.set_write_timeout(dur)
+        }
+
+        fn close(&mut self, how: Shutdown) -> io::Result<()> {
+            self.0.shutdown(how)
+        }
+    }
+}
+
+/// Counts the connections opened for each host, and how many of them are still open.
+mod pool {
+    use super::PoolStats;
+    use hyper;
+    use hyper::net::{NetworkConnector, NetworkStream};
+    use std::collections::HashMap;
+    use std::io::{self, Read, Write};
+    use std::net::{Shutdown, SocketAddr};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    pub type Stats = Arc<Mutex<HashMap<String, PoolStats>>>;
+
+    pub fn update<F: FnOnce(&mut PoolStats)>(stats: &Stats, host: &str, port: u16, f: F) {
+        let mut stats = stats.lock().unwrap_or_else(|err| err.into_inner());
+        f(stats.entry(format!("{}:{}", host, port)).or_default())
+    }
+
+    pub struct Counting<C> {
+        inner: C,
+        stats: Stats,
+    }
+
+    impl<C> Counting<C> {
+        pub fn new(inner: C, stats: &Stats) -> Counting<C> {
+            Counting {
+                inner,
+                stats: Arc::clone(stats),
+            }
+        }
+    }
+
+    impl<C> NetworkConnector for Counting<C>
+    where
+        C: NetworkConnector,
+        C::Stream: NetworkStream + Send,
+    {
+        type Stream = Stream<C::Stream>;
+
+        fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<Self::Stream> {
+            let inner = self.inner.connect(host, port, scheme)?;
+            debug!(target: "xml_rpc::client", "Opened a connection to {}:{}", host, port);
+            update(&self.stats, host, port, |stats| {
+                stats.open += 1;
+                stats.handshakes += 1;
+            });
+            Ok(Stream {
+                inner,
+                stats: Arc::clone(&self.stats),
+                host: host.into(),
+                port,
+            })
+        }
+    }
+
+    pub struct Stream<S> {
+        inner: S,
+        stats: Stats,
+        host: String,
+        port: u16,
+    }
+
+    impl<S> Drop for Stream<S> {
+        fn drop(&mut self) {
+            trace!(target: "xml_rpc::client", "Closed a connection to {}:{}", self.host, self.port);
+            update(&self.stats, &self.host, self.port, |stats| stats.open -= 1);
+        }
+    }
+
+    impl<S: Read> Read for Stream<S> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl<S: Write> Write for Stream<S> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.inner.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl<S: NetworkStream + Send> NetworkStream for Stream<S> {
+        fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+            self.inner.peer_addr()
+        }
+
+        fn set_read_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+            self.inner.set_read_timeout(dur)
+        }
+
+        fn set_write_timeout(&self, dur: Option<Duration>) -> io::Result<()> {
+            self.inner.set_write_timeout(dur)
+        }
+
+        fn close(&mut self, how: Shutdown) -> io::Result<()> {
+            self.inner.close(how)
+        }
+
+        fn set_previous_response_expected_no_content(&mut self, expected: bool) {
+            self.inner
+                .set_previous_response_expected_no_content(expected)
+        }
+
+        fn previous_response_expected_no_content(&self) -> bool {
+            self.inner.previous_response_expected_no_content()
         }
     }
 }
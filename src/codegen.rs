@@ -0,0 +1,410 @@
+//! Generates typed client functions for a server that supports introspection, and both ends of
+//! a service from a description of it.
+
+use super::client::CallResultExt;
+use super::error::Result;
+#[cfg(feature = "idl")]
+use super::error::ResultExt;
+use super::xmlfmt::{Value, ValueType};
+use std::collections::HashSet;
+#[cfg(feature = "idl")]
+use std::{env, fs, path::Path};
+use {Client, Url};
+
+/// A method as described by `system.methodSignature` and `system.methodHelp`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Method {
+    pub name: String,
+    /// Type names with the result first, as `system.methodSignature` lists them.
+    pub signatures: Vec<Vec<String>>,
+    pub help: String,
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Calls `system.listMethods`, and then describes each of the listed methods.
+pub fn introspect(client: &mut Client, uri: &Url) -> Result<Vec<Method>> {
+    let names: Vec<String> = match client.call(uri, "system.listMethods", ()).nested()? {
+        Ok(names) => names,
+        Err(fault) => bail!(
+            "system.listMethods failed with fault {}: {}",
+            fault.code,
+            fault.message
+        ),
+    };
+    let mut methods = Vec::with_capacity(names.len());
+    for name in names {
+        // Servers answer "undef", or with a fault, for methods they have no signatures for.
+        let signatures = match client
+            .call(uri, "system.methodSignature", (&name,))
+            .nested()?
+        {
+            Ok(Value::Array(signatures)) => signatures
+                .into_iter()
+                .filter_map(|signature| match signature {
+                    Value::Array(types) => types
+                        .into_iter()
+                        .map(|t| match t {
+                            Value::String(t) => Some(t),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let help = match client.call(uri, "system.methodHelp", (&name,)).nested()? {
+            Ok(Value::String(help)) => help,
+            _ => String::new(),
+        };
+        methods.push(Method {
+            name,
+            signatures,
+            help,
+        });
+    }
+    Ok(methods)
+}
+
+/// Writes one function per method, to be saved as a module of a crate depending on `xml_rpc`.
+///
+/// Methods with a single signature of up to eight params get typed functions, and the rest take
+/// and return raw values.
+pub fn generate(methods: &[Method]) -> String {
+    let mut used = HashSet::new();
+    let mut code = String::new();
+    for method in methods {
+        let mut name = function_name(&method.name);
+        while !used.insert(name.clone()) {
+            name.push('_');
+        }
+        if !code.is_empty() {
+            code.push('\n');
+        }
+        write_doc(&mut code, "", &method.help);
+        match *method.signatures.as_slice() {
+            [ref signature] if !signature.is_empty() && signature.len() <= 9 => {
+                generate_typed(&mut code, &name, &method.name, signature)
+            }
+            _ => generate_untyped(&mut code, &name, method),
+        }
+    }
+    code
+}
+
+fn generate_typed(code: &mut String, name: &str, method: &str, signature: &[String]) {
+    let params = &signature[1..];
+    code.push_str(&format!(
+        "pub fn {}(\n    client: &mut xml_rpc::Client,\n    uri: &xml_rpc::Url,\n",
+        name
+    ));
+    for (index, param) in params.iter().enumerate() {
+        code.push_str(&format!("    arg{}: {},\n", index, param_type(param)));
+    }
+    code.push_str(&format!(
+        ") -> Result<{}, xml_rpc::client::CallError> {{\n",
+        result_type(&signature[0])
+    ));
+    let args = params
+        .iter()
+        .enumerate()
+        .map(|(index, param)| match ValueType::from_name(param) {
+            Some(ValueType::Base64) => format!("xml_rpc::Base64(arg{}), ", index),
+            _ => format!("arg{}, ", index),
+        })
+        .collect::<String>();
+    code.push_str(&format!(
+        "    client.call(uri, {:?}, ({}))\n}}\n",
+        method,
+        args.trim_end()
+    ));
+}
+
+fn generate_untyped(code: &mut String, name: &str, method: &Method) {
+    if !method.signatures.is_empty() {
+        if !method.help.is_empty() {
+            code.push_str("///\n");
+        }
+        code.push_str("/// Signatures:\n///\n");
+        for signature in &method.signatures {
+            let mut types = signature.iter();
+            let result = types.next().map(String::as_str).unwrap_or("undef");
+            let params = types.map(String::as_str).collect::<Vec<_>>();
+            code.push_str(&format!("/// * `{}({})`\n", result, params.join(", ")));
+        }
+    }
+    code.push_str(&format!(
+        "pub fn {}(\n    client: &mut xml_rpc::Client,\n    uri: &xml_rpc::Url,\n    params: \
+         xml_rpc::Params,\n) -> xml_rpc::error::Result<xml_rpc::Response> {{\n    \
+         client.call_value(uri, {:?}, params)\n}}\n",
+        name, method.name
+    ));
+}
+
+fn param_type(name: &str) -> &'static str {
+    match ValueType::from_name(name) {
+        Some(ValueType::Int) => "i32",
+        Some(ValueType::I8) => "i64",
+        Some(ValueType::Boolean) => "bool",
+        Some(ValueType::String) => "&str",
+        Some(ValueType::Double) => "f64",
+        Some(ValueType::Base64) => "&[u8]",
+        Some(ValueType::Array) => "&[xml_rpc::Value]",
+        Some(ValueType::Struct) => "&std::collections::HashMap<String, xml_rpc::Value>",
+        Some(ValueType::Nil) => "()",
+        _ => "&xml_rpc::Value",
+    }
+}
+
+fn result_type(name: &str) -> &'static str {
+    match ValueType::from_name(name) {
+        Some(ValueType::Int) => "i32",
+        Some(ValueType::I8) => "i64",
+        Some(ValueType::Boolean) => "bool",
+        Some(ValueType::String) => "String",
+        Some(ValueType::Double) => "f64",
+        Some(ValueType::Base64) => "xml_rpc::Base64",
+        Some(ValueType::Array) => "Vec<xml_rpc::Value>",
+        Some(ValueType::Struct) => "std::collections::HashMap<String, xml_rpc::Value>",
+        Some(ValueType::Nil) => "()",
+        _ => "xml_rpc::Value",
+    }
+}
+
+/// Turns names like `system.listMethods` into `system_list_methods`.
+pub fn function_name(method: &str) -> String {
+    let mut name = String::new();
+    let mut after_lowercase = false;
+    for c in method.chars() {
+        if c.is_ascii_uppercase() {
+            if after_lowercase {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+            after_lowercase = false;
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+            after_lowercase = true;
+        } else {
+            if !name.is_empty() && !name.ends_with('_') {
+                name.push('_');
+            }
+            after_lowercase = false;
+        }
+    }
+    let name = name.trim_end_matches('_');
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("method_{}", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.into()
+    }
+}
+
+/// Methods shared by a server and its clients, usually written in TOML:
+///
+/// ```toml
+/// name = "Calculator"
+///
+/// [[methods]]
+/// name = "math.add"
+/// doc = "Adds two numbers."
+/// params = [{ name = "a", type = "i32" }, { name = "b", type = "i32" }]
+/// result = "i32"
+/// ```
+///
+/// Types are Rust types, and must be owned, as the server decodes params into them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Service {
+    /// Names the generated trait, and the client as the same name followed by `Client`.
+    pub name: String,
+    #[serde(default)]
+    pub doc: String,
+    #[serde(default)]
+    pub methods: Vec<ServiceMethod>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceMethod {
+    pub name: String,
+    /// Defaults to the method name, like `math_add` for `math.add`.
+    #[serde(default)]
+    pub function: Option<String>,
+    #[serde(default)]
+    pub params: Vec<ServiceParam>,
+    #[serde(default = "unit")]
+    pub result: String,
+    #[serde(default)]
+    pub doc: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+fn unit() -> String {
+    "()".into()
+}
+
+#[cfg(feature = "idl")]
+impl Service {
+    pub fn from_toml(source: &str) -> Result<Service> {
+        toml::from_str(source).chain_err(|| "Invalid service description.")
+    }
+}
+
+/// Generates the service from a TOML description, into a file in `OUT_DIR` with the same name
+/// but an `.rs` extension. Meant to be called from a build script, and the result included with
+/// `include!(concat!(env!("OUT_DIR"), "/calculator.rs"));`.
+#[cfg(feature = "idl")]
+pub fn compile_service<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+    let source =
+        fs::read_to_string(path).chain_err(|| format!("Failed to read {}.", path.display()))?;
+    let service = Service::from_toml(&source)?;
+    let out_dir = env::var_os("OUT_DIR").ok_or("OUT_DIR is not set, so not in a build script.")?;
+    let output = Path::new(&out_dir)
+        .join(path.file_name().unwrap_or_default())
+        .with_extension("rs");
+    fs::write(&output, generate_service(&service))
+        .chain_err(|| format!("Failed to write {}.", output.display()))
+}
+
+/// Writes a trait for the service, a `register` function serving an implementation of it, and a
+/// client with a function for each method.
+pub fn generate_service(service: &Service) -> String {
+    let functions = service
+        .methods
+        .iter()
+        .map(|method| match method.function {
+            Some(ref function) => identifier(function),
+            None => function_name(&method.name),
+        })
+        .collect::<Vec<_>>();
+    let mut code = String::new();
+    write_doc(&mut code, "", &service.doc);
+    code.push_str(&format!("pub trait {} {{\n", service.name));
+    for (method, function) in service.methods.iter().zip(&functions) {
+        write_doc(&mut code, "    ", &method.doc);
+        code.push_str(&format!(
+            "    fn {}(&self{}) -> Result<{}, xml_rpc::Fault>;\n",
+            function,
+            params(method),
+            method.result
+        ));
+    }
+    code.push_str("}\n\n");
+
+    // A service without methods goes unused.
+    let (server, service_param) = match service.methods.len() {
+        0 => ("_server", "_service"),
+        _ => ("server", "service"),
+    };
+    code.push_str(&format!(
+        "pub fn register<T: {} + Send + Sync + 'static>(\n    {}: &mut xml_rpc::Server,\n    \
+         {}: T,\n) {{\n",
+        service.name, server, service_param
+    ));
+    // Each handler but the last gets its own reference to the service, and the last takes it.
+    if service.methods.len() > 1 {
+        code.push_str("    let service = std::sync::Arc::new(service);\n");
+    }
+    for (index, (method, function)) in service.methods.iter().zip(&functions).enumerate() {
+        let last = index + 1 == functions.len();
+        let indent = if last { "    " } else { "        " };
+        if !last {
+            code.push_str("    {\n        let service = std::sync::Arc::clone(&service);\n");
+        }
+        let types = method
+            .params
+            .iter()
+            .map(|p| format!("{}, ", p.ty))
+            .collect::<String>();
+        code.push_str(&format!(
+            "{indent}server.register_simple({:?}, move |({}): ({})| {{\n{indent}    \
+             service.{}({})\n{indent}}});\n",
+            method.name,
+            tuple(method),
+            types.trim_end(),
+            function,
+            tuple(method).trim_end_matches(','),
+            indent = indent,
+        ));
+        if !last {
+            code.push_str("    }\n");
+        }
+    }
+    code.push_str("}\n\n");
+
+    code.push_str(&format!(
+        "pub struct {name}Client {{\n    endpoint: xml_rpc::client::Endpoint,\n}}\n\n\
+         impl {name}Client {{\n    pub fn new(uri: xml_rpc::Url) -> \
+         xml_rpc::error::Result<{name}Client> {{\n        Ok({name}Client {{\n            \
+         endpoint: xml_rpc::client::Endpoint::new(uri)?,\n        }})\n    }}\n",
+        name = service.name
+    ));
+    for (method, function) in service.methods.iter().zip(&functions) {
+        code.push('\n');
+        write_doc(&mut code, "    ", &method.doc);
+        code.push_str(&format!(
+            "    pub fn {}(&self{}) -> Result<{}, xml_rpc::client::CallError> \
+             {{\n        self.endpoint.call({:?}, ({}))\n    }}\n",
+            function,
+            params(method),
+            method.result,
+            method.name,
+            tuple(method)
+        ));
+    }
+    code.push_str("}\n");
+    code
+}
+
+/// Param names as the body of a tuple, with a trailing comma unless empty.
+fn tuple(method: &ServiceMethod) -> String {
+    method
+        .params
+        .iter()
+        .map(|p| format!("{},", identifier(&p.name)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn params(method: &ServiceMethod) -> String {
+    method
+        .params
+        .iter()
+        .map(|p| format!(", {}: {}", identifier(&p.name), p.ty))
+        .collect()
+}
+
+/// Escapes keywords, like `type` as `r#type`, or as `self_` for those that can't be escaped.
+fn identifier(name: &str) -> String {
+    match name {
+        "crate" | "self" | "Self" | "super" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.into(),
+    }
+}
+
+fn write_doc(code: &mut String, indent: &str, doc: &str) {
+    for line in doc.lines() {
+        code.push_str(format!("{}/// {}", indent, line).trim_end());
+        code.push('\n');
+    }
+}
@@ -1,5 +1,18 @@
-#![allow(unknown_lints, unused_doc_comments)]
+//! The errors of the client and server.
+//!
+//! Parsing and serde report `FmtError`s, which convert into these with `?` and can be told apart
+//! as `ErrorKind::XmlFormat`, so a single `Result` covers a whole call.
+//!
+//! The two stay separate types, since every function of `xmlfmt` returns `FmtError` and is
+//! usable without a client or server. Both `ErrorKind`s have a hidden variant keeping matches
+//! on them non-exhaustive, so kinds can be added to either without breaking anyone.
+#![allow(unknown_lints, unused_doc_comments, unexpected_cfgs)]
 pub use super::xmlfmt::error::{Error as FmtError, ErrorKind as FmtErrorKind};
+use super::xmlfmt::Fault;
+use hyper;
+use std::error::Error as StdError;
+use std::io;
+use std::iter;
 
 error_chain! {
     links {
@@ -10,5 +23,73 @@ error_chain! {
             description("Failed to bind XML-RPC server to port")
             display("Failed to bind XML-RPC server to port: {}", details)
         }
+        ConcurrencyLimitTimeout(limit: usize) {
+            description("Timed out waiting for a concurrent call to finish")
+            display("Timed out waiting for one of {} concurrent calls to finish", limit)
+        }
+        Fault(fault: Fault) {
+            description("Server returned a fault")
+            display("Server returned a fault: {}", fault)
+        }
+    }
+}
+
+impl Error {
+    /// Whether the call timed out, either waiting under a concurrency limit or on the connection.
+    pub fn is_timeout(&self) -> bool {
+        match *self.kind() {
+            ErrorKind::ConcurrencyLimitTimeout(_) => true,
+            _ => self.causes().filter_map(io_error).any(|err| {
+                err.kind() == io::ErrorKind::TimedOut || err.kind() == io::ErrorKind::WouldBlock
+            }),
+        }
+    }
+
+    /// Whether the call failed before a response could be read, so retrying it may help, as
+    /// opposed to the response being unreadable or a fault.
+    pub fn is_transport(&self) -> bool {
+        match *self.kind() {
+            ErrorKind::ConcurrencyLimitTimeout(_) => true,
+            _ => self
+                .causes()
+                .any(|err| err.is::<hyper::Error>() || err.is::<io::Error>()),
+        }
+    }
+
+    pub fn is_fault(&self) -> bool {
+        self.fault().is_some()
+    }
+
+    pub fn fault(&self) -> Option<&Fault> {
+        match *self.kind() {
+            ErrorKind::Fault(ref fault) => Some(fault),
+            _ => None,
+        }
+    }
+
+    fn causes(&self) -> impl Iterator<Item = &(dyn StdError + 'static)> {
+        let mut next = self
+            .1
+            .next_error
+            .as_ref()
+            .map(|err| &**err as &(dyn StdError + 'static));
+        iter::from_fn(move || {
+            let err = next?;
+            next = err.source();
+            Some(err)
+        })
+    }
+}
+
+impl From<Fault> for Error {
+    fn from(fault: Fault) -> Error {
+        ErrorKind::Fault(fault).into()
+    }
+}
+
+fn io_error<'a>(err: &'a (dyn StdError + 'static)) -> Option<&'a io::Error> {
+    match err.downcast_ref::<hyper::Error>() {
+        Some(hyper::Error::Io(err)) => Some(err),
+        _ => err.downcast_ref::<io::Error>(),
     }
 }
@@ -1,27 +1,127 @@
 #![recursion_limit = "1024"]
 
+#[cfg(feature = "async")]
+extern crate async_io;
 extern crate base64;
+#[cfg(feature = "simd")]
+extern crate base64_simd;
+#[cfg(feature = "bignum")]
+pub extern crate bigdecimal;
+#[cfg(feature = "async")]
+extern crate blocking;
+#[cfg(feature = "chrono")]
+extern crate chrono;
 #[macro_use]
 extern crate error_chain;
+#[cfg(feature = "async")]
+extern crate event_listener;
+extern crate flate2;
+#[cfg(all(test, feature = "async"))]
+extern crate futures_lite;
 #[macro_use]
-extern crate hyper;
+pub extern crate hyper;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "simd")]
+extern crate memchr;
+#[cfg(feature = "bignum")]
+pub extern crate num_bigint;
+#[cfg(feature = "testing")]
+#[macro_use]
+extern crate proptest;
 extern crate regex;
 #[macro_use]
 extern crate serde;
+#[cfg(unix)]
+extern crate libc;
 pub extern crate rouille;
 #[cfg(test)]
 extern crate serde_bytes;
-extern crate serde_xml_rs;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
+#[cfg(unix)]
+extern crate socket2;
+#[cfg(feature = "time")]
+extern crate time;
+extern crate tiny_http;
+#[cfg(feature = "idl")]
+extern crate toml;
 extern crate xml;
+#[cfg(feature = "macros")]
+extern crate xml_rpc_macros;
+// Lets the tests use code generated for other crates.
+#[cfg(test)]
+extern crate self as xml_rpc;
 
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "bugzilla")]
+pub mod bugzilla;
 pub mod client;
+pub mod codegen;
 pub mod error;
+#[cfg(feature = "metaweblog")]
+pub mod metaweblog;
+#[cfg(feature = "oauth2")]
+pub mod oauth2;
+pub mod proxy;
+#[cfg(feature = "ros")]
+pub mod ros;
+pub mod runtime;
+pub mod secret;
 pub mod server;
+#[cfg(feature = "supervisor")]
+pub mod supervisor;
+pub mod test_util;
+pub mod trace;
+#[cfg(feature = "wordpress")]
+pub mod wordpress;
 mod xmlfmt;
 
-pub use client::{call, call_value, Client};
+pub use client::{
+    call, call_value, CallError, CallResultExt, Client, ConcurrencyLimit, PoolStats, TokenSource,
+};
+pub use hyper::header::Headers;
 pub use hyper::Url;
-pub use server::Server;
-pub use xmlfmt::{from_params, into_params, Call, Fault, Params, Response, Value};
+pub use secret::Secret;
+pub use server::{serve, Ctx, Server, XmlRpcService};
+#[cfg(feature = "macros")]
+pub use xml_rpc_macros::xmlrpc_service;
+pub use xmlfmt::arena;
+pub use xmlfmt::datetime;
+pub use xmlfmt::fields::{as_base64, as_datetime, as_i64_string};
+pub use xmlfmt::multicall;
+pub use xmlfmt::parse;
+pub use xmlfmt::query;
+pub use xmlfmt::signature;
+pub use xmlfmt::stream;
+pub use xmlfmt::value::ToXml;
+pub use xmlfmt::{
+    diff, from_params, from_params_with, from_value, from_value_ref, from_value_ref_with,
+    from_value_with, from_xml_reader, from_xml_reader_with, into_params, into_params_with,
+    to_value, to_value_with, to_xml_writer, to_xml_writer_with, Base64, BorrowedCall,
+    BorrowedResponse, BorrowedValue, Call, CallBuilder, DateTimeFormat, DeserializeOptions,
+    Difference, DoctypePolicy, EmptyStrings, Encoder, EnumRepresentation, Fault, FromParams,
+    IntTag, IntegerPolicy, IntoParams, MethodNamePolicy, MulticallResponse, NaiveDateTimePolicy,
+    NonFinitePolicy, OptionRepresentation, Params, ParamsExt, ParamsReader, ParseOptions, Profile,
+    Query, Response, ResponseBuilder, ResponseExt, ResponseStream, SerializeOptions, Signature,
+    StreamedParam, Value, ValueDiff, ValueType, WriteOptions, XmlDeclaration,
+};
+
+/// Codes of the faults in the interoperability spec, and of the crate's own.
+pub mod fault_codes {
+    pub use xmlfmt::value::{
+        APPLICATION_ERROR, INTERNAL_ERROR, INVALID_CHARACTER, INVALID_PARAMS, INVALID_XML_RPC,
+        METHOD_NOT_FOUND, PARSE_ERROR, REPLAYED_CALL, SYSTEM_ERROR, TRANSPORT_ERROR,
+        UNSUPPORTED_ENCODING,
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde::{Deserializer, Serializer};
+}
@@ -0,0 +1,260 @@
+/// Builds a `Value` from JSON-like literal syntax.
+///
+/// ```
+/// #[macro_use]
+/// extern crate xml_rpc;
+///
+/// # fn main() {
+/// let value = xmlrpc_value!({"foo": [1, true, "bar"], "baz": {"qux": 2.5}});
+/// # }
+/// ```
+#[macro_export]
+macro_rules! xmlrpc_value {
+    ([]) => {
+        $crate::Value::Array(::std::vec::Vec::new())
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::xmlrpc_value_internal!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::Value::Struct(::std::collections::HashMap::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Struct({
+            let mut members = ::std::collections::HashMap::new();
+            $crate::xmlrpc_value_internal!(@struct members $($tt)+);
+            members
+        })
+    };
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! xmlrpc_value_internal {
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] , $($rest:tt)*) => {
+        $crate::xmlrpc_value_internal!(@array [$($elems,)*] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::xmlrpc_value_internal!(
+            @array [$($elems,)* $crate::xmlrpc_value!([$($array)*]),] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] {$($members:tt)*} $($rest:tt)*) => {
+        $crate::xmlrpc_value_internal!(
+            @array [$($elems,)* $crate::xmlrpc_value!({$($members)*}),] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::xmlrpc_value_internal!(
+            @array [$($elems,)* $crate::xmlrpc_value!($next),] $($rest)*
+        )
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::xmlrpc_value_internal!(@array [$($elems,)* $crate::xmlrpc_value!($last),])
+    };
+
+    (@struct $members:ident) => {};
+    (@struct $members:ident , $($rest:tt)*) => {
+        $crate::xmlrpc_value_internal!(@struct $members $($rest)*)
+    };
+    (@struct $members:ident $key:tt : [$($array:tt)*] $($rest:tt)*) => {
+        $members.insert(
+            ::std::string::String::from($key),
+            $crate::xmlrpc_value!([$($array)*]),
+        );
+        $crate::xmlrpc_value_internal!(@struct $members $($rest)*)
+    };
+    (@struct $members:ident $key:tt : {$($inner:tt)*} $($rest:tt)*) => {
+        $members.insert(
+            ::std::string::String::from($key),
+            $crate::xmlrpc_value!({$($inner)*}),
+        );
+        $crate::xmlrpc_value_internal!(@struct $members $($rest)*)
+    };
+    (@struct $members:ident $key:tt : $value:expr, $($rest:tt)*) => {
+        $members.insert(::std::string::String::from($key), $crate::xmlrpc_value!($value));
+        $crate::xmlrpc_value_internal!(@struct $members $($rest)*)
+    };
+    (@struct $members:ident $key:tt : $value:expr) => {
+        $members.insert(::std::string::String::from($key), $crate::xmlrpc_value!($value));
+    };
+}
+
+/// Asserts that two `Value`s are equal, panicking with every difference between them if not.
+///
+/// ```
+/// #[macro_use]
+/// extern crate xml_rpc;
+///
+/// # fn main() {
+/// let response = xmlrpc_value!({"items": [1, 2], "total": 2});
+/// assert_value_eq!(response, xmlrpc_value!({"total": 2, "items": [1, 2]}));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_value_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = $crate::diff(left, right);
+                if !diff.is_empty() {
+                    panic!("assertion failed: values differ\n{}", diff);
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = $crate::diff(left, right);
+                if !diff.is_empty() {
+                    panic!(
+                        "assertion failed: values differ: {}\n{}",
+                        format_args!($($arg)+),
+                        diff
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Declares a server's methods once, as name constants for both ends to share, and routes them
+/// to their handlers with a `match`.
+///
+/// Handlers are `fn(Params) -> Response`, like those of `Server::register_value`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate xml_rpc;
+///
+/// use xml_rpc::{Fault, Params, Response, Server};
+///
+/// fn add(params: Params) -> Response {
+///     let (a, b): (i32, i32) = xml_rpc::from_params(params)
+///         .map_err(|_| Fault::invalid_params("Expected two ints"))?;
+///     Ok(vec![(a + b).into()])
+/// }
+///
+/// fn echo(params: Params) -> Response {
+///     Ok(params)
+/// }
+///
+/// xmlrpc_routes! {
+///     pub struct Methods {
+///         ADD = "math.add" => add,
+///         ECHO = "echo" => echo,
+///     }
+/// }
+///
+/// # fn main() {
+/// let mut server = Server::new();
+/// Methods::register(&mut server);
+/// assert_eq!(Methods::ALL, &["math.add", "echo"]);
+/// assert!(Methods::dispatch(Methods::ECHO, vec![]).is_some());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! xmlrpc_routes {
+    (
+        $vis:vis struct $routes:ident {
+            $($constant:ident = $name:literal => $handler:path),* $(,)*
+        }
+    ) => {
+        $vis struct $routes;
+
+        #[allow(dead_code)]
+        impl $routes {
+            $(pub const $constant: &'static str = $name;)*
+
+            /// Every method's name, in the order they're declared.
+            pub const ALL: &'static [&'static str] = &[$($name),*];
+
+            /// Calls the handler of the method, or returns `None` if there's no such method.
+            pub fn dispatch(name: &str, params: $crate::Params) -> Option<$crate::Response> {
+                match name {
+                    $($name => Some($handler(params)),)*
+                    _ => None,
+                }
+            }
+
+            pub fn register(server: &mut $crate::Server) {
+                $(server.register_value($name, $handler);)*
+            }
+        }
+    };
+}
+
+/// Declares a module for `#[serde(with = "...")]` that writes datetime fields as plain `string`s
+/// in the given format, and reads them back, for servers that don't use `dateTime.iso8601`.
+///
+/// Works on the `chrono` and `time` types implementing `datetime::StringFormat`.
+///
+/// ```ignore
+/// xmlrpc_datetime_string!(pub mod created, "%Y-%m-%d %H:%M:%S");
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Post {
+///     #[serde(with = "created")]
+///     created: chrono::NaiveDateTime,
+/// }
+/// ```
+#[macro_export]
+macro_rules! xmlrpc_datetime_string {
+    ($vis:vis mod $name:ident, $format:expr) => {
+        $vis mod $name {
+            use $crate::__private::{Deserializer, Serializer};
+
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                T: $crate::datetime::StringFormat,
+                S: Serializer,
+            {
+                $crate::datetime::serialize_formatted(value, $format, serializer)
+            }
+
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                T: $crate::datetime::StringFormat,
+                D: Deserializer<'de>,
+            {
+                $crate::datetime::deserialize_formatted($format, deserializer)
+            }
+        }
+    };
+}
+
+// Stand-ins for the `log` macros used inside the crate, for builds without the `log` feature.
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        if false {
+            let _ = ($target, format_args!($($arg)+));
+        }
+    };
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        debug!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        debug!($($arg)+)
+    };
+}
@@ -0,0 +1,319 @@
+//! Payloads of the MetaWeblog and Blogger APIs, as extended by Movable Type and WordPress,
+//! and `register_metaweblog` to serve them.
+//!
+//! Blog and post IDs are passed as strings, though clients may send them as `int`s.
+
+use super::xmlfmt::{
+    from_params_with, to_value_with, Base64, DeserializeOptions, FromParams, OptionRepresentation,
+    SerializeOptions,
+};
+use serde::Serialize;
+use std;
+use std::sync::Arc;
+use {datetime, Fault, Secret, Server, Value};
+
+/// Optional members are left out when `None`, so calls must be made with
+/// `OptionRepresentation::Omit`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Post {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub postid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The body of the post.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(
+        rename = "dateCreated",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "datetime::option"
+    )]
+    pub date_created: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mt_excerpt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mt_text_more: Option<String>,
+    /// Comma separated tags.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mt_keywords: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mt_allow_comments: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wp_slug: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_status: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Category {
+    #[serde(
+        rename = "categoryId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub category_id: Option<String>,
+    #[serde(
+        rename = "categoryName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub category_name: Option<String>,
+    pub description: String,
+    #[serde(rename = "htmlUrl")]
+    pub html_url: String,
+    #[serde(rename = "rssUrl")]
+    pub rss_url: String,
+}
+
+/// A file uploaded with `metaWeblog.newMediaObject`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaObject {
+    pub name: String,
+    /// The MIME type, like `image/png`.
+    #[serde(rename = "type")]
+    pub media_type: String,
+    pub bits: Base64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaUrl {
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlogInfo {
+    pub blogid: String,
+    pub url: String,
+    #[serde(rename = "blogName")]
+    pub blog_name: String,
+    #[serde(rename = "isAdmin", default, skip_serializing_if = "Option::is_none")]
+    pub is_admin: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserInfo {
+    pub userid: String,
+    pub nickname: String,
+    pub firstname: String,
+    pub lastname: String,
+    pub email: String,
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: Secret,
+}
+
+/// The MetaWeblog and Blogger method set, served by `register_metaweblog`.
+///
+/// Methods not every blog supports reply with a fault unless implemented.
+pub trait MetaWeblogApi {
+    /// Returns the new post's ID.
+    fn new_post(
+        &self,
+        blog_id: String,
+        credentials: Credentials,
+        post: Post,
+        publish: bool,
+    ) -> std::result::Result<String, Fault>;
+
+    /// Only the members that are set are changed.
+    fn edit_post(
+        &self,
+        post_id: String,
+        credentials: Credentials,
+        post: Post,
+        publish: bool,
+    ) -> std::result::Result<bool, Fault>;
+
+    fn get_post(
+        &self,
+        post_id: String,
+        credentials: Credentials,
+    ) -> std::result::Result<Post, Fault>;
+
+    /// Returns up to `count` posts, newest first.
+    fn get_recent_posts(
+        &self,
+        blog_id: String,
+        credentials: Credentials,
+        count: i32,
+    ) -> std::result::Result<Vec<Post>, Fault>;
+
+    fn get_users_blogs(
+        &self,
+        credentials: Credentials,
+    ) -> std::result::Result<Vec<BlogInfo>, Fault>;
+
+    fn get_categories(
+        &self,
+        _blog_id: String,
+        _credentials: Credentials,
+    ) -> std::result::Result<Vec<Category>, Fault> {
+        Err(unsupported("metaWeblog.getCategories"))
+    }
+
+    fn new_media_object(
+        &self,
+        _blog_id: String,
+        _credentials: Credentials,
+        _media: MediaObject,
+    ) -> std::result::Result<MediaUrl, Fault> {
+        Err(unsupported("metaWeblog.newMediaObject"))
+    }
+
+    fn delete_post(
+        &self,
+        _post_id: String,
+        _credentials: Credentials,
+        _publish: bool,
+    ) -> std::result::Result<bool, Fault> {
+        Err(unsupported("blogger.deletePost"))
+    }
+
+    fn get_user_info(&self, _credentials: Credentials) -> std::result::Result<UserInfo, Fault> {
+        Err(unsupported("blogger.getUserInfo"))
+    }
+}
+
+fn unsupported(method: &str) -> Fault {
+    Fault::new(404, format!("{} is not supported", method))
+}
+
+/// Blog and post IDs, sent as `string`s by some clients and as `int`s by others.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Id {
+    Int(i64),
+    String(String),
+}
+
+impl From<Id> for String {
+    fn from(id: Id) -> String {
+        match id {
+            Id::Int(id) => id.to_string(),
+            Id::String(id) => id,
+        }
+    }
+}
+
+/// Decodes with `Omit` whatever the server's own options, since all optional members
+/// are left out.
+fn register<Treq, Tres, Thandler>(server: &mut Server, name: &str, handler: Thandler)
+where
+    Treq: FromParams,
+    Tres: Serialize,
+    Thandler: Fn(Treq) -> std::result::Result<Tres, Fault> + Send + Sync + 'static,
+{
+    server.register_value(name, move |params| {
+        let deserialize_options = DeserializeOptions {
+            options: OptionRepresentation::Omit,
+            ..DeserializeOptions::default()
+        };
+        let params = from_params_with(params, &deserialize_options)
+            .map_err(|err| Fault::invalid_params(format!("Invalid parameters: {}", err)))?;
+        let serialize_options = SerializeOptions {
+            options: OptionRepresentation::Omit,
+            ..SerializeOptions::default()
+        };
+        let value: Value = to_value_with(&handler(params)?, &serialize_options)
+            .map_err(|err| Fault::new(500, format!("Failed to encode response: {}", err)))?;
+        Ok(vec![value])
+    });
+}
+
+fn credentials(username: String, password: String) -> Credentials {
+    Credentials {
+        username,
+        password: Secret::new(password),
+    }
+}
+
+/// Serves `blog` as the `metaWeblog.*` and `blogger.*` methods on `server`.
+///
+/// `blogger.*` methods take an application key first, which is ignored.
+pub fn register_metaweblog<T: MetaWeblogApi + Send + Sync + 'static>(server: &mut Server, blog: T) {
+    let blog = Arc::new(blog);
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "metaWeblog.newPost",
+        move |(id, username, password, post, publish): (Id, _, _, _, _)| {
+            b.new_post(id.into(), credentials(username, password), post, publish)
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "metaWeblog.editPost",
+        move |(id, username, password, post, publish): (Id, _, _, _, _)| {
+            b.edit_post(id.into(), credentials(username, password), post, publish)
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "metaWeblog.getPost",
+        move |(id, username, password): (Id, _, _)| {
+            b.get_post(id.into(), credentials(username, password))
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "metaWeblog.getRecentPosts",
+        move |(id, username, password, count): (Id, _, _, _)| {
+            b.get_recent_posts(id.into(), credentials(username, password), count)
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "metaWeblog.getCategories",
+        move |(id, username, password): (Id, _, _)| {
+            b.get_categories(id.into(), credentials(username, password))
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "metaWeblog.newMediaObject",
+        move |(id, username, password, media): (Id, _, _, _)| {
+            b.new_media_object(id.into(), credentials(username, password), media)
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "blogger.deletePost",
+        move |(_, id, username, password, publish): (Value, Id, _, _, _)| {
+            b.delete_post(id.into(), credentials(username, password), publish)
+        },
+    );
+    let b = Arc::clone(&blog);
+    register(
+        server,
+        "blogger.getUsersBlogs",
+        move |(_, username, password): (Value, _, _)| {
+            b.get_users_blogs(credentials(username, password))
+        },
+    );
+    register(
+        server,
+        "blogger.getUserInfo",
+        move |(_, username, password): (Value, _, _)| {
+            blog.get_user_info(credentials(username, password))
+        },
+    );
+}
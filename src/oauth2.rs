@@ -0,0 +1,131 @@
+//! Access tokens from OAuth 2.0 token endpoints, for servers behind API gateways.
+
+use super::error::{Result, ResultExt};
+use hyper::header::{Authorization, Basic, ContentType, Headers};
+use hyper::Client as HyperClient;
+use serde_json;
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use {Secret, TokenSource, Url};
+
+/// Tokens are fetched again this long before they expire, so they don't expire mid-call.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Fetches tokens with the client credentials grant, keeping each until it's about to expire.
+///
+/// The client authenticates with HTTP Basic authentication, as the token endpoint's
+/// specification requires every server to support.
+pub struct ClientCredentials {
+    client: HyperClient,
+    token_url: Url,
+    client_id: String,
+    client_secret: Secret,
+    scopes: Vec<String>,
+    token: Mutex<Option<Token>>,
+}
+
+struct Token {
+    access_token: String,
+    expires: Option<Instant>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+impl ClientCredentials {
+    pub fn new<I, S>(token_url: Url, client_id: I, client_secret: S) -> ClientCredentials
+    where
+        I: Into<String>,
+        S: Into<Secret>,
+    {
+        ClientCredentials {
+            client: HyperClient::new(),
+            token_url,
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scopes: Vec::new(),
+            token: Mutex::new(None),
+        }
+    }
+
+    pub fn set_scopes(&mut self, scopes: Vec<String>) {
+        self.scopes = scopes;
+    }
+
+    fn fetch(&self) -> Result<Token> {
+        let mut body = "grant_type=client_credentials".to_string();
+        if !self.scopes.is_empty() {
+            body.push_str("&scope=");
+            body.push_str(&form_encode(&self.scopes.join(" ")));
+        }
+        let mut headers = Headers::new();
+        headers.set(ContentType::form_url_encoded());
+        headers.set(Authorization(Basic {
+            username: self.client_id.clone(),
+            password: Some(self.client_secret.expose().clone()),
+        }));
+        let mut response = self
+            .client
+            .post(self.token_url.as_ref())
+            .headers(headers)
+            .body(&body)
+            .send()
+            .chain_err(|| "Failed to request an access token.")?;
+        let mut reply = String::new();
+        response
+            .read_to_string(&mut reply)
+            .chain_err(|| "Failed to read the access token.")?;
+        if !response.status.is_success() {
+            bail!("Token endpoint replied with {}: {}", response.status, reply);
+        }
+        let reply: TokenResponse =
+            serde_json::from_str(&reply).chain_err(|| "Failed to decode the access token.")?;
+        Ok(Token {
+            access_token: reply.access_token,
+            expires: reply
+                .expires_in
+                .map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+        })
+    }
+}
+
+impl TokenSource for ClientCredentials {
+    fn token(&self) -> Result<String> {
+        let mut token = self.token.lock().unwrap_or_else(|err| err.into_inner());
+        let fresh = match *token {
+            Some(Token {
+                expires: Some(expires),
+                ..
+            }) => Instant::now() + EXPIRY_MARGIN < expires,
+            Some(Token { expires: None, .. }) => true,
+            None => false,
+        };
+        if !fresh {
+            *token = Some(self.fetch()?);
+        }
+        Ok(token.as_ref().unwrap().access_token.clone())
+    }
+
+    fn invalidate(&self) {
+        *self.token.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+}
+
+fn form_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'*' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
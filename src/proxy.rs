@@ -0,0 +1,124 @@
+//! Forwarding every call a server gets to another server, to watch and tweak what goes between
+//! a client and a service, like an undocumented vendor API.
+
+use super::error::Result;
+use server::Handler;
+use std::sync::{Arc, Mutex, MutexGuard};
+use {Call, Client, Fault, Params, Response, Server, Url};
+
+type CallRewrite = Box<dyn Fn(&mut Call) + Send + Sync>;
+type ResponseRewrite = Box<dyn Fn(&Call, &mut Response) + Send + Sync>;
+type Observer = Box<dyn Fn(&Call, &Response) + Send + Sync>;
+type NewClient = Box<dyn Fn() -> Result<Client> + Send + Sync>;
+
+pub struct Proxy {
+    upstream: Url,
+    new_client: NewClient,
+    /// Clients not forwarding a call right now, kept so their connections are reused.
+    idle: Mutex<Vec<Client>>,
+    call_rewrites: Vec<CallRewrite>,
+    response_rewrites: Vec<ResponseRewrite>,
+    observers: Vec<Observer>,
+}
+
+impl Proxy {
+    pub fn new(upstream: Url) -> Result<Proxy> {
+        let proxy = Proxy::with_clients(Client::new, upstream);
+        lock(&proxy.idle).push(Client::new()?);
+        Ok(proxy)
+    }
+
+    /// Forwards calls with clients made by `new_client`, one for each call forwarded while the
+    /// others are busy, so calls don't wait on each other. Clients are kept for later calls once
+    /// they're done.
+    pub fn with_clients<T>(new_client: T, upstream: Url) -> Proxy
+    where
+        T: Fn() -> Result<Client> + Send + Sync + 'static,
+    {
+        Proxy {
+            upstream,
+            new_client: Box::new(new_client),
+            idle: Mutex::new(Vec::new()),
+            call_rewrites: Vec::new(),
+            response_rewrites: Vec::new(),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Changes calls before they are forwarded, in the order the rewrites were added.
+    pub fn rewrite_calls<T>(&mut self, rewrite: T)
+    where
+        T: Fn(&mut Call) + Send + Sync + 'static,
+    {
+        self.call_rewrites.push(Box::new(rewrite));
+    }
+
+    /// Changes responses before they are returned, given the call as it was forwarded.
+    pub fn rewrite_responses<T>(&mut self, rewrite: T)
+    where
+        T: Fn(&Call, &mut Response) + Send + Sync + 'static,
+    {
+        self.response_rewrites.push(Box::new(rewrite));
+    }
+
+    /// Sees every call as it was forwarded, and its response as it was returned.
+    pub fn on_exchange<T>(&mut self, observer: T)
+    where
+        T: Fn(&Call, &Response) + Send + Sync + 'static,
+    {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Calls that fail to go through get a transport error fault.
+    pub fn forward(&self, mut call: Call) -> Response {
+        for rewrite in &self.call_rewrites {
+            rewrite(&mut call);
+        }
+        let idle = lock(&self.idle).pop();
+        let response = idle
+            .map_or_else(&self.new_client, Ok)
+            .and_then(|mut client| {
+                let response = client.send_call(&self.upstream, &call);
+                lock(&self.idle).push(client);
+                response
+            });
+        let mut response = response.unwrap_or_else(|err| {
+            debug!("Failed to forward {}: {}", call.name, err);
+            Err(Fault::transport_error(err.to_string()))
+        });
+        for rewrite in &self.response_rewrites {
+            rewrite(&call, &mut response);
+        }
+        for observer in &self.observers {
+            observer(&call, &response);
+        }
+        response
+    }
+
+    /// Forwards every call `server` gets, besides those to methods registered on it.
+    pub fn serve(self, server: &mut Server) {
+        let proxy = Arc::new(self);
+        // The server would otherwise answer this one itself.
+        let introspection = Arc::clone(&proxy);
+        server.register_value("system.methodSignature", move |params| {
+            introspection.forward(Call {
+                name: "system.methodSignature".into(),
+                params,
+            })
+        });
+        server.register_service("", proxy);
+    }
+}
+
+impl Handler for Proxy {
+    fn call(&self, method: &str, params: Params) -> Response {
+        self.forward(Call {
+            name: method.into(),
+            params,
+        })
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -0,0 +1,426 @@
+//! Typed calls for the ROS 1 Master, Parameter Server and Slave APIs.
+//!
+//! Every ROS method takes the caller's ID first, and replies with a `[code, statusMessage,
+//! value]` array. Replies with a code other than `SUCCESS` are returned as faults with the
+//! same code and message.
+
+use super::error::Result;
+use super::server::on_encode_fail;
+use super::xmlfmt::{
+    error, from_value, into_params, to_value, Fault, FromParams, IntoParams, Profile, Response,
+    Value,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std;
+use {Client, Server, Url};
+
+pub const SUCCESS: i32 = 1;
+pub const FAILURE: i32 = 0;
+pub const ERROR: i32 = -1;
+
+/// Topics with their types, like `("/chatter", "std_msgs/String")`.
+pub type Topics = Vec<(String, String)>;
+
+/// Names with the nodes involved with them, like `("/chatter", ["/talker"])`.
+pub type Registrations = Vec<(String, Vec<String>)>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemState {
+    pub publishers: Registrations,
+    pub subscribers: Registrations,
+    pub services: Registrations,
+}
+
+struct Caller {
+    client: Client,
+    uri: Url,
+    caller_id: String,
+}
+
+impl Caller {
+    fn new(uri: Url, caller_id: String) -> Result<Caller> {
+        let mut client = Client::new()?;
+        client.set_profile(Profile::ros());
+        Ok(Caller {
+            client,
+            uri,
+            caller_id,
+        })
+    }
+
+    fn call<Treq, Tres>(
+        &mut self,
+        name: &str,
+        params: Treq,
+    ) -> Result<std::result::Result<Tres, Fault>>
+    where
+        Treq: IntoParams,
+        Tres: DeserializeOwned,
+    {
+        let mut params = into_params(&params)?;
+        params.insert(0, Value::String(self.caller_id.clone()));
+        let mut response = match self.client.call_value(&self.uri, name, params)? {
+            Ok(response) => response,
+            Err(fault) => return Ok(Err(fault)),
+        };
+        let reply = response.pop().unwrap_or(Value::Nil);
+        let (code, message, value): (i32, String, Value) = from_value(reply)?;
+        if code != SUCCESS {
+            return Ok(Err(Fault::new(code, message)));
+        }
+        Ok(Ok(from_value(value)?))
+    }
+
+    /// For methods whose value carries no meaning.
+    fn call_ignored<Treq: IntoParams>(
+        &mut self,
+        name: &str,
+        params: Treq,
+    ) -> Result<std::result::Result<(), Fault>> {
+        self.call::<_, Value>(name, params)
+            .map(|response| response.map(|_| ()))
+    }
+}
+
+/// A client of the ROS Master, which also serves the Parameter Server API.
+pub struct Master {
+    caller: Caller,
+}
+
+impl Master {
+    pub fn new<T: Into<String>>(uri: Url, caller_id: T) -> Result<Master> {
+        Caller::new(uri, caller_id.into()).map(|caller| Master { caller })
+    }
+
+    pub fn register_service(
+        &mut self,
+        service: &str,
+        service_api: &str,
+        caller_api: &str,
+    ) -> Result<std::result::Result<(), Fault>> {
+        self.caller
+            .call_ignored("registerService", (service, service_api, caller_api))
+    }
+
+    /// Returns the number of registrations removed.
+    pub fn unregister_service(
+        &mut self,
+        service: &str,
+        service_api: &str,
+    ) -> Result<std::result::Result<i32, Fault>> {
+        self.caller
+            .call("unregisterService", (service, service_api))
+    }
+
+    /// Returns the XML-RPC URIs of the topic's publishers.
+    pub fn register_subscriber(
+        &mut self,
+        topic: &str,
+        topic_type: &str,
+        caller_api: &str,
+    ) -> Result<std::result::Result<Vec<String>, Fault>> {
+        self.caller
+            .call("registerSubscriber", (topic, topic_type, caller_api))
+    }
+
+    pub fn unregister_subscriber(
+        &mut self,
+        topic: &str,
+        caller_api: &str,
+    ) -> Result<std::result::Result<i32, Fault>> {
+        self.caller
+            .call("unregisterSubscriber", (topic, caller_api))
+    }
+
+    /// Returns the XML-RPC URIs of the topic's subscribers.
+    pub fn register_publisher(
+        &mut self,
+        topic: &str,
+        topic_type: &str,
+        caller_api: &str,
+    ) -> Result<std::result::Result<Vec<String>, Fault>> {
+        self.caller
+            .call("registerPublisher", (topic, topic_type, caller_api))
+    }
+
+    pub fn unregister_publisher(
+        &mut self,
+        topic: &str,
+        caller_api: &str,
+    ) -> Result<std::result::Result<i32, Fault>> {
+        self.caller.call("unregisterPublisher", (topic, caller_api))
+    }
+
+    /// Returns the node's XML-RPC URI.
+    pub fn lookup_node(&mut self, node_name: &str) -> Result<std::result::Result<String, Fault>> {
+        self.caller.call("lookupNode", (node_name,))
+    }
+
+    /// Lists topics with publishers, within the `subgraph` namespace, or everywhere if empty.
+    pub fn get_published_topics(
+        &mut self,
+        subgraph: &str,
+    ) -> Result<std::result::Result<Topics, Fault>> {
+        self.caller.call("getPublishedTopics", (subgraph,))
+    }
+
+    pub fn get_topic_types(&mut self) -> Result<std::result::Result<Topics, Fault>> {
+        self.caller.call("getTopicTypes", ())
+    }
+
+    pub fn get_system_state(&mut self) -> Result<std::result::Result<SystemState, Fault>> {
+        self.caller.call("getSystemState", ()).map(|response| {
+            response.map(|(publishers, subscribers, services)| SystemState {
+                publishers,
+                subscribers,
+                services,
+            })
+        })
+    }
+
+    pub fn get_uri(&mut self) -> Result<std::result::Result<String, Fault>> {
+        self.caller.call("getUri", ())
+    }
+
+    /// Returns the ROSRPC URI of the service.
+    pub fn lookup_service(&mut self, service: &str) -> Result<std::result::Result<String, Fault>> {
+        self.caller.call("lookupService", (service,))
+    }
+
+    pub fn delete_param(&mut self, key: &str) -> Result<std::result::Result<(), Fault>> {
+        self.caller.call_ignored("deleteParam", (key,))
+    }
+
+    pub fn set_param<T: Serialize>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<std::result::Result<(), Fault>> {
+        self.caller.call_ignored("setParam", (key, value))
+    }
+
+    pub fn get_param<T: DeserializeOwned>(
+        &mut self,
+        key: &str,
+    ) -> Result<std::result::Result<T, Fault>> {
+        self.caller.call("getParam", (key,))
+    }
+
+    /// Returns the closest key to the caller's namespace with the given name.
+    pub fn search_param(&mut self, key: &str) -> Result<std::result::Result<String, Fault>> {
+        self.caller.call("searchParam", (key,))
+    }
+
+    /// Returns the current value, which is an empty struct if the parameter isn't set.
+    pub fn subscribe_param(
+        &mut self,
+        caller_api: &str,
+        key: &str,
+    ) -> Result<std::result::Result<Value, Fault>> {
+        self.caller.call("subscribeParam", (caller_api, key))
+    }
+
+    pub fn unsubscribe_param(
+        &mut self,
+        caller_api: &str,
+        key: &str,
+    ) -> Result<std::result::Result<i32, Fault>> {
+        self.caller.call("unsubscribeParam", (caller_api, key))
+    }
+
+    pub fn has_param(&mut self, key: &str) -> Result<std::result::Result<bool, Fault>> {
+        self.caller.call("hasParam", (key,))
+    }
+
+    pub fn get_param_names(&mut self) -> Result<std::result::Result<Vec<String>, Fault>> {
+        self.caller.call("getParamNames", ())
+    }
+}
+
+/// A client of a node's Slave API.
+pub struct Slave {
+    caller: Caller,
+}
+
+impl Slave {
+    pub fn new<T: Into<String>>(uri: Url, caller_id: T) -> Result<Slave> {
+        Caller::new(uri, caller_id.into()).map(|caller| Slave { caller })
+    }
+
+    pub fn get_bus_stats(&mut self) -> Result<std::result::Result<Value, Fault>> {
+        self.caller.call("getBusStats", ())
+    }
+
+    pub fn get_bus_info(&mut self) -> Result<std::result::Result<Value, Fault>> {
+        self.caller.call("getBusInfo", ())
+    }
+
+    pub fn get_master_uri(&mut self) -> Result<std::result::Result<String, Fault>> {
+        self.caller.call("getMasterUri", ())
+    }
+
+    pub fn shutdown(&mut self, message: &str) -> Result<std::result::Result<(), Fault>> {
+        self.caller.call_ignored("shutdown", (message,))
+    }
+
+    pub fn get_pid(&mut self) -> Result<std::result::Result<i32, Fault>> {
+        self.caller.call("getPid", ())
+    }
+
+    pub fn get_subscriptions(&mut self) -> Result<std::result::Result<Topics, Fault>> {
+        self.caller.call("getSubscriptions", ())
+    }
+
+    pub fn get_publications(&mut self) -> Result<std::result::Result<Topics, Fault>> {
+        self.caller.call("getPublications", ())
+    }
+
+    pub fn param_update<T: Serialize>(
+        &mut self,
+        key: &str,
+        value: &T,
+    ) -> Result<std::result::Result<(), Fault>> {
+        self.caller.call_ignored("paramUpdate", (key, value))
+    }
+
+    pub fn publisher_update(
+        &mut self,
+        topic: &str,
+        publishers: &[String],
+    ) -> Result<std::result::Result<(), Fault>> {
+        self.caller
+            .call_ignored("publisherUpdate", (topic, publishers))
+    }
+
+    /// Protocols are listed in order of preference, like `[["TCPROS"]]`, and the chosen one
+    /// is returned with its params, like `["TCPROS", "localhost", 1234]`.
+    pub fn request_topic(
+        &mut self,
+        topic: &str,
+        protocols: &[Vec<Value>],
+    ) -> Result<std::result::Result<Vec<Value>, Fault>> {
+        self.caller.call("requestTopic", (topic, protocols))
+    }
+}
+
+/// The Slave API of a node, served with `register_slave`. Each method gets the caller's ID
+/// first, and failures are replied with the `FAILURE` code and the returned message.
+pub trait SlaveApi {
+    fn get_bus_stats(&self, _caller_id: String) -> std::result::Result<Value, String> {
+        Err("getBusStats is not supported".into())
+    }
+
+    fn get_bus_info(&self, _caller_id: String) -> std::result::Result<Value, String> {
+        Err("getBusInfo is not supported".into())
+    }
+
+    fn get_master_uri(&self, caller_id: String) -> std::result::Result<String, String>;
+
+    fn shutdown(&self, caller_id: String, message: String) -> std::result::Result<(), String>;
+
+    fn get_pid(&self, _caller_id: String) -> std::result::Result<i32, String> {
+        Ok(std::process::id() as i32)
+    }
+
+    fn get_subscriptions(&self, caller_id: String) -> std::result::Result<Topics, String>;
+
+    fn get_publications(&self, caller_id: String) -> std::result::Result<Topics, String>;
+
+    fn param_update(
+        &self,
+        caller_id: String,
+        key: String,
+        value: Value,
+    ) -> std::result::Result<(), String>;
+
+    fn publisher_update(
+        &self,
+        caller_id: String,
+        topic: String,
+        publishers: Vec<String>,
+    ) -> std::result::Result<(), String>;
+
+    fn request_topic(
+        &self,
+        caller_id: String,
+        topic: String,
+        protocols: Vec<Vec<Value>>,
+    ) -> std::result::Result<Vec<Value>, String>;
+}
+
+fn reply<T: Serialize>(
+    result: std::result::Result<T, String>,
+) -> std::result::Result<Value, Fault> {
+    let (code, message, value) = match result {
+        Ok(value) => (
+            SUCCESS,
+            String::new(),
+            to_value(&value)
+                .map_err(|err| Fault::new(500, format!("Failed to encode response: {}", err)))?,
+        ),
+        // ROS clients expect a value even on failure, and ignore it.
+        Err(message) => (FAILURE, message, Value::Int(0)),
+    };
+    Ok(vec![Value::Int(code), Value::String(message), value].into())
+}
+
+fn on_bad_params(err: &error::Error) -> Response {
+    Ok(vec![vec![
+        Value::Int(ERROR),
+        Value::String(format!("Invalid parameters: {}", err)),
+        Value::Int(0),
+    ]
+    .into()])
+}
+
+/// Replies to bad params with the `ERROR` code, rather than a fault.
+fn register<Treq, Thandler>(server: &mut Server, name: &str, handler: Thandler)
+where
+    Treq: FromParams,
+    Thandler: Fn(Treq) -> std::result::Result<Value, Fault> + Send + Sync + 'static,
+{
+    server.register(name, handler, on_encode_fail, on_bad_params);
+}
+
+/// Serves `node`'s Slave API on `server`, which should be set up with `Profile::ros`.
+pub fn register_slave<T: SlaveApi + Send + Sync + 'static>(server: &mut Server, node: T) {
+    use std::sync::Arc;
+
+    let node = Arc::new(node);
+    let n = Arc::clone(&node);
+    register(server, "getBusStats", move |(id,)| {
+        reply(n.get_bus_stats(id))
+    });
+    let n = Arc::clone(&node);
+    register(server, "getBusInfo", move |(id,)| reply(n.get_bus_info(id)));
+    let n = Arc::clone(&node);
+    register(server, "getMasterUri", move |(id,)| {
+        reply(n.get_master_uri(id))
+    });
+    let n = Arc::clone(&node);
+    register(server, "shutdown", move |(id, message)| {
+        reply(n.shutdown(id, message).map(|()| 0))
+    });
+    let n = Arc::clone(&node);
+    register(server, "getPid", move |(id,)| reply(n.get_pid(id)));
+    let n = Arc::clone(&node);
+    register(server, "getSubscriptions", move |(id,)| {
+        reply(n.get_subscriptions(id))
+    });
+    let n = Arc::clone(&node);
+    register(server, "getPublications", move |(id,)| {
+        reply(n.get_publications(id))
+    });
+    let n = Arc::clone(&node);
+    register(server, "paramUpdate", move |(id, key, value)| {
+        reply(n.param_update(id, key, value).map(|()| 0))
+    });
+    let n = Arc::clone(&node);
+    register(server, "publisherUpdate", move |(id, topic, publishers)| {
+        reply(n.publisher_update(id, topic, publishers).map(|()| 0))
+    });
+    register(server, "requestTopic", move |(id, topic, protocols)| {
+        reply(node.request_topic(id, topic, protocols))
+    });
+}
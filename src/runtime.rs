@@ -0,0 +1,155 @@
+//! How `client::Endpoint` makes calls: blocking, or, with the `async` feature, as futures.
+//!
+//! Code generic over `Runtime` is written once for both. Async calls run on a thread pool shared
+//! by every executor, so they work under tokio, async-std or smol alike, and wait for their turn
+//! under a `ConcurrencyLimit` before they take a thread.
+
+#[cfg(feature = "async")]
+use client::Acquire;
+use client::ConcurrencyLimit;
+use error::Error;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+pub trait Runtime: sealed::Sealed + Send + Sync + 'static {
+    /// What a call returns, `T` itself or a future of it.
+    type Output<T: Send + 'static>;
+
+    fn run<T, F>(call: F) -> Self::Output<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static;
+
+    /// Like `run`, once there's room for the call under `limit`, which it holds until it returns.
+    fn run_limited<T, E, F>(limit: Option<ConcurrencyLimit>, call: F) -> Self::Output<Result<T, E>>
+    where
+        T: Send + 'static,
+        E: From<Error> + Send + 'static,
+        F: FnOnce() -> Result<T, E> + Send + 'static;
+}
+
+/// Calls return once they're done.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blocking;
+
+impl sealed::Sealed for Blocking {}
+
+impl Runtime for Blocking {
+    type Output<T: Send + 'static> = T;
+
+    fn run<T, F>(call: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        call()
+    }
+
+    fn run_limited<T, E, F>(limit: Option<ConcurrencyLimit>, call: F) -> Result<T, E>
+    where
+        T: Send + 'static,
+        E: From<Error> + Send + 'static,
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+    {
+        let _permit = limit.as_ref().map(ConcurrencyLimit::acquire).transpose()?;
+        call()
+    }
+}
+
+/// Calls return futures, resolved on a thread pool.
+#[cfg(feature = "async")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Async;
+
+#[cfg(feature = "async")]
+impl sealed::Sealed for Async {}
+
+#[cfg(feature = "async")]
+impl Runtime for Async {
+    type Output<T: Send + 'static> = Task<T>;
+
+    fn run<T, F>(call: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        Task {
+            waiting: None,
+            running: Some(::blocking::unblock(call)),
+        }
+    }
+
+    fn run_limited<T, E, F>(limit: Option<ConcurrencyLimit>, call: F) -> Task<Result<T, E>>
+    where
+        T: Send + 'static,
+        E: From<Error> + Send + 'static,
+        F: FnOnce() -> Result<T, E> + Send + 'static,
+    {
+        match limit {
+            Some(limit) => Task {
+                waiting: Some(Waiting {
+                    acquire: limit.acquire_async(),
+                    call: Box::new(call),
+                    fail: |err| Err(E::from(err)),
+                }),
+                running: None,
+            },
+            None => Async::run(call),
+        }
+    }
+}
+
+/// A call made by `Async`, which only takes a thread from the pool once it's its turn.
+///
+/// Dropping it cancels the call, unless it's already running.
+#[cfg(feature = "async")]
+pub struct Task<T> {
+    waiting: Option<Waiting<T>>,
+    running: Option<::blocking::Task<T>>,
+}
+
+#[cfg(feature = "async")]
+struct Waiting<T> {
+    acquire: Acquire,
+    call: Box<dyn FnOnce() -> T + Send>,
+    /// What the call returns when it can't get its turn.
+    fail: fn(Error) -> T,
+}
+
+#[cfg(feature = "async")]
+impl<T: Send + 'static> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let task = self.get_mut();
+        if let Some(mut waiting) = task.waiting.take() {
+            match Pin::new(&mut waiting.acquire).poll(cx) {
+                Poll::Pending => {
+                    task.waiting = Some(waiting);
+                    return Poll::Pending;
+                }
+                Poll::Ready(Err(err)) => return Poll::Ready((waiting.fail)(err)),
+                Poll::Ready(Ok(permit)) => {
+                    let call = waiting.call;
+                    task.running = Some(::blocking::unblock(move || {
+                        let _permit = permit;
+                        call()
+                    }));
+                }
+            }
+        }
+        let running = task
+            .running
+            .as_mut()
+            .expect("Task polled after it finished");
+        Pin::new(running).poll(cx)
+    }
+}
@@ -0,0 +1,79 @@
+//! Credentials that stay out of logs and are compared without leaking timing.
+
+use std::fmt;
+use std::hint;
+
+/// A password, key or token, shown as `[redacted]` by `Debug` and `Display`.
+///
+/// Comparisons take the same time wherever the values differ, so they don't tell an attacker
+/// how much of a guess was right.
+#[derive(Clone, Default)]
+pub struct Secret<T = String>(T);
+
+impl<T> Secret<T> {
+    pub fn new(secret: T) -> Secret<T> {
+        Secret(secret)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(secret: T) -> Secret<T> {
+        Secret(secret)
+    }
+}
+
+impl<'a> From<&'a str> for Secret<String> {
+    fn from(secret: &'a str) -> Secret<String> {
+        Secret(secret.into())
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl<T: AsRef<[u8]>> PartialEq for Secret<T> {
+    fn eq(&self, other: &Secret<T>) -> bool {
+        constant_time_eq(self.0.as_ref(), other.0.as_ref())
+    }
+}
+
+impl<T: AsRef<[u8]>> Eq for Secret<T> {}
+
+impl PartialEq<str> for Secret<String> {
+    fn eq(&self, other: &str) -> bool {
+        constant_time_eq(self.0.as_bytes(), other.as_bytes())
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Secret<String> {
+    fn eq(&self, other: &&'a str) -> bool {
+        constant_time_eq(self.0.as_bytes(), other.as_bytes())
+    }
+}
+
+/// Compares every byte whatever the result, only taking less time for different lengths.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y));
+    // Keeps the compiler from stopping at the first difference.
+    hint::black_box(difference) == 0
+}
@@ -1,42 +1,529 @@
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
 use rouille;
-use serde::{Deserialize, Serialize};
+use secret::{constant_time_eq, Secret};
+use serde::Serialize;
 use std;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tiny_http;
+use trace::TraceContext;
 
-use super::error::{ErrorKind, Result};
-use super::xmlfmt::{error, from_params, into_params, parse, Call, Fault, Response, Value};
+use super::error::{ErrorKind, Result, ResultExt};
+use super::xmlfmt::{
+    charset, error, from_params_with, multicall, parse, signature, to_value_with, Call,
+    DeserializeOptions, Fault, FromParams, MulticallResponse, Params, ParamsReader, ParseOptions,
+    Profile, Response, ResponseStream, SerializeOptions, Signature, StreamedParam, Value,
+    WriteOptions,
+};
 
-type Handler = Box<dyn Fn(Vec<Value>) -> Response + Send + Sync>;
-type HandlerMap = HashMap<String, Handler>;
+type BoxedHandler = Box<dyn Fn(Vec<Value>) -> Response + Send + Sync>;
+type HandlerMap = HashMap<String, BoxedHandler>;
+type StreamingHandler = Box<
+    dyn Fn(Params) -> std::result::Result<Box<dyn Iterator<Item = StreamedParam> + Send>, Fault>
+        + Send
+        + Sync,
+>;
+type ErrorMapper = Box<dyn Fn(&(dyn std::error::Error + 'static)) -> Fault + Send + Sync>;
+type DateHeader = Box<dyn Fn() -> String + Send + Sync>;
 
+/// A service object handling many methods, registered with `Server::register_service`.
+pub trait Handler: Send + Sync {
+    /// Gets the name of the method with the service's prefix stripped.
+    fn call(&self, method: &str, params: Params) -> Response;
+}
+
+/// A whole API dispatched in one place, set with `Server::set_service` or run with `serve`, as
+/// an alternative to registering a closure for each method.
+pub trait XmlRpcService: Send + Sync {
+    fn dispatch(&self, call: Call, ctx: &Ctx) -> Response;
+}
+
+impl<T> XmlRpcService for T
+where
+    T: Fn(Call, &Ctx) -> Response + Send + Sync,
+{
+    fn dispatch(&self, call: Call, ctx: &Ctx) -> Response {
+        self(call, ctx)
+    }
+}
+
+/// Dispatches to the methods registered on the server, for composing it into another service.
+impl XmlRpcService for Server {
+    fn dispatch(&self, call: Call, ctx: &Ctx) -> Response {
+        self.handle(call, Some(ctx))
+    }
+}
+
+/// What a service is told about the request a call came in.
+#[derive(Clone, Debug, Default)]
+pub struct Ctx {
+    pub remote_addr: Option<SocketAddr>,
+    /// Like `request_id`.
+    pub request_id: Option<String>,
+    pub trace: Option<TraceContext>,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Ctx {
+    fn from_request(request: &rouille::Request) -> Ctx {
+        Ctx {
+            remote_addr: Some(*request.remote_addr()),
+            request_id: request_id(),
+            trace: TraceContext::current(),
+            headers: request
+                .headers()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+
+    /// The first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Serves `service` on `uri` until the process ends.
+pub fn serve<T: XmlRpcService + 'static>(uri: &SocketAddr, service: T) -> Result<()> {
+    let mut server = Server::new();
+    server.set_service(service);
+    server.bind(uri)?.run();
+    Ok(())
+}
+
+/// Answers calls whose params can't be read with a 400 fault, logging why rather than sending
+/// it.
 pub fn on_decode_fail(err: &error::Error) -> Response {
-    Err(Fault::new(
-        400,
-        format!("Failed to decode request: {}", err),
-    ))
+    warn!("Failed to decode request: {}", err);
+    Err(Fault::new(400, "Failed to decode request"))
 }
 
+/// Answers calls whose result can't be written with a 500 fault, logging why rather than
+/// sending it.
 pub fn on_encode_fail(err: &error::Error) -> Response {
-    Err(Fault::new(
-        500,
-        format!("Failed to encode response: {}", err),
-    ))
+    warn!("Failed to encode response: {}", err);
+    Err(Fault::new(500, "Failed to encode response"))
+}
+
+fn on_handler_error(err: &(dyn std::error::Error + 'static)) -> Fault {
+    warn!("Failed to answer a call: {}", err);
+    match err.downcast_ref::<error::Error>().map(error::Error::kind) {
+        Some(&error::ErrorKind::Encoding(_)) => Fault::new(500, "Failed to encode response"),
+        Some(_) => Fault::new(400, "Failed to decode request"),
+        None => Fault::new(500, "Internal error"),
+    }
+}
+
+fn map_error(mapper: &RwLock<ErrorMapper>, err: &error::Error) -> Response {
+    let mapper = mapper.read().unwrap_or_else(|err| err.into_inner());
+    Err(mapper(err))
 }
 
 fn on_missing_method(_: Vec<Value>) -> Response {
     Err(Fault::new(404, "Requested method does not exist"))
 }
 
+const METHOD_SIGNATURE: &str = "system.methodSignature";
+
+/// The HTTP methods the server takes, for `Allow` headers.
+const ALLOW: &str = "POST, OPTIONS";
+
+thread_local! {
+    static REQUEST_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// The ID of the request being handled on this thread, for handlers to log or pass on.
+///
+/// It's the request's `X-Request-Id` header, or else the trace ID of its `traceparent` header,
+/// or else a new one, and is sent back as the `X-Request-Id` header of the response.
+pub fn request_id() -> Option<String> {
+    REQUEST_ID.with(|id| id.borrow().clone())
+}
+
+/// Clears the request ID once the request is handled, even if the handler panics.
+struct RequestIdGuard;
+
+impl RequestIdGuard {
+    fn set(id: String) -> RequestIdGuard {
+        REQUEST_ID.with(|current| *current.borrow_mut() = Some(id));
+        RequestIdGuard
+    }
+}
+
+impl Drop for RequestIdGuard {
+    fn drop(&mut self) {
+        REQUEST_ID.with(|current| *current.borrow_mut() = None);
+    }
+}
+
+fn read_request_id(request: &rouille::Request, trace: Option<&TraceContext>) -> String {
+    let given = request
+        .header("X-Request-Id")
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic()));
+    if let Some(id) = given {
+        return id.into();
+    }
+    match trace {
+        Some(trace) => trace.trace_id.clone(),
+        None => new_request_id(),
+    }
+}
+
+/// Unique within the process, and very likely across processes and hosts too.
+fn new_request_id() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default();
+    format!(
+        "{:016x}{:08x}{:08x}",
+        nanos,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Receives the server's metrics, to pass on to a monitoring system.
+///
+/// Gauges are `requests_in_flight`, `requests_queued` for calls waiting on a method's
+/// concurrency limit, and `worker_utilization` between 0 and 1 when the number of workers is
+/// set. The counter is `requests_shed`, for calls rejected by a concurrency limit.
+pub trait MetricsSink: Send + Sync {
+    fn gauge(&self, name: &str, value: f64);
+    fn increment(&self, name: &str, by: u64);
+}
+
+#[derive(Default)]
+struct Metrics {
+    sink: Option<Arc<dyn MetricsSink>>,
+    workers: Option<usize>,
+    in_flight: AtomicUsize,
+    queued: AtomicUsize,
+}
+
+impl Metrics {
+    fn started(&self) -> Tracked<'_> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.report_in_flight(in_flight);
+        Tracked(self, true)
+    }
+
+    fn waiting(&self) -> Tracked<'_> {
+        let queued = self.queued.fetch_add(1, Ordering::SeqCst) + 1;
+        self.gauge("requests_queued", queued as f64);
+        Tracked(self, false)
+    }
+
+    fn shed(&self) {
+        if let Some(ref sink) = self.sink {
+            sink.increment("requests_shed", 1);
+        }
+    }
+
+    fn report_in_flight(&self, in_flight: usize) {
+        self.gauge("requests_in_flight", in_flight as f64);
+        if let Some(workers) = self.workers {
+            self.gauge("worker_utilization", in_flight as f64 / workers as f64);
+        }
+    }
+
+    fn gauge(&self, name: &str, value: f64) {
+        if let Some(ref sink) = self.sink {
+            sink.gauge(name, value);
+        }
+    }
+}
+
+/// Counts a request as in flight, or as queued, until dropped.
+struct Tracked<'a>(&'a Metrics, bool);
+
+impl<'a> Drop for Tracked<'a> {
+    fn drop(&mut self) {
+        let metrics = self.0;
+        if self.1 {
+            let in_flight = metrics.in_flight.fetch_sub(1, Ordering::SeqCst) - 1;
+            metrics.report_in_flight(in_flight);
+        } else {
+            let queued = metrics.queued.fetch_sub(1, Ordering::SeqCst) - 1;
+            metrics.gauge("requests_queued", queued as f64);
+        }
+    }
+}
+
+/// What calls to a method do once as many as its limit allows are running.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Saturation {
+    /// Wait for a running call to finish.
+    Queue,
+    /// Fail at once with a system error fault.
+    Reject,
+    /// Wait for a running call with the same params and answer with its response, or queue if
+    /// there's none.
+    Coalesce,
+}
+
+struct MethodLimit {
+    max: usize,
+    saturation: Saturation,
+    state: Mutex<LimitState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct LimitState {
+    next_id: u64,
+    running: Vec<RunningCall>,
+    /// Responses of finished calls still owed to the coalesced calls waiting on them.
+    results: HashMap<u64, (Response, usize)>,
+}
+
+struct RunningCall {
+    id: u64,
+    params: Params,
+    waiters: usize,
+}
+
+impl MethodLimit {
+    fn lock(&self) -> MutexGuard<'_, LimitState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn run(
+        &self,
+        name: &str,
+        params: Params,
+        handler: &dyn Fn(Params) -> Response,
+        metrics: &Metrics,
+    ) -> Response {
+        let mut state = self.lock();
+        while state.running.len() >= self.max {
+            match self.saturation {
+                Saturation::Reject => {
+                    metrics.shed();
+                    return Err(Fault::system_error(format!(
+                        "Too many concurrent calls to {}",
+                        name
+                    )));
+                }
+                Saturation::Coalesce => {
+                    if let Some(call) = state.running.iter_mut().find(|c| c.params == params) {
+                        call.waiters += 1;
+                        let id = call.id;
+                        let _waiting = metrics.waiting();
+                        return self.wait_for(state, id);
+                    }
+                }
+                Saturation::Queue => {}
+            }
+            let _waiting = metrics.waiting();
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+        let id = state.next_id;
+        state.next_id += 1;
+        state.running.push(RunningCall {
+            id,
+            params: match self.saturation {
+                Saturation::Coalesce => params.clone(),
+                _ => Params::new(),
+            },
+            waiters: 0,
+        });
+        drop(state);
+
+        let mut finish = FinishCall {
+            limit: self,
+            id,
+            response: None,
+        };
+        let response = handler(params);
+        finish.response = Some(response.clone());
+        response
+    }
+
+    fn wait_for(&self, state: MutexGuard<LimitState>, id: u64) -> Response {
+        let mut state = self
+            .changed
+            .wait_while(state, |state| !state.results.contains_key(&id))
+            .unwrap_or_else(|err| err.into_inner());
+        let (response, waiters) = state.results.get_mut(&id).expect("Waited for the result");
+        let response = response.clone();
+        *waiters -= 1;
+        if *waiters == 0 {
+            state.results.remove(&id);
+        }
+        response
+    }
+}
+
+/// Frees the call's place once it's done, even if its handler panics.
+struct FinishCall<'a> {
+    limit: &'a MethodLimit,
+    id: u64,
+    response: Option<Response>,
+}
+
+impl<'a> Drop for FinishCall<'a> {
+    fn drop(&mut self) {
+        let mut state = self.limit.lock();
+        if let Some(index) = state.running.iter().position(|call| call.id == self.id) {
+            let call = state.running.remove(index);
+            if call.waiters > 0 {
+                let response = self
+                    .response
+                    .take()
+                    .unwrap_or_else(|| Err(Fault::system_error("The coalesced call failed")));
+                state.results.insert(self.id, (response, call.waiters));
+            }
+        }
+        self.limit.changed.notify_all();
+    }
+}
+
+/// Nonces of protected calls seen within the window, with their timestamps.
+struct ReplayGuard {
+    methods: HashSet<String>,
+    window: Duration,
+    seen: Mutex<HashMap<String, u64>>,
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        ReplayGuard {
+            methods: HashSet::new(),
+            window: Duration::from_secs(300),
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl ReplayGuard {
+    /// Takes the nonce and timestamp from the `X-Nonce` and `X-Timestamp` headers, or else from
+    /// the `nonce` and `timestamp` members of a struct as the first param.
+    fn check(&self, call: &Call, request: &rouille::Request) -> std::result::Result<(), Fault> {
+        if !self.methods.contains(&call.name) {
+            return Ok(());
+        }
+        let member = |name| call.params.first().and_then(|param| param.get(name));
+        let nonce = request
+            .header("X-Nonce")
+            .map(str::to_owned)
+            .or_else(|| member("nonce").and_then(Value::as_str).map(str::to_owned))
+            .filter(|nonce| !nonce.is_empty() && nonce.len() <= 128)
+            .ok_or_else(|| Fault::invalid_params("Missing nonce"))?;
+        let timestamp = request
+            .header("X-Timestamp")
+            .and_then(|timestamp| timestamp.trim().parse().ok())
+            .or_else(|| member("timestamp").and_then(Value::as_i64))
+            .and_then(|timestamp| u64::try_from(timestamp).ok())
+            .ok_or_else(|| Fault::invalid_params("Missing timestamp"))?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        let window = self.window.as_secs();
+        if timestamp.abs_diff(now) > window {
+            return Err(Fault::replayed_call(format!(
+                "Timestamp {} is too far from the server's time",
+                timestamp
+            )));
+        }
+        let mut seen = self.seen.lock().unwrap_or_else(|err| err.into_inner());
+        // Nonces older than the window can go, as their calls would be rejected as stale.
+        seen.retain(|_, &mut seen_at| now.saturating_sub(seen_at) <= window);
+        if seen.insert(nonce, timestamp).is_some() {
+            return Err(Fault::replayed_call("Nonce was already used"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn reuse_port_listener(addr: &SocketAddr) -> std::io::Result<std::net::TcpListener> {
+    use socket2::{Domain, Socket, Type};
+    let socket = Socket::new(Domain::for_address(*addr), Type::STREAM, None)?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    socket.bind(&(*addr).into())?;
+    socket.listen(128)?;
+    Ok(socket.into())
+}
+
+/// Whether a `Content-Type` names an XML media type, whatever its parameters.
+fn is_xml(content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    media_type == "text/xml" || media_type == "application/xml" || media_type.ends_with("+xml")
+}
+
 pub struct Server {
     handlers: HandlerMap,
-    on_missing_method: Handler,
+    services: Vec<(String, Arc<dyn Handler>)>,
+    streaming: HashMap<String, StreamingHandler>,
+    signatures: HashMap<String, Vec<Signature>>,
+    limits: HashMap<String, MethodLimit>,
+    replays: ReplayGuard,
+    on_missing_method: BoxedHandler,
+    service: Option<Arc<dyn XmlRpcService>>,
+    error_mapper: Arc<RwLock<ErrorMapper>>,
+    serialize_options: SerializeOptions,
+    deserialize_options: DeserializeOptions,
+    parse_options: ParseOptions,
+    write_options: WriteOptions,
+    content_type: String,
+    strict_content_type: bool,
+    server_name: Option<String>,
+    date_header: Option<DateHeader>,
+    basic_auth: Option<(String, Secret)>,
+    answer_probes: bool,
+    cors_origins: Vec<String>,
+    metrics: Metrics,
 }
 
 impl Default for Server {
     fn default() -> Self {
         Server {
             handlers: HashMap::new(),
+            services: Vec::new(),
+            streaming: HashMap::new(),
+            signatures: HashMap::new(),
+            limits: HashMap::new(),
+            replays: ReplayGuard::default(),
             on_missing_method: Box::new(on_missing_method),
+            service: None,
+            error_mapper: Arc::new(RwLock::new(Box::new(on_handler_error))),
+            serialize_options: SerializeOptions::default(),
+            deserialize_options: DeserializeOptions::default(),
+            parse_options: ParseOptions::default(),
+            write_options: WriteOptions::default(),
+            content_type: "text/xml; charset=utf-8".into(),
+            strict_content_type: false,
+            server_name: None,
+            date_header: None,
+            basic_auth: None,
+            answer_probes: true,
+            cors_origins: Vec::new(),
+            metrics: Metrics::default(),
         }
     }
 }
@@ -46,6 +533,85 @@ impl Server {
         Server::default()
     }
 
+    /// Applies to methods registered after this call.
+    pub fn set_serialize_options(&mut self, options: SerializeOptions) {
+        self.serialize_options = options;
+    }
+
+    /// Applies to methods registered after this call.
+    pub fn set_deserialize_options(&mut self, options: DeserializeOptions) {
+        self.deserialize_options = options;
+    }
+
+    pub fn set_parse_options(&mut self, options: ParseOptions) {
+        self.parse_options = options;
+    }
+
+    pub fn set_write_options(&mut self, options: WriteOptions) {
+        self.write_options = options;
+    }
+
+    /// Sets the options of every step of a call at once. Serialize and deserialize options apply
+    /// to methods registered after this call.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.serialize_options = profile.serialize;
+        self.deserialize_options = profile.deserialize;
+        self.parse_options = profile.parse;
+        self.write_options = profile.write;
+    }
+
+    /// Sent with responses, `text/xml; charset=utf-8` unless changed, like to `application/xml`
+    /// for peers that insist on it.
+    pub fn set_content_type<T: Into<String>>(&mut self, content_type: T) {
+        self.content_type = content_type.into();
+    }
+
+    /// Answers requests without an XML `Content-Type`, like `text/xml`, `application/xml` or
+    /// `application/soap+xml`, with `415 Unsupported Media Type`. Any are accepted by default.
+    pub fn set_strict_content_type(&mut self, strict: bool) {
+        self.strict_content_type = strict;
+    }
+
+    /// Sent as the `Server` header of responses, instead of the HTTP server's own name.
+    ///
+    /// Responses always carry a `Date` header, and a `Content-Length` unless streamed by a
+    /// method registered with `register_streaming`, or sent chunked to a client asking for it.
+    pub fn set_server_name<T: Into<String>>(&mut self, name: T) {
+        self.server_name = Some(name.into());
+    }
+
+    /// Makes the `Date` header of each response, instead of sending the current time, like to
+    /// send a fixed one from tests. A response can't be sent without one.
+    pub fn set_date_header<T>(&mut self, date: T)
+    where
+        T: Fn() -> String + Send + Sync + 'static,
+    {
+        self.date_header = Some(Box::new(date));
+    }
+
+    /// Answers requests without these HTTP Basic credentials with `401 Unauthorized`.
+    pub fn set_basic_auth<U, P>(&mut self, username: U, password: P)
+    where
+        U: Into<String>,
+        P: Into<Secret>,
+    {
+        self.basic_auth = Some((username.into(), password.into()));
+    }
+
+    /// Answers `OPTIONS` requests with the methods the server takes, and `HEAD` ones with the
+    /// headers of a response, which monitoring sends to check on the server. On by default, and
+    /// otherwise they're rejected as invalid calls.
+    pub fn set_answer_probes(&mut self, answer: bool) {
+        self.answer_probes = answer;
+    }
+
+    /// Lets pages from `origin`, like `https://example.com`, or from anywhere with `*`, call the
+    /// server from browsers, answering their CORS preflight requests. Origins named this way may
+    /// send credentials, like cookies or HTTP Basic ones, while those only allowed by `*` may not.
+    pub fn allow_cors_origin<K: Into<String>>(&mut self, origin: K) {
+        self.cors_origins.push(origin.into());
+    }
+
     pub fn register_value<K, T>(&mut self, name: K, handler: T)
     where
         K: Into<String>,
@@ -54,7 +620,13 @@ impl Server {
         self.handlers.insert(name.into(), Box::new(handler));
     }
 
-    pub fn register<'a, K, Treq, Tres, Thandler, Tef, Tdf>(
+    /// Serves a method taking params read like `from_params`, and answering with its result as
+    /// the response's only param, as the spec allows no more. A result that is a tuple or `Vec`
+    /// is sent as a single array, rather than spread over several params.
+    ///
+    /// `decode_fail` answers calls whose params can't be read, and `encode_fail` results that
+    /// can't be written.
+    pub fn register<K, Treq, Tres, Thandler, Tef, Tdf>(
         &mut self,
         name: K,
         handler: Thandler,
@@ -62,30 +634,166 @@ impl Server {
         decode_fail: Tdf,
     ) where
         K: Into<String>,
-        Treq: Deserialize<'a>,
+        Treq: FromParams,
         Tres: Serialize,
         Thandler: Fn(Treq) -> std::result::Result<Tres, Fault> + Send + Sync + 'static,
         Tef: Fn(&error::Error) -> Response + Send + Sync + 'static,
         Tdf: Fn(&error::Error) -> Response + Send + Sync + 'static,
     {
+        let serialize_options = self.serialize_options.clone();
+        let deserialize_options = self.deserialize_options.clone();
         self.register_value(name, move |req| {
-            let params = match from_params(req) {
+            let params = match from_params_with(req, &deserialize_options) {
                 Ok(v) => v,
                 Err(err) => return decode_fail(&err),
             };
             let response = handler(params)?;
-            into_params(&response).or_else(|v| encode_fail(&v))
+            to_value_with(&response, &serialize_options)
+                .map(|v| vec![v])
+                .or_else(|v| encode_fail(&v))
         });
     }
 
-    pub fn register_simple<'a, K, Treq, Tres, Thandler>(&mut self, name: K, handler: Thandler)
+    /// Like `register`, with params that can't be read and results that can't be written turned
+    /// into faults by the function set with `map_errors`.
+    pub fn register_simple<K, Treq, Tres, Thandler>(&mut self, name: K, handler: Thandler)
     where
         K: Into<String>,
-        Treq: Deserialize<'a>,
+        Treq: FromParams,
         Tres: Serialize,
         Thandler: Fn(Treq) -> std::result::Result<Tres, Fault> + Send + Sync + 'static,
     {
-        self.register(name, handler, on_encode_fail, on_decode_fail);
+        let on_encode = Arc::clone(&self.error_mapper);
+        let on_decode = Arc::clone(&self.error_mapper);
+        self.register(
+            name,
+            handler,
+            move |err| map_error(&on_encode, err),
+            move |err| map_error(&on_decode, err),
+        );
+    }
+
+    /// Serves results too big to hold in memory at once, encoding and sending each param only
+    /// once the ones before it are sent, and base64 params a chunk at a time.
+    ///
+    /// Signatures apply, but concurrency limits don't, as the result is produced after the
+    /// handler returns. Calls to it from a `system.multicall` fail with a 400 fault.
+    pub fn register_streaming<K, T, I>(&mut self, name: K, handler: T)
+    where
+        K: Into<String>,
+        T: Fn(Params) -> std::result::Result<I, Fault> + Send + Sync + 'static,
+        I: IntoIterator<Item = StreamedParam>,
+        I::IntoIter: Send + 'static,
+    {
+        self.streaming.insert(
+            name.into(),
+            Box::new(move |params| {
+                handler(params).map(|params| {
+                    Box::new(params.into_iter()) as Box<dyn Iterator<Item = StreamedParam> + Send>
+                })
+            }),
+        );
+    }
+
+    /// Like `register_simple`, but for handlers failing with application errors, which are
+    /// turned into faults by the function set with `map_errors`.
+    pub fn register_fallible<K, Treq, Tres, Terr, Thandler>(&mut self, name: K, handler: Thandler)
+    where
+        K: Into<String>,
+        Treq: FromParams,
+        Tres: Serialize,
+        Terr: Into<Box<dyn std::error::Error + Send + Sync>>,
+        Thandler: Fn(Treq) -> std::result::Result<Tres, Terr> + Send + Sync + 'static,
+    {
+        let error_mapper = Arc::clone(&self.error_mapper);
+        self.register_simple(name, move |params| {
+            handler(params).map_err(|err| match err.into().downcast::<Fault>() {
+                Ok(fault) => *fault,
+                Err(err) => {
+                    let mapper = error_mapper.read().unwrap_or_else(|err| err.into_inner());
+                    mapper(&*err)
+                }
+            })
+        });
+    }
+
+    /// Decides the fault for each error of a handler registered with `register_fallible`, and
+    /// for params that `register_simple` and `register_fallible` can't read or results they
+    /// can't write, which are this crate's `Error`. Faults returned by handlers are sent as they
+    /// are. Applies to handlers registered before as well.
+    ///
+    /// By default, errors are logged and answered without their message, which could give away
+    /// details of the server: with code 400 for params that can't be read, and 500 for the rest.
+    pub fn map_errors<T>(&mut self, mapper: T)
+    where
+        T: Fn(&(dyn std::error::Error + 'static)) -> Fault + Send + Sync + 'static,
+    {
+        *self
+            .error_mapper
+            .write()
+            .unwrap_or_else(|err| err.into_inner()) = Box::new(mapper);
+    }
+
+    /// Serves every method named `prefix` followed by anything, like `math.` for `math.add`,
+    /// through `service`. Methods registered on their own take precedence, and so do services
+    /// with longer prefixes.
+    pub fn register_service<K: Into<String>>(&mut self, prefix: K, service: Arc<dyn Handler>) {
+        let prefix = prefix.into();
+        self.services.retain(|(p, _)| *p != prefix);
+        self.services.push((prefix, service));
+        self.services
+            .sort_by_key(|(p, _)| std::cmp::Reverse(p.len()));
+    }
+
+    /// Params are checked against the method's signatures before its handler runs, and
+    /// `system.methodSignature` lists them.
+    pub fn add_signature<K: Into<String>>(&mut self, name: K, signature: Signature) {
+        self.signatures
+            .entry(name.into())
+            .or_default()
+            .push(signature);
+    }
+
+    /// Runs at most `max` calls to the method at once, with `saturation` deciding what any more
+    /// calls do in the meantime.
+    pub fn limit_concurrency<K: Into<String>>(
+        &mut self,
+        name: K,
+        max: usize,
+        saturation: Saturation,
+    ) {
+        let limit = MethodLimit {
+            max: max.max(1),
+            saturation,
+            state: Mutex::new(LimitState::default()),
+            changed: Condvar::new(),
+        };
+        self.limits.insert(name.into(), limit);
+    }
+
+    /// Rejects calls to the method without a fresh nonce and a timestamp, as unix seconds, in
+    /// the `X-Nonce` and `X-Timestamp` headers, or the `nonce` and `timestamp` members of a
+    /// struct as the first param.
+    ///
+    /// Calls reusing a nonce, or with a timestamp outside the replay window of the server's time,
+    /// fail with a `Fault::replayed_call`.
+    pub fn protect_from_replays<K: Into<String>>(&mut self, name: K) {
+        self.replays.methods.insert(name.into());
+    }
+
+    /// How far timestamps may be from the server's time, and how long nonces are remembered.
+    /// Defaults to 5 minutes.
+    pub fn set_replay_window(&mut self, window: Duration) {
+        self.replays.window = window;
+    }
+
+    pub fn set_metrics_sink<T: MetricsSink + 'static>(&mut self, sink: T) {
+        self.metrics.sink = Some(Arc::new(sink));
+    }
+
+    /// Handles requests on a pool of `workers` threads, instead of a thread for each of them.
+    pub fn set_workers(&mut self, workers: usize) {
+        self.metrics.workers = Some(workers.max(1));
     }
 
     pub fn set_on_missing<T>(&mut self, handler: T)
@@ -95,38 +803,306 @@ impl Server {
         self.on_missing_method = Box::new(handler);
     }
 
+    /// Sends calls to methods without a handler of their own to `service`, rather than the
+    /// missing method handler.
+    pub fn set_service<T: XmlRpcService + 'static>(&mut self, service: T) {
+        self.service = Some(Arc::new(service));
+    }
+
     pub fn bind(
         self,
         uri: &std::net::SocketAddr,
     ) -> Result<BoundServer<impl Fn(&rouille::Request) -> rouille::Response + Send + Sync + 'static>>
     {
-        rouille::Server::new(uri, move |req| self.handle_outer(req))
-            .map_err(|err| ErrorKind::BindFail(err.to_string()).into())
-            .map(BoundServer::new)
+        let server =
+            tiny_http::Server::http(uri).map_err(|err| ErrorKind::BindFail(err.to_string()))?;
+        Ok(self.serve_on(server))
+    }
+
+    /// Like `bind`, but lets other sockets bind the same address and port too, so that several
+    /// processes can share it, with the kernel spreading connections between them.
+    ///
+    /// Every process has to bind with `SO_REUSEPORT`, and run as the same user.
+    #[cfg(unix)]
+    pub fn bind_reuse_port(
+        self,
+        uri: &std::net::SocketAddr,
+    ) -> Result<BoundServer<impl Fn(&rouille::Request) -> rouille::Response + Send + Sync + 'static>>
+    {
+        let server = reuse_port_listener(uri)
+            .map_err(Into::into)
+            .and_then(|listener| tiny_http::Server::from_listener(listener, None))
+            .map_err(|err| ErrorKind::BindFail(err.to_string()))?;
+        Ok(self.serve_on(server))
+    }
+
+    fn serve_on(
+        self,
+        server: tiny_http::Server,
+    ) -> BoundServer<impl Fn(&rouille::Request) -> rouille::Response + Send + Sync + 'static> {
+        let body_limit = self.parse_options.max_size;
+        let workers = self.metrics.workers;
+        BoundServer::new(
+            server,
+            move |req| self.handle_outer(req),
+            body_limit,
+            workers,
+        )
     }
 
     fn handle_outer(&self, request: &rouille::Request) -> rouille::Response {
+        let origin = request.header("Origin").and_then(|origin| {
+            if self.cors_origins.iter().any(|allowed| allowed == origin) {
+                Some(origin)
+            } else if self.cors_origins.iter().any(|allowed| allowed == "*") {
+                Some("*")
+            } else {
+                None
+            }
+        });
+        let response = match request.method() {
+            "OPTIONS" if self.answer_probes => self.options(request, origin.is_some()),
+            "HEAD" if self.answer_probes => self.head(),
+            _ => self.handle_call(request),
+        };
+        let response = match self.server_name {
+            Some(ref name) => response.with_unique_header("Server", name.clone()),
+            None => response,
+        };
+        let response = match self.date_header {
+            Some(ref date) => response.with_unique_header("Date", date()),
+            None => response,
+        };
+        match origin {
+            // Browsers only send cookies and credentials to origins that are named.
+            Some("*") => response.with_unique_header("Access-Control-Allow-Origin", "*"),
+            Some(origin) => response
+                .with_unique_header("Access-Control-Allow-Origin", origin.to_owned())
+                .with_unique_header("Access-Control-Allow-Credentials", "true")
+                .with_additional_header("Vary", "Origin"),
+            None => response,
+        }
+    }
+
+    fn options(&self, request: &rouille::Request, cors: bool) -> rouille::Response {
+        let response = rouille::Response::empty_204().with_unique_header("Allow", ALLOW);
+        if !cors || request.header("Access-Control-Request-Method").is_none() {
+            return response;
+        }
+        let headers = request
+            .header("Access-Control-Request-Headers")
+            .unwrap_or("Content-Type")
+            .to_owned();
+        response
+            .with_unique_header("Access-Control-Allow-Methods", ALLOW)
+            .with_unique_header("Access-Control-Allow-Headers", headers)
+            .with_unique_header("Access-Control-Max-Age", "86400")
+    }
+
+    fn head(&self) -> rouille::Response {
+        rouille::Response {
+            status_code: 200,
+            headers: vec![
+                ("Content-Type".into(), self.content_type.clone().into()),
+                ("Allow".into(), ALLOW.into()),
+            ],
+            data: rouille::ResponseBody::empty(),
+            upgrade: None,
+        }
+    }
+
+    fn handle_call(&self, request: &rouille::Request) -> rouille::Response {
+        let _in_flight = self.metrics.started();
+        let trace = request
+            .header("traceparent")
+            .and_then(|header| TraceContext::from_headers(header, request.header("tracestate")));
+        let id = read_request_id(request, trace.as_ref());
+        let response = {
+            let _guard = RequestIdGuard::set(id.clone());
+            let _trace = trace.map(TraceContext::enter);
+            self.respond(request)
+        };
+        response.with_unique_header("X-Request-Id", id)
+    }
+
+    fn respond(&self, request: &rouille::Request) -> rouille::Response {
         use super::xmlfmt::value::ToXml;
 
+        if let Some((ref username, ref password)) = self.basic_auth {
+            let authorized = rouille::input::basic_http_auth(request).is_some_and(|auth| {
+                // Both are checked in full, so timing doesn't tell which one was wrong.
+                constant_time_eq(auth.login.as_bytes(), username.as_bytes())
+                    & (*password == *auth.password)
+            });
+            if !authorized {
+                return rouille::Response::basic_http_auth_login_required("XML-RPC");
+            }
+        }
+        if self.strict_content_type && !request.header("Content-Type").is_some_and(is_xml) {
+            return rouille::Response::empty_400().with_status_code(415);
+        }
         let body = match request.data() {
             Some(data) => data,
             None => return rouille::Response::empty_400(),
         };
+        // Parsing stops at `max_size` either way, so compressed calls can't grow past it.
+        let body: Box<dyn Read> = match request.header("Content-Encoding").map(str::trim) {
+            None | Some("") => Box::new(body),
+            Some(encoding) if encoding.eq_ignore_ascii_case("identity") => Box::new(body),
+            Some(encoding)
+                if encoding.eq_ignore_ascii_case("gzip")
+                    || encoding.eq_ignore_ascii_case("x-gzip") =>
+            {
+                Box::new(MultiGzDecoder::new(body))
+            }
+            Some(encoding) if encoding.eq_ignore_ascii_case("deflate") => {
+                Box::new(ZlibDecoder::new(body))
+            }
+            Some(encoding) => {
+                debug!("Rejecting a call sent as {}", encoding);
+                return rouille::Response::empty_400().with_status_code(415);
+            }
+        };
 
         // TODO: use the right error type
-        let call: Call = match parse::call(body) {
+        let options = ParseOptions {
+            charset: request
+                .header("Content-Type")
+                .and_then(charset::from_content_type)
+                .map(Into::into),
+            ..self.parse_options.clone()
+        };
+        let call: Call = match parse::call_with(body, &options) {
             Ok(data) => data,
-            Err(_err) => return rouille::Response::empty_400(),
+            Err(err) => {
+                debug!("Failed to parse a call: {}", err);
+                return rouille::Response::empty_400();
+            }
         };
-        let res = self.handle(call);
-        let body = res.to_xml();
-        rouille::Response::from_data("text/xml", body)
+        debug!("Dispatching {}", call.name);
+        let ctx = self.service.as_ref().map(|_| Ctx::from_request(request));
+        let data = match self.replays.check(&call, request) {
+            Err(fault) => {
+                let response: Response = Err(fault);
+                rouille::ResponseBody::from_data(response.to_xml_with(&self.write_options))
+            }
+            Ok(()) => match self.streaming.get(&call.name) {
+                Some(handler) => self.stream(handler, call),
+                None => rouille::ResponseBody::from_data(
+                    self.handle(call, ctx.as_ref())
+                        .to_xml_with(&self.write_options),
+                ),
+            },
+        };
+        rouille::Response {
+            status_code: 200,
+            headers: vec![("Content-Type".into(), self.content_type.clone().into())],
+            data,
+            upgrade: None,
+        }
     }
 
-    fn handle(&self, req: Call) -> Response {
-        self.handlers
-            .get(&req.name)
-            .unwrap_or(&self.on_missing_method)(req.params)
+    fn handle(&self, req: Call, ctx: Option<&Ctx>) -> Response {
+        let call_service;
+        let handler: &dyn Fn(Params) -> Response = match self.handlers.get(&req.name) {
+            Some(handler) => handler,
+            None if req.name == METHOD_SIGNATURE => return self.method_signature(req.params),
+            None if req.name == multicall::METHOD => return self.multicall(req.params, ctx),
+            None => match self.service_for(&req.name) {
+                Some((service, method)) => {
+                    call_service = move |params| service.call(method, params);
+                    &call_service
+                }
+                None => match self.service {
+                    Some(ref service) => {
+                        return service.dispatch(req, ctx.unwrap_or(&Ctx::default()))
+                    }
+                    None => return (self.on_missing_method)(req.params),
+                },
+            },
+        };
+        let params = match self.signatures.get(&req.name) {
+            Some(signatures) => signature::coerce_any(signatures, req.params)?,
+            None => req.params,
+        };
+        match self.limits.get(&req.name) {
+            Some(limit) => limit.run(&req.name, params, handler, &self.metrics),
+            None => handler(params),
+        }
+    }
+
+    fn stream(&self, handler: &StreamingHandler, req: Call) -> rouille::ResponseBody {
+        use super::xmlfmt::value::ToXml;
+
+        let params = match self.signatures.get(&req.name) {
+            Some(signatures) => signature::coerce_any(signatures, req.params),
+            None => Ok(req.params),
+        };
+        match params.and_then(handler) {
+            Ok(params) => rouille::ResponseBody::from_reader(ResponseStream::new(
+                params,
+                self.write_options.clone(),
+            )),
+            Err(fault) => {
+                let response: Response = Err(fault);
+                rouille::ResponseBody::from_data(response.to_xml_with(&self.write_options))
+            }
+        }
+    }
+
+    fn service_for<'a>(&'a self, name: &'a str) -> Option<(&'a dyn Handler, &'a str)> {
+        self.services.iter().find_map(|(prefix, service)| {
+            name.strip_prefix(prefix.as_str())
+                .map(|method| (&**service, method))
+        })
+    }
+
+    fn multicall(&self, params: Params, ctx: Option<&Ctx>) -> Response {
+        let results = multicall::calls(params)?
+            .into_iter()
+            .map(|call| match call {
+                Ok(call) if call.name == multicall::METHOD => (
+                    call.name,
+                    Err(Fault::new(400, "Recursive system.multicall is not allowed")),
+                ),
+                // Nonces are only checked for calls made on their own, and streamed results
+                // can't be held in the array.
+                Ok(call)
+                    if self.replays.methods.contains(&call.name)
+                        || self.streaming.contains_key(&call.name) =>
+                {
+                    (
+                        call.name,
+                        Err(Fault::new(400, "Method can't be called in a multicall")),
+                    )
+                }
+                Ok(call) => (call.name.clone(), self.handle(call, ctx)),
+                Err(fault) => (String::new(), Err(fault)),
+            })
+            .collect::<MulticallResponse>();
+        Ok(vec![results.into_value()])
+    }
+
+    fn method_signature(&self, params: Vec<Value>) -> Response {
+        let mut params = ParamsReader::new(params);
+        let name: String = params.next()?;
+        params.finish()?;
+        if !self.handlers.contains_key(&name)
+            && !self.streaming.contains_key(&name)
+            && self.service_for(&name).is_none()
+        {
+            return on_missing_method(Vec::new());
+        }
+        // Introspection returns a non-array for methods without signatures.
+        Ok(vec![match self.signatures.get(&name) {
+            Some(signatures) => signatures
+                .iter()
+                .cloned()
+                .map(Value::from)
+                .collect::<Vec<_>>()
+                .into(),
+            None => Value::String("undef".into()),
+        }])
     }
 }
 
@@ -134,27 +1110,276 @@ pub struct BoundServer<F>
 where
     F: Send + Sync + 'static + Fn(&rouille::Request) -> rouille::Response,
 {
-    server: rouille::Server<F>,
-    // server: hyper::Server<NewService, hyper::Body>,
+    server: tiny_http::Server,
+    handler: Arc<F>,
+    body_limit: usize,
+    executor: Executor,
+    in_flight: Arc<InFlight>,
+    stop: StopHandle,
 }
 
 impl<F> BoundServer<F>
 where
     F: Send + Sync + 'static + Fn(&rouille::Request) -> rouille::Response,
 {
-    fn new(server: rouille::Server<F>) -> Self {
-        Self { server }
+    fn new(
+        server: tiny_http::Server,
+        handler: F,
+        body_limit: usize,
+        workers: Option<usize>,
+    ) -> Self {
+        Self {
+            server,
+            handler: Arc::new(handler),
+            body_limit,
+            executor: Executor::new(workers),
+            in_flight: Arc::default(),
+            stop: StopHandle::default(),
+        }
     }
 
     pub fn local_addr(&self) -> std::net::SocketAddr {
-        self.server.server_addr()
+        self.server
+            .server_addr()
+            .to_ip()
+            .expect("Servers only listen on TCP sockets")
     }
 
+    /// Serves until stopped through `stop_handle`.
     pub fn run(self) {
-        self.server.run()
+        while !self.stop.is_stopped() {
+            self.poll_timeout(STOP_CHECK_INTERVAL);
+        }
+    }
+
+    /// Serves until stopped through `stop_handle`, and then shuts down like `shutdown`.
+    pub fn run_and_drain(self, deadline: std::time::Duration) -> usize {
+        while !self.stop.is_stopped() {
+            self.poll_timeout(STOP_CHECK_INTERVAL);
+        }
+        self.shutdown(deadline)
+    }
+
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
     }
 
     pub fn poll(&self) {
-        self.server.poll()
+        while let Ok(Some(request)) = self.server.try_recv() {
+            self.process(request);
+        }
+    }
+
+    /// Like `poll`, but waits up to `timeout` for a request to come in.
+    pub fn poll_timeout(&self, timeout: std::time::Duration) {
+        while let Ok(Some(request)) = self.server.recv_timeout(timeout) {
+            self.process(request);
+        }
+    }
+
+    /// Stops taking connections, handles the requests already received, and waits up to
+    /// `deadline` for all handlers to finish.
+    ///
+    /// Returns how many handlers were still running by the deadline. They are abandoned, and
+    /// their connections close once they finish or the process exits.
+    pub fn shutdown(self, deadline: std::time::Duration) -> usize {
+        self.poll();
+        let BoundServer {
+            server, in_flight, ..
+        } = self;
+        drop(server);
+        let running = in_flight
+            .running
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let (running, _) = in_flight
+            .finished
+            .wait_timeout_while(running, deadline, |running| *running > 0)
+            .unwrap_or_else(|err| err.into_inner());
+        *running
+    }
+
+    fn process(&self, request: tiny_http::Request) {
+        let handler = Arc::clone(&self.handler);
+        let body_limit = self.body_limit;
+        let in_flight = InFlight::start(&self.in_flight);
+        self.executor.execute(Box::new(move || {
+            respond(request, &*handler, body_limit);
+            drop(in_flight);
+        }));
+    }
+}
+
+const STOP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+static SIGNALLED: AtomicBool = AtomicBool::new(false);
+
+/// Stops a running server from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct StopHandle {
+    stopped: Arc<AtomicBool>,
+    on_signals: Arc<AtomicBool>,
+}
+
+impl StopHandle {
+    /// Makes the server return from `run` within a moment.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+            || self.on_signals.load(Ordering::SeqCst) && SIGNALLED.load(Ordering::SeqCst)
+    }
+
+    /// Stops the server on `SIGTERM` or `SIGINT`, replacing any handlers installed for them.
+    #[cfg(unix)]
+    pub fn stop_on_signals(&self) -> Result<()> {
+        extern "C" fn on_signal(_: libc::c_int) {
+            SIGNALLED.store(true, Ordering::SeqCst);
+        }
+
+        for &signal in &[libc::SIGTERM, libc::SIGINT] {
+            // Safe, as the handler only stores into an atomic.
+            let installed = unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as usize;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+                libc::sigaction(signal, &action, std::ptr::null_mut())
+            };
+            if installed != 0 {
+                return Err(std::io::Error::last_os_error())
+                    .chain_err(|| "Failed to install signal handlers");
+            }
+        }
+        self.on_signals.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Requests being handled, for shutting down once they're done.
+#[derive(Default)]
+struct InFlight {
+    running: Mutex<usize>,
+    finished: Condvar,
+}
+
+impl InFlight {
+    fn start(in_flight: &Arc<InFlight>) -> InFlightGuard {
+        *in_flight
+            .running
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) += 1;
+        InFlightGuard(Arc::clone(in_flight))
+    }
+}
+
+struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        *self.0.running.lock().unwrap_or_else(|err| err.into_inner()) -= 1;
+        self.0.finished.notify_all();
+    }
+}
+
+/// Runs the handler on a request, in the handler's own types.
+fn respond<F>(mut request: tiny_http::Request, handler: &F, body_limit: usize)
+where
+    F: Fn(&rouille::Request) -> rouille::Response,
+{
+    // Anything past the limit is left for the parser to reject.
+    let mut data = Vec::new();
+    let read = request
+        .as_reader()
+        .take(body_limit as u64 + 1)
+        .read_to_end(&mut data);
+    if read.is_err() {
+        let _ = request.respond(tiny_http::Response::empty(400));
+        return;
+    }
+    let headers = request
+        .headers()
+        .iter()
+        .map(|header| (header.field.to_string(), header.value.to_string()))
+        .collect();
+    let from = request
+        .remote_addr()
+        .copied()
+        .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 0)));
+    let converted = rouille::Request::fake_http_from(
+        from,
+        request.method().as_str(),
+        request.url(),
+        headers,
+        data,
+    );
+    let response = panic::catch_unwind(AssertUnwindSafe(|| handler(&converted)))
+        .unwrap_or_else(|_| rouille::Response::text("Internal Server Error").with_status_code(500));
+
+    let (body, length) = response.data.into_reader_and_size();
+    let mut reply =
+        tiny_http::Response::new(response.status_code.into(), Vec::new(), body, length, None);
+    // Bodies of a known length are sent with it, however large, as some clients insist on it.
+    if length.is_some() {
+        reply = reply.with_chunked_threshold(usize::MAX);
+    }
+    for (name, value) in response.headers {
+        // The length comes from the body.
+        if name.eq_ignore_ascii_case("Content-Length") {
+            continue;
+        }
+        if let Ok(header) = tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+            reply.add_header(header);
+        }
+    }
+    let _ = request.respond(reply);
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs each request on a new thread, or on a fixed pool of them.
+enum Executor {
+    Threads,
+    Pool(Mutex<mpsc::Sender<Job>>),
+}
+
+impl Executor {
+    fn new(workers: Option<usize>) -> Executor {
+        let workers = match workers {
+            Some(workers) => workers,
+            None => return Executor::Threads,
+        };
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..workers {
+            let receiver = Arc::clone(&receiver);
+            thread::spawn(move || loop {
+                let job = receiver
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .recv();
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => break,
+                }
+            });
+        }
+        Executor::Pool(Mutex::new(sender))
+    }
+
+    fn execute(&self, job: Job) {
+        match *self {
+            Executor::Threads => {
+                thread::spawn(job);
+            }
+            Executor::Pool(ref sender) => {
+                let _ = sender
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .send(job);
+            }
+        }
     }
 }
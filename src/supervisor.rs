@@ -0,0 +1,281 @@
+//! Typed calls for the XML-RPC interface of supervisord, served at `/RPC2`.
+//!
+//! supervisord usually listens on a Unix domain socket, like supervisorctl's default
+//! `unix:///tmp/supervisor.sock`, which `Supervisor::unix_socket` connects to.
+
+use super::client::CallResultExt;
+use super::error::Result;
+use serde::de::DeserializeOwned;
+use std;
+use {Client, Fault, IntoParams, Url};
+
+/// Codes of the faults supervisord replies with.
+pub mod faults {
+    pub const UNKNOWN_METHOD: i32 = 1;
+    pub const INCORRECT_PARAMETERS: i32 = 2;
+    pub const BAD_ARGUMENTS: i32 = 3;
+    pub const SIGNATURE_UNSUPPORTED: i32 = 4;
+    pub const SHUTDOWN_STATE: i32 = 6;
+    pub const BAD_NAME: i32 = 10;
+    pub const BAD_SIGNAL: i32 = 11;
+    pub const NO_FILE: i32 = 20;
+    pub const NOT_EXECUTABLE: i32 = 21;
+    pub const FAILED: i32 = 30;
+    pub const ABNORMAL_TERMINATION: i32 = 40;
+    pub const SPAWN_ERROR: i32 = 50;
+    pub const ALREADY_STARTED: i32 = 60;
+    pub const NOT_RUNNING: i32 = 70;
+    pub const SUCCESS: i32 = 80;
+    pub const ALREADY_ADDED: i32 = 90;
+    pub const STILL_RUNNING: i32 = 91;
+    pub const CANT_REREAD: i32 = 92;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct State {
+    pub statecode: i32,
+    pub statename: String,
+}
+
+/// Times are in seconds since the epoch, and 0 when the event never happened.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ProcessInfo {
+    pub name: String,
+    pub group: String,
+    pub description: String,
+    pub start: i64,
+    pub stop: i64,
+    pub now: i64,
+    pub state: i32,
+    pub statename: String,
+    pub spawnerr: String,
+    pub exitstatus: i32,
+    #[serde(default)]
+    pub logfile: String,
+    pub stdout_logfile: String,
+    pub stderr_logfile: String,
+    pub pid: i32,
+}
+
+/// The outcome for one process of a call acting on several of them.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ProcessStatus {
+    pub name: String,
+    pub group: String,
+    /// One of the codes in `faults`, with `faults::SUCCESS` for processes that succeeded.
+    pub status: i32,
+    pub description: String,
+}
+
+/// Bytes read from the end of a log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tail {
+    pub bytes: String,
+    /// Where to continue reading from in the next call.
+    pub offset: i32,
+    /// Whether more bytes were written since the given offset than were read.
+    pub overflow: bool,
+}
+
+pub struct Supervisor {
+    client: Client,
+    uri: Url,
+}
+
+impl Supervisor {
+    /// Takes the full URL of the interface, like `http://localhost:9001/RPC2`.
+    pub fn new(uri: Url) -> Result<Supervisor> {
+        Ok(Supervisor {
+            client: Client::new()?,
+            uri,
+        })
+    }
+
+    #[cfg(unix)]
+    pub fn unix_socket<P>(path: P) -> Result<Supervisor>
+    where
+        P: Into<std::path::PathBuf>,
+    {
+        Ok(Supervisor {
+            client: Client::with_unix_socket(path)?,
+            uri: Url::parse("http://localhost/RPC2").expect("Valid URL"),
+        })
+    }
+
+    fn call<Treq, Tres>(
+        &mut self,
+        name: &str,
+        params: Treq,
+    ) -> Result<std::result::Result<Tres, Fault>>
+    where
+        Treq: IntoParams,
+        Tres: DeserializeOwned,
+    {
+        self.client.call(&self.uri, name, params).nested()
+    }
+
+    pub fn get_api_version(&mut self) -> Result<std::result::Result<String, Fault>> {
+        self.call("supervisor.getAPIVersion", ())
+    }
+
+    pub fn get_supervisor_version(&mut self) -> Result<std::result::Result<String, Fault>> {
+        self.call("supervisor.getSupervisorVersion", ())
+    }
+
+    pub fn get_identification(&mut self) -> Result<std::result::Result<String, Fault>> {
+        self.call("supervisor.getIdentification", ())
+    }
+
+    pub fn get_state(&mut self) -> Result<std::result::Result<State, Fault>> {
+        self.call("supervisor.getState", ())
+    }
+
+    pub fn get_pid(&mut self) -> Result<std::result::Result<i32, Fault>> {
+        self.call("supervisor.getPID", ())
+    }
+
+    pub fn read_log(
+        &mut self,
+        offset: i32,
+        length: i32,
+    ) -> Result<std::result::Result<String, Fault>> {
+        self.call("supervisor.readLog", (offset, length))
+    }
+
+    pub fn clear_log(&mut self) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.clearLog", ())
+    }
+
+    pub fn shutdown(&mut self) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.shutdown", ())
+    }
+
+    pub fn restart(&mut self) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.restart", ())
+    }
+
+    /// Takes the process name, or `group:name` for processes in groups.
+    pub fn get_process_info(
+        &mut self,
+        name: &str,
+    ) -> Result<std::result::Result<ProcessInfo, Fault>> {
+        self.call("supervisor.getProcessInfo", (name,))
+    }
+
+    pub fn get_all_process_info(&mut self) -> Result<std::result::Result<Vec<ProcessInfo>, Fault>> {
+        self.call("supervisor.getAllProcessInfo", ())
+    }
+
+    /// With `wait`, only replies once the process is fully started.
+    pub fn start_process(
+        &mut self,
+        name: &str,
+        wait: bool,
+    ) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.startProcess", (name, wait))
+    }
+
+    pub fn start_all_processes(
+        &mut self,
+        wait: bool,
+    ) -> Result<std::result::Result<Vec<ProcessStatus>, Fault>> {
+        self.call("supervisor.startAllProcesses", (wait,))
+    }
+
+    /// With `wait`, only replies once the process is fully stopped.
+    pub fn stop_process(
+        &mut self,
+        name: &str,
+        wait: bool,
+    ) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.stopProcess", (name, wait))
+    }
+
+    pub fn stop_all_processes(
+        &mut self,
+        wait: bool,
+    ) -> Result<std::result::Result<Vec<ProcessStatus>, Fault>> {
+        self.call("supervisor.stopAllProcesses", (wait,))
+    }
+
+    /// Takes the signal's name, like `HUP`, or number.
+    pub fn signal_process(
+        &mut self,
+        name: &str,
+        signal: &str,
+    ) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.signalProcess", (name, signal))
+    }
+
+    pub fn send_process_stdin(
+        &mut self,
+        name: &str,
+        chars: &str,
+    ) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.sendProcessStdin", (name, chars))
+    }
+
+    pub fn read_process_stdout_log(
+        &mut self,
+        name: &str,
+        offset: i32,
+        length: i32,
+    ) -> Result<std::result::Result<String, Fault>> {
+        self.call("supervisor.readProcessStdoutLog", (name, offset, length))
+    }
+
+    pub fn read_process_stderr_log(
+        &mut self,
+        name: &str,
+        offset: i32,
+        length: i32,
+    ) -> Result<std::result::Result<String, Fault>> {
+        self.call("supervisor.readProcessStderrLog", (name, offset, length))
+    }
+
+    /// Reads up to `length` bytes ending at the end of the log, or starting at `offset` if
+    /// fewer were written since.
+    pub fn tail_process_stdout_log(
+        &mut self,
+        name: &str,
+        offset: i32,
+        length: i32,
+    ) -> Result<std::result::Result<Tail, Fault>> {
+        self.tail("supervisor.tailProcessStdoutLog", name, offset, length)
+    }
+
+    pub fn tail_process_stderr_log(
+        &mut self,
+        name: &str,
+        offset: i32,
+        length: i32,
+    ) -> Result<std::result::Result<Tail, Fault>> {
+        self.tail("supervisor.tailProcessStderrLog", name, offset, length)
+    }
+
+    fn tail(
+        &mut self,
+        method: &str,
+        name: &str,
+        offset: i32,
+        length: i32,
+    ) -> Result<std::result::Result<Tail, Fault>> {
+        let response: std::result::Result<(String, i32, bool), Fault> =
+            self.call(method, (name, offset, length))?;
+        Ok(response.map(|(bytes, offset, overflow)| Tail {
+            bytes,
+            offset,
+            overflow,
+        }))
+    }
+
+    pub fn clear_process_logs(&mut self, name: &str) -> Result<std::result::Result<bool, Fault>> {
+        self.call("supervisor.clearProcessLogs", (name,))
+    }
+
+    pub fn clear_all_process_logs(
+        &mut self,
+    ) -> Result<std::result::Result<Vec<ProcessStatus>, Fault>> {
+        self.call("supervisor.clearAllProcessLogs", ())
+    }
+}
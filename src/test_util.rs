@@ -0,0 +1,206 @@
+//! Helpers for tests running a server and calling it.
+
+use server::MetricsSink;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use {Fault, Params, Response, Server, Url};
+
+/// A server running on its own thread, until it's dropped.
+pub struct TestServer {
+    addr: SocketAddr,
+    url: Url,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Like `http://127.0.0.1:40000/`.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Serves on a free port of the loopback interface, panicking if none can be bound.
+pub fn spawn(server: Server) -> TestServer {
+    let bound_server = server
+        .bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+        .expect("Failed to bind the test server");
+    let addr = bound_server.local_addr();
+    let url = format!("http://{}/", addr)
+        .parse()
+        .expect("Failed to build the test server's URL");
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                bound_server.poll_timeout(Duration::from_millis(10));
+            }
+        })
+    };
+    TestServer {
+        addr,
+        url,
+        stop,
+        thread: Some(thread),
+    }
+}
+
+/// A call a `StubServer` got.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StubCall {
+    pub method: String,
+    pub params: Params,
+}
+
+/// A server answering each method with a fixed response, and recording the calls it gets.
+///
+/// Dropping it panics if it got calls to methods without a response, failing the test.
+pub struct StubServer {
+    server: TestServer,
+    calls: Arc<Mutex<Vec<StubCall>>>,
+    unmatched: Arc<Mutex<Vec<Params>>>,
+}
+
+impl StubServer {
+    pub fn new<I, K>(responses: I) -> StubServer
+    where
+        I: IntoIterator<Item = (K, Response)>,
+        K: Into<String>,
+    {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let unmatched = Arc::new(Mutex::new(Vec::new()));
+        let mut server = Server::new();
+        for (method, response) in responses {
+            let method = method.into();
+            let calls = Arc::clone(&calls);
+            server.register_value(method.clone(), move |params| {
+                lock(&calls).push(StubCall {
+                    method: method.clone(),
+                    params,
+                });
+                response.clone()
+            });
+        }
+        {
+            let unmatched = Arc::clone(&unmatched);
+            server.set_on_missing(move |params| {
+                lock(&unmatched).push(params);
+                Err(Fault::new(404, "No response stubbed for the method"))
+            });
+        }
+        StubServer {
+            server: spawn(server),
+            calls,
+            unmatched,
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.server.addr()
+    }
+
+    pub fn url(&self) -> &Url {
+        self.server.url()
+    }
+
+    /// All calls to stubbed methods, in the order they came in.
+    pub fn calls(&self) -> Vec<StubCall> {
+        lock(&self.calls).clone()
+    }
+
+    /// The params of each call to `method`.
+    pub fn calls_to(&self, method: &str) -> Vec<Params> {
+        lock(&self.calls)
+            .iter()
+            .filter(|call| call.method == method)
+            .map(|call| call.params.clone())
+            .collect()
+    }
+
+    pub fn count(&self, method: &str) -> usize {
+        lock(&self.calls)
+            .iter()
+            .filter(|call| call.method == method)
+            .count()
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        let unmatched = lock(&self.unmatched);
+        if !unmatched.is_empty() && !thread::panicking() {
+            panic!(
+                "StubServer got {} calls to methods without a response, with params {:?}",
+                unmatched.len(),
+                *unmatched
+            );
+        }
+    }
+}
+
+/// A `MetricsSink` keeping everything reported to it, for tests to check or wait for.
+#[derive(Clone, Default)]
+pub struct MetricsRecorder {
+    records: Arc<Mutex<Vec<(String, f64)>>>,
+    recorded: Arc<Condvar>,
+}
+
+impl MetricsRecorder {
+    /// Every value reported as `name`, oldest first, with counters as the amounts added.
+    pub fn values(&self, name: &str) -> Vec<f64> {
+        lock(&self.records)
+            .iter()
+            .filter(|record| record.0 == name)
+            .map(|record| record.1)
+            .collect()
+    }
+
+    /// Blocks until `name` is reported as `value`, or returns at once if it already was.
+    pub fn wait_for(&self, name: &str, value: f64) {
+        let records = lock(&self.records);
+        let _records = self
+            .recorded
+            .wait_while(records, |records| {
+                !records
+                    .iter()
+                    .any(|record| record.0 == name && record.1 == value)
+            })
+            .unwrap_or_else(|err| err.into_inner());
+    }
+
+    fn record(&self, name: &str, value: f64) {
+        lock(&self.records).push((name.into(), value));
+        self.recorded.notify_all();
+    }
+}
+
+impl MetricsSink for MetricsRecorder {
+    fn gauge(&self, name: &str, value: f64) {
+        self.record(name, value);
+    }
+
+    fn increment(&self, name: &str, by: u64) {
+        self.record(name, by as f64);
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> ::std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
@@ -0,0 +1,109 @@
+//! W3C trace context, passed on from the calls a server handles to the calls its handlers make.
+//!
+//! The server reads the `traceparent` and `tracestate` headers of each request, and the client
+//! sends them on with calls made while handling it, so the hops show up linked in traces.
+
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+thread_local! {
+    static CURRENT: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 32 lowercase hex digits, shared by every hop of the trace.
+    pub trace_id: String,
+    /// 16 lowercase hex digits naming the caller's span.
+    pub parent_id: String,
+    /// 16 lowercase hex digits naming this hop's span, the parent of calls made from it.
+    pub span_id: String,
+    pub flags: u8,
+    /// The `tracestate` header, passed on as is.
+    pub state: Option<String>,
+}
+
+impl TraceContext {
+    /// Reads a `traceparent` header like `00-<trace ID>-<parent ID>-<flags>`, starting a new
+    /// span under it. Fails on anything else, like IDs that are all zeros.
+    pub fn from_headers(traceparent: &str, tracestate: Option<&str>) -> Option<TraceContext> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+        // Later versions may add fields, but version 00 has exactly these.
+        if !is_hex(version, 2) || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if !is_id(trace_id, 32) || !is_id(parent_id, 16) || !is_hex(flags, 2) {
+            return None;
+        }
+        let state = tracestate
+            .map(str::trim)
+            .filter(|state| !state.is_empty() && state.len() <= 512)
+            .filter(|state| state.bytes().all(|b| b == b' ' || b.is_ascii_graphic()));
+        Some(TraceContext {
+            trace_id: trace_id.to_ascii_lowercase(),
+            parent_id: parent_id.to_ascii_lowercase(),
+            span_id: new_span_id(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            state: state.map(Into::into),
+        })
+    }
+
+    /// The `traceparent` header of calls made from this span.
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+
+    /// The context of the call being handled on this thread, if it came with one.
+    pub fn current() -> Option<TraceContext> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Makes this the current context until the returned guard is dropped, for calls made from
+    /// threads other than the server's.
+    pub fn enter(self) -> TraceGuard {
+        TraceGuard {
+            previous: CURRENT.with(|current| current.borrow_mut().replace(self)),
+        }
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.traceparent())
+    }
+}
+
+/// Restores the previous trace context when dropped, even if the code under it panics.
+pub struct TraceGuard {
+    previous: Option<TraceContext>,
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn is_id(value: &str, len: usize) -> bool {
+    is_hex(value, len) && value.bytes().any(|b| b != b'0')
+}
+
+fn new_span_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    // All zeros is invalid, and this is as good as any other ID.
+    format!("{:016x}", hasher.finish().max(1))
+}
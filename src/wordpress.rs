@@ -0,0 +1,306 @@
+//! Typed calls for the WordPress XML-RPC API, usually served at `/xmlrpc.php`.
+//!
+//! WordPress methods take the blog ID, username and password first, and the MetaWeblog ones
+//! take the post or blog ID, username and password. `WordPress` fills these in for each call.
+
+use super::client::CallResultExt;
+use super::error::Result;
+use super::metaweblog;
+use super::xmlfmt::{
+    DeserializeOptions, Fault, IntegerPolicy, OptionRepresentation, SerializeOptions,
+};
+use serde::de::DeserializeOwned;
+use std;
+use std::collections::HashMap;
+use {datetime, Client, IntoParams, Secret, Url};
+
+/// Narrows down `wp.getPosts`, with members left out when `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PostFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orderby: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Term {
+    pub term_id: String,
+    pub name: String,
+    pub slug: String,
+    pub taxonomy: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomField {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub key: String,
+    pub value: String,
+}
+
+/// `datetime::option` for datetimes in the blog's timezone, which are read as they were sent,
+/// rather than taken to be in UTC.
+mod local_datetime {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use {datetime, Value};
+
+    pub fn serialize<S>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        datetime::option::serialize(value, serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::DateTime(v) | Value::String(v) => Ok(Some(v)),
+            v => Err(D::Error::invalid_type(v.unexpected(), &"dateTime.iso8601")),
+        }
+    }
+}
+
+/// A post as read by `wp.getPost`, or written by `wp.newPost` and `wp.editPost`, with members
+/// left out when `None`.
+///
+/// `post_date` and `post_modified` are in the blog's timezone, and kept without one, as
+/// WordPress sends them. `post_date_gmt` is in UTC.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Post {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_title: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "local_datetime"
+    )]
+    pub post_date: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "datetime::option"
+    )]
+    pub post_date_gmt: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "local_datetime"
+    )]
+    pub post_modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_format: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_excerpt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sticky: Option<bool>,
+    /// Only read, as writing takes term IDs or names instead.
+    #[serde(default, skip_serializing)]
+    pub terms: Option<Vec<Term>>,
+    /// Only written, as names of terms by taxonomy, like `{"post_tag": ["rust"]}`.
+    #[serde(default, skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub terms_names: Option<HashMap<String, Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_fields: Option<Vec<CustomField>>,
+}
+
+pub struct WordPress {
+    client: Client,
+    uri: Url,
+    blog_id: i32,
+    username: String,
+    password: Secret,
+}
+
+impl WordPress {
+    pub fn new<U, P>(uri: Url, username: U, password: P) -> Result<WordPress>
+    where
+        U: Into<String>,
+        P: Into<Secret>,
+    {
+        let mut client = Client::new()?;
+        client.set_serialize_options(SerializeOptions {
+            integers: IntegerPolicy::IntWhenFits,
+            options: OptionRepresentation::Omit,
+            ..SerializeOptions::default()
+        });
+        client.set_deserialize_options(DeserializeOptions {
+            options: OptionRepresentation::Omit,
+            ..DeserializeOptions::default()
+        });
+        Ok(WordPress {
+            client,
+            uri,
+            blog_id: 1,
+            username: username.into(),
+            password: password.into(),
+        })
+    }
+
+    /// Only matters for multisite installations, and defaults to 1.
+    pub fn set_blog_id(&mut self, blog_id: i32) {
+        self.blog_id = blog_id;
+    }
+
+    fn call<Treq, Tres>(
+        &mut self,
+        name: &str,
+        params: Treq,
+    ) -> Result<std::result::Result<Tres, Fault>>
+    where
+        Treq: IntoParams,
+        Tres: DeserializeOwned,
+    {
+        self.client.call(&self.uri, name, params).nested()
+    }
+
+    pub fn get_posts(
+        &mut self,
+        filter: &PostFilter,
+    ) -> Result<std::result::Result<Vec<Post>, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            filter,
+        );
+        self.call("wp.getPosts", params)
+    }
+
+    pub fn get_post(&mut self, post_id: &str) -> Result<std::result::Result<Post, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            post_id,
+        );
+        self.call("wp.getPost", params)
+    }
+
+    /// Returns the new post's ID.
+    pub fn new_post(&mut self, post: &Post) -> Result<std::result::Result<String, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            post,
+        );
+        self.call("wp.newPost", params)
+    }
+
+    /// Only the members that are set are changed.
+    pub fn edit_post(
+        &mut self,
+        post_id: &str,
+        post: &Post,
+    ) -> Result<std::result::Result<bool, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            post_id,
+            post,
+        );
+        self.call("wp.editPost", params)
+    }
+
+    pub fn delete_post(&mut self, post_id: &str) -> Result<std::result::Result<bool, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            post_id,
+        );
+        self.call("wp.deletePost", params)
+    }
+
+    /// Returns the new post's ID.
+    pub fn new_metaweblog_post(
+        &mut self,
+        post: &metaweblog::Post,
+        publish: bool,
+    ) -> Result<std::result::Result<String, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            post,
+            publish,
+        );
+        self.call("metaWeblog.newPost", params)
+    }
+
+    pub fn edit_metaweblog_post(
+        &mut self,
+        post_id: &str,
+        post: &metaweblog::Post,
+        publish: bool,
+    ) -> Result<std::result::Result<bool, Fault>> {
+        let params = (
+            post_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            post,
+            publish,
+        );
+        self.call("metaWeblog.editPost", params)
+    }
+
+    pub fn get_metaweblog_post(
+        &mut self,
+        post_id: &str,
+    ) -> Result<std::result::Result<metaweblog::Post, Fault>> {
+        let params = (
+            post_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+        );
+        self.call("metaWeblog.getPost", params)
+    }
+
+    pub fn get_recent_metaweblog_posts(
+        &mut self,
+        count: i32,
+    ) -> Result<std::result::Result<Vec<metaweblog::Post>, Fault>> {
+        let params = (
+            self.blog_id,
+            self.username.clone(),
+            self.password.expose().clone(),
+            count,
+        );
+        self.call("metaWeblog.getRecentPosts", params)
+    }
+}
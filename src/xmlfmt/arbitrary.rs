@@ -0,0 +1,89 @@
+//! Proptest strategies for values that survive being written and parsed again.
+
+use super::{Call, Fault, Value};
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::collection::{hash_map, vec};
+use proptest::option;
+use proptest::strategy::{BoxedStrategy, Just, Strategy};
+
+/// Text XML can carry verbatim. Other control characters and the U+FFFE and U+FFFF noncharacters
+/// are not allowed at all, and carriage returns are normalized into newlines.
+const TEXT: &str = "[^\u{0}-\u{8}\u{b}-\u{1f}\u{fffe}\u{ffff}]{0,32}";
+
+fn datetime() -> impl Strategy<Value = String> {
+    (0..10000i32, 1..=12u8, 1..=28u8, 0..24u8, 0..60u8, 0..60u8).prop_map(
+        |(year, month, day, hour, minute, second)| {
+            format!(
+                "{:04}{:02}{:02}T{:02}:{:02}:{:02}",
+                year, month, day, hour, minute, second
+            )
+        },
+    )
+}
+
+fn leaf() -> BoxedStrategy<Value> {
+    let leaf = prop_oneof![
+        any::<i32>().prop_map(Value::Int),
+        any::<i64>().prop_map(Value::I8),
+        any::<bool>().prop_map(Value::Bool),
+        TEXT.prop_map(Value::String),
+        any::<f64>()
+            .prop_filter("Doubles must be finite", |v| v.is_finite())
+            .prop_map(Value::Double),
+        datetime().prop_map(Value::DateTime),
+        vec(any::<u8>(), 0..64).prop_map(Value::Base64),
+        Just(Value::Nil),
+    ];
+    #[cfg(feature = "bignum")]
+    let leaf = prop_oneof![
+        8 => leaf,
+        1 => any::<i128>().prop_map(|v| Value::BigInt(v.into())),
+        1 => (any::<i64>(), -20..20i64)
+            .prop_map(|(v, scale)| Value::BigDecimal(::bigdecimal::BigDecimal::new(v.into(), scale))),
+    ];
+    leaf.boxed()
+}
+
+impl Arbitrary for Value {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Value>;
+
+    fn arbitrary_with(_: ()) -> BoxedStrategy<Value> {
+        leaf()
+            .prop_recursive(4, 64, 8, |inner| {
+                prop_oneof![
+                    vec(inner.clone(), 0..8).prop_map(Value::Array),
+                    hash_map(TEXT, inner, 0..8).prop_map(Value::Struct),
+                ]
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Call {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Call>;
+
+    fn arbitrary_with(_: ()) -> BoxedStrategy<Call> {
+        ("[A-Za-z0-9_.:/]{1,32}", vec(any::<Value>(), 0..8))
+            .prop_map(|(name, params)| Call { name, params })
+            .boxed()
+    }
+}
+
+/// Details are always structs, as other values don't read back the same.
+impl Arbitrary for Fault {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Fault>;
+
+    fn arbitrary_with(_: ()) -> BoxedStrategy<Fault> {
+        let members = hash_map("detail_[a-z]{0,8}", any::<Value>(), 1..4);
+        (any::<i32>(), TEXT, option::of(members))
+            .prop_map(|(code, message, detail)| Fault {
+                code,
+                message,
+                detail: detail.map(Value::Struct),
+            })
+            .boxed()
+    }
+}
@@ -0,0 +1,421 @@
+//! Value trees kept in a few flat buffers, for servers that handle many requests.
+//!
+//! A `ValueArena` holds all the text, bytes and nodes of the values put into it in shared
+//! buffers, and frees them all at once when cleared, instead of a `String`, `Vec` or `HashMap`
+//! for each value. Kept around and cleared between requests, its buffers stop growing once they
+//! fit them.
+//!
+//! `ParamReader::next_param_in` reads params straight into an arena, without building `Value`s
+//! first. That still allocates for every XML element and text read, as xml-rs hands each out as
+//! owned strings, so the arena saves the allocations of the tree, not those of parsing.
+
+#[cfg(feature = "bignum")]
+use bigdecimal::BigDecimal;
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::ops::Range;
+use xmlfmt::build::TreeBuilder;
+use xmlfmt::error::Result;
+use xmlfmt::Value;
+
+/// A value in a `ValueArena`, valid until the arena is cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ValueId(u32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Span {
+    start: u32,
+    end: u32,
+}
+
+impl Span {
+    fn range(self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Node {
+    Int(i32),
+    I8(i64),
+    Bool(bool),
+    String(Span),
+    Double(f64),
+    DateTime(Span),
+    Base64(Span),
+    Base64Text(Span),
+    /// Into `items`.
+    Array(Span),
+    /// Into `members`.
+    Struct(Span),
+    Nil,
+    /// Into `others`, for values without a flat form.
+    #[cfg(feature = "bignum")]
+    Other(u32),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ValueArena {
+    nodes: Vec<Node>,
+    text: String,
+    bytes: Vec<u8>,
+    items: Vec<ValueId>,
+    members: Vec<(Span, ValueId)>,
+    #[cfg(feature = "bignum")]
+    others: Vec<Value>,
+    /// Items and members of the arrays and structs being read, which are moved into `items` and
+    /// `members` once complete, so nested ones don't split them up.
+    pending_items: Vec<ValueId>,
+    pending_members: Vec<(Span, ValueId)>,
+}
+
+impl ValueArena {
+    pub fn new() -> ValueArena {
+        ValueArena::default()
+    }
+
+    /// Drops every value, keeping the buffers for the next ones.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.text.clear();
+        self.bytes.clear();
+        self.items.clear();
+        self.members.clear();
+        #[cfg(feature = "bignum")]
+        self.others.clear();
+        self.pending_items.clear();
+        self.pending_members.clear();
+    }
+
+    /// How many values, counting each array item and struct member, the arena holds.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn insert(&mut self, value: &Value) -> ValueId {
+        let node = match *value {
+            Value::Int(v) => Node::Int(v),
+            Value::I8(v) => Node::I8(v),
+            Value::Bool(v) => Node::Bool(v),
+            Value::String(ref v) => Node::String(self.push_text(v)),
+            Value::Double(v) => Node::Double(v),
+            Value::DateTime(ref v) => Node::DateTime(self.push_text(v)),
+            Value::Base64(ref v) => {
+                let start = self.bytes.len();
+                self.bytes.extend_from_slice(v);
+                Node::Base64(span(start, self.bytes.len()))
+            }
+            Value::Base64Text(ref v) => Node::Base64Text(self.push_text(v)),
+            Value::Array(ref v) => {
+                // Items are placed first, so nested arrays don't split them up.
+                let start = self.items.len();
+                self.items.resize(start + v.len(), ValueId(0));
+                for (index, item) in v.iter().enumerate() {
+                    self.items[start + index] = self.insert(item);
+                }
+                Node::Array(span(start, start + v.len()))
+            }
+            Value::Struct(ref v) => {
+                let start = self.members.len();
+                self.members
+                    .resize(start + v.len(), (span(0, 0), ValueId(0)));
+                for (index, (key, value)) in v.iter().enumerate() {
+                    let key = self.push_text(key);
+                    self.members[start + index] = (key, self.insert(value));
+                }
+                Node::Struct(span(start, start + v.len()))
+            }
+            Value::Nil => Node::Nil,
+            #[cfg(feature = "bignum")]
+            Value::BigInt(_) | Value::BigDecimal(_) => {
+                self.others.push(value.clone());
+                Node::Other(index(self.others.len() - 1))
+            }
+        };
+        self.push(node)
+    }
+
+    /// Panics if `id` is from another arena, or from before the arena was cleared.
+    pub fn get(&self, id: ValueId) -> ValueRef<'_> {
+        match self.nodes[id.0 as usize] {
+            Node::Int(v) => ValueRef::Int(v),
+            Node::I8(v) => ValueRef::I8(v),
+            Node::Bool(v) => ValueRef::Bool(v),
+            Node::String(span) => ValueRef::String(&self.text[span.range()]),
+            Node::Double(v) => ValueRef::Double(v),
+            Node::DateTime(span) => ValueRef::DateTime(&self.text[span.range()]),
+            Node::Base64(span) => ValueRef::Base64(&self.bytes[span.range()]),
+            Node::Base64Text(span) => ValueRef::Base64Text(&self.text[span.range()]),
+            Node::Array(span) => ValueRef::Array(ArrayRef {
+                arena: self,
+                items: &self.items[span.range()],
+            }),
+            Node::Struct(span) => ValueRef::Struct(StructRef {
+                arena: self,
+                members: &self.members[span.range()],
+            }),
+            Node::Nil => ValueRef::Nil,
+            #[cfg(feature = "bignum")]
+            Node::Other(index) => match self.others[index as usize] {
+                Value::BigInt(ref v) => ValueRef::BigInt(v),
+                Value::BigDecimal(ref v) => ValueRef::BigDecimal(v),
+                _ => unreachable!("Only big numbers are kept whole"),
+            },
+        }
+    }
+
+    fn push(&mut self, node: Node) -> ValueId {
+        self.nodes.push(node);
+        ValueId(index(self.nodes.len() - 1))
+    }
+
+    fn push_text(&mut self, text: &str) -> Span {
+        let start = self.text.len();
+        self.text.push_str(text);
+        span(start, self.text.len())
+    }
+}
+
+/// Text is copied into the arena's buffer, and arrays and structs are gathered in its pending
+/// buffers, so a warmed up arena allocates nothing on top of what xml-rs does for the events.
+impl TreeBuilder for ValueArena {
+    type Value = ValueId;
+    type Text = Range<usize>;
+    type Array = usize;
+    type Struct = usize;
+
+    fn start_text(&mut self) -> Range<usize> {
+        self.text.len()..self.text.len()
+    }
+
+    fn push_text(&mut self, text: &mut Range<usize>, piece: String) {
+        self.text.push_str(&piece);
+        text.end = self.text.len();
+    }
+
+    fn text<'a>(&'a self, text: &'a Range<usize>) -> &'a str {
+        &self.text[text.clone()]
+    }
+
+    fn remove_whitespace(&mut self, text: &mut Range<usize>) {
+        if !self.text[text.clone()].contains(|c: char| c.is_ascii_whitespace()) {
+            return;
+        }
+        let compact = self.text[text.clone()]
+            .split_ascii_whitespace()
+            .collect::<String>();
+        self.discard(text.clone());
+        let start = self.text.len();
+        self.text.push_str(&compact);
+        *text = start..self.text.len();
+    }
+
+    fn discard(&mut self, text: Range<usize>) {
+        if text.end == self.text.len() {
+            self.text.truncate(text.start);
+        }
+    }
+
+    fn string(&mut self, text: Range<usize>) -> ValueId {
+        self.push(Node::String(span(text.start, text.end)))
+    }
+
+    fn datetime(&mut self, text: Range<usize>) -> ValueId {
+        self.push(Node::DateTime(span(text.start, text.end)))
+    }
+
+    fn base64_text(&mut self, text: Range<usize>) -> ValueId {
+        self.push(Node::Base64Text(span(text.start, text.end)))
+    }
+
+    fn base64<F>(&mut self, decode: F) -> Result<ValueId>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<()>,
+    {
+        let start = self.bytes.len();
+        decode(&mut self.bytes)?;
+        let end = self.bytes.len();
+        Ok(self.push(Node::Base64(span(start, end))))
+    }
+
+    fn scalar(&mut self, value: Value) -> ValueId {
+        self.insert(&value)
+    }
+
+    fn start_array(&mut self) -> usize {
+        self.pending_items.len()
+    }
+
+    fn push_item(&mut self, _: &mut usize, item: ValueId) {
+        self.pending_items.push(item);
+    }
+
+    fn array(&mut self, start: usize) -> ValueId {
+        let first = self.items.len();
+        self.items.extend(self.pending_items.drain(start..));
+        let end = self.items.len();
+        self.push(Node::Array(span(first, end)))
+    }
+
+    fn start_struct(&mut self) -> usize {
+        self.pending_members.len()
+    }
+
+    fn push_member(&mut self, _: &mut usize, name: Range<usize>, value: ValueId) {
+        self.pending_members
+            .push((span(name.start, name.end), value));
+    }
+
+    fn structure(&mut self, start: usize) -> ValueId {
+        let first = self.members.len();
+        self.members.extend(self.pending_members.drain(start..));
+        let end = self.members.len();
+        self.push(Node::Struct(span(first, end)))
+    }
+}
+
+fn index(index: usize) -> u32 {
+    u32::try_from(index).expect("Arenas hold at most 4 GiB of each kind of data")
+}
+
+fn span(start: usize, end: usize) -> Span {
+    Span {
+        start: index(start),
+        end: index(end),
+    }
+}
+
+/// A value borrowed from a `ValueArena`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ValueRef<'a> {
+    Int(i32),
+    I8(i64),
+    Bool(bool),
+    String(&'a str),
+    Double(f64),
+    DateTime(&'a str),
+    Base64(&'a [u8]),
+    Base64Text(&'a str),
+    Array(ArrayRef<'a>),
+    Struct(StructRef<'a>),
+    Nil,
+    #[cfg(feature = "bignum")]
+    BigInt(&'a BigInt),
+    #[cfg(feature = "bignum")]
+    BigDecimal(&'a BigDecimal),
+}
+
+impl<'a> ValueRef<'a> {
+    pub fn to_value(self) -> Value {
+        match self {
+            ValueRef::Int(v) => Value::Int(v),
+            ValueRef::I8(v) => Value::I8(v),
+            ValueRef::Bool(v) => Value::Bool(v),
+            ValueRef::String(v) => Value::String(v.into()),
+            ValueRef::Double(v) => Value::Double(v),
+            ValueRef::DateTime(v) => Value::DateTime(v.into()),
+            ValueRef::Base64(v) => Value::Base64(v.into()),
+            ValueRef::Base64Text(v) => Value::Base64Text(v.into()),
+            ValueRef::Array(v) => Value::Array(v.iter().map(ValueRef::to_value).collect()),
+            ValueRef::Struct(v) => Value::Struct(
+                v.iter()
+                    .map(|(key, value)| (key.into(), value.to_value()))
+                    .collect(),
+            ),
+            ValueRef::Nil => Value::Nil,
+            #[cfg(feature = "bignum")]
+            ValueRef::BigInt(v) => Value::BigInt(v.clone()),
+            #[cfg(feature = "bignum")]
+            ValueRef::BigDecimal(v) => Value::BigDecimal(v.clone()),
+        }
+    }
+
+    /// The member named `key`, if this is a struct that has one.
+    pub fn get(self, key: &str) -> Option<ValueRef<'a>> {
+        match self {
+            ValueRef::Struct(v) => v.get(key),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArrayRef<'a> {
+    arena: &'a ValueArena,
+    items: &'a [ValueId],
+}
+
+impl<'a> ArrayRef<'a> {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<ValueRef<'a>> {
+        self.items.get(index).map(|&id| self.arena.get(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ValueRef<'a>> + 'a {
+        let arena = self.arena;
+        self.items.iter().map(move |&id| arena.get(id))
+    }
+}
+
+impl<'a> PartialEq for ArrayRef<'a> {
+    fn eq(&self, other: &ArrayRef<'a>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+/// Members are kept in no particular order, and looked up by going through them.
+///
+/// A member repeated in a document is kept each time it's read, and `get` finds the last one,
+/// like a `Value` would only keep that one.
+#[derive(Clone, Copy, Debug)]
+pub struct StructRef<'a> {
+    arena: &'a ValueArena,
+    members: &'a [(Span, ValueId)],
+}
+
+impl<'a> StructRef<'a> {
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<ValueRef<'a>> {
+        self.members
+            .iter()
+            .rev()
+            .find(|&&(name, _)| &self.arena.text[name.range()] == key)
+            .map(|&(_, id)| self.arena.get(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, ValueRef<'a>)> + 'a {
+        let arena = self.arena;
+        self.members
+            .iter()
+            .map(move |&(key, id)| (&arena.text[key.range()], arena.get(id)))
+    }
+}
+
+impl<'a> PartialEq for StructRef<'a> {
+    fn eq(&self, other: &StructRef<'a>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
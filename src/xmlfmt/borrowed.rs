@@ -0,0 +1,695 @@
+//! Parsing that borrows strings, datetimes, base64 text and member names from the document
+//! instead of copying them, for large struct-heavy documents where allocating every string
+//! dominates. Text is only copied when it has to be changed, like to replace entities.
+//!
+//! Documents have to be UTF-8. Faults are read like `response_from_slice` reads them, and of
+//! lenient parsing's fixes, only unknown types and arrays without `<data>` are taken. Anything
+//! `response_from_slice` would reject is rejected, and so are namespace prefixes and names
+//! that aren't ASCII, which XML-RPC documents don't use.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::str;
+use xmlfmt::build::{decode_base64, is_base64, scalar, Scalar, SCALAR_TYPES};
+use xmlfmt::error::{Error, ErrorKind, Result, ResultExt};
+use xmlfmt::parse::check_doctype;
+use xmlfmt::value::check_method_name;
+use xmlfmt::{charset, Fault, MethodNamePolicy, ParseOptions, Value};
+
+/// A value borrowing its text from the document it was read from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Int(i32),
+    Bool(bool),
+    String(Cow<'a, str>),
+    Double(f64),
+    DateTime(Cow<'a, str>),
+    /// Base64 text, without whitespace, in the standard alphabet.
+    Base64(Cow<'a, str>),
+    Array(Vec<BorrowedValue<'a>>),
+    /// Members in the order they were read.
+    Struct(Vec<(Cow<'a, str>, BorrowedValue<'a>)>),
+    Nil,
+    /// Extension types with nothing to borrow, like `i8`, and URL-safe base64, which is decoded.
+    Other(Value),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BorrowedCall<'a> {
+    pub name: Cow<'a, str>,
+    pub params: Vec<BorrowedValue<'a>>,
+}
+
+pub type BorrowedResponse<'a> = ::std::result::Result<Vec<BorrowedValue<'a>>, Fault>;
+
+impl<'a> BorrowedValue<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            BorrowedValue::String(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The member named `key`, or the last one if it's repeated, like a `Value` would keep.
+    pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+        match *self {
+            BorrowedValue::Struct(ref members) => members
+                .iter()
+                .rev()
+                .find(|&(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Copies the value out of the document.
+    pub fn to_value(&self) -> Value {
+        match *self {
+            BorrowedValue::Int(v) => Value::Int(v),
+            BorrowedValue::Bool(v) => Value::Bool(v),
+            BorrowedValue::String(ref v) => Value::String(v.as_ref().into()),
+            BorrowedValue::Double(v) => Value::Double(v),
+            BorrowedValue::DateTime(ref v) => Value::DateTime(v.as_ref().into()),
+            BorrowedValue::Base64(ref v) => Value::Base64Text(v.as_ref().into()),
+            BorrowedValue::Array(ref items) => {
+                Value::Array(items.iter().map(BorrowedValue::to_value).collect())
+            }
+            BorrowedValue::Struct(ref members) => Value::Struct(
+                members
+                    .iter()
+                    .map(|(name, value)| (name.as_ref().into(), value.to_value()))
+                    .collect(),
+            ),
+            BorrowedValue::Nil => Value::Nil,
+            BorrowedValue::Other(ref v) => v.clone(),
+        }
+    }
+}
+
+impl<'a> From<Value> for BorrowedValue<'a> {
+    fn from(value: Value) -> BorrowedValue<'a> {
+        match value {
+            Value::Int(v) => BorrowedValue::Int(v),
+            Value::Bool(v) => BorrowedValue::Bool(v),
+            Value::String(v) => BorrowedValue::String(Cow::Owned(v)),
+            Value::Double(v) => BorrowedValue::Double(v),
+            Value::DateTime(v) => BorrowedValue::DateTime(Cow::Owned(v)),
+            Value::Base64Text(v) => BorrowedValue::Base64(Cow::Owned(v)),
+            Value::Array(items) => {
+                BorrowedValue::Array(items.into_iter().map(BorrowedValue::from).collect())
+            }
+            Value::Struct(members) => BorrowedValue::Struct(
+                members
+                    .into_iter()
+                    .map(|(name, value)| (Cow::Owned(name), value.into()))
+                    .collect(),
+            ),
+            Value::Nil => BorrowedValue::Nil,
+            value => BorrowedValue::Other(value),
+        }
+    }
+}
+
+/// The document as text, if it can be borrowed from.
+pub fn document<'a>(data: &'a [u8], options: &ParseOptions) -> Result<&'a str> {
+    match charset::decode_slice(data, options.charset.as_deref())? {
+        Cow::Borrowed(data) => str::from_utf8(data).chain_err(|| "Document is not valid UTF-8"),
+        Cow::Owned(_) => bail!(ErrorKind::Decoding(
+            "Only UTF-8 documents can be borrowed from".into()
+        )),
+    }
+}
+
+pub fn read_call<'a>(document: &'a str, options: &ParseOptions) -> Result<BorrowedCall<'a>> {
+    check_chars(document)?;
+    let mut parser = Parser::new(document, options);
+    parser.expect_root("methodCall")?;
+    parser.expect_child("methodCall", "methodName")?;
+    let name = parser.read_text("methodName")?;
+    let policy = if options.strict {
+        MethodNamePolicy::Spec
+    } else {
+        options.method_names
+    };
+    check_method_name(&name, policy)?;
+    let params = match parser.next_child("methodCall", &["params"])? {
+        Some(_) => parser.read_params("methodCall")?,
+        None => Vec::new(),
+    };
+    parser.expect_eof()?;
+    Ok(BorrowedCall { name, params })
+}
+
+/// The params of a response, or `None` for a fault.
+pub fn read_response<'a>(
+    document: &'a str,
+    options: &ParseOptions,
+) -> Result<Option<Vec<BorrowedValue<'a>>>> {
+    check_chars(document)?;
+    let mut parser = Parser::new(document, options);
+    parser.expect_root("methodResponse")?;
+    let params = match parser.next_child("methodResponse", &["params", "fault"])? {
+        Some("params") => parser.read_params("methodResponse")?,
+        Some(_) => return Ok(None),
+        // The spec has no example of this, but some servers leave empty params out.
+        None if !options.strict => Vec::new(),
+        None => bail!(ErrorKind::Decoding(
+            "Expected <params> or <fault> inside <methodResponse>".into()
+        )),
+    };
+    parser.expect_eof()?;
+    Ok(Some(params))
+}
+
+#[derive(Debug)]
+enum Event<'a> {
+    /// The local name of an element's start tag.
+    Start(&'a str),
+    End(&'a str),
+    Text(Cow<'a, str>),
+}
+
+/// Splits a document into the events an XML-RPC document is made of, skipping comments and
+/// processing instructions.
+///
+/// Anything xml-rs would reject is rejected too, and so are namespace prefixes, which xml-rs
+/// would need declared, and names that aren't ASCII.
+struct Scanner<'a, 'o> {
+    document: &'a str,
+    position: usize,
+    /// The end of an empty element, like `<value/>`, which comes right after its start.
+    empty: Option<&'a str>,
+    /// Whether the root element started, after which a DOCTYPE is out of place.
+    started: bool,
+    doctype: bool,
+    options: &'o ParseOptions,
+}
+
+impl<'a, 'o> Scanner<'a, 'o> {
+    fn next(&mut self) -> Result<Option<Event<'a>>> {
+        if let Some(name) = self.empty.take() {
+            return Ok(Some(Event::End(name)));
+        }
+        loop {
+            let rest = &self.document[self.position..];
+            if rest.is_empty() {
+                return Ok(None);
+            }
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.position += end;
+                if rest[..end].contains("]]>") {
+                    bail!(ErrorKind::Decoding("Text can't contain ]]>".into()));
+                }
+                return unescape(&rest[..end]).map(|text| Some(Event::Text(text)));
+            }
+            if let Some(inside) = rest.strip_prefix("<![CDATA[") {
+                let end = self.skip_past(inside, "]]>")?;
+                return Ok(Some(Event::Text(Cow::Borrowed(&inside[..end]))));
+            }
+            if let Some(inside) = rest.strip_prefix("<!--") {
+                let end = self.skip_past(inside, "-->")?;
+                if inside[..end].contains("--") || inside[..end].ends_with('-') {
+                    bail!(ErrorKind::Decoding("Comments can't contain --".into()));
+                }
+            } else if let Some(inside) = rest.strip_prefix("<?") {
+                let at_start = self.position == 0;
+                let end = self.skip_past(inside, "?>")?;
+                check_processing_instruction(&inside[..end], at_start)?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                if self.started || self.doctype {
+                    bail!(ErrorKind::Decoding("Misplaced DOCTYPE declaration".into()));
+                }
+                self.doctype = true;
+                let end = doctype_end(rest)?;
+                check_doctype(Some(&rest[..end]), self.options.doctype)?;
+                self.position += end;
+            } else if let Some(inside) = rest.strip_prefix("</") {
+                let end = inside.find('>').ok_or_else(unexpected_eof)?;
+                self.position += end + 3;
+                return Ok(Some(Event::End(check_name(inside[..end].trim_end())?)));
+            } else {
+                let end = tag_end(rest)?;
+                self.position += end + 1;
+                let tag = &rest[1..end];
+                let (tag, empty) = match tag.strip_suffix('/') {
+                    Some(tag) => (tag, true),
+                    None => (tag, false),
+                };
+                let name_end = tag
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(tag.len());
+                let name = check_name(&tag[..name_end])?;
+                check_attributes(&tag[name_end..])?;
+                if empty {
+                    self.empty = Some(name);
+                }
+                self.started = true;
+                return Ok(Some(Event::Start(name)));
+            }
+        }
+    }
+
+    /// Moves past the first `end` in `inside`, which starts the rest of the document after a
+    /// `prefix`, returning where in `inside` it was.
+    fn skip_past(&mut self, inside: &'a str, end: &str) -> Result<usize> {
+        let found = inside.find(end).ok_or_else(unexpected_eof)?;
+        let prefix = self.document.len() - self.position - inside.len();
+        self.position += prefix + found + end.len();
+        Ok(found)
+    }
+}
+
+fn unexpected_eof() -> Error {
+    ErrorKind::Decoding("Unexpected end of document".into()).into()
+}
+
+/// Fails on characters XML doesn't allow anywhere, like most control characters.
+fn check_chars(document: &str) -> Result<()> {
+    if !document.chars().all(is_xml_char) {
+        bail!(ErrorKind::Decoding(
+            "Document contains characters XML doesn't allow".into()
+        ));
+    }
+    Ok(())
+}
+
+fn is_xml_char(c: char) -> bool {
+    !matches!(c, '\u{0}'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}')
+}
+
+/// Only takes ASCII names without a namespace prefix.
+fn check_name(name: &str) -> Result<&str> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        bail!(ErrorKind::Decoding(format!("Unsupported name {:?}", name)));
+    }
+    Ok(name)
+}
+
+/// Checks the attributes after a tag's name, which are otherwise ignored.
+fn check_attributes(mut rest: &str) -> Result<()> {
+    let mut names = Vec::new();
+    loop {
+        let attribute = rest.trim_start_matches(|c: char| c.is_ascii_whitespace());
+        if attribute.is_empty() {
+            return Ok(());
+        }
+        let invalid = || ErrorKind::Decoding(format!("Invalid attributes {:?}", rest.trim()));
+        if attribute.len() == rest.len() {
+            bail!(invalid());
+        }
+        let name_end = attribute
+            .find(|c: char| c == '=' || c.is_ascii_whitespace())
+            .unwrap_or(attribute.len());
+        let name = check_name(&attribute[..name_end])?;
+        if names.contains(&name) {
+            bail!(ErrorKind::Decoding(format!("Repeated attribute {}", name)));
+        }
+        names.push(name);
+        let value = attribute[name_end..]
+            .trim_start_matches(|c: char| c.is_ascii_whitespace())
+            .strip_prefix('=')
+            .ok_or_else(invalid)?
+            .trim_start_matches(|c: char| c.is_ascii_whitespace());
+        let quote = match value.chars().next() {
+            Some(quote @ '"') | Some(quote @ '\'') => quote,
+            _ => bail!(invalid()),
+        };
+        let end = value[1..].find(quote).ok_or_else(invalid)? + 1;
+        if value[1..end].contains('<') {
+            bail!(invalid());
+        }
+        unescape(&value[1..end])?;
+        rest = &value[end + 1..];
+    }
+}
+
+/// Checks what's between `<?` and `?>`, which can only be an XML declaration at the very start.
+fn check_processing_instruction(instruction: &str, at_start: bool) -> Result<()> {
+    lazy_static! {
+        static ref DECLARATION: Regex = Regex::new(concat!(
+            r#"^xml\s+version\s*=\s*(?:"1\.[0-9]+"|'1\.[0-9]+')"#,
+            r#"(?:\s+encoding\s*=\s*(?:"[A-Za-z][\w.-]*"|'[A-Za-z][\w.-]*'))?"#,
+            r#"(?:\s+standalone\s*=\s*(?:"(?:yes|no)"|'(?:yes|no)'))?\s*$"#
+        ))
+        .unwrap();
+    }
+    let target_end = instruction
+        .find(|c: char| c.is_ascii_whitespace())
+        .unwrap_or(instruction.len());
+    let target = check_name(&instruction[..target_end])?;
+    if target.eq_ignore_ascii_case("xml") && !(at_start && DECLARATION.is_match(instruction)) {
+        bail!(ErrorKind::Decoding("Invalid XML declaration".into()));
+    }
+    Ok(())
+}
+
+/// Where the `>` closing the tag at the start of `rest` is, skipping those in quotes.
+fn tag_end(rest: &str) -> Result<usize> {
+    let mut quote = None;
+    for (index, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Ok(index),
+            _ => {}
+        }
+    }
+    Err(unexpected_eof())
+}
+
+/// Where a DOCTYPE declaration ends, after its internal subset if it has one.
+fn doctype_end(rest: &str) -> Result<usize> {
+    let mut quote = None;
+    let mut brackets = 0;
+    for (index, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '[') => brackets += 1,
+            (None, ']') => brackets -= 1,
+            (None, '>') if brackets == 0 => return Ok(index + 1),
+            _ => {}
+        }
+    }
+    Err(unexpected_eof())
+}
+
+/// Replaces entity and character references, only copying text that has any.
+fn unescape(text: &str) -> Result<Cow<'_, str>> {
+    if !text.contains('&') {
+        return Ok(Cow::Borrowed(text));
+    }
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let end = rest
+            .find(';')
+            .ok_or_else(|| ErrorKind::Decoding("Unterminated entity reference".into()))?;
+        let c = match &rest[..end] {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            reference => {
+                let code = if let Some(hex) = reference.strip_prefix("#x") {
+                    Some(hex)
+                        .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                } else if let Some(decimal) = reference.strip_prefix('#') {
+                    Some(decimal)
+                        .filter(|decimal| decimal.bytes().all(|b| b.is_ascii_digit()))
+                        .and_then(|decimal| decimal.parse().ok())
+                } else {
+                    bail!(ErrorKind::Decoding(format!(
+                        "Unknown entity &{};",
+                        reference
+                    )))
+                };
+                match code.and_then(::std::char::from_u32) {
+                    Some(c) if is_xml_char(c) => c,
+                    _ => bail!(ErrorKind::Decoding(format!(
+                        "Invalid character reference &{};",
+                        reference
+                    ))),
+                }
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(Cow::Owned(unescaped))
+}
+
+fn describe(event: &Option<Event>) -> String {
+    match *event {
+        Some(Event::Start(name)) => format!("<{}>", name),
+        Some(Event::End(name)) => format!("</{}>", name),
+        Some(Event::Text(ref text)) => format!("text {:?}", text),
+        None => "the end of the document".into(),
+    }
+}
+
+fn limit_exceeded<T>(what: &str, limit: usize) -> Result<T> {
+    bail!(ErrorKind::LimitExceeded(format!("{} {}", what, limit)))
+}
+
+struct Parser<'a, 'o> {
+    scanner: Scanner<'a, 'o>,
+    options: &'o ParseOptions,
+    values: usize,
+}
+
+impl<'a, 'o> Parser<'a, 'o> {
+    fn new(document: &'a str, options: &'o ParseOptions) -> Parser<'a, 'o> {
+        Parser {
+            scanner: Scanner {
+                document: document.strip_prefix('\u{feff}').unwrap_or(document),
+                position: 0,
+                empty: None,
+                started: false,
+                doctype: false,
+                options,
+            },
+            options,
+            values: 0,
+        }
+    }
+
+    fn lenient(&self) -> bool {
+        self.options.lenient && !self.options.strict
+    }
+
+    /// The next event that isn't whitespace between elements.
+    fn next_event(&mut self) -> Result<Option<Event<'a>>> {
+        loop {
+            match self.scanner.next()? {
+                Some(Event::Text(ref text)) if text.trim().is_empty() => {}
+                event => return Ok(event),
+            }
+        }
+    }
+
+    fn expect_root(&mut self, root: &str) -> Result<()> {
+        match self.next_event()? {
+            Some(Event::Start(name)) if name == root => Ok(()),
+            event => bail!(ErrorKind::Decoding(format!(
+                "Expected <{}>, got {}",
+                root,
+                describe(&event)
+            ))),
+        }
+    }
+
+    fn expect_eof(&mut self) -> Result<()> {
+        match self.next_event()? {
+            None => Ok(()),
+            event => bail!(ErrorKind::Decoding(format!(
+                "Expected the end of the document, got {}",
+                describe(&event)
+            ))),
+        }
+    }
+
+    /// Returns the tag of the next child element of `parent`, or `None` once `parent` ends.
+    fn next_child(&mut self, parent: &str, expected: &[&str]) -> Result<Option<&'a str>> {
+        match self.next_event()? {
+            Some(Event::End(name)) if name == parent => Ok(None),
+            Some(Event::Start(name)) if expected.contains(&name) => Ok(Some(name)),
+            event => {
+                let expected = expected
+                    .iter()
+                    .map(|tag| format!("<{}>", tag))
+                    .chain(Some(format!("</{}>", parent)))
+                    .collect::<Vec<_>>();
+                bail!(ErrorKind::Decoding(format!(
+                    "Expected {}, got {}",
+                    expected.join(" or "),
+                    describe(&event)
+                )))
+            }
+        }
+    }
+
+    fn expect_child(&mut self, parent: &str, tag: &str) -> Result<()> {
+        match self.next_child(parent, &[tag])? {
+            Some(_) => Ok(()),
+            None => bail!(ErrorKind::Decoding(format!(
+                "Expected <{}> inside <{}>",
+                tag, parent
+            ))),
+        }
+    }
+
+    fn expect_end(&mut self, tag: &str) -> Result<()> {
+        self.next_child(tag, &[]).map(|_| ())
+    }
+
+    /// Reads the params inside `<params>`, and the end of `root` after them.
+    fn read_params(&mut self, root: &str) -> Result<Vec<BorrowedValue<'a>>> {
+        let mut params = Vec::new();
+        while self.next_child("params", &["param"])?.is_some() {
+            self.expect_child("param", "value")?;
+            params.push(self.read_value(1)?);
+            self.expect_end("param")?;
+        }
+        self.expect_end(root)?;
+        Ok(params)
+    }
+
+    /// Appends text, borrowing it if it's the first piece.
+    fn push_text(&self, text: &mut Cow<'a, str>, piece: Cow<'a, str>) -> Result<()> {
+        if text.len() + piece.len() > self.options.max_string_length {
+            return limit_exceeded("text longer than", self.options.max_string_length);
+        }
+        if text.is_empty() {
+            *text = piece;
+        } else {
+            text.to_mut().push_str(&piece);
+        }
+        Ok(())
+    }
+
+    fn read_text(&mut self, tag: &str) -> Result<Cow<'a, str>> {
+        let mut text = Cow::Borrowed("");
+        loop {
+            match self.scanner.next()? {
+                Some(Event::Text(piece)) => self.push_text(&mut text, piece)?,
+                Some(Event::End(name)) if name == tag => return Ok(text),
+                event => bail!(ErrorKind::Decoding(format!(
+                    "Expected text inside <{}>, got {}",
+                    tag,
+                    describe(&event)
+                ))),
+            }
+        }
+    }
+
+    fn count_value(&mut self, depth: usize) -> Result<()> {
+        if depth > self.options.max_depth {
+            return limit_exceeded("values nested deeper than", self.options.max_depth);
+        }
+        self.values += 1;
+        if self.values > self.options.max_values {
+            return limit_exceeded("more values than", self.options.max_values);
+        }
+        Ok(())
+    }
+
+    /// Reads the rest of a value whose `<value>` tag was just consumed.
+    fn read_value(&mut self, depth: usize) -> Result<BorrowedValue<'a>> {
+        let mut text = Cow::Borrowed("");
+        let tag = loop {
+            match self.scanner.next()? {
+                Some(Event::Text(piece)) => self.push_text(&mut text, piece)?,
+                Some(Event::Start(name)) => break name,
+                Some(Event::End("value")) => {
+                    self.count_value(depth)?;
+                    return Ok(BorrowedValue::String(text));
+                }
+                event => bail!(ErrorKind::Decoding(format!(
+                    "Expected value content, got {}",
+                    describe(&event)
+                ))),
+            }
+        };
+        let value = self.read_typed(tag, depth)?;
+        self.expect_end("value")?;
+        Ok(value)
+    }
+
+    /// Reads the rest of a value whose type tag, like `<int>`, was just consumed.
+    fn read_typed(&mut self, tag: &str, depth: usize) -> Result<BorrowedValue<'a>> {
+        self.count_value(depth)?;
+        match tag {
+            "i8" | "nil" | "dateTime" | "serializable" | "biginteger" | "bigdecimal"
+                if self.options.strict =>
+            {
+                bail!(ErrorKind::Decoding(format!(
+                    "<{}> is not part of the XML-RPC spec",
+                    tag
+                )))
+            }
+            "base64" | "serializable" => self.read_base64(tag),
+            "array" => self.read_array(depth),
+            "struct" => self.read_struct(depth),
+            _ => {
+                if !SCALAR_TYPES.contains(&tag) && !self.lenient() {
+                    bail!(ErrorKind::Decoding(format!("Unknown value type <{}>", tag)));
+                }
+                let text = self.read_text(tag)?;
+                Ok(match scalar(tag, &text, self.options, &mut |_| {})? {
+                    Scalar::String => BorrowedValue::String(text),
+                    Scalar::DateTime => BorrowedValue::DateTime(text),
+                    Scalar::Parsed(value) => value.into(),
+                })
+            }
+        }
+    }
+
+    fn read_base64(&mut self, tag: &str) -> Result<BorrowedValue<'a>> {
+        let mut text = self.read_text(tag)?;
+        if text.contains(|c: char| c.is_ascii_whitespace()) {
+            text.to_mut().retain(|c| !c.is_ascii_whitespace());
+        }
+        if self.options.base64_url_safe && text.contains(['-', '_']) {
+            let bytes = decode_base64(text.as_bytes(), true)?;
+            return Ok(BorrowedValue::Other(Value::Base64(bytes)));
+        }
+        if !is_base64(text.as_bytes()) {
+            bail!(ErrorKind::Decoding("Failed to parse base64".into()));
+        }
+        Ok(BorrowedValue::Base64(text))
+    }
+
+    fn read_array(&mut self, depth: usize) -> Result<BorrowedValue<'a>> {
+        let mut items = Vec::new();
+        // Lenient parsing also takes values placed straight into the array.
+        let expected: &[&str] = if self.lenient() {
+            &["data", "value"]
+        } else {
+            &["data"]
+        };
+        match self.next_child("array", expected)? {
+            None => {}
+            Some("data") => {
+                while self.next_child("data", &["value"])?.is_some() {
+                    items.push(self.read_value(depth + 1)?);
+                }
+                self.expect_end("array")?;
+            }
+            Some(_) => {
+                items.push(self.read_value(depth + 1)?);
+                while self.next_child("array", &["value"])?.is_some() {
+                    items.push(self.read_value(depth + 1)?);
+                }
+            }
+        }
+        Ok(BorrowedValue::Array(items))
+    }
+
+    fn read_struct(&mut self, depth: usize) -> Result<BorrowedValue<'a>> {
+        let mut members = Vec::new();
+        while self.next_child("struct", &["member"])?.is_some() {
+            if members.len() >= self.options.max_members {
+                return limit_exceeded("struct with more members than", self.options.max_members);
+            }
+            self.expect_child("member", "name")?;
+            let name = self.read_text("name")?;
+            self.expect_child("member", "value")?;
+            let value = self.read_value(depth + 1)?;
+            self.expect_end("member")?;
+            members.push((name, value));
+        }
+        Ok(BorrowedValue::Struct(members))
+    }
+}
@@ -0,0 +1,316 @@
+//! Where `ParamReader` puts the values it reads, so the same parser builds `Value` trees and
+//! fills `ValueArena`s straight from the XML, and the reading of scalar and base64 text shared
+//! with the borrowing parser.
+
+#[cfg(not(feature = "simd"))]
+use base64::engine::general_purpose::STANDARD;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::{alphabet, Engine as _};
+#[cfg(feature = "simd")]
+use base64_simd;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Write;
+use xmlfmt::datetime;
+use xmlfmt::error::{ErrorKind, Result, ResultExt};
+use xmlfmt::stream::WarningKind;
+use xmlfmt::{ParseOptions, Value};
+
+/// Values are built bottom up: text is read in pieces, and arrays and structs are started,
+/// filled with finished values, and then finished themselves.
+pub trait TreeBuilder {
+    type Value;
+    /// Text being read, which becomes a value or a member name, or is parsed and discarded.
+    type Text;
+    type Array;
+    type Struct;
+
+    fn start_text(&mut self) -> Self::Text;
+
+    fn push_text(&mut self, text: &mut Self::Text, piece: String);
+
+    fn text<'a>(&'a self, text: &'a Self::Text) -> &'a str;
+
+    fn remove_whitespace(&mut self, text: &mut Self::Text);
+
+    /// Drops text that was parsed into some other value.
+    fn discard(&mut self, text: Self::Text);
+
+    fn string(&mut self, text: Self::Text) -> Self::Value;
+
+    fn datetime(&mut self, text: Self::Text) -> Self::Value;
+
+    fn base64_text(&mut self, text: Self::Text) -> Self::Value;
+
+    /// Base64 data, written by `decode`.
+    fn base64<F>(&mut self, decode: F) -> Result<Self::Value>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<()>;
+
+    /// Values without text or children, like numbers.
+    fn scalar(&mut self, value: Value) -> Self::Value;
+
+    fn start_array(&mut self) -> Self::Array;
+
+    fn push_item(&mut self, array: &mut Self::Array, item: Self::Value);
+
+    fn array(&mut self, array: Self::Array) -> Self::Value;
+
+    fn start_struct(&mut self) -> Self::Struct;
+
+    fn push_member(&mut self, members: &mut Self::Struct, name: Self::Text, value: Self::Value);
+
+    fn structure(&mut self, members: Self::Struct) -> Self::Value;
+}
+
+/// Builds `Value` trees, moving the text xml-rs hands out into them.
+pub struct ValueBuilder;
+
+impl TreeBuilder for ValueBuilder {
+    type Value = Value;
+    type Text = String;
+    type Array = Vec<Value>;
+    type Struct = HashMap<String, Value>;
+
+    fn start_text(&mut self) -> String {
+        String::new()
+    }
+
+    fn push_text(&mut self, text: &mut String, piece: String) {
+        if text.is_empty() {
+            *text = piece;
+        } else {
+            text.push_str(&piece);
+        }
+    }
+
+    fn text<'a>(&'a self, text: &'a String) -> &'a str {
+        text
+    }
+
+    fn remove_whitespace(&mut self, text: &mut String) {
+        text.retain(|c| !c.is_ascii_whitespace());
+    }
+
+    fn discard(&mut self, _: String) {}
+
+    fn string(&mut self, text: String) -> Value {
+        Value::String(text)
+    }
+
+    fn datetime(&mut self, text: String) -> Value {
+        Value::DateTime(text)
+    }
+
+    fn base64_text(&mut self, text: String) -> Value {
+        Value::Base64Text(text)
+    }
+
+    fn base64<F>(&mut self, decode: F) -> Result<Value>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<()>,
+    {
+        let mut bytes = Vec::new();
+        decode(&mut bytes)?;
+        Ok(Value::Base64(bytes))
+    }
+
+    fn scalar(&mut self, value: Value) -> Value {
+        value
+    }
+
+    fn start_array(&mut self) -> Vec<Value> {
+        Vec::new()
+    }
+
+    fn push_item(&mut self, array: &mut Vec<Value>, item: Value) {
+        array.push(item);
+    }
+
+    fn array(&mut self, array: Vec<Value>) -> Value {
+        Value::Array(array)
+    }
+
+    fn start_struct(&mut self) -> HashMap<String, Value> {
+        HashMap::new()
+    }
+
+    fn push_member(&mut self, members: &mut HashMap<String, Value>, name: String, value: Value) {
+        members.insert(name, value);
+    }
+
+    fn structure(&mut self, members: HashMap<String, Value>) -> Value {
+        Value::Struct(members)
+    }
+}
+
+const URL_SAFE_LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub fn decode_base64(data: &[u8], url_safe: bool) -> Result<Vec<u8>> {
+    if url_safe {
+        URL_SAFE_LENIENT
+            .decode(data)
+            .chain_err(|| "Failed to parse base64")
+    } else {
+        decode_standard(data).chain_err(|| "Failed to parse base64")
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+fn decode_standard(data: &[u8]) -> ::std::result::Result<Vec<u8>, base64::DecodeError> {
+    STANDARD.decode(data)
+}
+
+#[cfg(feature = "simd")]
+fn decode_standard(data: &[u8]) -> ::std::result::Result<Vec<u8>, base64_simd::Error> {
+    base64_simd::STANDARD.decode_to_vec(data)
+}
+
+/// Whether `data` decodes as padded standard base64, without decoding it.
+pub fn is_base64(data: &[u8]) -> bool {
+    let sextet = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    if !data.len().is_multiple_of(4) {
+        return false;
+    }
+    let padding = data
+        .iter()
+        .rev()
+        .take(2)
+        .take_while(|&&c| c == b'=')
+        .count();
+    let (digits, _) = data.split_at(data.len() - padding);
+    if digits.iter().any(|&c| sextet(c).is_none()) {
+        return false;
+    }
+    // Bits past the end of the data have to be zero, as in any encoder's output.
+    let unused = [0, 0b11, 0b1111][padding];
+    digits
+        .last()
+        .is_none_or(|&c| sextet(c).unwrap() & unused == 0)
+}
+
+lazy_static! {
+    static ref INTEGER: Regex = Regex::new(r"^[+-]?[0-9]+$").unwrap();
+    static ref BOOLEAN: Regex = Regex::new(r"^[01]$").unwrap();
+    static ref DOUBLE: Regex = Regex::new(r"^[+-]?(?:[0-9]+\.?[0-9]*|\.[0-9]+)$").unwrap();
+    static ref DATETIME: Regex = Regex::new(r"^[0-9]{8}T[0-9]{2}:[0-9]{2}:[0-9]{2}$").unwrap();
+}
+
+/// The types `scalar` reads, and not just keeps as strings.
+pub const SCALAR_TYPES: &[&str] = &[
+    "i4",
+    "int",
+    "boolean",
+    "string",
+    "double",
+    "dateTime.iso8601",
+    "i8",
+    "nil",
+    "dateTime",
+    "biginteger",
+    "bigdecimal",
+];
+
+/// What a scalar type's text becomes.
+pub enum Scalar {
+    String,
+    DateTime,
+    Parsed(Value),
+}
+
+/// Strict parsing only takes text matching the formats from the spec.
+fn check_spec(options: &ParseOptions, pattern: &Regex, kind: &str, text: &str) -> Result<()> {
+    if options.strict && !pattern.is_match(text) {
+        bail!(ErrorKind::Decoding(format!(
+            "{:?} is not a valid {} per the XML-RPC spec",
+            text, kind
+        )));
+    }
+    Ok(())
+}
+
+/// Makes a value of the text of a scalar type, or tells how to keep the text as one, calling
+/// `warn` with what lenient parsing let through.
+pub fn scalar(
+    tag: &str,
+    text: &str,
+    options: &ParseOptions,
+    warn: &mut dyn FnMut(WarningKind),
+) -> Result<Scalar> {
+    let lenient = options.lenient && !options.strict;
+    Ok(Scalar::Parsed(match tag {
+        "i4" | "int" => {
+            check_spec(options, &INTEGER, "integer", text)?;
+            Value::Int(
+                text.trim()
+                    .parse()
+                    .chain_err(|| "Failed to parse integer")?,
+            )
+        }
+        "boolean" => {
+            check_spec(options, &BOOLEAN, "boolean", text)?;
+            let text = text.trim();
+            // PHP and Python services often send the word form.
+            if text.eq_ignore_ascii_case("true") {
+                Value::Bool(true)
+            } else if text.eq_ignore_ascii_case("false") {
+                Value::Bool(false)
+            } else {
+                let v: i32 = text.parse().chain_err(|| "Failed to parse boolean")?;
+                Value::Bool(v != 0)
+            }
+        }
+        "string" => return Ok(Scalar::String),
+        "double" => {
+            check_spec(options, &DOUBLE, "double", text)?;
+            Value::Double(text.trim().parse().chain_err(|| "Failed to parse double")?)
+        }
+        "dateTime.iso8601" => {
+            check_spec(options, &DATETIME, "dateTime.iso8601", text)?;
+            match datetime::complete_truncated(text) {
+                Some(completed) if lenient => {
+                    warn(WarningKind::TruncatedDateTime(text.into()));
+                    Value::DateTime(completed)
+                }
+                _ => return Ok(Scalar::DateTime),
+            }
+        }
+        "i8" => Value::I8(text.trim().parse().chain_err(|| "Failed to parse i8")?),
+        "nil" => {
+            if !text.trim().is_empty() {
+                bail!(ErrorKind::Decoding("<nil> must be empty".into()));
+            }
+            Value::Nil
+        }
+        // Apache's `ex:dateTime` and `ex:serializable`, passed through as they are.
+        "dateTime" => return Ok(Scalar::DateTime),
+        #[cfg(feature = "bignum")]
+        "biginteger" => Value::BigInt(
+            text.trim()
+                .parse()
+                .chain_err(|| "Failed to parse biginteger")?,
+        ),
+        #[cfg(feature = "bignum")]
+        "bigdecimal" => Value::BigDecimal(
+            text.trim()
+                .parse()
+                .chain_err(|| "Failed to parse bigdecimal")?,
+        ),
+        // Without the `bignum` feature, big numbers are kept as their text.
+        #[cfg(not(feature = "bignum"))]
+        "biginteger" | "bigdecimal" => Value::String(text.trim().into()),
+        // Unknown types in lenient parsing, which are kept as strings.
+        _ => return Ok(Scalar::String),
+    }))
+}
@@ -0,0 +1,61 @@
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Bytes written as `base64`, rather than as an array of `int`s like a plain `Vec<u8>` or
+/// `[u8; N]` without `serde_bytes`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Base64<T = Vec<u8>>(pub T);
+
+impl<T: AsRef<[u8]>> Serialize for Base64<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0.as_ref())
+    }
+}
+
+impl<'de, T: TryFrom<Vec<u8>>> Deserialize<'de> for Base64<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Base64<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+        let len = bytes.len();
+        T::try_from(bytes)
+            .map(Base64)
+            .map_err(|_| de::Error::invalid_length(len, &"bytes of a fitting length"))
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("base64 bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    // Other formats, like JSON, write bytes as a sequence of numbers.
+    fn visit_seq<A>(self, mut seq: A) -> Result<Vec<u8>, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
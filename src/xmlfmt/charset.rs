@@ -0,0 +1,126 @@
+use super::error::{ErrorKind, Result};
+use regex::bytes::Regex;
+use std::borrow::Cow;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Charset {
+    Utf8,
+    Latin1,
+    Windows1252,
+}
+
+impl Charset {
+    fn from_label(label: &str) -> Result<Charset> {
+        Ok(match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "us-ascii" | "ascii" => Charset::Utf8,
+            "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" | "l1" => Charset::Latin1,
+            "windows-1252" | "cp1252" | "x-cp1252" => Charset::Windows1252,
+            _ => bail!(ErrorKind::UnsupportedData(format!(
+                "unsupported charset {:?}",
+                label
+            ))),
+        })
+    }
+}
+
+// Code points of 0x80..0xA0 in windows-1252, the only range that differs from latin-1.
+// Undefined bytes map to the C1 control with the same value, as browsers do.
+static WINDOWS_1252_HIGH: [u16; 32] = [
+    0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x008D, 0x017D, 0x008F, 0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178,
+];
+
+fn decode_byte(charset: Charset, byte: u8) -> char {
+    match (charset, byte) {
+        (Charset::Windows1252, 0x80..=0x9F) => {
+            std::char::from_u32(u32::from(WINDOWS_1252_HIGH[usize::from(byte - 0x80)]))
+                .unwrap_or('\u{FFFD}')
+        }
+        _ => char::from(byte),
+    }
+}
+
+/// Extracts the `charset` parameter of a `Content-Type` header value.
+pub fn from_content_type(content_type: &str) -> Option<&str> {
+    content_type
+        .split(';')
+        .skip(1)
+        .filter_map(|param| {
+            let mut parts = param.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if key.trim().eq_ignore_ascii_case("charset") => {
+                    Some(value.trim().trim_matches('"'))
+                }
+                _ => None,
+            }
+        })
+        .next()
+}
+
+/// Turns a document into UTF-8, following `charset` if given, or the XML declaration otherwise.
+///
+/// The declaration's encoding is rewritten to match, so XML parsers read the result correctly.
+pub fn decode(content: Vec<u8>, charset: Option<&str>) -> Result<String> {
+    let (charset, declared) = detect(&content, charset)?;
+    if charset == Charset::Utf8 {
+        return String::from_utf8(content).map_err(|_| {
+            ErrorKind::Decoding("Data is not valid UTF-8, and declares no other charset".into())
+                .into()
+        });
+    }
+    Ok(transcode(&content, charset, declared))
+}
+
+/// Like `decode`, but borrows documents that are already UTF-8, leaving them to be validated as
+/// they are parsed.
+pub fn decode_slice<'a>(content: &'a [u8], charset: Option<&str>) -> Result<Cow<'a, [u8]>> {
+    Ok(match detect(content, charset)? {
+        (Charset::Utf8, _) => Cow::Borrowed(content),
+        (charset, declared) => Cow::Owned(transcode(content, charset, declared).into_bytes()),
+    })
+}
+
+type Declared = Option<(Range<usize>, String)>;
+
+fn detect(content: &[u8], charset: Option<&str>) -> Result<(Charset, Declared)> {
+    lazy_static! {
+        static ref DECLARATION: Regex =
+            Regex::new(r#"^(?-u:\xEF\xBB\xBF)?\s*<\?xml[^>]*?\sencoding\s*=\s*["']([^"']*)["']"#)
+                .unwrap();
+    }
+    let declared = DECLARATION
+        .captures(content)
+        .and_then(|captures| captures.get(1))
+        .map(|label| {
+            (
+                label.range(),
+                String::from_utf8_lossy(label.as_bytes()).into_owned(),
+            )
+        });
+    let charset = match (charset, declared.as_ref()) {
+        (Some(label), _) => Charset::from_label(label)?,
+        (None, Some((_, label))) => Charset::from_label(label)?,
+        (None, None) => Charset::Utf8,
+    };
+    Ok((charset, declared))
+}
+
+fn transcode(content: &[u8], charset: Charset, declared: Declared) -> String {
+    let mut decoded = String::with_capacity(content.len());
+    let rest = match declared {
+        Some((range, _)) => {
+            decoded.extend(
+                content[..range.start]
+                    .iter()
+                    .map(|&b| decode_byte(charset, b)),
+            );
+            decoded.push_str("UTF-8");
+            &content[range.end..]
+        }
+        None => content,
+    };
+    decoded.extend(rest.iter().map(|&b| decode_byte(charset, b)));
+    decoded
+}
@@ -0,0 +1,249 @@
+use super::super::error::{Error, ErrorKind, Result};
+use super::super::options::NaiveDateTimePolicy;
+use super::super::Value;
+use super::{DateTime, StringFormat};
+use chrono::{
+    self, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+    Timelike, Utc,
+};
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+
+fn invalid(what: &str) -> Error {
+    ErrorKind::Decoding(what.into()).into()
+}
+
+impl DateTime {
+    pub fn to_naive_date_time(&self) -> Result<NaiveDateTime> {
+        let date = NaiveDate::from_ymd_opt(self.year, self.month.into(), self.day.into())
+            .ok_or_else(|| invalid("Invalid date"))?;
+        let time = NaiveTime::from_hms_nano_opt(
+            self.hour.into(),
+            self.minute.into(),
+            self.second.min(59).into(),
+            self.nanosecond,
+        )
+        .ok_or_else(|| invalid("Invalid time"))?;
+        Ok(date.and_time(time))
+    }
+
+    pub fn to_fixed_offset(
+        &self,
+        policy: NaiveDateTimePolicy,
+    ) -> Result<chrono::DateTime<FixedOffset>> {
+        let naive = self.to_naive_date_time()?;
+        let offset =
+            match (self.offset, policy) {
+                (Some(offset), _) => FixedOffset::east_opt(offset)
+                    .ok_or_else(|| invalid("Invalid timezone offset"))?,
+                (None, NaiveDateTimePolicy::AssumeUtc) => Utc.fix(),
+                (None, NaiveDateTimePolicy::AssumeOffset(offset)) => FixedOffset::east_opt(offset)
+                    .ok_or_else(|| invalid("Invalid timezone offset"))?,
+                (None, NaiveDateTimePolicy::AssumeLocal) => {
+                    return Local
+                        .from_local_datetime(&naive)
+                        .earliest()
+                        .map(|v| v.fixed_offset())
+                        .ok_or_else(|| invalid("Time does not exist in the local timezone"));
+                }
+            };
+        offset
+            .from_local_datetime(&naive)
+            .single()
+            .ok_or_else(|| invalid("Invalid timezone offset"))
+    }
+}
+
+impl From<NaiveDateTime> for DateTime {
+    fn from(v: NaiveDateTime) -> DateTime {
+        DateTime {
+            year: v.year(),
+            month: v.month() as u8,
+            day: v.day() as u8,
+            hour: v.hour() as u8,
+            minute: v.minute() as u8,
+            second: v.second() as u8,
+            // Leap seconds are kept as the last second of the minute.
+            nanosecond: v.nanosecond().min(999_999_999),
+            offset: None,
+        }
+    }
+}
+
+impl<Tz: TimeZone> From<chrono::DateTime<Tz>> for DateTime {
+    fn from(v: chrono::DateTime<Tz>) -> DateTime {
+        DateTime {
+            offset: Some(v.offset().fix().local_minus_utc()),
+            ..v.naive_local().into()
+        }
+    }
+}
+
+impl<Tz: TimeZone> From<chrono::DateTime<Tz>> for Value {
+    fn from(v: chrono::DateTime<Tz>) -> Value {
+        Value::DateTime(DateTime::from(v).to_string())
+    }
+}
+
+impl From<NaiveDateTime> for Value {
+    fn from(v: NaiveDateTime) -> Value {
+        Value::DateTime(DateTime::from(v).to_string())
+    }
+}
+
+fn parse(v: Value) -> Result<DateTime> {
+    match v {
+        Value::DateTime(ref v) => DateTime::parse(v),
+        v => bail!(ErrorKind::Decoding(format!(
+            "Expected dateTime.iso8601, got {}",
+            v.unexpected()
+        ))),
+    }
+}
+
+/// Values without a timezone are assumed to be in UTC.
+impl TryFrom<Value> for chrono::DateTime<FixedOffset> {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<chrono::DateTime<FixedOffset>> {
+        parse(v)?.to_fixed_offset(NaiveDateTimePolicy::AssumeUtc)
+    }
+}
+
+/// Values without a timezone are assumed to be in UTC.
+impl TryFrom<Value> for chrono::DateTime<Utc> {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<chrono::DateTime<Utc>> {
+        chrono::DateTime::<FixedOffset>::try_from(v).map(|v| v.with_timezone(&Utc))
+    }
+}
+
+/// The timezone, if present, is ignored.
+impl TryFrom<Value> for NaiveDateTime {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<NaiveDateTime> {
+        parse(v)?.to_naive_date_time()
+    }
+}
+
+fn format_with<F: fmt::Display>(v: F) -> ::std::result::Result<String, String> {
+    let mut formatted = String::new();
+    write!(formatted, "{}", v).map_err(|_| "Invalid datetime format".to_string())?;
+    Ok(formatted)
+}
+
+impl StringFormat for NaiveDateTime {
+    fn format(&self, format: &str) -> ::std::result::Result<String, String> {
+        format_with(self.format(format))
+    }
+
+    fn parse(value: &str, format: &str) -> ::std::result::Result<NaiveDateTime, String> {
+        NaiveDateTime::parse_from_str(value, format).map_err(|err| err.to_string())
+    }
+}
+
+impl StringFormat for chrono::DateTime<FixedOffset> {
+    fn format(&self, format: &str) -> ::std::result::Result<String, String> {
+        format_with(self.format(format))
+    }
+
+    fn parse(
+        value: &str,
+        format: &str,
+    ) -> ::std::result::Result<chrono::DateTime<FixedOffset>, String> {
+        chrono::DateTime::parse_from_str(value, format).map_err(|err| err.to_string())
+    }
+}
+
+/// Strings without a timezone are taken to be in UTC.
+impl StringFormat for chrono::DateTime<Utc> {
+    fn format(&self, format: &str) -> ::std::result::Result<String, String> {
+        format_with(self.format(format))
+    }
+
+    fn parse(value: &str, format: &str) -> ::std::result::Result<chrono::DateTime<Utc>, String> {
+        chrono::DateTime::parse_from_str(value, format)
+            .map(|v| v.with_timezone(&Utc))
+            .or_else(|_| NaiveDateTime::parse_from_str(value, format).map(|v| v.and_utc()))
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::datetime::chrono::fixed_offset")]` on
+/// `DateTime<FixedOffset>` fields.
+///
+/// Values without a timezone follow `DeserializeOptions::naive_datetimes`.
+pub mod fixed_offset {
+    use super::super::super::options::NaiveDateTimePolicy;
+    use super::super::{deserialize_string, serialize_str, DateTime};
+    use chrono::{self, FixedOffset};
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S>(
+        value: &chrono::DateTime<FixedOffset>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_str(&DateTime::from(*value).to_string(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<chrono::DateTime<FixedOffset>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = deserialize_string(deserializer)?;
+        DateTime::parse(&value)
+            .and_then(|v| v.to_fixed_offset(NaiveDateTimePolicy::AssumeLocal))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::datetime::chrono::utc")]` on `DateTime<Utc>` fields.
+///
+/// Values are written with a `Z` timezone, and values read with any other timezone are converted.
+pub mod utc {
+    use chrono::{self, Utc};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &chrono::DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::fixed_offset::serialize(&value.fixed_offset(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<chrono::DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::fixed_offset::deserialize(deserializer).map(|v| v.with_timezone(&Utc))
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::datetime::chrono::naive")]` on `NaiveDateTime` fields.
+pub mod naive {
+    use super::super::{deserialize_string, serialize_str, DateTime};
+    use chrono::NaiveDateTime;
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_str(&DateTime::from(*value).to_string(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = deserialize_string(deserializer)?;
+        DateTime::parse(&value)
+            .and_then(|v| v.to_naive_date_time())
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,408 @@
+//! `dateTime.iso8601` values, and serde helpers writing fields as them.
+//!
+//! The helpers for types with a timezone, `chrono::fixed_offset`, `chrono::utc` and
+//! `time::offset`, take values read without one to be in the local timezone. The XML-RPC
+//! deserializer gives such values the timezone of `DeserializeOptions::naive_datetimes` first,
+//! so with it, only `NaiveDateTimePolicy::AssumeLocal` leaves any. Other deserializers, like
+//! serde_json's, hand them over as they are.
+
+use super::error::{self, ErrorKind};
+use super::options::{DateTimeFormat, NaiveDateTimePolicy};
+use super::Value;
+use serde::de::{self, Visitor};
+use serde::{ser, Deserialize, Deserializer, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "chrono")]
+pub mod chrono;
+#[cfg(feature = "time")]
+pub mod time;
+
+/// A parsed `dateTime.iso8601` value.
+///
+/// Accepts the spec's compact `19980717T14:08:55` as well as the extended `1998-07-17T14:08:55`
+/// form, either of them optionally followed by fractional seconds and a timezone. Fractions may
+/// be separated by a comma too, as ISO 8601 allows and some .NET and Java peers write them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in seconds, or `None` for values without a timezone.
+    pub offset: Option<i32>,
+}
+
+impl DateTime {
+    pub fn parse(v: &str) -> error::Result<DateTime> {
+        Parser::new(v.trim()).datetime().ok_or_else(|| {
+            ErrorKind::Decoding(format!("Invalid dateTime.iso8601 value: {}", v)).into()
+        })
+    }
+
+    pub fn is_naive(&self) -> bool {
+        self.offset.is_none()
+    }
+
+    pub fn apply_policy(mut self, policy: NaiveDateTimePolicy) -> DateTime {
+        if self.offset.is_none() {
+            match policy {
+                NaiveDateTimePolicy::AssumeUtc => self.offset = Some(0),
+                NaiveDateTimePolicy::AssumeOffset(offset) => self.offset = Some(offset),
+                NaiveDateTimePolicy::AssumeLocal => {}
+            }
+        }
+        self
+    }
+
+    /// The same instant in the timezone `offset` seconds east of UTC. Datetimes without a
+    /// timezone are returned as they are.
+    pub fn to_offset(self, offset: i32) -> DateTime {
+        let current = match self.offset {
+            Some(current) => current,
+            None => return self,
+        };
+        // A leap second stays one, rather than rolling over into the next minute.
+        let leap = self.second.saturating_sub(59);
+        let seconds = days_from_civil(self.year, self.month, self.day) * 86_400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second - leap)
+            + i64::from(offset - current);
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time = seconds.rem_euclid(86_400);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (time / 3600) as u8,
+            minute: (time / 60 % 60) as u8,
+            second: (time % 60) as u8 + leap,
+            nanosecond: self.nanosecond,
+            offset: Some(offset),
+        }
+    }
+
+    pub fn format(&self, format: DateTimeFormat) -> String {
+        let mut out = match format {
+            DateTimeFormat::Compact => format!(
+                "{:04}{:02}{:02}T{:02}:{:02}:{:02}",
+                self.year, self.month, self.day, self.hour, self.minute, self.second
+            ),
+            DateTimeFormat::Extended => format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                self.year, self.month, self.day, self.hour, self.minute, self.second
+            ),
+        };
+        if self.nanosecond != 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            out.push('.');
+            out.push_str(fraction.trim_end_matches('0'));
+        }
+        match self.offset {
+            None => {}
+            Some(0) => out.push('Z'),
+            Some(offset) => {
+                let minutes = offset.abs() / 60;
+                out.push_str(&format!(
+                    "{}{:02}:{:02}",
+                    if offset < 0 { '-' } else { '+' },
+                    minutes / 60,
+                    minutes % 60
+                ));
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format(DateTimeFormat::Compact))
+    }
+}
+
+impl From<DateTime> for Value {
+    fn from(v: DateTime) -> Value {
+        Value::DateTime(v.to_string())
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = error::Error;
+
+    fn from_str(v: &str) -> error::Result<DateTime> {
+        DateTime::parse(v)
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(v: &'a str) -> Self {
+        Parser { rest: v }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.rest.starts_with(c) {
+            self.rest = &self.rest[c.len_utf8()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    fn digits(&mut self, count: usize) -> Option<u32> {
+        let digits = self.rest.get(..count)?;
+        if !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        self.rest = &self.rest[count..];
+        digits.parse().ok()
+    }
+
+    fn datetime(mut self) -> Option<DateTime> {
+        let year = self.digits(4)? as i32;
+        let extended = self.eat('-');
+        let month = self.digits(2)? as u8;
+        if extended && !self.eat('-') {
+            return None;
+        }
+        let day = self.digits(2)? as u8;
+        if !self.eat('T') {
+            return None;
+        }
+        let hour = self.digits(2)? as u8;
+        let extended = self.eat(':');
+        let minute = self.digits(2)? as u8;
+        if extended && !self.eat(':') {
+            return None;
+        }
+        let second = self.digits(2)? as u8;
+        let nanosecond = if self.eat('.') || self.eat(',') {
+            self.fraction()?
+        } else {
+            0
+        };
+        let offset = self.offset()?;
+        if !self.rest.is_empty()
+            || !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return None;
+        }
+        Some(DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset,
+        })
+    }
+
+    fn fraction(&mut self) -> Option<u32> {
+        let len = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        if len == 0 {
+            return None;
+        }
+        let digits = &self.rest[..len.min(9)];
+        self.rest = &self.rest[len..];
+        let value: u32 = digits.parse().ok()?;
+        Some(value * 10u32.pow(9 - digits.len() as u32))
+    }
+
+    fn offset(&mut self) -> Option<Option<i32>> {
+        if self.eat('Z') {
+            return Some(Some(0));
+        }
+        let sign = if self.eat('+') {
+            1
+        } else if self.eat('-') {
+            -1
+        } else {
+            return Some(None);
+        };
+        let hours = self.digits(2)? as i32;
+        let extended = self.eat(':');
+        let minutes = if extended || !self.rest.is_empty() {
+            self.digits(2)? as i32
+        } else {
+            0
+        };
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        Some(Some(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+/// Completes a date alone, like `1998-07-17`, with midnight, and a time alone, like `14:08:55`,
+/// with the epoch date, as some calendaring APIs send them. Returns `None` for anything else,
+/// including whole datetimes.
+pub fn complete_truncated(v: &str) -> Option<String> {
+    let v = v.trim();
+    if v.contains('T') {
+        return None;
+    }
+    let completed = if v.contains(':') {
+        format!("19700101T{}", v)
+    } else {
+        format!("{}T00:00:00", v)
+    };
+    DateTime::parse(&completed).ok().map(|_| completed)
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01, after Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year as i32, month as u8, day as u8)
+}
+
+/// Newtype name that makes the XML-RPC serializer emit `dateTime.iso8601` instead of `string`.
+pub const NEWTYPE_NAME: &str = "$xml_rpc::DateTime";
+
+/// Like `NEWTYPE_NAME`, but the value is kept exactly as it is, without reformatting.
+pub const VERBATIM_NEWTYPE_NAME: &str = "$xml_rpc::DateTime::Verbatim";
+
+pub fn serialize_str<S>(value: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(NEWTYPE_NAME, value)
+}
+
+pub fn deserialize_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(NEWTYPE_NAME, DateTimeVisitor)
+}
+
+/// `serialize_str` and `deserialize_string` for `Option<String>` members, to use as
+/// `#[serde(default, with = "xml_rpc::datetime::option")]`.
+pub mod option {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *value {
+            Some(ref value) => super::serialize_str(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize_string(deserializer).map(Some)
+    }
+}
+
+/// Datetimes that `xmlrpc_datetime_string!` can write to and read from plain strings.
+///
+/// Formats are in the syntax of the crate the type comes from: `strftime`-like for `chrono`, and
+/// format descriptions like `[year]-[month]-[day]` for `time`.
+pub trait StringFormat: Sized {
+    fn format(&self, format: &str) -> Result<String, String>;
+    fn parse(value: &str, format: &str) -> Result<Self, String>;
+}
+
+pub fn serialize_formatted<T, S>(value: &T, format: &str, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: StringFormat,
+    S: Serializer,
+{
+    let value = value.format(format).map_err(ser::Error::custom)?;
+    serializer.serialize_str(&value)
+}
+
+pub fn deserialize_formatted<'de, T, D>(format: &str, deserializer: D) -> Result<T, D::Error>
+where
+    T: StringFormat,
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    T::parse(&value, format).map_err(de::Error::custom)
+}
+
+struct DateTimeVisitor;
+
+impl<'de> Visitor<'de> for DateTimeVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("dateTime.iso8601")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_string(self)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<String, E>
+    where
+        E: de::Error,
+    {
+        Ok(v.into())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<String, E>
+    where
+        E: de::Error,
+    {
+        Ok(v)
+    }
+}
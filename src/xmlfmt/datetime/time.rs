@@ -0,0 +1,181 @@
+use super::super::error::{Error, ErrorKind, Result, ResultExt};
+use super::super::options::NaiveDateTimePolicy;
+use super::super::Value;
+use super::{DateTime, StringFormat};
+use std::convert::TryFrom;
+use time::format_description::{self, OwnedFormatItem};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+impl DateTime {
+    pub fn to_primitive_date_time(&self) -> Result<PrimitiveDateTime> {
+        let month = Month::try_from(self.month).chain_err(|| "Invalid month")?;
+        let date =
+            Date::from_calendar_date(self.year, month, self.day).chain_err(|| "Invalid date")?;
+        let time =
+            Time::from_hms_nano(self.hour, self.minute, self.second.min(59), self.nanosecond)
+                .chain_err(|| "Invalid time")?;
+        Ok(PrimitiveDateTime::new(date, time))
+    }
+
+    pub fn to_offset_date_time(&self, policy: NaiveDateTimePolicy) -> Result<OffsetDateTime> {
+        let naive = self.to_primitive_date_time()?;
+        let offset = match (self.offset, policy) {
+            (Some(offset), _) => {
+                UtcOffset::from_whole_seconds(offset).chain_err(|| "Invalid timezone offset")?
+            }
+            (None, NaiveDateTimePolicy::AssumeUtc) => UtcOffset::UTC,
+            (None, NaiveDateTimePolicy::AssumeOffset(offset)) => {
+                UtcOffset::from_whole_seconds(offset).chain_err(|| "Invalid timezone offset")?
+            }
+            (None, NaiveDateTimePolicy::AssumeLocal) => local_offset(naive)?,
+        };
+        Ok(naive.assume_offset(offset))
+    }
+}
+
+/// The local offset in effect at `naive`, rather than now. It is looked up at the instant
+/// `naive` would be in UTC first, which only gets a different offset near a change.
+fn local_offset(naive: PrimitiveDateTime) -> Result<UtcOffset> {
+    let offset_at =
+        |v| UtcOffset::local_offset_at(v).chain_err(|| "Failed to determine the local timezone");
+    offset_at(naive.assume_offset(offset_at(naive.assume_utc())?))
+}
+
+impl From<PrimitiveDateTime> for DateTime {
+    fn from(v: PrimitiveDateTime) -> DateTime {
+        DateTime {
+            year: v.year(),
+            month: v.month().into(),
+            day: v.day(),
+            hour: v.hour(),
+            minute: v.minute(),
+            second: v.second(),
+            nanosecond: v.nanosecond(),
+            offset: None,
+        }
+    }
+}
+
+impl From<OffsetDateTime> for DateTime {
+    fn from(v: OffsetDateTime) -> DateTime {
+        DateTime {
+            offset: Some(v.offset().whole_seconds()),
+            ..PrimitiveDateTime::new(v.date(), v.time()).into()
+        }
+    }
+}
+
+impl From<OffsetDateTime> for Value {
+    fn from(v: OffsetDateTime) -> Value {
+        Value::DateTime(DateTime::from(v).to_string())
+    }
+}
+
+impl From<PrimitiveDateTime> for Value {
+    fn from(v: PrimitiveDateTime) -> Value {
+        Value::DateTime(DateTime::from(v).to_string())
+    }
+}
+
+fn parse(v: Value) -> Result<DateTime> {
+    match v {
+        Value::DateTime(ref v) => DateTime::parse(v),
+        v => bail!(ErrorKind::Decoding(format!(
+            "Expected dateTime.iso8601, got {}",
+            v.unexpected()
+        ))),
+    }
+}
+
+/// Values without a timezone are assumed to be in UTC.
+impl TryFrom<Value> for OffsetDateTime {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<OffsetDateTime> {
+        parse(v)?.to_offset_date_time(NaiveDateTimePolicy::AssumeUtc)
+    }
+}
+
+/// The timezone, if present, is ignored.
+impl TryFrom<Value> for PrimitiveDateTime {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<PrimitiveDateTime> {
+        parse(v)?.to_primitive_date_time()
+    }
+}
+
+fn description(format: &str) -> ::std::result::Result<OwnedFormatItem, String> {
+    format_description::parse_owned::<2>(format).map_err(|err| err.to_string())
+}
+
+impl StringFormat for OffsetDateTime {
+    fn format(&self, format: &str) -> ::std::result::Result<String, String> {
+        OffsetDateTime::format(*self, &description(format)?).map_err(|err| err.to_string())
+    }
+
+    fn parse(value: &str, format: &str) -> ::std::result::Result<OffsetDateTime, String> {
+        OffsetDateTime::parse(value, &description(format)?).map_err(|err| err.to_string())
+    }
+}
+
+impl StringFormat for PrimitiveDateTime {
+    fn format(&self, format: &str) -> ::std::result::Result<String, String> {
+        PrimitiveDateTime::format(*self, &description(format)?).map_err(|err| err.to_string())
+    }
+
+    fn parse(value: &str, format: &str) -> ::std::result::Result<PrimitiveDateTime, String> {
+        PrimitiveDateTime::parse(value, &description(format)?).map_err(|err| err.to_string())
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::datetime::time::offset")]` on `OffsetDateTime` fields.
+///
+/// Values without a timezone follow `DeserializeOptions::naive_datetimes`.
+pub mod offset {
+    use super::super::super::options::NaiveDateTimePolicy;
+    use super::super::{deserialize_string, serialize_str, DateTime};
+    use serde::{de, Deserializer, Serializer};
+    use time::OffsetDateTime;
+
+    pub fn serialize<S>(value: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_str(&DateTime::from(*value).to_string(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = deserialize_string(deserializer)?;
+        DateTime::parse(&value)
+            .and_then(|v| v.to_offset_date_time(NaiveDateTimePolicy::AssumeLocal))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::datetime::time::primitive")]` on `PrimitiveDateTime` fields.
+pub mod primitive {
+    use super::super::{deserialize_string, serialize_str, DateTime};
+    use serde::{de, Deserializer, Serializer};
+    use time::PrimitiveDateTime;
+
+    pub fn serialize<S>(value: &PrimitiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_str(&DateTime::from(*value).to_string(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<PrimitiveDateTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = deserialize_string(deserializer)?;
+        DateTime::parse(&value)
+            .and_then(|v| v.to_primitive_date_time())
+            .map_err(de::Error::custom)
+    }
+}
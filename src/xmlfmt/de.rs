@@ -1,30 +1,184 @@
+use super::datetime;
 use super::error::{Error, Result};
+use super::options::{DeserializeOptions, EnumRepresentation, OptionRepresentation};
+use super::ser::I8_NEWTYPE_NAME;
 use super::Value;
 use serde::de::{
     DeserializeSeed, EnumAccess, MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
 };
-use serde::{self, Deserializer};
+use serde::{self, Deserializer as SerdeDeserializer};
 use std;
 use std::collections::HashMap;
 use std::vec;
 
+lazy_static! {
+    static ref DEFAULT_OPTIONS: DeserializeOptions = DeserializeOptions::default();
+}
+
+/// Newtype name through which `Value` asks to receive datetimes as tagged structs.
+const VALUE_NEWTYPE_NAME: &str = "$xml_rpc::Value";
+
+pub struct Deserializer<'a> {
+    value: Value,
+    options: &'a DeserializeOptions,
+    tag_datetimes: bool,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new(value: Value, options: &'a DeserializeOptions) -> Self {
+        Deserializer {
+            value,
+            options,
+            tag_datetimes: false,
+        }
+    }
+
+    /// Turns structs keyed by index into arrays, for `php_arrays`.
+    fn positional(self) -> Self {
+        match self.value {
+            Value::Struct(members) if self.options.php_arrays && is_positional(&members) => {
+                let mut items = members
+                    .into_iter()
+                    .map(|(key, value)| (key.parse::<usize>().unwrap_or_default(), value))
+                    .collect::<Vec<_>>();
+                items.sort_unstable_by_key(|&(index, _)| index);
+                Deserializer {
+                    value: Value::Array(items.into_iter().map(|(_, value)| value).collect()),
+                    ..self
+                }
+            }
+            value => Deserializer { value, ..self },
+        }
+    }
+
+    /// Turns arrays into structs keyed by index, for `php_arrays`.
+    fn associative(self) -> Self {
+        match self.value {
+            Value::Array(items) if self.options.php_arrays => Deserializer {
+                value: Value::Struct(
+                    items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, item)| (index.to_string(), item))
+                        .collect(),
+                ),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Lets byte sequences like `Vec<u8>` be read from `base64` without `serde_bytes`.
+    fn expand_base64(self) -> Result<Self> {
+        Ok(match self.value {
+            Value::Base64(_) | Value::Base64Text(_) if !self.options.strict => Deserializer {
+                value: Value::Array(
+                    self.value
+                        .into_bytes()?
+                        .into_iter()
+                        .map(|b| Value::Int(b.into()))
+                        .collect(),
+                ),
+                ..self
+            },
+            _ => self,
+        })
+    }
+}
+
+/// Whether the keys are `"0"` up to one less than their number, as PHP writes lists.
+fn is_positional(members: &HashMap<String, Value>) -> bool {
+    members.keys().all(|key| match key.parse::<usize>() {
+        Ok(index) => index < members.len() && *key == index.to_string(),
+        Err(_) => false,
+    })
+}
+
+macro_rules! forward_to_default_options {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                Deserializer::new(self, &DEFAULT_OPTIONS).$method($($arg,)* visitor)
+            }
+        )*
+    }
+}
+
 impl<'de> serde::Deserializer<'de> for Value {
     type Error = Error;
 
+    forward_to_default_options! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
     #[inline]
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        match self {
+        let options = self.options;
+        match self.value {
             Value::Int(v) => visitor.visit_i32(v),
+            Value::I8(v) if self.tag_datetimes => {
+                let mut members = HashMap::new();
+                members.insert(I8_NEWTYPE_NAME.into(), Value::String(v.to_string()));
+                visitor.visit_map(MapDeserializer::new(members, options))
+            }
+            Value::I8(v) => visitor.visit_i64(v),
             Value::Bool(v) => visitor.visit_bool(v),
+            Value::Nil => visitor.visit_unit(),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(v) => visitor.visit_string(v.to_string()),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(v) => visitor.visit_string(v.to_string()),
+            Value::DateTime(v) if self.tag_datetimes => {
+                let mut members = HashMap::new();
+                members.insert(datetime::NEWTYPE_NAME.into(), Value::String(v));
+                visitor.visit_map(MapDeserializer::new(members, options))
+            }
             Value::DateTime(v) | Value::String(v) => visitor.visit_string(v),
             Value::Double(v) => visitor.visit_f64(v),
             Value::Base64(v) => visitor.visit_bytes(v.as_slice()),
+            Value::Base64Text(v) => visitor.visit_byte_buf(Value::Base64Text(v).into_bytes()?),
             Value::Array(v) => {
                 let len = v.len();
-                let mut deserializer = SeqDeserializer::new(v);
+                let mut deserializer = SeqDeserializer::new(v, options);
                 let seq = visitor.visit_seq(&mut deserializer)?;
                 let remaining = deserializer.iter.len();
                 if remaining == 0 {
@@ -38,7 +192,7 @@ impl<'de> serde::Deserializer<'de> for Value {
             }
             Value::Struct(v) => {
                 let len = v.len();
-                let mut deserializer = MapDeserializer::new(v);
+                let mut deserializer = MapDeserializer::new(v, options);
                 let map = visitor.visit_map(&mut deserializer)?;
                 let remaining = deserializer.iter.len();
                 if remaining == 0 {
@@ -57,17 +211,19 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
             Value::Bool(v) => visitor.visit_bool(v),
-            Value::String(v) => match v.as_str() {
-                "true" => visitor.visit_bool(true),
-                "false" => visitor.visit_bool(false),
+            // Many servers type their booleans as `int`.
+            Value::Int(v @ 0..=1) if !self.options.strict => visitor.visit_bool(v == 1),
+            Value::String(ref v) if !self.options.strict => match v.as_str() {
+                "true" | "1" => visitor.visit_bool(true),
+                "false" | "0" => visitor.visit_bool(false),
                 _ => Err(serde::de::Error::invalid_value(
-                    Unexpected::Str(&v),
+                    Unexpected::Str(v),
                     &visitor,
                 )),
             },
-            _ => Err(serde::de::Error::invalid_value(self.unexpected(), &visitor)),
+            ref v => Err(serde::de::Error::invalid_value(v.unexpected(), &visitor)),
         }
     }
 
@@ -139,15 +295,15 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
             Value::Double(v) => visitor.visit_f32(v as f32),
-            Value::String(v) => {
+            Value::String(ref v) if !self.options.strict => {
                 let x: Result<f32> = v
                     .parse()
-                    .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(&v), &visitor));
+                    .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(v), &visitor));
                 visitor.visit_f32(x?)
             }
-            _ => Err(serde::de::Error::invalid_value(self.unexpected(), &visitor)),
+            ref v => Err(serde::de::Error::invalid_value(v.unexpected(), &visitor)),
         }
     }
 
@@ -155,16 +311,17 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
             Value::Double(v) => visitor.visit_f64(v),
-            Value::String(v) => {
+            Value::String(ref v) if !self.options.strict => {
                 let x: Result<f64> = v
                     .parse()
-                    .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(&v), &visitor));
+                    .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(v), &visitor));
                 visitor.visit_f64(x?)
             }
-            Value::Int(v) => visitor.visit_f64(v as f64),
-            _ => Err(serde::de::Error::invalid_value(self.unexpected(), &visitor)),
+            Value::Int(v) if !self.options.strict => visitor.visit_f64(f64::from(v)),
+            Value::I8(v) if !self.options.strict => visitor.visit_f64(v as f64),
+            ref v => Err(serde::de::Error::invalid_value(v.unexpected(), &visitor)),
         }
     }
 
@@ -172,7 +329,7 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Value::String(v) = self {
+        if let Value::String(v) = self.value {
             if v.len() != 1 {
                 return Err(serde::de::Error::invalid_value(
                     Unexpected::Str(&v),
@@ -181,7 +338,10 @@ impl<'de> serde::Deserializer<'de> for Value {
             }
             visitor.visit_char(v.chars().next().unwrap())
         } else {
-            Err(serde::de::Error::invalid_value(self.unexpected(), &visitor))
+            Err(serde::de::Error::invalid_value(
+                self.value.unexpected(),
+                &visitor,
+            ))
         }
     }
 
@@ -189,10 +349,13 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Value::String(v) = self {
+        if let Value::String(v) = self.value {
             visitor.visit_str(&v)
         } else {
-            Err(serde::de::Error::invalid_value(self.unexpected(), &visitor))
+            Err(serde::de::Error::invalid_value(
+                self.value.unexpected(),
+                &visitor,
+            ))
         }
     }
 
@@ -200,10 +363,13 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Value::String(v) = self {
+        if let Value::String(v) = self.value {
             visitor.visit_string(v)
         } else {
-            Err(serde::de::Error::invalid_value(self.unexpected(), &visitor))
+            Err(serde::de::Error::invalid_value(
+                self.value.unexpected(),
+                &visitor,
+            ))
         }
     }
 
@@ -211,10 +377,13 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Value::Base64(v) = self {
-            visitor.visit_bytes(v.as_slice())
-        } else {
-            Err(serde::de::Error::invalid_value(self.unexpected(), &visitor))
+        match self.value {
+            Value::Base64(v) => visitor.visit_bytes(v.as_slice()),
+            Value::Base64Text(_) => visitor.visit_byte_buf(self.value.into_bytes()?),
+            _ => Err(serde::de::Error::invalid_value(
+                self.value.unexpected(),
+                &visitor,
+            )),
         }
     }
 
@@ -222,10 +391,14 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Value::Base64(v) = self {
-            visitor.visit_byte_buf(v)
-        } else {
-            Err(serde::de::Error::invalid_value(self.unexpected(), &visitor))
+        match self.value {
+            Value::Base64(_) | Value::Base64Text(_) => {
+                visitor.visit_byte_buf(self.value.into_bytes()?)
+            }
+            _ => Err(serde::de::Error::invalid_value(
+                self.value.unexpected(),
+                &visitor,
+            )),
         }
     }
 
@@ -233,7 +406,14 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
+        match self.value {
+            Value::Nil => return visitor.visit_none(),
+            _ if self.options.options != OptionRepresentation::Array => {
+                return visitor.visit_some(self)
+            }
+            _ => {}
+        }
+        match self.value {
             Value::Array(mut v) => {
                 let v1 = v.pop();
                 if !v.is_empty() {
@@ -243,12 +423,15 @@ impl<'de> serde::Deserializer<'de> for Value {
                     ));
                 }
                 match v1 {
-                    Some(x) => visitor.visit_some(x),
+                    Some(x) => visitor.visit_some(Deserializer::new(x, self.options)),
                     None => visitor.visit_none(),
                 }
             }
-
-            v => visitor.visit_some(v),
+            ref v if self.options.strict => Err(serde::de::Error::invalid_value(
+                v.unexpected(),
+                &"array with at most one element",
+            )),
+            _ => visitor.visit_some(self),
         }
     }
 
@@ -256,7 +439,10 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        if let Value::Struct(v) = self {
+        if let Value::Nil = self.value {
+            return visitor.visit_unit();
+        }
+        if let Value::Struct(v) = self.value {
             if !v.is_empty() {
                 return Err(serde::de::Error::invalid_value(
                     Unexpected::Map,
@@ -266,7 +452,7 @@ impl<'de> serde::Deserializer<'de> for Value {
             visitor.visit_unit()
         } else {
             Err(serde::de::Error::invalid_value(
-                self.unexpected(),
+                self.value.unexpected(),
                 &"empty map",
             ))
         }
@@ -279,25 +465,47 @@ impl<'de> serde::Deserializer<'de> for Value {
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name == VALUE_NEWTYPE_NAME {
+            return visitor.visit_newtype_struct(Deserializer {
+                tag_datetimes: true,
+                ..self
+            });
+        }
+        if name != datetime::NEWTYPE_NAME {
+            return visitor.visit_newtype_struct(self);
+        }
+        let value = match self.value {
+            Value::DateTime(ref v) => v,
+            Value::String(ref v) if !self.options.strict => v,
+            ref v => {
+                return Err(serde::de::Error::invalid_value(
+                    v.unexpected(),
+                    &"dateTime.iso8601",
+                ))
+            }
+        };
+        let value = datetime::DateTime::parse(value)?
+            .apply_policy(self.options.naive_datetimes)
+            .to_string();
+        visitor.visit_newtype_struct(Deserializer::new(Value::String(value), self.options))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        self.positional().expand_base64()?.deserialize_any(visitor)
     }
 
     fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        self.positional().expand_base64()?.deserialize_any(visitor)
     }
 
     fn deserialize_tuple_struct<V>(
@@ -316,19 +524,27 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        self.associative().deserialize_any(visitor)
     }
 
     fn deserialize_struct<V>(
         self,
         _name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        let de = self.associative();
+        if let Value::Struct(ref members) = de.value {
+            if de.options.require_all_members {
+                if let Some(field) = fields.iter().find(|f| !members.contains_key(**f)) {
+                    return Err(serde::de::Error::missing_field(field));
+                }
+            }
+        }
+        de.deserialize_any(visitor)
     }
 
     fn deserialize_enum<V>(
@@ -340,14 +556,35 @@ impl<'de> serde::Deserializer<'de> for Value {
     where
         V: Visitor<'de>,
     {
-        match self {
-            Value::Struct(members) => {
+        match (&self.options.enums, self.value) {
+            (EnumRepresentation::Internal { tag }, Value::Struct(mut members)) => {
+                match members.remove(tag) {
+                    Some(Value::String(variant)) => visitor.visit_enum(EnumDeserializer {
+                        variant,
+                        value: Value::Struct(members),
+                        options: self.options,
+                    }),
+                    _ => Err(serde::de::Error::invalid_value(
+                        Unexpected::Map,
+                        &format!("map with a string {} member", tag).as_str(),
+                    )),
+                }
+            }
+            (EnumRepresentation::UnitAsString, Value::String(variant)) => {
+                visitor.visit_enum(EnumDeserializer {
+                    variant,
+                    value: Value::Struct(HashMap::new()),
+                    options: self.options,
+                })
+            }
+            (_, Value::Struct(members)) => {
                 let mut member_iter = members.into_iter();
                 if let Some((key, value)) = member_iter.next() {
                     if member_iter.next().is_none() {
                         return visitor.visit_enum(EnumDeserializer {
                             variant: key,
                             value,
+                            options: self.options,
                         });
                     }
                 }
@@ -356,7 +593,7 @@ impl<'de> serde::Deserializer<'de> for Value {
                     &"map with a single key",
                 ))
             }
-            other => Err(serde::de::Error::invalid_value(
+            (_, other) => Err(serde::de::Error::invalid_value(
                 other.unexpected(),
                 &"map with a single key",
             )),
@@ -368,19 +605,142 @@ impl<'de> serde::Deserializer<'de> for Value {
     }
 }
 
-struct SeqDeserializer {
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(VALUE_NEWTYPE_NAME, ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any XML-RPC value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i32<E>(self, v: i32) -> std::result::Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Value, E> {
+        Ok(match std::convert::TryFrom::try_from(v) {
+            Ok(v) => Value::Int(v),
+            Err(_) => Value::String(v.to_string()),
+        })
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Value, E> {
+        Ok(match std::convert::TryFrom::try_from(v) {
+            Ok(v) => Value::Int(v),
+            Err(_) => Value::String(v.to_string()),
+        })
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Value, E> {
+        Ok(Value::Double(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Value, E> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> std::result::Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Value, E> {
+        Ok(Value::Base64(v.into()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> std::result::Result<Value, E> {
+        Ok(Value::Base64(v))
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Array(Vec::new()))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> std::result::Result<Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        serde::Deserialize::deserialize(deserializer).map(|v| Value::Array(vec![v]))
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Value, E> {
+        Ok(Value::Nil)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut members = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(key) = map.next_key::<String>()? {
+            if key == datetime::NEWTYPE_NAME && members.is_empty() {
+                let value: String = map.next_value()?;
+                if map.next_key::<String>()?.is_none() {
+                    return Ok(Value::DateTime(value));
+                }
+                return Err(serde::de::Error::custom("datetime with extra members"));
+            }
+            if key == I8_NEWTYPE_NAME && members.is_empty() {
+                let value: String = map.next_value()?;
+                let value = value.parse().map_err(serde::de::Error::custom)?;
+                if map.next_key::<String>()?.is_none() {
+                    return Ok(Value::I8(value));
+                }
+                return Err(serde::de::Error::custom("i8 with extra members"));
+            }
+            let value = map.next_value()?;
+            members.insert(key, value);
+        }
+        Ok(Value::Struct(members))
+    }
+}
+
+struct SeqDeserializer<'a> {
     iter: vec::IntoIter<Value>,
+    options: &'a DeserializeOptions,
 }
 
-impl SeqDeserializer {
-    fn new(vec: Vec<Value>) -> Self {
+impl<'a> SeqDeserializer<'a> {
+    fn new(vec: Vec<Value>, options: &'a DeserializeOptions) -> Self {
         SeqDeserializer {
             iter: vec.into_iter(),
+            options,
         }
     }
 }
 
-impl<'de> serde::Deserializer<'de> for SeqDeserializer {
+impl<'a, 'de> serde::Deserializer<'de> for SeqDeserializer<'a> {
     type Error = Error;
 
     #[inline]
@@ -408,7 +768,7 @@ impl<'de> serde::Deserializer<'de> for SeqDeserializer {
     }
 }
 
-impl<'de> SeqAccess<'de> for SeqDeserializer {
+impl<'a, 'de> SeqAccess<'de> for SeqDeserializer<'a> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -416,7 +776,9 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => seed
+                .deserialize(Deserializer::new(value, self.options))
+                .map(Some),
             None => Ok(None),
         }
     }
@@ -429,21 +791,23 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
     }
 }
 
-struct MapDeserializer {
+struct MapDeserializer<'a> {
     iter: <HashMap<String, Value> as IntoIterator>::IntoIter,
     value: Option<Value>,
+    options: &'a DeserializeOptions,
 }
 
-impl MapDeserializer {
-    fn new(map: HashMap<String, Value>) -> Self {
+impl<'a> MapDeserializer<'a> {
+    fn new(map: HashMap<String, Value>, options: &'a DeserializeOptions) -> Self {
         MapDeserializer {
             iter: map.into_iter(),
             value: None,
+            options,
         }
     }
 }
 
-impl<'de> MapAccess<'de> for MapDeserializer {
+impl<'a, 'de> MapAccess<'de> for MapDeserializer<'a> {
     type Error = Error;
 
     fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
@@ -453,7 +817,8 @@ impl<'de> MapAccess<'de> for MapDeserializer {
         match self.iter.next() {
             Some((key, value)) => {
                 self.value = Some(value);
-                seed.deserialize(Value::String(key)).map(Some)
+                seed.deserialize(Deserializer::new(Value::String(key), self.options))
+                    .map(Some)
             }
             None => Ok(None),
         }
@@ -464,7 +829,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(Deserializer::new(value, self.options)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -477,7 +842,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
     }
 }
 
-impl<'de> serde::Deserializer<'de> for MapDeserializer {
+impl<'a, 'de> serde::Deserializer<'de> for MapDeserializer<'a> {
     type Error = Error;
 
     #[inline]
@@ -495,21 +860,22 @@ impl<'de> serde::Deserializer<'de> for MapDeserializer {
     }
 }
 
-struct EnumDeserializer {
+struct EnumDeserializer<'a> {
     variant: String,
     value: Value,
+    options: &'a DeserializeOptions,
 }
 
-impl<'de> EnumAccess<'de> for EnumDeserializer {
+impl<'a, 'de> EnumAccess<'de> for EnumDeserializer<'a> {
     type Error = Error;
-    type Variant = Value;
+    type Variant = Deserializer<'a>;
 
-    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Value)>
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Deserializer<'a>)>
     where
         V: DeserializeSeed<'de>,
     {
-        let value = self.value;
-        let variant = Value::String(self.variant);
+        let value = Deserializer::new(self.value, self.options);
+        let variant = Deserializer::new(Value::String(self.variant), self.options);
         seed.deserialize(variant).map(|v| (v, value))
     }
 }
@@ -518,7 +884,36 @@ impl<'de> VariantAccess<'de> for Value {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
-        if let Value::Struct(v) = self {
+        Deserializer::new(self, &DEFAULT_OPTIONS).unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Deserializer::new(self, &DEFAULT_OPTIONS).newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Deserializer::new(self, &DEFAULT_OPTIONS).tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Deserializer::new(self, &DEFAULT_OPTIONS).struct_variant(fields, visitor)
+    }
+}
+
+impl<'a, 'de> VariantAccess<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        if let Value::Struct(v) = self.value {
             if !v.is_empty() {
                 return Err(serde::de::Error::invalid_value(
                     Unexpected::Map,
@@ -528,7 +923,7 @@ impl<'de> VariantAccess<'de> for Value {
             Ok(())
         } else {
             Err(serde::de::Error::invalid_value(
-                self.unexpected(),
+                self.value.unexpected(),
                 &"empty map",
             ))
         }
@@ -582,16 +977,544 @@ impl FromI32 for i64 {
     }
 }
 
-fn handle_integer<'de, T, V>(value: Value, visitor: &V) -> Result<T>
+fn handle_integer<'de, T, V>(de: Deserializer, visitor: &V) -> Result<T>
 where
     T: FromI32 + std::str::FromStr,
     V: Visitor<'de>,
 {
-    match value {
+    match de.value {
         Value::Int(v) => Ok(T::from_i32(v)),
-        Value::String(v) => v
+        Value::I8(v) => v
+            .to_string()
+            .parse()
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Signed(v), visitor)),
+        #[cfg(feature = "bignum")]
+        Value::BigInt(ref v) => v
+            .to_string()
             .parse()
-            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(&v), visitor)),
-        _ => Err(serde::de::Error::invalid_value(value.unexpected(), visitor)),
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Other("biginteger"), visitor)),
+        Value::String(ref v) if !de.options.strict => v
+            .parse()
+            .map_err(|_| serde::de::Error::invalid_value(Unexpected::Str(v), visitor)),
+        Value::Double(v) if de.options.integral_doubles && !de.options.strict => {
+            if v.fract() != 0.0 || v.abs() >= 1e20 {
+                return Err(serde::de::Error::invalid_value(
+                    Unexpected::Float(v),
+                    visitor,
+                ));
+            }
+            (v as i128)
+                .to_string()
+                .parse()
+                .map_err(|_| serde::de::Error::invalid_value(Unexpected::Float(v), visitor))
+        }
+        ref v => Err(serde::de::Error::invalid_value(v.unexpected(), visitor)),
+    }
+}
+
+/// Deserializes from a value it borrows, lending out its strings, bytes and member names, and
+/// going through a copy for everything else.
+pub struct BorrowedDeserializer<'de, 'a> {
+    value: &'de Value,
+    options: &'a DeserializeOptions,
+}
+
+impl<'de, 'a> BorrowedDeserializer<'de, 'a> {
+    pub fn new(value: &'de Value, options: &'a DeserializeOptions) -> Self {
+        BorrowedDeserializer { value, options }
+    }
+
+    fn owned(self) -> Deserializer<'a> {
+        Deserializer::new(self.value.clone(), self.options)
+    }
+
+    fn visit_array<V>(&self, values: &'de [Value], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut deserializer = BorrowedSeqDeserializer {
+            iter: values.iter(),
+            options: self.options,
+        };
+        let seq = visitor.visit_seq(&mut deserializer)?;
+        if deserializer.iter.len() == 0 {
+            Ok(seq)
+        } else {
+            Err(serde::de::Error::invalid_length(
+                values.len(),
+                &"fewer elements in array",
+            ))
+        }
+    }
+
+    fn visit_struct<V>(&self, members: &'de HashMap<String, Value>, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut deserializer = BorrowedMapDeserializer {
+            iter: members.iter(),
+            value: None,
+            options: self.options,
+        };
+        let map = visitor.visit_map(&mut deserializer)?;
+        if deserializer.iter.len() == 0 {
+            Ok(map)
+        } else {
+            Err(serde::de::Error::invalid_length(
+                members.len(),
+                &"fewer elements in map",
+            ))
+        }
+    }
+}
+
+macro_rules! forward_to_owned {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                self.owned().$method($($arg,)* visitor)
+            }
+        )*
+    }
+}
+
+impl<'de, 'a> serde::Deserializer<'de> for BorrowedDeserializer<'de, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::String(ref v) => visitor.visit_borrowed_str(v),
+            Value::Base64(ref v) => visitor.visit_borrowed_bytes(v),
+            Value::Array(ref v) => self.visit_array(v, visitor),
+            Value::Struct(ref v) => self.visit_struct(v, visitor),
+            _ => self.owned().deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::String(ref v) => visitor.visit_borrowed_str(v),
+            _ => self.owned().deserialize_str(visitor),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::Base64(ref v) => visitor.visit_borrowed_bytes(v),
+            _ => self.owned().deserialize_bytes(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::Nil => visitor.visit_none(),
+            _ if self.options.options != OptionRepresentation::Array => visitor.visit_some(self),
+            Value::Array(ref items) => match items.len() {
+                0 => visitor.visit_none(),
+                1 => visitor.visit_some(BorrowedDeserializer::new(&items[0], self.options)),
+                _ => Err(serde::de::Error::invalid_value(
+                    Unexpected::Seq,
+                    &"array with a single element",
+                )),
+            },
+            ref v if self.options.strict => Err(serde::de::Error::invalid_value(
+                v.unexpected(),
+                &"array with at most one element",
+            )),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if name == VALUE_NEWTYPE_NAME || name == datetime::NEWTYPE_NAME {
+            return self.owned().deserialize_newtype_struct(name, visitor);
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::Array(ref v) => self.visit_array(v, visitor),
+            _ => self.owned().deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::Struct(ref v) => self.visit_struct(v, visitor),
+            _ => self.owned().deserialize_map(visitor),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.value {
+            Value::Struct(ref members) => {
+                if self.options.require_all_members {
+                    if let Some(field) = fields.iter().find(|f| !members.contains_key(**f)) {
+                        return Err(serde::de::Error::missing_field(field));
+                    }
+                }
+                self.visit_struct(members, visitor)
+            }
+            _ => self.owned().deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let options = self.options;
+        match (&options.enums, self.value) {
+            (EnumRepresentation::Internal { tag }, Value::Struct(members)) => {
+                match members.get(tag) {
+                    Some(Value::String(variant)) => visitor.visit_enum(BorrowedEnumDeserializer {
+                        variant,
+                        value: BorrowedVariant {
+                            value: Some(self.value),
+                            tag: Some(tag),
+                            options,
+                        },
+                    }),
+                    _ => Err(serde::de::Error::invalid_value(
+                        Unexpected::Map,
+                        &format!("map with a string {} member", tag).as_str(),
+                    )),
+                }
+            }
+            (EnumRepresentation::UnitAsString, Value::String(variant)) => {
+                visitor.visit_enum(BorrowedEnumDeserializer {
+                    variant,
+                    value: BorrowedVariant {
+                        value: None,
+                        tag: None,
+                        options,
+                    },
+                })
+            }
+            (_, Value::Struct(members)) if members.len() == 1 => {
+                let (variant, value) = members.iter().next().unwrap();
+                visitor.visit_enum(BorrowedEnumDeserializer {
+                    variant,
+                    value: BorrowedVariant {
+                        value: Some(value),
+                        tag: None,
+                        options,
+                    },
+                })
+            }
+            (_, Value::Struct(_)) => Err(serde::de::Error::invalid_value(
+                Unexpected::Map,
+                &"map with a single key",
+            )),
+            (_, other) => Err(serde::de::Error::invalid_value(
+                other.unexpected(),
+                &"map with a single key",
+            )),
+        }
+    }
+
+    /// Skipped values are left as they are, without a copy.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_owned! {
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_byte_buf()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+    }
+}
+
+struct BorrowedEnumDeserializer<'de, 'a> {
+    variant: &'de str,
+    value: BorrowedVariant<'de, 'a>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for BorrowedEnumDeserializer<'de, 'a> {
+    type Error = Error;
+    type Variant = BorrowedVariant<'de, 'a>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, BorrowedVariant<'de, 'a>)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = serde::de::value::BorrowedStrDeserializer::new(self.variant);
+        seed.deserialize(variant).map(|v| (v, self.value))
+    }
+}
+
+/// The content of an enum variant, which is nothing for a unit variant written as a string, and
+/// the members other than the tag for internally tagged ones.
+struct BorrowedVariant<'de, 'a> {
+    value: Option<&'de Value>,
+    tag: Option<&'a str>,
+    options: &'a DeserializeOptions,
+}
+
+impl<'de, 'a> BorrowedVariant<'de, 'a> {
+    fn members(&self) -> impl Iterator<Item = (&'de String, &'de Value)> + '_ {
+        let members = match self.value {
+            Some(Value::Struct(members)) => Some(members.iter()),
+            _ => None,
+        };
+        members
+            .into_iter()
+            .flatten()
+            .filter(move |&(key, _)| Some(key.as_str()) != self.tag)
+    }
+
+    fn visit_members<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let members = self.members().collect::<Vec<_>>();
+        let mut deserializer = BorrowedMapDeserializer {
+            iter: members.into_iter(),
+            value: None,
+            options: self.options,
+        };
+        let map = visitor.visit_map(&mut deserializer)?;
+        match deserializer.iter.len() {
+            0 => Ok(map),
+            _ => Err(serde::de::Error::invalid_length(
+                deserializer.iter.len(),
+                &"fewer elements in map",
+            )),
+        }
+    }
+}
+
+/// Reads the members besides the tag of an internally tagged variant as a struct.
+impl<'de, 'a> serde::Deserializer<'de> for BorrowedVariant<'de, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_members(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for BorrowedVariant<'de, 'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.value {
+            None => Ok(()),
+            Some(&Value::Struct(_)) if self.members().next().is_none() => Ok(()),
+            Some(&Value::Struct(_)) => Err(serde::de::Error::invalid_value(
+                Unexpected::Map,
+                &"empty map",
+            )),
+            Some(v) => Err(serde::de::Error::invalid_value(
+                v.unexpected(),
+                &"empty map",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match (self.value, self.tag) {
+            (Some(value), None) => seed.deserialize(BorrowedDeserializer::new(value, self.options)),
+            // The members besides the tag are a struct of their own, which can't be borrowed.
+            (Some(_), Some(_)) => seed.deserialize(self),
+            (None, _) => Err(serde::de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match (self.value, self.tag) {
+            (Some(value), None) => {
+                BorrowedDeserializer::new(value, self.options).deserialize_seq(visitor)
+            }
+            _ => Err(serde::de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match (self.value, self.tag) {
+            (Some(value), None) => BorrowedDeserializer::new(value, self.options)
+                .deserialize_struct("", fields, visitor),
+            (Some(_), Some(_)) => self.visit_members(visitor),
+            (None, _) => Err(serde::de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+struct BorrowedSeqDeserializer<'de, 'a> {
+    iter: std::slice::Iter<'de, Value>,
+    options: &'a DeserializeOptions,
+}
+
+impl<'de, 'a> SeqAccess<'de> for BorrowedSeqDeserializer<'de, 'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(value) => seed
+                .deserialize(BorrowedDeserializer::new(value, self.options))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct BorrowedMapDeserializer<'de, 'a, I> {
+    iter: I,
+    value: Option<&'de Value>,
+    options: &'a DeserializeOptions,
+}
+
+impl<'de, 'a, I> MapAccess<'de> for BorrowedMapDeserializer<'de, 'a, I>
+where
+    I: ExactSizeIterator<Item = (&'de String, &'de Value)>,
+{
+    type Error = Error;
+
+    fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(BorrowedDeserializer::new(value, self.options)),
+            None => Err(serde::de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
     }
 }
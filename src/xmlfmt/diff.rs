@@ -0,0 +1,156 @@
+//! Differences between value trees, listed by where they are, for tests comparing large
+//! responses.
+
+use std::fmt;
+use xmlfmt::visit::{index_path, member_path};
+use xmlfmt::{Value, ValueType};
+
+/// A difference at `path`, like `$.items[2].name`, with `$` standing for the values compared.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difference {
+    /// A struct member or array item only the left value has.
+    Missing { path: String, left: Value },
+    /// A struct member or array item only the right value has.
+    Added { path: String, right: Value },
+    TypeMismatch {
+        path: String,
+        left: ValueType,
+        right: ValueType,
+    },
+    /// Scalars of the same type, but with different values.
+    Changed {
+        path: String,
+        left: Value,
+        right: Value,
+    },
+}
+
+impl Difference {
+    pub fn path(&self) -> &str {
+        match *self {
+            Difference::Missing { ref path, .. }
+            | Difference::Added { ref path, .. }
+            | Difference::TypeMismatch { ref path, .. }
+            | Difference::Changed { ref path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Difference::Missing { ref path, ref left } => {
+                write!(f, "{}: only on the left, {}", path, left)
+            }
+            Difference::Added {
+                ref path,
+                ref right,
+            } => {
+                write!(f, "{}: only on the right, {}", path, right)
+            }
+            Difference::TypeMismatch {
+                ref path,
+                left,
+                right,
+            } => write!(
+                f,
+                "{}: {} on the left, {} on the right",
+                path,
+                left.name(),
+                right.name()
+            ),
+            Difference::Changed {
+                ref path,
+                ref left,
+                ref right,
+            } => write!(f, "{}: {} on the left, {} on the right", path, left, right),
+        }
+    }
+}
+
+/// Every difference between two values, empty if they are equal.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValueDiff {
+    pub differences: Vec<Difference>,
+}
+
+impl ValueDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// One difference per line.
+impl fmt::Display for ValueDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, difference) in self.differences.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two values member by member and item by item. Struct members are listed by name.
+pub fn diff(left: &Value, right: &Value) -> ValueDiff {
+    let mut diff = ValueDiff::default();
+    compare("$".into(), left, right, &mut diff.differences);
+    diff
+}
+
+fn compare(path: String, left: &Value, right: &Value, differences: &mut Vec<Difference>) {
+    match (left, right) {
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                let path = index_path(&path, index);
+                match (left.get(index), right.get(index)) {
+                    (Some(left), Some(right)) => compare(path, left, right, differences),
+                    (Some(left), None) => differences.push(Difference::Missing {
+                        path,
+                        left: left.clone(),
+                    }),
+                    (None, Some(right)) => differences.push(Difference::Added {
+                        path,
+                        right: right.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (Value::Struct(left), Value::Struct(right)) => {
+            let mut keys = left.keys().chain(right.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = member_path(&path, key);
+                match (left.get(key), right.get(key)) {
+                    (Some(left), Some(right)) => compare(path, left, right, differences),
+                    (Some(left), None) => differences.push(Difference::Missing {
+                        path,
+                        left: left.clone(),
+                    }),
+                    (None, Some(right)) => differences.push(Difference::Added {
+                        path,
+                        right: right.clone(),
+                    }),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        _ if ValueType::of(left) != ValueType::of(right) => {
+            differences.push(Difference::TypeMismatch {
+                path,
+                left: ValueType::of(left),
+                right: ValueType::of(right),
+            })
+        }
+        _ if left != right => differences.push(Difference::Changed {
+            path,
+            left: left.clone(),
+            right: right.clone(),
+        }),
+        _ => {}
+    }
+}
@@ -0,0 +1,46 @@
+use super::options::WriteOptions;
+use super::value::ToXml;
+
+/// Writes calls, responses and values into a buffer that is kept between uses.
+///
+/// Once the buffer has grown to fit the largest message, encoding doesn't allocate, which
+/// pays off when sending many small messages.
+#[derive(Clone, Debug, Default)]
+pub struct Encoder {
+    buffer: Vec<u8>,
+    options: WriteOptions,
+}
+
+impl Encoder {
+    pub fn new() -> Encoder {
+        Encoder::default()
+    }
+
+    pub fn with_options(options: WriteOptions) -> Encoder {
+        Encoder {
+            buffer: Vec::with_capacity(options.write_buffer_size),
+            options,
+        }
+    }
+
+    pub fn options(&self) -> &WriteOptions {
+        &self.options
+    }
+
+    /// Encodes `data`, replacing whatever the previous call returned.
+    pub fn encode<T: ToXml + ?Sized>(&mut self, data: &T) -> &[u8] {
+        self.buffer.clear();
+        data.write_xml_with(&mut self.buffer, &self.options)
+            .expect("Writing into a Vec can't fail");
+        &self.buffer
+    }
+
+    pub fn encode_str<T: ToXml + ?Sized>(&mut self, data: &T) -> &str {
+        let data = self.encode(data);
+        std::str::from_utf8(data).expect("Generated XML is always valid UTF-8")
+    }
+
+    pub fn into_buffer(self) -> Vec<u8> {
+        self.buffer
+    }
+}
@@ -1,4 +1,5 @@
-#![allow(unknown_lints, unused_doc_comments)]
+#![allow(unknown_lints, unused_doc_comments, unexpected_cfgs)]
+use super::Value;
 use serde::{de, ser};
 use std::fmt::{self, Display};
 
@@ -16,10 +17,28 @@ error_chain! {
             description("Issue while encoding data structure")
             display("Issue while encoding data structure: {}", t)
         }
+        DoctypeNotAllowed(t: String) {
+            description("Document type declaration not allowed")
+            display("Document type declaration not allowed: {}", t)
+        }
+        LimitExceeded(t: String) {
+            description("Parsing limit exceeded")
+            display("Parsing limit exceeded: {}", t)
+        }
         UnsupportedData(t: String) {
             description("Given structure is not supported")
             display("Given structure is not supported: {}", t)
         }
+        InvalidQuery(query: String, reason: String) {
+            description("Invalid value query")
+            display("Invalid value query {:?}: {}", query, reason)
+        }
+        /// A fault without an int `faultCode` and string `faultString`, with its value and the
+        /// XML of the whole response, or of the value when read with a `ParamReader`.
+        MalformedFault(value: Value, xml: String) {
+            description("Fault doesn't have the expected members")
+            display("Fault doesn't have the expected members: {}", value)
+        }
     }
 }
 
@@ -0,0 +1,93 @@
+//! Modules for `#[serde(with = "...")]`, pinning how single fields are written.
+
+/// Use with `#[serde(with = "xml_rpc::as_base64")]` on `Vec<u8>` and other byte fields, to write
+/// them as `base64` rather than as arrays of `int`s.
+pub mod as_base64 {
+    use super::super::bytes::Base64;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::convert::TryFrom;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]>,
+        S: Serializer,
+    {
+        Base64(value.as_ref()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        Base64::<T>::deserialize(deserializer).map(|v| v.0)
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::as_datetime")]` on `String` fields, to write them as
+/// `dateTime.iso8601`, in the format of `SerializeOptions::datetime_format`.
+pub mod as_datetime {
+    use super::super::datetime::{deserialize_string, serialize_str};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &str, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_str(value, serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_string(deserializer)
+    }
+}
+
+/// Use with `#[serde(with = "xml_rpc::as_i64_string")]` on `i64` fields, to write them as decimal
+/// strings for peers without `i8`. Reads them back from strings and from either integer type.
+pub mod as_i64_string {
+    use serde::de::{self, Visitor};
+    use serde::{Deserializer, Serializer};
+    use std::convert::TryFrom;
+    use std::fmt;
+
+    pub fn serialize<S>(value: &i64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(I64Visitor)
+    }
+
+    struct I64Visitor;
+
+    impl<'de> Visitor<'de> for I64Visitor {
+        type Value = i64;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an integer, or a string holding one")
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<i64, E> {
+            Ok(v)
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<i64, E> {
+            i64::try_from(v).map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<i64, E> {
+            v.trim()
+                .parse()
+                .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::error::{Error, ErrorKind, Result};
+use super::Value;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{self, Map, Number};
+use std::convert::TryFrom;
+
+pub const DATETIME_KEY: &str = "$dateTime.iso8601";
+pub const BASE64_KEY: &str = "$base64";
+
+/// Struct member names that would be read back as a tag, or that start with `$$`, are written
+/// with another `$` in front, which is taken off again when reading.
+fn escape(key: String) -> String {
+    if key == DATETIME_KEY || key == BASE64_KEY || key.starts_with("$$") {
+        format!("${}", key)
+    } else {
+        key
+    }
+}
+
+fn unescape(key: String) -> String {
+    match key.strip_prefix("$$") {
+        Some(rest) => format!("${}", rest),
+        None => key,
+    }
+}
+
+fn tagged(key: &str, content: String) -> serde_json::Value {
+    let mut map = Map::new();
+    map.insert(key.into(), serde_json::Value::String(content));
+    serde_json::Value::Object(map)
+}
+
+/// Fails only on non-finite doubles, which JSON can't represent.
+impl TryFrom<Value> for serde_json::Value {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<serde_json::Value> {
+        Ok(match v {
+            Value::Int(v) => v.into(),
+            Value::I8(v) => v.into(),
+            Value::Bool(v) => v.into(),
+            Value::String(v) => v.into(),
+            Value::Double(v) => match Number::from_f64(v) {
+                Some(v) => serde_json::Value::Number(v),
+                None => bail!(ErrorKind::UnsupportedData(format!(
+                    "double {} has no JSON representation",
+                    v
+                ))),
+            },
+            Value::DateTime(v) => tagged(DATETIME_KEY, v),
+            Value::Base64(v) => tagged(BASE64_KEY, STANDARD.encode(v)),
+            Value::Base64Text(v) => tagged(BASE64_KEY, v),
+            Value::Array(v) => serde_json::Value::Array(
+                v.into_iter()
+                    .map(serde_json::Value::try_from)
+                    .collect::<Result<_>>()?,
+            ),
+            Value::Struct(v) => serde_json::Value::Object(
+                v.into_iter()
+                    .map(|(key, value)| Ok((escape(key), serde_json::Value::try_from(value)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Value::Nil => serde_json::Value::Null,
+            #[cfg(feature = "bignum")]
+            Value::BigInt(v) => v.to_string().into(),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(v) => v.to_string().into(),
+        })
+    }
+}
+
+fn untag(map: &Map<String, serde_json::Value>) -> Result<Option<Value>> {
+    if map.len() != 1 {
+        return Ok(None);
+    }
+    Ok(match map.iter().next() {
+        Some((key, serde_json::Value::String(v))) if key == DATETIME_KEY => {
+            Some(Value::DateTime(v.clone()))
+        }
+        Some((key, serde_json::Value::String(v))) if key == BASE64_KEY => {
+            Some(Value::Base64(STANDARD.decode(v).map_err(|err| {
+                ErrorKind::Decoding(format!("invalid base64 in {}: {}", BASE64_KEY, err))
+            })?))
+        }
+        _ => None,
+    })
+}
+
+/// Fails only on malformed base64 in a `$base64` object.
+impl TryFrom<serde_json::Value> for Value {
+    type Error = Error;
+
+    fn try_from(v: serde_json::Value) -> Result<Value> {
+        Ok(match v {
+            serde_json::Value::Null => Value::Nil,
+            serde_json::Value::Bool(v) => Value::Bool(v),
+            serde_json::Value::Number(v) => {
+                if let Some(v) = v.as_i64() {
+                    i32::try_from(v)
+                        .map(Value::Int)
+                        .unwrap_or_else(|_| Value::String(v.to_string()))
+                } else if let Some(v) = v.as_u64() {
+                    Value::String(v.to_string())
+                } else {
+                    Value::Double(v.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            serde_json::Value::String(v) => Value::String(v),
+            serde_json::Value::Array(v) => {
+                Value::Array(v.into_iter().map(Value::try_from).collect::<Result<_>>()?)
+            }
+            serde_json::Value::Object(v) => match untag(&v)? {
+                Some(v) => v,
+                None => Value::Struct(
+                    v.into_iter()
+                        .map(|(key, value)| Ok((unescape(key), Value::try_from(value)?)))
+                        .collect::<Result<_>>()?,
+                ),
+            },
+        })
+    }
+}
@@ -1,29 +1,136 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
 
+#[cfg(feature = "testing")]
+mod arbitrary;
+pub mod arena;
+mod borrowed;
+mod build;
+pub mod bytes;
+pub mod charset;
+pub mod datetime;
 mod de;
+pub mod diff;
+pub mod encoder;
 pub mod error;
+pub mod fields;
+#[cfg(feature = "serde_json")]
+pub mod json;
+pub mod multicall;
+pub mod options;
+pub mod params;
 pub mod parse;
+pub mod query;
 mod ser;
+pub mod signature;
+pub mod stream;
 #[cfg(test)]
 mod tests;
 pub mod value;
+mod visit;
 
-pub use self::value::{Call, Fault, Params, Response, Value};
+pub use self::borrowed::{BorrowedCall, BorrowedResponse, BorrowedValue};
+pub use self::bytes::Base64;
+pub use self::diff::{diff, Difference, ValueDiff};
+pub use self::encoder::Encoder;
+pub use self::multicall::MulticallResponse;
+pub use self::options::{
+    DateTimeFormat, DeserializeOptions, DoctypePolicy, EmptyStrings, EnumRepresentation, IntTag,
+    IntegerPolicy, MethodNamePolicy, NaiveDateTimePolicy, NonFinitePolicy, OptionRepresentation,
+    ParseOptions, Profile, SerializeOptions, WriteOptions, XmlDeclaration,
+};
+pub use self::params::{FromParams, IntoParams, ParamsExt, ParamsReader};
+pub use self::query::Query;
+pub use self::signature::{Signature, ValueType};
+pub use self::value::{
+    Call, CallBuilder, Fault, Params, Response, ResponseBuilder, ResponseExt, ResponseStream,
+    StreamedParam, Value,
+};
 
-pub fn from_params<'a, T: Deserialize<'a>>(mut params: Params) -> error::Result<T> {
+pub fn from_params<T: FromParams>(params: Params) -> error::Result<T> {
+    from_params_with(params, &DeserializeOptions::default())
+}
+
+pub fn from_params_with<T: FromParams>(
+    params: Params,
+    options: &DeserializeOptions,
+) -> error::Result<T> {
+    use self::error::ResultExt;
+    T::from_params(params, options).chain_err(|| "Failed to convert XML-RPC to structure.")
+}
+
+pub fn from_value<'a, T: Deserialize<'a>>(value: Value) -> error::Result<T> {
+    from_value_with(value, &DeserializeOptions::default())
+}
+
+pub fn from_value_with<'a, T: Deserialize<'a>>(
+    value: Value,
+    options: &DeserializeOptions,
+) -> error::Result<T> {
     use self::error::ResultExt;
-    let data = if params.len() == 1 {
-        params.pop().unwrap()
-    } else {
-        Value::Array(params)
-    };
+    T::deserialize(de::Deserializer::new(value, options))
+        .chain_err(|| "Failed to convert XML-RPC to structure.")
+}
+
+/// Like `from_value`, but borrows from `value`, so that `T` can hold `&str`, `&[u8]` or `Cow`
+/// fields pointing into it instead of copies.
+pub fn from_value_ref<'a, T: Deserialize<'a>>(value: &'a Value) -> error::Result<T> {
+    from_value_ref_with(value, &DeserializeOptions::default())
+}
+
+pub fn from_value_ref_with<'a, T: Deserialize<'a>>(
+    value: &'a Value,
+    options: &DeserializeOptions,
+) -> error::Result<T> {
+    use self::error::ResultExt;
+    T::deserialize(de::BorrowedDeserializer::new(value, options))
+        .chain_err(|| "Failed to convert XML-RPC to structure.")
+}
 
-    T::deserialize(data).chain_err(|| "Failed to convert XML-RPC to structure.")
+pub fn into_params<T: IntoParams>(v: &T) -> error::Result<Params> {
+    into_params_with(v, &SerializeOptions::default())
 }
 
-pub fn into_params<T: Serialize>(v: &T) -> error::Result<Params> {
-    Ok(match v.serialize(ser::Serializer {})? {
-        Value::Array(params) => params,
-        data => vec![data],
-    })
+pub fn into_params_with<T: IntoParams>(v: &T, options: &SerializeOptions) -> error::Result<Params> {
+    v.to_params(options)
+}
+
+pub fn to_value<T: Serialize>(v: &T) -> error::Result<Value> {
+    to_value_with(v, &SerializeOptions::default())
+}
+
+pub fn to_value_with<T: Serialize>(v: &T, options: &SerializeOptions) -> error::Result<Value> {
+    v.serialize(ser::Serializer::new(options))
+}
+
+/// Reads the XML of a single value, like `<value><i4>3</i4></value>`, straight into `T`.
+pub fn from_xml_reader<T: DeserializeOwned, R: Read>(r: R) -> error::Result<T> {
+    from_xml_reader_with(r, &ParseOptions::default(), &DeserializeOptions::default())
+}
+
+pub fn from_xml_reader_with<T: DeserializeOwned, R: Read>(
+    r: R,
+    parse_options: &ParseOptions,
+    options: &DeserializeOptions,
+) -> error::Result<T> {
+    from_value_with(parse::xml_with(r, parse_options)?, options)
+}
+
+/// Writes `v` as the XML of a single value, which `from_xml_reader` reads back.
+pub fn to_xml_writer<T: Serialize, W: Write>(v: &T, w: W) -> error::Result<()> {
+    to_xml_writer_with(v, w, &SerializeOptions::default(), &WriteOptions::default())
+}
+
+pub fn to_xml_writer_with<T: Serialize, W: Write>(
+    v: &T,
+    mut w: W,
+    options: &SerializeOptions,
+    write_options: &WriteOptions,
+) -> error::Result<()> {
+    use self::error::ResultExt;
+    use self::value::ToXml;
+    to_value_with(v, options)?
+        .write_xml_with(&mut w, write_options)
+        .chain_err(|| "Failed to write XML-RPC data.")
 }
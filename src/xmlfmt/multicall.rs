@@ -0,0 +1,155 @@
+//! `system.multicall`, which makes several calls in one request.
+
+use serde::de::DeserializeOwned;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use xmlfmt::error::{ErrorKind, Result};
+use xmlfmt::{from_value, Call, Fault, Params, Response, Value};
+
+pub const METHOD: &str = "system.multicall";
+
+/// The result of each call of a multicall, in the order they were made.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MulticallResponse {
+    /// Each call's method name, with its single result or fault.
+    pub entries: Vec<(String, std::result::Result<Value, Fault>)>,
+}
+
+impl MulticallResponse {
+    /// Reads the array a server answers a multicall of `calls` with.
+    pub fn from_value(calls: &[Call], value: Value) -> Result<MulticallResponse> {
+        let names = calls.iter().map(|call| call.name.clone()).collect();
+        MulticallResponse::from_value_named(names, value)
+    }
+
+    /// Like `from_value`, with only the method names of the calls, in order, so the calls
+    /// themselves can be given away to be sent.
+    pub fn from_value_named(names: Vec<String>, value: Value) -> Result<MulticallResponse> {
+        let results = Vec::<Value>::try_from(value)?;
+        if results.len() != names.len() {
+            bail!(ErrorKind::Decoding(format!(
+                "Expected {} multicall results, got {}",
+                names.len(),
+                results.len()
+            )));
+        }
+        let entries = names
+            .into_iter()
+            .zip(results)
+            .map(|(name, result)| {
+                let result = match result {
+                    // Results are wrapped in single item arrays, to tell them from faults.
+                    Value::Array(mut result) if result.len() == 1 => Ok(result.remove(0)),
+                    Value::Struct(_) => Err(Fault::try_from(result)?),
+                    _ => bail!(ErrorKind::Decoding(format!(
+                        "Invalid multicall result of {}",
+                        name
+                    ))),
+                };
+                Ok((name, result))
+            })
+            .collect::<Result<_>>()?;
+        Ok(MulticallResponse { entries })
+    }
+
+    /// The array sent back for a multicall.
+    pub fn into_value(self) -> Value {
+        Value::Array(
+            self.entries
+                .into_iter()
+                .map(|(_, result)| match result {
+                    Ok(value) => Value::Array(vec![value]),
+                    Err(fault) => fault.into(),
+                })
+                .collect(),
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&std::result::Result<Value, Fault>> {
+        self.entries.get(index).map(|(_, result)| result)
+    }
+
+    /// Converts the result of the call at `index` like `from_value`, passing faults through.
+    pub fn deserialize<T: DeserializeOwned>(
+        &self,
+        index: usize,
+    ) -> Result<std::result::Result<T, Fault>> {
+        match self.get(index) {
+            Some(Ok(value)) => from_value(value.clone()).map(Ok),
+            Some(Err(fault)) => Ok(Err(fault.clone())),
+            None => bail!(ErrorKind::Decoding(format!(
+                "No multicall result {} of {}",
+                index,
+                self.len()
+            ))),
+        }
+    }
+}
+
+/// Collects method names and their responses, with responses of several params as an array.
+impl FromIterator<(String, Response)> for MulticallResponse {
+    fn from_iter<I: IntoIterator<Item = (String, Response)>>(iter: I) -> MulticallResponse {
+        let entries = iter
+            .into_iter()
+            .map(|(name, response)| (name, response.map(single)))
+            .collect();
+        MulticallResponse { entries }
+    }
+}
+
+/// The params of a multicall of `calls`.
+pub fn params(calls: Vec<Call>) -> Params {
+    let calls = calls
+        .into_iter()
+        .map(|call| {
+            Value::Struct(
+                vec![
+                    ("methodName".to_owned(), Value::String(call.name)),
+                    ("params".to_owned(), Value::Array(call.params)),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        })
+        .collect();
+    vec![Value::Array(calls)]
+}
+
+/// Reads the calls out of the params of a multicall, each of them or why it's invalid.
+pub fn calls(params: Params) -> std::result::Result<Vec<std::result::Result<Call, Fault>>, Fault> {
+    let mut params = params.into_iter();
+    let calls = match (params.next(), params.next()) {
+        (Some(Value::Array(calls)), None) => calls,
+        _ => return Err(Fault::invalid_params("Expected an array of calls")),
+    };
+    Ok(calls.into_iter().map(call).collect())
+}
+
+fn call(value: Value) -> std::result::Result<Call, Fault> {
+    let mut members = match value {
+        Value::Struct(members) => members,
+        _ => return Err(Fault::invalid_params("Expected a struct with a call")),
+    };
+    match (members.remove("methodName"), members.remove("params")) {
+        (Some(Value::String(name)), Some(Value::Array(params))) => Ok(Call { name, params }),
+        _ => Err(Fault::invalid_params(
+            "Expected a string methodName and an array of params",
+        )),
+    }
+}
+
+fn single(mut params: Params) -> Value {
+    if params.len() == 1 {
+        params.pop().unwrap()
+    } else {
+        Value::Array(params)
+    }
+}
@@ -0,0 +1,341 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntegerPolicy {
+    /// `i64`, `u32` and `u64` are always written as strings, even when they would fit into `i4`.
+    #[default]
+    WideAsString,
+    /// Integers that fit into `i4` are written as `int`, the rest as strings.
+    IntWhenFits,
+    /// Integers that fit into `i4` are written as `int`, the rest are an error.
+    ErrorWhenWide,
+    /// Integers that fit into `i4` are written as `int`, the rest as the `i8` extension.
+    WideAsI8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OptionRepresentation {
+    /// `None` is an empty array, `Some(v)` is an array containing just `v`.
+    #[default]
+    Array,
+    /// `Some(v)` is just `v`, and `None` struct members are left out.
+    Omit,
+    /// `Some(v)` is just `v`, and `None` is the `nil` extension.
+    Nil,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum EnumRepresentation {
+    /// A struct with one member, named after the variant and holding its content.
+    #[default]
+    External,
+    /// Unit variants are just their name as a string, the rest are written as `External`.
+    UnitAsString,
+    /// A struct with the variant name in the `tag` member, next to the members of the content,
+    /// like serde's `#[serde(tag = "...")]`. Tuple variants and ones holding anything but a
+    /// struct are not supported.
+    Internal { tag: String },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MethodNamePolicy {
+    /// Any printable name, without whitespace or the XML markup characters `<>&"'`.
+    #[default]
+    Printable,
+    /// Only non-empty names of the spec's `A-Z a-z 0-9 _ . : /`.
+    Spec,
+    /// Any name, for servers with exotic ones.
+    Any,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateTimeFormat {
+    /// The spec's `19980717T14:08:55`.
+    #[default]
+    Compact,
+    /// ISO 8601 extended format, `1998-07-17T14:08:55`.
+    Extended,
+}
+
+impl DateTimeFormat {
+    /// The format a datetime is written in, judging by its date alone.
+    pub fn of(datetime: &str) -> DateTimeFormat {
+        match datetime.trim().as_bytes().get(4) {
+            Some(b'-') => DateTimeFormat::Extended,
+            _ => DateTimeFormat::Compact,
+        }
+    }
+}
+
+/// The tag `Value::Int` is written with. Both are in the spec, but some peers only read one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntTag {
+    #[default]
+    I4,
+    Int,
+}
+
+/// How empty strings are written, as some peers fail on one form or another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyStrings {
+    /// `<string></string>`, or `<value></value>` with `untyped_strings`.
+    #[default]
+    Pair,
+    /// `<string/>`, or `<value/>` with `untyped_strings`.
+    SelfClosing,
+    /// `<value/>`.
+    BareValue,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NaiveDateTimePolicy {
+    /// Datetimes without a timezone are in UTC.
+    #[default]
+    AssumeUtc,
+    /// Datetimes without a timezone are in the local timezone.
+    AssumeLocal,
+    /// Datetimes without a timezone are this many seconds east of UTC, like those of a .NET
+    /// server writing its own local time.
+    AssumeOffset(i32),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NonFinitePolicy {
+    /// Fail to serialize.
+    Error,
+    /// Keep the value, which gets written as `NaN`, `inf` or `-inf`.
+    #[default]
+    Verbatim,
+    /// Replace the value with this one.
+    Substitute(f64),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SerializeOptions {
+    pub integers: IntegerPolicy,
+    pub options: OptionRepresentation,
+    pub enums: EnumRepresentation,
+    pub datetime_format: DateTimeFormat,
+    /// What to do with NaN and infinite doubles, which the spec has no representation for.
+    pub non_finite: NonFinitePolicy,
+    /// Reject data without a direct XML-RPC counterpart, like non-string map keys.
+    ///
+    /// Non-finite doubles are rejected too, regardless of `non_finite`.
+    pub strict: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeserializeOptions {
+    pub options: OptionRepresentation,
+    pub enums: EnumRepresentation,
+    pub naive_datetimes: NaiveDateTimePolicy,
+    /// Fail on struct members missing from the data, instead of reading missing `Option` members
+    /// as `None`. Members of fields with `#[serde(default)]` have to be sent as well.
+    pub require_all_members: bool,
+    /// Read integers from doubles without a fractional part, like `42.0`, when they fit.
+    pub integral_doubles: bool,
+    /// Read structs keyed `"0"`, `"1"`, `"2"` and so on as sequences, and arrays as maps and
+    /// structs keyed by index, as PHP services write their arrays either way, empty ones
+    /// included.
+    pub php_arrays: bool,
+    /// Disable coercions between types, like reading numbers from strings.
+    pub strict: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DoctypePolicy {
+    /// Documents with a DOCTYPE declaration are an error.
+    #[default]
+    Reject,
+    /// DOCTYPE declarations may define internal entities, but may not refer to anything external.
+    AllowInternalEntities,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseOptions {
+    /// Deepest allowed nesting of `<value>` elements, with top level values at depth 1.
+    pub max_depth: usize,
+    /// Largest allowed document, in bytes.
+    pub max_size: usize,
+    /// Most `<value>` elements allowed in the whole document.
+    pub max_values: usize,
+    /// Most members allowed in a single struct.
+    pub max_members: usize,
+    /// Longest allowed text content of a single element, like a string or base64 value, in bytes.
+    pub max_string_length: usize,
+    /// Charset given by the transport, like a `Content-Type` header, which overrides the one in
+    /// the XML declaration. UTF-8, ISO-8859-1 and windows-1252 are supported.
+    pub charset: Option<String>,
+    pub doctype: DoctypePolicy,
+    /// Tolerate almost-XML-RPC: skip unknown elements and stray text, accept arrays without
+    /// `<data>`, read unknown value types as strings, and complete datetimes with only a date or
+    /// a time. The `parse::*_with_warnings` functions report each of these, and responses without
+    /// `<params>` too.
+    pub lenient: bool,
+    /// Also accept base64 in the URL-safe alphabet, with or without padding.
+    pub base64_url_safe: bool,
+    /// Keep base64 encoded as `Value::Base64Text`, only checking that it's valid, so values that
+    /// are just passed on aren't decoded and encoded again. Deserializing and
+    /// `Value::into_bytes` decode it.
+    pub lazy_base64: bool,
+    pub method_names: MethodNamePolicy,
+    /// Only accept the exact formats from the spec, for validating output of other implementations.
+    ///
+    /// Rejects doubles like `NaN`, `inf` or `1e5`, booleans other than `0` and `1`, datetimes not
+    /// in the `19980717T14:08:55` form, method names with characters outside of
+    /// `A-Z a-z 0-9 _ . : /`, and responses without `<params>`. Overrides `lenient` and
+    /// `method_names`.
+    ///
+    /// Faults without the spec's members fail with `MalformedFault`, rather than being kept
+    /// whole as the detail.
+    pub strict: bool,
+    /// Size of the buffer documents are read through, in bytes.
+    pub read_buffer_size: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_depth: 64,
+            max_size: 128 << 20,
+            max_values: 1 << 20,
+            max_members: 1 << 16,
+            max_string_length: 64 << 20,
+            charset: None,
+            doctype: DoctypePolicy::Reject,
+            lenient: false,
+            base64_url_safe: false,
+            lazy_base64: false,
+            method_names: MethodNamePolicy::Printable,
+            strict: false,
+            read_buffer_size: 8 << 10,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct XmlDeclaration {
+    /// Add `encoding="UTF-8"`.
+    pub encoding: bool,
+    /// Add `standalone="yes"` or `standalone="no"`.
+    pub standalone: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WriteOptions {
+    /// The `<?xml ...?>` prolog of calls and responses, or `None` to leave it out.
+    pub declaration: Option<XmlDeclaration>,
+    /// Most digits after the decimal point in doubles, or `None` for as many as it takes to read
+    /// back the exact same value. Doubles are never written in scientific notation.
+    pub double_precision: Option<usize>,
+    /// Break base64 into lines of at most this many characters, for peers that expect MIME style.
+    pub base64_line_length: Option<usize>,
+    /// Write `i8` and `nil` values as the Apache `ex:i8` and `ex:nil`, declaring their namespace.
+    ///
+    /// Without this they are written unprefixed, as Python and most other peers expect.
+    pub apache_extensions: bool,
+    /// Write equal data as the same bytes, for hashing, signing or comparing output.
+    ///
+    /// Struct members are sorted by name, datetimes are written in the spec's compact form,
+    /// `-0` doubles as `0` and base64 without line breaks.
+    pub canonical: bool,
+    /// Bytes reserved up front for calls and responses written into memory, which still grow
+    /// past it as needed.
+    pub write_buffer_size: usize,
+    /// Write the fractional seconds of datetimes. Without them, datetimes are truncated to whole
+    /// seconds, for peers like older .NET and Java ones that reject anything else.
+    pub datetime_fractions: bool,
+    /// Write struct members sorted by name, for reproducible output without the rest of
+    /// `canonical`. Members are otherwise written in no particular order, as structs are maps.
+    ///
+    /// There's no option to keep the order fields were declared in: serialized data becomes a
+    /// `Value` before it's written, and `Value::Struct` is a `HashMap`, so that order is gone by
+    /// the time the writer sees the members.
+    pub sort_members: bool,
+    /// Leave out `<params>` of calls and responses without any, rather than writing it empty, for
+    /// peers that tell the two apart. Both are read the same either way.
+    pub omit_empty_params: bool,
+    pub int_tag: IntTag,
+    /// Write strings as bare text inside `<value>`, without `<string>`, like some old peers do.
+    /// The spec allows it, and both forms read the same.
+    pub untyped_strings: bool,
+    pub empty_strings: EmptyStrings,
+    /// Write datetimes with a timezone in this one, as seconds east of UTC, and leave the
+    /// timezone out, for .NET-style peers that reject it. Those without one are kept as they are.
+    pub naive_datetime_offset: Option<i32>,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            declaration: Some(XmlDeclaration::default()),
+            double_precision: None,
+            base64_line_length: None,
+            apache_extensions: false,
+            canonical: false,
+            write_buffer_size: 0,
+            datetime_fractions: true,
+            sort_members: false,
+            omit_empty_params: false,
+            int_tag: IntTag::I4,
+            untyped_strings: false,
+            empty_strings: EmptyStrings::Pair,
+            naive_datetime_offset: None,
+        }
+    }
+}
+
+/// Settings for every step of a call, for peers with their own quirks, set at once with
+/// `Client::set_profile` or `Server::set_profile`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    pub serialize: SerializeOptions,
+    pub deserialize: DeserializeOptions,
+    pub parse: ParseOptions,
+    pub write: WriteOptions,
+}
+
+impl Profile {
+    /// What roscpp's XmlRpc++ and rospy write and read: `i4` and untyped strings, with empty
+    /// ones as `<value></value>`, compact datetimes without fractions, and neither `i8` nor `nil`.
+    ///
+    /// ROS APIs reply with `[code, statusMessage, value]` arrays rather than faults, which the
+    /// `ros` module takes care of.
+    pub fn ros() -> Profile {
+        Profile {
+            serialize: SerializeOptions {
+                integers: IntegerPolicy::ErrorWhenWide,
+                options: OptionRepresentation::Omit,
+                ..SerializeOptions::default()
+            },
+            deserialize: DeserializeOptions {
+                options: OptionRepresentation::Omit,
+                ..DeserializeOptions::default()
+            },
+            parse: ParseOptions::default(),
+            write: WriteOptions {
+                int_tag: IntTag::I4,
+                untyped_strings: true,
+                empty_strings: EmptyStrings::Pair,
+                datetime_fractions: false,
+                ..WriteOptions::default()
+            },
+        }
+    }
+
+    /// What .NET-style servers that write datetimes without a timezone, and reject one, expect:
+    /// datetimes in their zone, `offset` seconds east of UTC, without it or fractional seconds.
+    pub fn dotnet(offset: i32) -> Profile {
+        Profile {
+            serialize: SerializeOptions::default(),
+            deserialize: DeserializeOptions {
+                naive_datetimes: NaiveDateTimePolicy::AssumeOffset(offset),
+                ..DeserializeOptions::default()
+            },
+            parse: ParseOptions::default(),
+            write: WriteOptions {
+                datetime_fractions: false,
+                naive_datetime_offset: Some(offset),
+                ..WriteOptions::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use super::de::Deserializer;
+use super::error::{self, ErrorKind, ResultExt};
+use super::{to_value_with, DeserializeOptions, Fault, Params, SerializeOptions, Value};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::iter::Enumerate;
+use std::vec;
+
+lazy_static! {
+    static ref DEFAULT_OPTIONS: DeserializeOptions = DeserializeOptions::default();
+}
+
+fn convert<'de, T: Deserialize<'de>>(value: Value, index: usize) -> Result<T, Fault> {
+    T::deserialize(Deserializer::new(value, &DEFAULT_OPTIONS))
+        .map_err(|err| Fault::invalid_params(format!("Invalid parameter {}: {}", index, err)))
+}
+
+/// Checks on the params a handler receives, failing with an "invalid params" fault.
+pub trait ParamsExt {
+    fn expect_len(&self, len: usize) -> Result<(), Fault>;
+
+    /// Converts a copy of the param at `index`.
+    fn take<'de, T: Deserialize<'de>>(&self, index: usize) -> Result<T, Fault>;
+}
+
+impl ParamsExt for [Value] {
+    fn expect_len(&self, len: usize) -> Result<(), Fault> {
+        if self.len() != len {
+            return Err(Fault::invalid_params(format!(
+                "Expected {} parameters, got {}",
+                len,
+                self.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn take<'de, T: Deserialize<'de>>(&self, index: usize) -> Result<T, Fault> {
+        match self.get(index) {
+            Some(value) => convert(value.clone(), index),
+            None => Err(Fault::invalid_params(format!(
+                "Missing parameter {}",
+                index
+            ))),
+        }
+    }
+}
+
+/// Reads params one after another, moving them out instead of copying.
+///
+/// ```
+/// # use xml_rpc::{Fault, ParamsReader, Value};
+/// fn add(params: Vec<Value>) -> Result<Vec<Value>, Fault> {
+///     let mut params = ParamsReader::new(params);
+///     let a: i32 = params.next()?;
+///     let b: i32 = params.optional()?.unwrap_or(1);
+///     params.finish()?;
+///     Ok(vec![Value::Int(a + b)])
+/// }
+/// # assert_eq!(vec![Value::Int(3)], add(vec![Value::Int(2)]).unwrap());
+/// ```
+pub struct ParamsReader {
+    params: vec::IntoIter<Value>,
+    index: usize,
+}
+
+impl ParamsReader {
+    pub fn new(params: Vec<Value>) -> ParamsReader {
+        ParamsReader {
+            params: params.into_iter(),
+            index: 0,
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn next<'de, T: Deserialize<'de>>(&mut self) -> Result<T, Fault> {
+        match self.optional()? {
+            Some(v) => Ok(v),
+            None => Err(Fault::invalid_params(format!(
+                "Missing parameter {}",
+                self.index
+            ))),
+        }
+    }
+
+    /// Reads the next param, or `None` if there are no more.
+    pub fn optional<'de, T: Deserialize<'de>>(&mut self) -> Result<Option<T>, Fault> {
+        match self.params.next() {
+            Some(value) => {
+                self.index += 1;
+                convert(value, self.index - 1).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Fails if any params are left unread.
+    pub fn finish(self) -> Result<(), Fault> {
+        let extra = self.params.len();
+        if extra > 0 {
+            return Err(Fault::invalid_params(format!(
+                "Expected {} parameters, got {}",
+                self.index,
+                self.index + extra
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Positional params converted into handler arguments, one tuple element per param.
+///
+/// A method taking a single array is `(Vec<T>,)`, never `Vec<T>` spread over the params.
+pub trait FromParams: Sized {
+    fn from_params(params: Params, options: &DeserializeOptions) -> error::Result<Self>;
+}
+
+fn expect_len(params: &[Value], len: usize) -> error::Result<()> {
+    if params.len() != len {
+        bail!(ErrorKind::Decoding(format!(
+            "Expected {} parameters, got {}",
+            len,
+            params.len()
+        )));
+    }
+    Ok(())
+}
+
+fn next_param<T: DeserializeOwned>(
+    params: &mut Enumerate<vec::IntoIter<Value>>,
+    options: &DeserializeOptions,
+) -> error::Result<T> {
+    let (index, value) = params
+        .next()
+        .expect("Params should be counted before reading");
+    T::deserialize(Deserializer::new(value, options))
+        .chain_err(|| format!("Invalid parameter {}", index))
+}
+
+impl FromParams for () {
+    fn from_params(params: Params, _options: &DeserializeOptions) -> error::Result<()> {
+        // Older clients, this crate's included, send `()` as a lone empty struct.
+        match params.as_slice() {
+            [Value::Struct(members)] if members.is_empty() => Ok(()),
+            _ => expect_len(&params, 0),
+        }
+    }
+}
+
+macro_rules! from_params_tuple {
+    ($len:expr => $($name:ident)+) => {
+        impl<$($name: DeserializeOwned),+> FromParams for ($($name,)+) {
+            fn from_params(params: Params, options: &DeserializeOptions) -> error::Result<Self> {
+                expect_len(&params, $len)?;
+                let mut params = params.into_iter().enumerate();
+                Ok(($(next_param::<$name>(&mut params, options)?,)+))
+            }
+        }
+    };
+}
+
+from_params_tuple!(1 => A);
+from_params_tuple!(2 => A B);
+from_params_tuple!(3 => A B C);
+from_params_tuple!(4 => A B C D);
+from_params_tuple!(5 => A B C D E);
+from_params_tuple!(6 => A B C D E F);
+from_params_tuple!(7 => A B C D E F G);
+from_params_tuple!(8 => A B C D E F G H);
+
+/// Handler arguments written as positional params, one param per tuple element.
+pub trait IntoParams {
+    fn to_params(&self, options: &SerializeOptions) -> error::Result<Params>;
+}
+
+impl IntoParams for () {
+    fn to_params(&self, _options: &SerializeOptions) -> error::Result<Params> {
+        Ok(Vec::new())
+    }
+}
+
+macro_rules! into_params_tuple {
+    ($($name:ident $value:ident)+) => {
+        impl<$($name: Serialize),+> IntoParams for ($($name,)+) {
+            fn to_params(&self, options: &SerializeOptions) -> error::Result<Params> {
+                let ($(ref $value,)+) = *self;
+                Ok(vec![$(to_value_with($value, options)?),+])
+            }
+        }
+    };
+}
+
+into_params_tuple!(A a);
+into_params_tuple!(A a B b);
+into_params_tuple!(A a B b C c);
+into_params_tuple!(A a B b C c D d);
+into_params_tuple!(A a B b C c D d E e);
+into_params_tuple!(A a B b C c D d E e F f);
+into_params_tuple!(A a B b C c D d E e F f G g);
+into_params_tuple!(A a B b C c D d E e F f G g H h);
@@ -1,238 +1,262 @@
-use super::error::{Result, ResultExt};
-use super::{Call, Fault, Response, Value};
-use base64::{engine::general_purpose::STANDARD, Engine as _};
+use super::borrowed::{self, BorrowedCall, BorrowedResponse};
+use super::error::{Error, ErrorKind, Result, ResultExt};
+use super::stream::{self, Header, ParamReader, Warning};
+use super::{charset, Call, DoctypePolicy, ParseOptions, Response, Value};
 use regex::Regex;
 use std;
-use std::collections::HashMap;
 
-fn wrap_in_string(content: String) -> String {
+/// Enforces `policy` on the DOCTYPE declaration seen by the time the root element starts.
+pub fn check_doctype(doctype: Option<&str>, policy: DoctypePolicy) -> Result<()> {
     lazy_static! {
-        static ref RE1: Regex = Regex::new(r"<value\s*/>").unwrap();
-        static ref RE2: Regex = Regex::new(r"<value\s*>\s*<string\s*/>\s*</value\s*>").unwrap();
-        static ref RE3: Regex = Regex::new(r"<value\s*>(?P<rest>[^<>]*)</value\s*>").unwrap();
+        static ref EXTERNAL: Regex = Regex::new(r#"\b(?:SYSTEM|PUBLIC)\s*["']|%"#).unwrap();
     }
-    RE3.replace_all(
-        &RE2.replace_all(
-            &RE1.replace_all(&content, "<value><string></string></value>"),
-            "<value><string></string></value>",
+    let doctype = match doctype {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    match policy {
+        DoctypePolicy::Reject => bail!(ErrorKind::DoctypeNotAllowed(doctype.into())),
+        DoctypePolicy::AllowInternalEntities if EXTERNAL.is_match(doctype) => bail!(
+            ErrorKind::DoctypeNotAllowed(format!("external references in {}", doctype))
         ),
-        "<value><string>$rest</string></value>",
-    )
-    .into()
+        DoctypePolicy::AllowInternalEntities => Ok(()),
+    }
 }
 
-#[allow(dead_code)]
-pub fn xml<T: std::io::Read>(mut r: T) -> Result<Value> {
-    let mut content = String::new();
-    r.read_to_string(&mut content)
-        .chain_err(|| "Failed to read data source.")?;
-    let data: XmlValue = serde_xml_rs::from_str(&wrap_in_string(content))
-        .chain_err(|| "Failed to parse XML-RPC data.")?;
-    data.into()
+fn check_size(size: usize, options: &ParseOptions) -> Result<()> {
+    if size > options.max_size {
+        bail!(ErrorKind::LimitExceeded(format!(
+            "document larger than {}",
+            options.max_size
+        )));
+    }
+    Ok(())
 }
 
-pub fn call<T: std::io::Read>(mut r: T) -> Result<Call> {
-    let mut content = String::new();
-    r.read_to_string(&mut content)
+fn read_document<T: std::io::Read>(r: T, options: &ParseOptions) -> Result<String> {
+    use std::io::Read;
+    let mut content = Vec::new();
+    r.take(options.max_size as u64 + 1)
+        .read_to_end(&mut content)
         .chain_err(|| "Failed to read data source.")?;
-    let data: XmlCall = serde_xml_rs::from_str(&wrap_in_string(content))
-        .chain_err(|| "Failed to parse XML-RPC call.")?;
-    data.into()
+    check_size(content.len(), options)?;
+    charset::decode(content, options.charset.as_deref())
 }
 
-pub fn response<T: std::io::Read>(mut r: T) -> Result<Response> {
-    let mut content = String::new();
-    r.read_to_string(&mut content)
-        .chain_err(|| "Failed to read data source.")?;
-    let data: XmlResponse = serde_xml_rs::from_str(&wrap_in_string(content))
-        .chain_err(|| "Failed to parse XML-RPC response.")?;
-    data.into()
-}
-
-#[derive(Debug, PartialEq, Deserialize)]
-enum XmlValue {
-    #[serde(rename = "i4")]
-    I4(i32),
-    #[serde(rename = "int")]
-    Int(i32),
-    #[serde(rename = "boolean")]
-    Bool(i32),
-    #[serde(rename = "string")]
-    Str(String),
-    #[serde(rename = "double")]
-    Double(String),
-    #[serde(rename = "dateTime.iso8601")]
-    DateTime(String),
-    #[serde(rename = "base64")]
-    Base64(String),
-    #[serde(rename = "array")]
-    Array(XmlArray),
-    #[serde(rename = "struct")]
-    Struct(XmlStruct),
-}
-
-impl From<XmlValue> for Result<Value> {
-    fn from(val: XmlValue) -> Self {
-        Ok(match val {
-            XmlValue::I4(v) | XmlValue::Int(v) => Value::Int(v),
-            XmlValue::Bool(v) => Value::Bool(v != 0),
-            XmlValue::Str(v) => Value::String(v),
-            XmlValue::Double(v) => Value::Double(v.parse().chain_err(|| "Failed to parse double")?),
-            XmlValue::DateTime(v) => Value::DateTime(v),
-            XmlValue::Base64(v) => Value::Base64(
-                STANDARD
-                    .decode(v.as_bytes())
-                    .chain_err(|| "Failed to parse base64")?,
-            ),
-            XmlValue::Array(v) => {
-                let items: Result<Vec<Value>> = v.into();
-                Value::Array(items?)
-            }
-            XmlValue::Struct(v) => {
-                let items: Result<HashMap<String, Value>> = v.into();
-                Value::Struct(items?)
-            }
-        })
-    }
+pub fn xml<T: std::io::Read>(r: T) -> Result<Value> {
+    xml_with(r, &ParseOptions::default())
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-#[serde(rename = "methodCall")]
-struct XmlCall {
-    #[serde(rename = "methodName")]
-    pub name: String,
-    pub params: XmlParams,
+pub fn xml_with<T: std::io::Read>(r: T, options: &ParseOptions) -> Result<Value> {
+    let content = read_document(r, options)?;
+    stream::read_value(content.as_bytes(), options.clone())
 }
 
-impl From<XmlCall> for Result<Call> {
-    fn from(val: XmlCall) -> Self {
-        let params: Result<Vec<Value>> = val.params.into();
-        Ok(Call {
-            name: val.name,
-            params: params?,
-        })
-    }
+pub fn call<T: std::io::Read>(r: T) -> Result<Call> {
+    call_with(r, &ParseOptions::default())
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-enum XmlResponseResult {
-    #[serde(rename = "params")]
-    Success(XmlParams),
-    #[serde(rename = "fault")]
-    Failure { value: XmlValue },
+pub fn call_with<T: std::io::Read>(r: T, options: &ParseOptions) -> Result<Call> {
+    call_with_warnings(r, options).map(|(call, _)| call)
 }
 
-impl From<XmlResponseResult> for Result<Response> {
-    fn from(val: XmlResponseResult) -> Self {
-        match val {
-            XmlResponseResult::Success(params) => {
-                let params: Result<Vec<Value>> = params.into();
-                Ok(Ok(params?))
-            }
-            XmlResponseResult::Failure { value: v } => {
-                use serde::Deserialize;
+/// Like `call_with`, but also returns whatever lenient parsing let through.
+pub fn call_with_warnings<T: std::io::Read>(
+    r: T,
+    options: &ParseOptions,
+) -> Result<(Call, Vec<Warning>)> {
+    let content = read_document(r, options)?;
+    read_call(content.as_bytes(), options)
+}
 
-                let val: Result<Value> = v.into();
+/// Like `call`, but parses straight from the bytes of a UTF-8 document, without copying it.
+pub fn call_from_slice(data: &[u8]) -> Result<Call> {
+    call_from_slice_with(data, &ParseOptions::default())
+}
 
-                Ok(Err(
-                    Fault::deserialize(val?).chain_err(|| "Failed to decode fault structure")?
-                ))
-            }
-        }
-    }
+pub fn call_from_slice_with(data: &[u8], options: &ParseOptions) -> Result<Call> {
+    check_size(data.len(), options)?;
+    let content = charset::decode_slice(data, options.charset.as_deref())?;
+    read_call(&content, options).map(|(call, _)| call)
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-enum XmlResponse {
-    #[serde(rename = "methodResponse")]
-    Response(XmlResponseResult),
+/// Like `call_from_slice`, but borrows strings and member names from `data` instead of copying
+/// them, as described in `BorrowedValue`.
+pub fn call_borrowed(data: &[u8]) -> Result<BorrowedCall<'_>> {
+    call_borrowed_with(data, &ParseOptions::default())
 }
 
-impl From<XmlResponse> for Result<Response> {
-    fn from(val: XmlResponse) -> Self {
-        match val {
-            XmlResponse::Response(v) => v.into(),
-        }
-    }
+pub fn call_borrowed_with<'a>(data: &'a [u8], options: &ParseOptions) -> Result<BorrowedCall<'a>> {
+    check_size(data.len(), options)?;
+    borrowed::read_call(borrowed::document(data, options)?, options)
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct XmlParams {
-    #[serde(rename = "param", default)]
-    pub params: Vec<XmlParamData>,
+fn read_call(content: &[u8], options: &ParseOptions) -> Result<(Call, Vec<Warning>)> {
+    let mut reader = ParamReader::with_options(content, options.clone())?;
+    let name = match *reader.header() {
+        Header::Call(ref name) => name.clone(),
+        _ => bail!(ErrorKind::Decoding("Expected a method call".into())),
+    };
+    let params = reader.collect_params()?;
+    Ok((Call { name, params }, reader.take_warnings()))
 }
 
-impl From<XmlParams> for Result<Vec<Value>> {
-    fn from(val: XmlParams) -> Self {
-        val.params
-            .into_iter()
-            .map(Into::<Result<Value>>::into)
-            .collect()
-    }
+pub fn response<T: std::io::Read>(r: T) -> Result<Response> {
+    response_with(r, &ParseOptions::default())
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct XmlParamData {
-    pub value: XmlValue,
+pub fn response_with<T: std::io::Read>(r: T, options: &ParseOptions) -> Result<Response> {
+    response_with_warnings(r, options).map(|(response, _)| response)
 }
 
-impl From<XmlParamData> for Result<Value> {
-    fn from(val: XmlParamData) -> Self {
-        val.value.into()
-    }
+/// Like `response_with`, but also returns whatever lenient parsing let through.
+pub fn response_with_warnings<T: std::io::Read>(
+    r: T,
+    options: &ParseOptions,
+) -> Result<(Response, Vec<Warning>)> {
+    let content = read_document(r, options)?;
+    read_response(content.as_bytes(), options)
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct XmlArray {
-    #[serde(rename = "data")]
-    pub data: XmlArrayData,
+/// Like `response`, but parses straight from the bytes of a UTF-8 document, without copying it.
+pub fn response_from_slice(data: &[u8]) -> Result<Response> {
+    response_from_slice_with(data, &ParseOptions::default())
 }
 
-impl From<XmlArray> for Result<Vec<Value>> {
-    fn from(val: XmlArray) -> Self {
-        val.data.into()
-    }
+pub fn response_from_slice_with(data: &[u8], options: &ParseOptions) -> Result<Response> {
+    check_size(data.len(), options)?;
+    let content = charset::decode_slice(data, options.charset.as_deref())?;
+    read_response(&content, options).map(|(response, _)| response)
+}
+
+/// Like `response_from_slice`, but borrows strings and member names from `data` instead of
+/// copying them, as described in `BorrowedValue`.
+pub fn response_borrowed(data: &[u8]) -> Result<BorrowedResponse<'_>> {
+    response_borrowed_with(data, &ParseOptions::default())
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct XmlArrayData {
-    #[serde(default)]
-    pub value: Vec<XmlValue>,
+pub fn response_borrowed_with<'a>(
+    data: &'a [u8],
+    options: &ParseOptions,
+) -> Result<BorrowedResponse<'a>> {
+    check_size(data.len(), options)?;
+    match borrowed::read_response(borrowed::document(data, options)?, options)? {
+        Some(params) => Ok(Ok(params)),
+        // Faults are small, so they're simply read again as `Value`s.
+        None => read_response(data, options).map(|(response, _)| response.map(|_| Vec::new())),
+    }
 }
 
-impl From<XmlArrayData> for Result<Vec<Value>> {
-    fn from(val: XmlArrayData) -> Self {
-        val.value
-            .into_iter()
-            .map(Into::<Result<Value>>::into)
-            .collect()
+fn read_response(content: &[u8], options: &ParseOptions) -> Result<(Response, Vec<Warning>)> {
+    let mut reader = ParamReader::with_options(content, options.clone()).map_err(|mut err| {
+        if let ErrorKind::MalformedFault(_, ref mut xml) = err.0 {
+            *xml = String::from_utf8_lossy(content).into_owned();
+        }
+        err
+    })?;
+    match *reader.header() {
+        Header::Response => {}
+        Header::Fault(ref fault) => return Ok((Err(fault.clone()), reader.take_warnings())),
+        Header::Call(_) => bail!(ErrorKind::Decoding("Expected a method response".into())),
     }
+    let params = reader.collect_params()?;
+    Ok((Ok(params), reader.take_warnings()))
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct XmlStruct {
-    #[serde(rename = "member", default)]
-    pub members: Vec<XmlStructItem>,
+/// Parses a document that arrives in chunks, like a chunked request body, failing as soon as
+/// it grows past `max_size` or what arrived so far can't be the start of a call or response,
+/// rather than once it's complete.
+///
+/// The chunks are kept until the document is complete, and the data is parsed again from the
+/// start each time it has doubled in size since it was last parsed, so parsing takes at most
+/// about three times as long as for the complete document.
+#[derive(Clone, Debug, Default)]
+pub struct Feed {
+    data: Vec<u8>,
+    parsed: usize,
+    options: ParseOptions,
 }
 
-impl From<XmlStruct> for Result<HashMap<String, Value>> {
-    fn from(val: XmlStruct) -> Self {
-        val.members
-            .into_iter()
-            .map(Into::<Result<(String, Value)>>::into)
-            .collect()
+impl Feed {
+    pub fn new() -> Feed {
+        Feed::default()
+    }
+
+    pub fn with_options(options: ParseOptions) -> Feed {
+        Feed {
+            data: Vec::new(),
+            parsed: 0,
+            options,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<()> {
+        check_size(self.data.len() + chunk.len(), &self.options)?;
+        self.data.extend_from_slice(chunk);
+        let start = self
+            .data
+            .strip_prefix(b"\xEF\xBB\xBF")
+            .unwrap_or(&self.data)
+            .iter()
+            .find(|b| !b.is_ascii_whitespace());
+        match start {
+            Some(&b'<') => {}
+            Some(_) => bail!(ErrorKind::Decoding("Data is not an XML document".into())),
+            None => return Ok(()),
+        }
+        if self.data.len() >= 2 * self.parsed.max(64) {
+            self.parsed = self.data.len();
+            check_start(&self.data, &self.options)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish_call(self) -> Result<Call> {
+        call_from_slice_with(&self.data, &self.options)
+    }
+
+    pub fn finish_response(self) -> Result<Response> {
+        response_from_slice_with(&self.data, &self.options)
+    }
+}
+
+/// Parses `data` as the start of a call or response, failing only on errors that more data
+/// couldn't fix.
+fn check_start(data: &[u8], options: &ParseOptions) -> Result<()> {
+    let content = charset::decode_slice(data, options.charset.as_deref())?;
+    let parsed = ParamReader::with_options(Unfinished(&content), options.clone())
+        .and_then(|mut reader| reader.collect_params());
+    match parsed {
+        Err(ref err) if is_unfinished(err) => Ok(()),
+        parsed => parsed.map(|_| ()),
     }
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct XmlStructItem {
-    pub name: String,
-    pub value: XmlValue,
+/// Reads a slice, failing at its end rather than ending, so errors from running out of data
+/// can be told apart from those where the data ended too soon.
+struct Unfinished<'a>(&'a [u8]);
+
+impl<'a> std::io::Read for Unfinished<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "More data is yet to arrive",
+            ));
+        }
+        self.0.read(buf)
+    }
 }
 
-impl From<XmlStructItem> for Result<(String, Value)> {
-    fn from(val: XmlStructItem) -> Self {
-        let value: Result<Value> = val.value.into();
-        Ok((val.name, value?))
+fn is_unfinished(err: &Error) -> bool {
+    let mut cause = std::error::Error::source(err);
+    while let Some(err) = cause {
+        if let Some(err) = err.downcast_ref::<xml::reader::Error>() {
+            if let xml::reader::ErrorKind::Io(ref err) = *err.kind() {
+                return err.kind() == std::io::ErrorKind::UnexpectedEof;
+            }
+        }
+        cause = err.source();
     }
+    false
 }
@@ -0,0 +1,190 @@
+//! Picking values out of a tree by path, for code that needs a few fields of a response without
+//! modelling all of it.
+//!
+//! Queries are written like the paths of `diff` and `Value::walk_with_paths`, with wildcards:
+//!
+//! - `$` is the value queried, and can be left out,
+//! - `.name` and `["odd name"]` pick a struct member, and `[2]` an array item,
+//! - `.*` and `[*]` pick every struct member or array item, members in no particular order,
+//! - `..name`, `..*` and `..[2]` pick from the value and every value nested in it.
+//!
+//! ```
+//! # #[macro_use] extern crate xml_rpc;
+//! # fn main() {
+//! let response = xmlrpc_value!([{"web": {"port": 80}, "db": {"port": 5432}}]);
+//! let mut ports = response
+//!     .select("[0].*.port")
+//!     .unwrap()
+//!     .into_iter()
+//!     .filter_map(|port| port.as_i64())
+//!     .collect::<Vec<_>>();
+//! ports.sort();
+//! assert_eq!(vec![80, 5432], ports);
+//! # }
+//! ```
+
+use std::str::FromStr;
+use xmlfmt::error::{Error, ErrorKind, Result};
+use xmlfmt::Value;
+
+/// A parsed query, to run many times without parsing it again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Step {
+    /// Whether the selector applies to every nested value, and not just the current ones.
+    descendants: bool,
+    selector: Selector,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Selector {
+    Member(String),
+    Index(usize),
+    Any,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Query> {
+        match steps(query.trim()) {
+            Ok(steps) => Ok(Query { steps }),
+            Err(reason) => Err(ErrorKind::InvalidQuery(query.into(), reason.into()).into()),
+        }
+    }
+
+    /// Every value matching the query, with array items in order.
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        self.steps.iter().fold(vec![value], |current, step| {
+            let mut next = Vec::new();
+            for value in current {
+                if step.descendants {
+                    let mut nested = Vec::new();
+                    descendants(value, &mut nested);
+                    for value in nested {
+                        step.selector.apply(value, &mut next);
+                    }
+                } else {
+                    step.selector.apply(value, &mut next);
+                }
+            }
+            next
+        })
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(query: &str) -> Result<Query> {
+        Query::parse(query)
+    }
+}
+
+impl Selector {
+    fn apply<'a>(&self, value: &'a Value, matches: &mut Vec<&'a Value>) {
+        match (self, value) {
+            (Selector::Member(name), Value::Struct(members)) => matches.extend(members.get(name)),
+            (Selector::Index(index), Value::Array(items)) => matches.extend(items.get(*index)),
+            (Selector::Any, Value::Struct(members)) => matches.extend(members.values()),
+            (Selector::Any, Value::Array(items)) => matches.extend(items),
+            _ => {}
+        }
+    }
+}
+
+impl Value {
+    /// Every value matching the query, as described in the `query` module.
+    pub fn select(&self, query: &str) -> Result<Vec<&Value>> {
+        Ok(Query::parse(query)?.select(self))
+    }
+}
+
+fn steps(query: &str) -> ::std::result::Result<Vec<Step>, &'static str> {
+    let mut steps = Vec::new();
+    let mut rest = if let Some(rest) = query.strip_prefix('$') {
+        rest
+    } else if query.is_empty() || query.starts_with(['.', '[']) {
+        query
+    } else {
+        // A leading member name needs no dot.
+        let (selector, after) = name(query)?;
+        steps.push(step(false, selector));
+        after
+    };
+    while !rest.is_empty() {
+        let (descendants, after) = if let Some(after) = rest.strip_prefix("..") {
+            (true, after)
+        } else if let Some(after) = rest.strip_prefix('.') {
+            (false, after)
+        } else if rest.starts_with('[') {
+            (false, rest)
+        } else {
+            return Err("expected `.` or `[` between steps");
+        };
+        let (selector, after) = match after.strip_prefix('[') {
+            Some(inside) => bracket(inside)?,
+            None => name(after)?,
+        };
+        steps.push(step(descendants, selector));
+        rest = after;
+    }
+    Ok(steps)
+}
+
+fn step(descendants: bool, selector: Selector) -> Step {
+    Step {
+        descendants,
+        selector,
+    }
+}
+
+/// The value and every value nested in it, each before those nested in it.
+fn descendants<'a>(value: &'a Value, all: &mut Vec<&'a Value>) {
+    all.push(value);
+    match *value {
+        Value::Array(ref items) => items.iter().for_each(|item| descendants(item, all)),
+        Value::Struct(ref members) => members.values().for_each(|member| descendants(member, all)),
+        _ => {}
+    }
+}
+
+/// A member name or `*`, up to the next step.
+fn name(rest: &str) -> ::std::result::Result<(Selector, &str), &'static str> {
+    let end = rest.find(['.', '[']).unwrap_or(rest.len());
+    match &rest[..end] {
+        "" => Err("expected a member name"),
+        "*" => Ok((Selector::Any, &rest[end..])),
+        name => Ok((Selector::Member(name.into()), &rest[end..])),
+    }
+}
+
+/// What's between brackets: `*`, an index, or a quoted member name.
+fn bracket(rest: &str) -> ::std::result::Result<(Selector, &str), &'static str> {
+    if rest.starts_with('"') {
+        let mut name = String::new();
+        let mut chars = rest.char_indices().skip(1);
+        while let Some((_, c)) = chars.next() {
+            match c {
+                '"' => {
+                    let (index, _) = chars.next().ok_or("expected `]`")?;
+                    if !rest[index..].starts_with(']') {
+                        return Err("expected `]`");
+                    }
+                    return Ok((Selector::Member(name), &rest[index + 1..]));
+                }
+                '\\' => name.push(chars.next().ok_or("unterminated member name")?.1),
+                c => name.push(c),
+            }
+        }
+        return Err("unterminated member name");
+    }
+    let end = rest.find(']').ok_or("expected `]`")?;
+    let selector = match rest[..end].trim() {
+        "*" => Selector::Any,
+        index => Selector::Index(index.parse().map_err(|_| "expected an index")?),
+    };
+    Ok((selector, &rest[end + 1..]))
+}
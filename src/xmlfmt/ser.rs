@@ -1,21 +1,95 @@
+use super::datetime;
 use super::error::{Error, ErrorKind};
+use super::options::{
+    EnumRepresentation, IntegerPolicy, NonFinitePolicy, OptionRepresentation, SerializeOptions,
+};
 use super::Value;
+use serde::ser::Impossible;
 use serde::{self, Serialize};
+use std;
 use std::collections::HashMap;
+use std::fmt;
 
-pub struct Serializer;
+/// Names through which `Value` passes its extension types to this serializer.
+pub const I8_NEWTYPE_NAME: &str = "$xml_rpc::i8";
+pub const NIL_NAME: &str = "$xml_rpc::nil";
 
-impl serde::Serializer for Serializer {
+lazy_static! {
+    static ref DEFAULT_OPTIONS: SerializeOptions = SerializeOptions::default();
+}
+
+#[derive(Clone, Copy)]
+pub struct Serializer<'a> {
+    options: &'a SerializeOptions,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(options: &'a SerializeOptions) -> Self {
+        Serializer { options }
+    }
+
+    fn wide_integer<T>(self, v: T) -> Result<Value, Error>
+    where
+        T: Copy + fmt::Display + std::convert::TryInto<i32> + std::convert::TryInto<i64>,
+    {
+        match (self.options.integers, v.try_into()) {
+            (IntegerPolicy::WideAsString, _) => Ok(Value::String(v.to_string())),
+            (_, Ok(v)) => Ok(Value::Int(v)),
+            (IntegerPolicy::IntWhenFits, Err(_)) => Ok(Value::String(v.to_string())),
+            (IntegerPolicy::WideAsI8, Err(_)) => match v.try_into() {
+                Ok(v) => Ok(Value::I8(v)),
+                Err(_) => bail!(ErrorKind::UnsupportedData(format!(
+                    "Integer {} does not fit into i8.",
+                    v
+                ))),
+            },
+            (IntegerPolicy::ErrorWhenWide, Err(_)) => bail!(ErrorKind::UnsupportedData(format!(
+                "Integer {} does not fit into i4.",
+                v
+            ))),
+        }
+    }
+}
+
+fn tag_variant(
+    options: &SerializeOptions,
+    variant: String,
+    content: Value,
+) -> Result<Value, Error> {
+    let mut members = match options.enums {
+        EnumRepresentation::Internal { ref tag } => match content {
+            Value::Struct(mut members) => {
+                members.insert(tag.clone(), Value::String(variant));
+                return Ok(Value::Struct(members));
+            }
+            _ => bail!(ErrorKind::UnsupportedData(format!(
+                "Variant {} must hold a struct to be internally tagged.",
+                variant
+            ))),
+        },
+        _ => HashMap::new(),
+    };
+    members.insert(variant, content);
+    Ok(Value::Struct(members))
+}
+
+impl Default for Serializer<'static> {
+    fn default() -> Self {
+        Serializer::new(&DEFAULT_OPTIONS)
+    }
+}
+
+impl<'a> serde::Serializer for Serializer<'a> {
     type Ok = Value;
     type Error = Error;
 
-    type SerializeSeq = SerializeVec;
-    type SerializeTuple = SerializeVec;
-    type SerializeTupleStruct = SerializeVec;
-    type SerializeTupleVariant = SerializeVec;
-    type SerializeMap = SerializeMap;
-    type SerializeStruct = SerializeMap;
-    type SerializeStructVariant = SerializeMap;
+    type SerializeSeq = SerializeVec<'a>;
+    type SerializeTuple = SerializeVec<'a>;
+    type SerializeTupleStruct = SerializeVec<'a>;
+    type SerializeTupleVariant = SerializeVec<'a>;
+    type SerializeMap = SerializeMap<'a>;
+    type SerializeStruct = SerializeMap<'a>;
+    type SerializeStructVariant = SerializeMap<'a>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         Ok(Value::Bool(v))
@@ -34,7 +108,7 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::String(v.to_string()))
+        self.wide_integer(v)
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
@@ -46,19 +120,31 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::String(v.to_string()))
+        self.wide_integer(v)
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::String(v.to_string()))
+        self.wide_integer(v)
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::Double(f64::from(v)))
+        self.serialize_f64(f64::from(v))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::Double(v))
+        if v.is_finite() {
+            return Ok(Value::Double(v));
+        }
+        match self.options.non_finite {
+            _ if self.options.strict => {}
+            NonFinitePolicy::Error => {}
+            NonFinitePolicy::Verbatim => return Ok(Value::Double(v)),
+            NonFinitePolicy::Substitute(sentinel) => return Ok(Value::Double(sentinel)),
+        }
+        bail!(ErrorKind::UnsupportedData(format!(
+            "Double {} has no XML-RPC representation.",
+            v
+        )))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
@@ -74,21 +160,33 @@ impl serde::Serializer for Serializer {
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::Array(Vec::new()))
+        match self.options.options {
+            OptionRepresentation::Array => Ok(Value::Array(Vec::new())),
+            OptionRepresentation::Nil => Ok(Value::Nil),
+            OptionRepresentation::Omit => bail!(ErrorKind::UnsupportedData(
+                "None can only be omitted as a struct member.".into(),
+            )),
+        }
     }
 
     fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        Ok(Value::Array(vec![value.serialize(self)?]))
+        match self.options.options {
+            OptionRepresentation::Array => Ok(Value::Array(vec![value.serialize(self)?])),
+            OptionRepresentation::Omit | OptionRepresentation::Nil => value.serialize(self),
+        }
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
         Ok(Value::Struct(HashMap::new()))
     }
 
-    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        if name == NIL_NAME {
+            return Ok(Value::Nil);
+        }
         self.serialize_unit()
     }
 
@@ -98,20 +196,38 @@ impl serde::Serializer for Serializer {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        let mut members = HashMap::new();
-        members.insert(variant.into(), self.serialize_unit()?);
-        Ok(Value::Struct(members))
+        if self.options.enums == EnumRepresentation::UnitAsString {
+            return Ok(Value::String(variant.into()));
+        }
+        tag_variant(self.options, variant.into(), self.serialize_unit()?)
     }
 
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize + ?Sized,
     {
-        value.serialize(self)
+        let options = self.options;
+        if name == I8_NEWTYPE_NAME {
+            return match value.serialize(Serializer::default())? {
+                Value::String(ref v) => {
+                    Ok(Value::I8(v.parse().map_err(|_| {
+                        ErrorKind::UnsupportedData(format!("{:?} is not an i8", v))
+                    })?))
+                }
+                v => Ok(v),
+            };
+        }
+        match value.serialize(self)? {
+            Value::String(ref v) if name == datetime::NEWTYPE_NAME => Ok(Value::DateTime(
+                datetime::DateTime::parse(v)?.format(options.datetime_format),
+            )),
+            Value::String(v) if name == datetime::VERBATIM_NEWTYPE_NAME => Ok(Value::DateTime(v)),
+            v => Ok(v),
+        }
     }
 
     fn serialize_newtype_variant<T>(
@@ -124,9 +240,7 @@ impl serde::Serializer for Serializer {
     where
         T: Serialize + ?Sized,
     {
-        let mut members = HashMap::new();
-        members.insert(variant.into(), value.serialize(self)?);
-        Ok(Value::Struct(members))
+        tag_variant(self.options, variant.into(), value.serialize(self)?)
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
@@ -137,6 +251,7 @@ impl serde::Serializer for Serializer {
         Ok(SerializeVec {
             vec: Vec::with_capacity(len),
             variant: None,
+            options: self.options,
         })
     }
 
@@ -158,6 +273,7 @@ impl serde::Serializer for Serializer {
         Ok(SerializeVec {
             vec: Vec::with_capacity(len),
             variant: Some(variant.into()),
+            options: self.options,
         })
     }
 
@@ -166,6 +282,7 @@ impl serde::Serializer for Serializer {
             map: HashMap::new(),
             next_key: None,
             variant: None,
+            options: self.options,
         })
     }
 
@@ -188,24 +305,70 @@ impl serde::Serializer for Serializer {
             map: HashMap::new(),
             next_key: None,
             variant: Some(variant.into()),
+            options: self.options,
         })
     }
 }
 
-fn to_value<T>(value: &T) -> Result<Value, Error>
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        match *self {
+            Value::Int(v) => serializer.serialize_i32(v),
+            Value::I8(v) => serializer.serialize_newtype_struct(I8_NEWTYPE_NAME, &v),
+            Value::Bool(v) => serializer.serialize_bool(v),
+            Value::String(ref v) => serializer.serialize_str(v),
+            Value::Double(v) => serializer.serialize_f64(v),
+            Value::DateTime(ref v) => {
+                serializer.serialize_newtype_struct(datetime::VERBATIM_NEWTYPE_NAME, v)
+            }
+            Value::Base64(ref v) => serializer.serialize_bytes(v),
+            Value::Base64Text(_) => match self.clone().into_bytes() {
+                Ok(v) => serializer.serialize_bytes(&v),
+                Err(err) => Err(serde::ser::Error::custom(err)),
+            },
+            Value::Array(ref v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for item in v {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Value::Struct(ref v) => {
+                let mut map = serializer.serialize_map(Some(v.len()))?;
+                for (key, value) in v {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Value::Nil => serializer.serialize_unit_struct(NIL_NAME),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(ref v) => serializer.collect_str(v),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(ref v) => serializer.collect_str(v),
+        }
+    }
+}
+
+fn to_value<T>(value: &T, options: &SerializeOptions) -> Result<Value, Error>
 where
     T: Serialize,
 {
-    value.serialize(Serializer)
+    value.serialize(Serializer::new(options))
 }
 
 #[doc(hidden)]
-pub struct SerializeVec {
+pub struct SerializeVec<'a> {
     vec: Vec<Value>,
     variant: Option<String>,
+    options: &'a SerializeOptions,
 }
 
-impl serde::ser::SerializeSeq for SerializeVec {
+impl<'a> serde::ser::SerializeSeq for SerializeVec<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -213,24 +376,20 @@ impl serde::ser::SerializeSeq for SerializeVec {
     where
         T: Serialize + ?Sized,
     {
-        self.vec.push(to_value(&value)?);
+        self.vec.push(to_value(&value, self.options)?);
         Ok(())
     }
 
     fn end(self) -> Result<Value, Error> {
         let content = Value::Array(self.vec);
-        Ok(match self.variant {
-            Some(variant) => {
-                let mut members = HashMap::new();
-                members.insert(variant, content);
-                Value::Struct(members)
-            }
-            None => content,
-        })
+        match self.variant {
+            Some(variant) => tag_variant(self.options, variant, content),
+            None => Ok(content),
+        }
     }
 }
 
-impl serde::ser::SerializeTuple for SerializeVec {
+impl<'a> serde::ser::SerializeTuple for SerializeVec<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -246,7 +405,7 @@ impl serde::ser::SerializeTuple for SerializeVec {
     }
 }
 
-impl serde::ser::SerializeTupleStruct for SerializeVec {
+impl<'a> serde::ser::SerializeTupleStruct for SerializeVec<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -262,7 +421,7 @@ impl serde::ser::SerializeTupleStruct for SerializeVec {
     }
 }
 
-impl serde::ser::SerializeTupleVariant for SerializeVec {
+impl<'a> serde::ser::SerializeTupleVariant for SerializeVec<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -279,13 +438,14 @@ impl serde::ser::SerializeTupleVariant for SerializeVec {
 }
 
 #[doc(hidden)]
-pub struct SerializeMap {
+pub struct SerializeMap<'a> {
     map: HashMap<String, Value>,
     next_key: Option<String>,
     variant: Option<String>,
+    options: &'a SerializeOptions,
 }
 
-impl serde::ser::SerializeMap for SerializeMap {
+impl<'a> serde::ser::SerializeMap for SerializeMap<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -293,11 +453,14 @@ impl serde::ser::SerializeMap for SerializeMap {
     where
         T: Serialize + ?Sized,
     {
-        match to_value(&key)? {
+        match to_value(&key, self.options)? {
+            Value::String(s) => self.next_key = Some(s),
+            _ if self.options.strict => bail!(ErrorKind::UnsupportedData(
+                "Key must be a char or string.".into(),
+            )),
             Value::Bool(v) => self.next_key = Some(v.to_string()),
             Value::Int(v) => self.next_key = Some(v.to_string()),
             Value::Double(v) => self.next_key = Some(v.to_string()),
-            Value::String(s) => self.next_key = Some(s),
             _ => bail!(ErrorKind::UnsupportedData(
                 "Key must be a bool, int, float, char or string.".into(),
             )),
@@ -313,24 +476,23 @@ impl serde::ser::SerializeMap for SerializeMap {
         // Panic because this indicates a bug in the program rather than an
         // expected failure.
         let key = key.expect("serialize_value called before serialize_key");
-        self.map.insert(key, to_value(&value)?);
+        if self.options.options == OptionRepresentation::Omit && is_none(value) {
+            return Ok(());
+        }
+        self.map.insert(key, to_value(&value, self.options)?);
         Ok(())
     }
 
     fn end(self) -> Result<Value, Error> {
         let content = Value::Struct(self.map);
-        Ok(match self.variant {
-            Some(variant) => {
-                let mut members = HashMap::new();
-                members.insert(variant, content);
-                Value::Struct(members)
-            }
-            None => content,
-        })
+        match self.variant {
+            Some(variant) => tag_variant(self.options, variant, content),
+            None => Ok(content),
+        }
     }
 }
 
-impl serde::ser::SerializeStruct for SerializeMap {
+impl<'a> serde::ser::SerializeStruct for SerializeMap<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -347,7 +509,7 @@ impl serde::ser::SerializeStruct for SerializeMap {
     }
 }
 
-impl serde::ser::SerializeStructVariant for SerializeMap {
+impl<'a> serde::ser::SerializeStructVariant for SerializeMap<'a> {
     type Ok = Value;
     type Error = Error;
 
@@ -363,3 +525,152 @@ impl serde::ser::SerializeStructVariant for SerializeMap {
         serde::ser::SerializeMap::end(self)
     }
 }
+
+fn is_none<T>(value: &T) -> bool
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(NoneProbe).unwrap_or(false)
+}
+
+/// Checks whether a value serializes as `None`, bailing out early on anything else.
+struct NoneProbe;
+
+#[derive(Debug)]
+struct NotNone;
+
+impl fmt::Display for NotNone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("value is not None")
+    }
+}
+
+impl std::error::Error for NotNone {}
+
+impl serde::ser::Error for NotNone {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        NotNone
+    }
+}
+
+macro_rules! probe_not_none {
+    ($($method:ident($($arg:ty),*))*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<bool, NotNone> {
+                Ok(false)
+            }
+        )*
+    }
+}
+
+impl serde::Serializer for NoneProbe {
+    type Ok = bool;
+    type Error = NotNone;
+
+    type SerializeSeq = Impossible<bool, NotNone>;
+    type SerializeTuple = Impossible<bool, NotNone>;
+    type SerializeTupleStruct = Impossible<bool, NotNone>;
+    type SerializeTupleVariant = Impossible<bool, NotNone>;
+    type SerializeMap = Impossible<bool, NotNone>;
+    type SerializeStruct = Impossible<bool, NotNone>;
+    type SerializeStructVariant = Impossible<bool, NotNone>;
+
+    probe_not_none! {
+        serialize_bool(bool)
+        serialize_i8(i8)
+        serialize_i16(i16)
+        serialize_i32(i32)
+        serialize_i64(i64)
+        serialize_u8(u8)
+        serialize_u16(u16)
+        serialize_u32(u32)
+        serialize_u64(u64)
+        serialize_f32(f32)
+        serialize_f64(f64)
+        serialize_char(char)
+        serialize_str(&str)
+        serialize_bytes(&[u8])
+        serialize_unit()
+        serialize_unit_struct(&'static str)
+        serialize_unit_variant(&'static str, u32, &'static str)
+    }
+
+    fn serialize_none(self) -> Result<bool, NotNone> {
+        Ok(true)
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<bool, NotNone>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(false)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<bool, NotNone>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<bool, NotNone>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(false)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, NotNone> {
+        Err(NotNone)
+    }
+}
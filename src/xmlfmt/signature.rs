@@ -0,0 +1,195 @@
+use super::{Fault, Params, Value};
+use std::fmt;
+
+/// The type of a param or result, named as in `system.methodSignature`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    Int,
+    I8,
+    Boolean,
+    String,
+    Double,
+    DateTime,
+    Base64,
+    Array,
+    Struct,
+    Nil,
+    #[cfg(feature = "bignum")]
+    BigInt,
+    #[cfg(feature = "bignum")]
+    BigDecimal,
+}
+
+impl ValueType {
+    pub fn of(value: &Value) -> ValueType {
+        match *value {
+            Value::Int(_) => ValueType::Int,
+            Value::I8(_) => ValueType::I8,
+            Value::Bool(_) => ValueType::Boolean,
+            Value::String(_) => ValueType::String,
+            Value::Double(_) => ValueType::Double,
+            Value::DateTime(_) => ValueType::DateTime,
+            Value::Base64(_) | Value::Base64Text(_) => ValueType::Base64,
+            Value::Array(_) => ValueType::Array,
+            Value::Struct(_) => ValueType::Struct,
+            Value::Nil => ValueType::Nil,
+            #[cfg(feature = "bignum")]
+            Value::BigInt(_) => ValueType::BigInt,
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(_) => ValueType::BigDecimal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueType::Int => "int",
+            ValueType::I8 => "i8",
+            ValueType::Boolean => "boolean",
+            ValueType::String => "string",
+            ValueType::Double => "double",
+            ValueType::DateTime => "dateTime.iso8601",
+            ValueType::Base64 => "base64",
+            ValueType::Array => "array",
+            ValueType::Struct => "struct",
+            ValueType::Nil => "nil",
+            #[cfg(feature = "bignum")]
+            ValueType::BigInt => "biginteger",
+            #[cfg(feature = "bignum")]
+            ValueType::BigDecimal => "bigdecimal",
+        }
+    }
+
+    /// Also accepts the `i4` and `dateTime` spellings some servers use.
+    pub fn from_name(name: &str) -> Option<ValueType> {
+        Some(match name {
+            "int" | "i4" => ValueType::Int,
+            "i8" => ValueType::I8,
+            "boolean" => ValueType::Boolean,
+            "string" => ValueType::String,
+            "double" => ValueType::Double,
+            "dateTime.iso8601" | "dateTime" => ValueType::DateTime,
+            "base64" => ValueType::Base64,
+            "array" => ValueType::Array,
+            "struct" => ValueType::Struct,
+            "nil" => ValueType::Nil,
+            #[cfg(feature = "bignum")]
+            "biginteger" => ValueType::BigInt,
+            #[cfg(feature = "bignum")]
+            "bigdecimal" => ValueType::BigDecimal,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The params and result of a method.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<ValueType>,
+    pub result: ValueType,
+}
+
+impl Signature {
+    pub fn new(params: Vec<ValueType>, result: ValueType) -> Signature {
+        Signature { params, result }
+    }
+
+    /// Fails with an "invalid params" fault naming the first param that doesn't fit.
+    pub fn check(&self, params: &[Value]) -> Result<(), Fault> {
+        if params.len() != self.params.len() {
+            return Err(Fault::invalid_params(format!(
+                "Expected {} parameters, got {}",
+                self.params.len(),
+                params.len()
+            )));
+        }
+        for (index, (expected, value)) in self.params.iter().zip(params).enumerate() {
+            let found = ValueType::of(value);
+            if found != *expected {
+                return Err(Fault::invalid_params(format!(
+                    "Expected {} for parameter {}, got {}",
+                    expected, index, found
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts params that don't fit into the declared types where the intent is clear:
+    /// strings holding numbers or booleans, ints 0 and 1 into booleans, and single values into
+    /// one-element arrays. Leaves anything else as is, for `check` to reject.
+    pub fn coerce(&self, params: Params) -> Params {
+        params
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| match self.params.get(index) {
+                Some(&expected) => coerce(value, expected),
+                None => value,
+            })
+            .collect()
+    }
+}
+
+fn coerce(value: Value, expected: ValueType) -> Value {
+    if ValueType::of(&value) == expected {
+        return value;
+    }
+    let coerced = match (expected, &value) {
+        (ValueType::Int, Value::String(s)) => s.trim().parse().ok().map(Value::Int),
+        (ValueType::I8, Value::String(s)) => s.trim().parse().ok().map(Value::I8),
+        (ValueType::Double, Value::String(s)) => s.trim().parse().ok().map(Value::Double),
+        (ValueType::Boolean, Value::String(s)) => match s.trim() {
+            "1" | "true" => Some(Value::Bool(true)),
+            "0" | "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (ValueType::Boolean, &Value::Int(i)) if i == 0 || i == 1 => Some(Value::Bool(i == 1)),
+        (ValueType::Array, _) => Some(Value::Array(vec![value.clone()])),
+        _ => None,
+    };
+    coerced.unwrap_or(value)
+}
+
+/// The result type comes first, as `system.methodSignature` lists it.
+impl From<Signature> for Value {
+    fn from(signature: Signature) -> Value {
+        Some(signature.result)
+            .into_iter()
+            .chain(signature.params)
+            .map(|t| Value::String(t.name().into()))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+/// Passes params through as they are if any of a method's signatures fits them, or else coerced
+/// to the first signature that fits them after `Signature::coerce`.
+pub fn coerce_any(signatures: &[Signature], params: Params) -> Result<Params, Fault> {
+    let err = match check_any(signatures, &params) {
+        Ok(()) => return Ok(params),
+        Err(err) => err,
+    };
+    signatures
+        .iter()
+        .filter(|signature| signature.params.len() == params.len())
+        .map(|signature| signature.coerce(params.clone()))
+        .find(|coerced| check_any(signatures, coerced).is_ok())
+        .ok_or(err)
+}
+
+/// Checks params against each of a method's signatures, passing if any of them fits.
+pub fn check_any(signatures: &[Signature], params: &[Value]) -> Result<(), Fault> {
+    match *signatures {
+        [] => Ok(()),
+        [ref signature] => signature.check(params),
+        _ if signatures.iter().any(|s| s.check(params).is_ok()) => Ok(()),
+        _ => Err(Fault::invalid_params(
+            "Parameters fit none of the method's signatures",
+        )),
+    }
+}
@@ -0,0 +1,758 @@
+use super::arena::{ValueArena, ValueId};
+use super::build::{
+    decode_base64, is_base64, scalar, Scalar, TreeBuilder, ValueBuilder, SCALAR_TYPES,
+};
+use super::error::{Error, ErrorKind, Result, ResultExt};
+use super::parse::check_doctype;
+use super::value::{check_method_name, ToXml};
+use super::{Fault, MethodNamePolicy, ParseOptions, Value};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, BufReader, Read, Write};
+use xml::common::Position;
+use xml::reader::{EventReader, ParserConfig, XmlEvent};
+
+/// What a document turned out to be, read before any of its params.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Header {
+    Call(String),
+    Response,
+    Fault(Fault),
+}
+
+/// Something lenient parsing let through that a strict reader would have rejected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub line: u64,
+    pub column: u64,
+    pub kind: WarningKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WarningKind {
+    /// An unknown element, with everything inside it, was skipped.
+    SkippedElement { parent: String, tag: String },
+    /// Text between elements was ignored.
+    IgnoredText { parent: String },
+    /// A value of an unknown type was read as a string.
+    UnknownType(String),
+    /// A response had neither `<params>` nor `<fault>`.
+    MissingParams,
+    /// An array held its values without a `<data>` element.
+    MissingData,
+    /// A datetime had only a date or only a time, and was completed with midnight or the epoch.
+    TruncatedDateTime(String),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            WarningKind::SkippedElement {
+                ref parent,
+                ref tag,
+            } => write!(f, "Skipped unknown <{}> inside <{}>", tag, parent)?,
+            WarningKind::IgnoredText { ref parent } => {
+                write!(f, "Ignored text inside <{}>", parent)?
+            }
+            WarningKind::UnknownType(ref tag) => {
+                write!(f, "Read unknown value type <{}> as a string", tag)?
+            }
+            WarningKind::MissingParams => {
+                f.write_str("Expected <params> or <fault> inside <methodResponse>")?
+            }
+            WarningKind::MissingData => f.write_str("Expected <data> inside <array>")?,
+            WarningKind::TruncatedDateTime(ref v) => {
+                write!(f, "Completed truncated dateTime.iso8601 {:?}", v)?
+            }
+        }
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Reads the params of a call or response one at a time, without building the whole document.
+pub struct ParamReader<R: Read> {
+    events: EventReader<BufReader<SizeLimit<R>>>,
+    options: ParseOptions,
+    header: Header,
+    values: usize,
+    done: bool,
+    warnings: Vec<Warning>,
+}
+
+struct SizeLimit<R> {
+    inner: R,
+    remaining: usize,
+}
+
+impl<R: Read> Read for SizeLimit<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        if count > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "document larger than the allowed size",
+            ));
+        }
+        self.remaining -= count;
+        Ok(count)
+    }
+}
+
+/// Decodes base64 text handed over in pieces, like one XML text event at a time, writing the
+/// data out in blocks so none of it has to be kept whole.
+struct Base64Decoder {
+    url_safe: bool,
+    /// Characters of the current block, of which the first `pending` are yet to be decoded.
+    block: [u8; 4096],
+    pending: usize,
+    /// Whether padding was seen, after which only whitespace may follow.
+    padded: bool,
+}
+
+impl Base64Decoder {
+    fn new(url_safe: bool) -> Base64Decoder {
+        Base64Decoder {
+            url_safe,
+            block: [0; 4096],
+            pending: 0,
+            padded: false,
+        }
+    }
+
+    fn feed(&mut self, text: &str, out: &mut dyn Write) -> Result<()> {
+        for &c in text.as_bytes() {
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+            if self.padded && c != b'=' {
+                bail!(ErrorKind::Decoding("Failed to parse base64".into()));
+            }
+            self.padded = c == b'=';
+            self.block[self.pending] = c;
+            self.pending += 1;
+            if self.pending == self.block.len() {
+                self.flush(self.pending, out)?;
+            }
+        }
+        // Decoding stops at a multiple of 4 characters, to carry the rest over to the next piece.
+        self.flush(self.pending - self.pending % 4, out)
+    }
+
+    fn finish(mut self, out: &mut dyn Write) -> Result<()> {
+        self.flush(self.pending, out)
+    }
+
+    fn flush(&mut self, len: usize, out: &mut dyn Write) -> Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        let chunk = &self.block[..len];
+        // Only the URL-safe alphabet may leave out padding.
+        let url_safe = self.url_safe
+            && (!chunk.len().is_multiple_of(4) || chunk.iter().any(|&c| c == b'-' || c == b'_'));
+        let bytes = decode_base64(chunk, url_safe)?;
+        out.write_all(&bytes)
+            .chain_err(|| "Failed to write base64 data")?;
+        self.block.copy_within(len..self.pending, 0);
+        self.pending -= len;
+        Ok(())
+    }
+}
+
+fn unexpected<T>(event: &XmlEvent, expected: &str) -> Result<T> {
+    bail!(ErrorKind::Decoding(format!(
+        "Expected {}, got {:?}",
+        expected, event
+    )))
+}
+
+fn limit_exceeded<T>(what: &str, limit: usize) -> Result<T> {
+    bail!(ErrorKind::LimitExceeded(format!("{} {}", what, limit)))
+}
+
+fn append_position(mut err: Error, line: u64, column: u64) -> Error {
+    let position = format!(" at line {}, column {}", line, column);
+    match err.0 {
+        ErrorKind::Msg(ref mut text)
+        | ErrorKind::Decoding(ref mut text)
+        | ErrorKind::Encoding(ref mut text)
+        | ErrorKind::DoctypeNotAllowed(ref mut text)
+        | ErrorKind::LimitExceeded(ref mut text)
+        | ErrorKind::UnsupportedData(ref mut text) => {
+            if text.ends_with('.') {
+                text.pop();
+                text.push_str(&position);
+                text.push('.');
+            } else {
+                text.push_str(&position);
+            }
+        }
+        _ => {}
+    }
+    err
+}
+
+fn is_end(event: &XmlEvent, tag: &str) -> bool {
+    match *event {
+        XmlEvent::EndElement { ref name } => name.local_name == tag,
+        _ => false,
+    }
+}
+
+/// Reads a document holding just a value, like `<int>3</int>`.
+pub fn read_value<R: Read>(source: R, options: ParseOptions) -> Result<Value> {
+    let mut reader = ParamReader::open(source, options);
+    let value = reader.read_root_value();
+    reader.locate(value)
+}
+
+impl<R: Read> ParamReader<R> {
+    pub fn new(source: R) -> Result<ParamReader<R>> {
+        ParamReader::with_options(source, ParseOptions::default())
+    }
+
+    pub fn with_options(source: R, options: ParseOptions) -> Result<ParamReader<R>> {
+        let mut reader = ParamReader::open(source, options);
+        let header = reader.read_header();
+        reader.header = reader.locate(header)?;
+        Ok(reader)
+    }
+
+    fn open(source: R, options: ParseOptions) -> ParamReader<R> {
+        // xml-rs reads a byte at a time, which is slow on anything but in-memory sources.
+        let source = BufReader::with_capacity(
+            options.read_buffer_size.max(1),
+            SizeLimit {
+                inner: source,
+                remaining: options.max_size,
+            },
+        );
+        // Text split by CDATA sections, comments or processing instructions arrives as separate
+        // events, which `read_text` concatenates and base64 is decoded from one at a time.
+        let events = ParserConfig::new()
+            .cdata_to_characters(true)
+            .coalesce_characters(false)
+            .ignore_comments(true)
+            .create_reader(source);
+        ParamReader {
+            events,
+            options,
+            header: Header::Response,
+            values: 0,
+            done: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Everything lenient parsing has let through so far.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn next_param(&mut self) -> Result<Option<Value>> {
+        let param = self.read_param(&mut ValueBuilder, None);
+        self.locate(param)
+    }
+
+    /// Like `next_param`, but decodes any base64 values inside the param straight into `sink`,
+    /// as their text is read.
+    ///
+    /// Those values are left empty in the returned param.
+    ///
+    /// xml-rs hands out each run of text between two pieces of markup whole, so a base64 value
+    /// sent as a single text node, as most peers send them, is still held in memory at once,
+    /// though only as text, and never with its decoded bytes next to it. Only base64 split up,
+    /// like into CDATA sections or by comments, is decoded and dropped a piece at a time.
+    /// `ParseOptions::max_size` bounds how large that text can get.
+    pub fn next_param_to<W: Write>(&mut self, sink: &mut W) -> Result<Option<Value>> {
+        let param = self.read_param(&mut ValueBuilder, Some(sink));
+        self.locate(param)
+    }
+
+    /// Like `next_param`, but reads the param straight into `arena`.
+    pub fn next_param_in(&mut self, arena: &mut ValueArena) -> Result<Option<ValueId>> {
+        let param = self.read_param(arena, None);
+        self.locate(param)
+    }
+
+    pub fn collect_params(&mut self) -> Result<Vec<Value>> {
+        let mut params = Vec::new();
+        while let Some(param) = self.next_param()? {
+            params.push(param);
+        }
+        Ok(params)
+    }
+
+    /// Points errors at where the reader stopped in the document.
+    fn locate<T>(&self, result: Result<T>) -> Result<T> {
+        result.map_err(|err| {
+            let position = self.events.position();
+            append_position(err, position.row + 1, position.column + 1)
+        })
+    }
+
+    fn read_root_value(&mut self) -> Result<Value> {
+        let tag = match self.next_event()? {
+            XmlEvent::StartElement { name, .. } => name.local_name,
+            ref event => return unexpected(event, "a value"),
+        };
+        check_doctype(self.events.doctype(), self.options.doctype)?;
+        if tag == "value" {
+            self.read_value(1, &mut ValueBuilder, &mut None)
+        } else {
+            self.read_typed(&tag, 1, &mut ValueBuilder, &mut None)
+        }
+    }
+
+    fn next_event(&mut self) -> Result<XmlEvent> {
+        loop {
+            match self
+                .events
+                .next()
+                .chain_err(|| "Failed to parse XML-RPC data.")?
+            {
+                XmlEvent::StartDocument { .. }
+                | XmlEvent::ProcessingInstruction { .. }
+                | XmlEvent::Comment(_)
+                | XmlEvent::Whitespace(_) => {}
+                event => return Ok(event),
+            }
+        }
+    }
+
+    fn lenient(&self) -> bool {
+        self.options.lenient && !self.options.strict
+    }
+
+    fn warn(&mut self, kind: WarningKind) {
+        let position = self.events.position();
+        self.warnings.push(Warning {
+            line: position.row + 1,
+            column: position.column + 1,
+            kind,
+        });
+    }
+
+    /// Skips an unknown element whose start tag was just consumed.
+    fn skip_unknown(&mut self, parent: &str, tag: String) -> Result<()> {
+        self.warn(WarningKind::SkippedElement {
+            parent: parent.into(),
+            tag,
+        });
+        self.skip_element()
+    }
+
+    fn skip_element(&mut self) -> Result<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_event()? {
+                XmlEvent::StartElement { .. } => depth += 1,
+                XmlEvent::EndElement { .. } => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the tag of the next child element of `parent`, or `None` once `parent` ends.
+    ///
+    /// Lenient parsing skips stray text and any children not in `expected`.
+    fn next_child(&mut self, parent: &str, expected: &[&str]) -> Result<Option<String>> {
+        loop {
+            match self.next_event()? {
+                ref event if is_end(event, parent) => return Ok(None),
+                XmlEvent::StartElement { name, .. }
+                    if expected.contains(&name.local_name.as_str()) =>
+                {
+                    return Ok(Some(name.local_name))
+                }
+                XmlEvent::StartElement { name, .. } if self.lenient() => {
+                    self.skip_unknown(parent, name.local_name)?
+                }
+                XmlEvent::Characters(_) if self.lenient() => self.warn(WarningKind::IgnoredText {
+                    parent: parent.into(),
+                }),
+                ref event => {
+                    let expected = expected
+                        .iter()
+                        .map(|tag| format!("<{}>", tag))
+                        .chain(Some(format!("</{}>", parent)))
+                        .collect::<Vec<_>>();
+                    return unexpected(event, &expected.join(" or "));
+                }
+            }
+        }
+    }
+
+    fn expect_child(&mut self, parent: &str, tag: &str) -> Result<()> {
+        match self.next_child(parent, &[tag])? {
+            Some(_) => Ok(()),
+            None => bail!(ErrorKind::Decoding(format!(
+                "Expected <{}> inside <{}>",
+                tag, parent
+            ))),
+        }
+    }
+
+    fn expect_end(&mut self, tag: &str) -> Result<()> {
+        self.next_child(tag, &[]).map(|_| ())
+    }
+
+    fn read_header(&mut self) -> Result<Header> {
+        let root = match self.next_event()? {
+            XmlEvent::StartElement { name, .. } => name.local_name,
+            ref event => return unexpected(event, "<methodCall> or <methodResponse>"),
+        };
+        check_doctype(self.events.doctype(), self.options.doctype)?;
+        match root.as_str() {
+            "methodCall" => {
+                self.expect_child("methodCall", "methodName")?;
+                let name = self.read_text("methodName")?;
+                let policy = if self.options.strict {
+                    MethodNamePolicy::Spec
+                } else {
+                    self.options.method_names
+                };
+                check_method_name(&name, policy)?;
+                if self.next_child("methodCall", &["params"])?.is_none() {
+                    self.done = true;
+                }
+                Ok(Header::Call(name))
+            }
+            "methodResponse" => match self.next_child("methodResponse", &["params", "fault"])? {
+                Some(ref tag) if tag == "params" => Ok(Header::Response),
+                Some(_) => {
+                    self.expect_child("fault", "value")?;
+                    let value = self.read_value(1, &mut ValueBuilder, &mut None)?;
+                    self.expect_end("fault")?;
+                    self.expect_end("methodResponse")?;
+                    self.done = true;
+                    Ok(Header::Fault(self.read_fault(value)?))
+                }
+                // The spec has no example of this, but some servers leave empty params out.
+                None if !self.options.strict => {
+                    if self.lenient() {
+                        self.warn(WarningKind::MissingParams);
+                    }
+                    self.done = true;
+                    Ok(Header::Response)
+                }
+                None => bail!(ErrorKind::Decoding(
+                    "Expected <params> or <fault> inside <methodResponse>".into()
+                )),
+            },
+            _ => bail!(ErrorKind::Decoding(format!(
+                "Expected <methodCall> or <methodResponse>, got <{}>",
+                root
+            ))),
+        }
+    }
+
+    /// Faults that don't have the expected members are kept whole as the detail, unless strict.
+    fn read_fault(&self, value: Value) -> Result<Fault> {
+        if self.options.strict {
+            match (value.get("faultCode"), value.get("faultString")) {
+                (Some(&Value::Int(_)), Some(&Value::String(_))) => {}
+                _ => {
+                    let xml = value.to_xml();
+                    bail!(ErrorKind::MalformedFault(value, xml))
+                }
+            }
+            return Fault::try_from(value).chain_err(|| "Failed to decode fault structure");
+        }
+        if let Ok(fault) = Fault::try_from(value.clone()) {
+            return Ok(fault);
+        }
+        Ok(Fault {
+            code: value
+                .get("faultCode")
+                .and_then(Value::as_i64)
+                .and_then(|code| i32::try_from(code).ok())
+                .unwrap_or(0),
+            message: match value.get("faultString") {
+                Some(Value::String(message)) => message.clone(),
+                _ => value.to_string(),
+            },
+            detail: Some(value),
+        })
+    }
+
+    fn read_param<B: TreeBuilder>(
+        &mut self,
+        build: &mut B,
+        mut sink: Option<&mut dyn Write>,
+    ) -> Result<Option<B::Value>> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.next_child("params", &["param"])?.is_none() {
+            let root = match self.header {
+                Header::Call(_) => "methodCall",
+                _ => "methodResponse",
+            };
+            self.expect_end(root)?;
+            self.done = true;
+            return Ok(None);
+        }
+        self.expect_child("param", "value")?;
+        let value = self.read_value(1, build, &mut sink)?;
+        self.expect_end("param")?;
+        Ok(Some(value))
+    }
+
+    /// Appends text read from an event.
+    fn push_text<B: TreeBuilder>(
+        &self,
+        build: &mut B,
+        text: &mut B::Text,
+        content: String,
+    ) -> Result<()> {
+        if build.text(text).len() + content.len() > self.options.max_string_length {
+            return limit_exceeded("text longer than", self.options.max_string_length);
+        }
+        build.push_text(text, content);
+        Ok(())
+    }
+
+    fn read_text(&mut self, tag: &str) -> Result<String> {
+        self.read_text_into(tag, &mut ValueBuilder)
+    }
+
+    fn read_text_into<B: TreeBuilder>(&mut self, tag: &str, build: &mut B) -> Result<B::Text> {
+        let mut text = build.start_text();
+        loop {
+            match self
+                .events
+                .next()
+                .chain_err(|| "Failed to parse XML-RPC data.")?
+            {
+                XmlEvent::Characters(v) | XmlEvent::Whitespace(v) => {
+                    self.push_text(build, &mut text, v)?
+                }
+                XmlEvent::Comment(_) | XmlEvent::ProcessingInstruction { .. } => {}
+                XmlEvent::StartElement { name, .. } if self.lenient() => {
+                    self.skip_unknown(tag, name.local_name)?
+                }
+                ref event if is_end(event, tag) => return Ok(text),
+                ref event => return unexpected(event, &format!("text inside <{}>", tag)),
+            }
+        }
+    }
+
+    fn count_value(&mut self, depth: usize) -> Result<()> {
+        if depth > self.options.max_depth {
+            return limit_exceeded("values nested deeper than", self.options.max_depth);
+        }
+        self.values += 1;
+        if self.values > self.options.max_values {
+            return limit_exceeded("more values than", self.options.max_values);
+        }
+        Ok(())
+    }
+
+    /// Reads the rest of a value whose `<value>` tag was just consumed.
+    fn read_value<B: TreeBuilder>(
+        &mut self,
+        depth: usize,
+        build: &mut B,
+        sink: &mut Option<&mut dyn Write>,
+    ) -> Result<B::Value> {
+        let mut text = build.start_text();
+        let tag = loop {
+            match self
+                .events
+                .next()
+                .chain_err(|| "Failed to parse XML-RPC data.")?
+            {
+                XmlEvent::Characters(v) | XmlEvent::Whitespace(v) => {
+                    self.push_text(build, &mut text, v)?
+                }
+                XmlEvent::Comment(_) | XmlEvent::ProcessingInstruction { .. } => {}
+                XmlEvent::StartElement { name, .. } => break name.local_name,
+                ref event if is_end(event, "value") => {
+                    self.count_value(depth)?;
+                    return Ok(build.string(text));
+                }
+                ref event => return unexpected(event, "value content"),
+            }
+        };
+        // Whitespace around the type tag.
+        build.discard(text);
+        self.count_value(depth)?;
+        let value = self.read_typed(&tag, depth, build, sink)?;
+        self.expect_end("value")?;
+        Ok(value)
+    }
+
+    /// Reads the rest of a value whose type tag, like `<int>`, was just consumed.
+    ///
+    /// Only `<value>` elements count towards `max_values`, so a root value without one doesn't.
+    fn read_typed<B: TreeBuilder>(
+        &mut self,
+        tag: &str,
+        depth: usize,
+        build: &mut B,
+        sink: &mut Option<&mut dyn Write>,
+    ) -> Result<B::Value> {
+        match tag {
+            "i8" | "nil" | "dateTime" | "serializable" | "biginteger" | "bigdecimal"
+                if self.options.strict =>
+            {
+                bail!(ErrorKind::Decoding(format!(
+                    "<{}> is not part of the XML-RPC spec",
+                    tag
+                )))
+            }
+            "base64" | "serializable" => self.read_base64(tag, build, sink),
+            "array" => self.read_array(depth, build, sink),
+            "struct" => self.read_struct(depth, build, sink),
+            _ => {
+                if !SCALAR_TYPES.contains(&tag) {
+                    if !self.lenient() {
+                        bail!(ErrorKind::Decoding(format!("Unknown value type <{}>", tag)));
+                    }
+                    self.warn(WarningKind::UnknownType(tag.into()));
+                }
+                let text = self.read_text_into(tag, build)?;
+                let mut warnings = Vec::new();
+                let scalar = scalar(tag, build.text(&text), &self.options, &mut |kind| {
+                    warnings.push(kind)
+                })?;
+                for kind in warnings {
+                    self.warn(kind);
+                }
+                Ok(match scalar {
+                    Scalar::String => build.string(text),
+                    Scalar::DateTime => build.datetime(text),
+                    Scalar::Parsed(value) => {
+                        build.discard(text);
+                        build.scalar(value)
+                    }
+                })
+            }
+        }
+    }
+
+    fn read_base64<B: TreeBuilder>(
+        &mut self,
+        tag: &str,
+        build: &mut B,
+        sink: &mut Option<&mut dyn Write>,
+    ) -> Result<B::Value> {
+        if sink.is_none() && self.options.lazy_base64 {
+            let mut text = self.read_text_into(tag, build)?;
+            build.remove_whitespace(&mut text);
+            let url_safe = self.options.base64_url_safe && build.text(&text).contains(['-', '_']);
+            if url_safe {
+                let bytes = decode_base64(build.text(&text).as_bytes(), url_safe)?;
+                build.discard(text);
+                return build.base64(|out| {
+                    out.write_all(&bytes)
+                        .chain_err(|| "Failed to write base64 data")
+                });
+            }
+            if !is_base64(build.text(&text).as_bytes()) {
+                bail!(ErrorKind::Decoding("Failed to parse base64".into()));
+            }
+            return Ok(build.base64_text(text));
+        }
+        // Data written to the sink is left out of the value.
+        build.base64(|out| match *sink {
+            Some(ref mut sink) => self.decode_text(tag, *sink),
+            None => self.decode_text(tag, out),
+        })
+    }
+
+    /// Decodes the base64 text of `tag` into `out` as it's read.
+    fn decode_text(&mut self, tag: &str, out: &mut dyn Write) -> Result<()> {
+        let mut decoder = Base64Decoder::new(self.options.base64_url_safe);
+        let mut length = 0;
+        loop {
+            match self
+                .events
+                .next()
+                .chain_err(|| "Failed to parse XML-RPC data.")?
+            {
+                XmlEvent::Characters(v) | XmlEvent::Whitespace(v) => {
+                    length += v.len();
+                    if length > self.options.max_string_length {
+                        return limit_exceeded("text longer than", self.options.max_string_length);
+                    }
+                    decoder.feed(&v, out)?;
+                }
+                XmlEvent::Comment(_) | XmlEvent::ProcessingInstruction { .. } => {}
+                XmlEvent::StartElement { name, .. } if self.lenient() => {
+                    self.skip_unknown(tag, name.local_name)?
+                }
+                ref event if is_end(event, tag) => return decoder.finish(out),
+                ref event => return unexpected(event, &format!("text inside <{}>", tag)),
+            }
+        }
+    }
+
+    fn read_array<B: TreeBuilder>(
+        &mut self,
+        depth: usize,
+        build: &mut B,
+        sink: &mut Option<&mut dyn Write>,
+    ) -> Result<B::Value> {
+        let mut items = build.start_array();
+        // Lenient parsing also takes values placed straight into the array.
+        let expected: &[&str] = if self.lenient() {
+            &["data", "value"]
+        } else {
+            &["data"]
+        };
+        match self.next_child("array", expected)? {
+            None => {}
+            Some(ref tag) if tag == "data" => {
+                while self.next_child("data", &["value"])?.is_some() {
+                    let item = self.read_value(depth + 1, build, sink)?;
+                    build.push_item(&mut items, item);
+                }
+                self.expect_end("array")?;
+            }
+            Some(_) => {
+                self.warn(WarningKind::MissingData);
+                let item = self.read_value(depth + 1, build, sink)?;
+                build.push_item(&mut items, item);
+                while self.next_child("array", &["value"])?.is_some() {
+                    let item = self.read_value(depth + 1, build, sink)?;
+                    build.push_item(&mut items, item);
+                }
+            }
+        }
+        Ok(build.array(items))
+    }
+
+    fn read_struct<B: TreeBuilder>(
+        &mut self,
+        depth: usize,
+        build: &mut B,
+        sink: &mut Option<&mut dyn Write>,
+    ) -> Result<B::Value> {
+        let mut members = build.start_struct();
+        let mut count = 0;
+        while self.next_child("struct", &["member"])?.is_some() {
+            if count >= self.options.max_members {
+                return limit_exceeded("struct with more members than", self.options.max_members);
+            }
+            count += 1;
+            self.expect_child("member", "name")?;
+            let name = self.read_text_into("name", build)?;
+            self.expect_child("member", "value")?;
+            let value = self.read_value(depth + 1, build, sink)?;
+            self.expect_end("member")?;
+            build.push_member(&mut members, name, value);
+        }
+        Ok(build.structure(members))
+    }
+}
@@ -0,0 +1,22 @@
+use hyper;
+use std::time::Duration;
+use test_util;
+use {Client, Server};
+
+#[test]
+fn calls_through_a_configured_http_client() {
+    let mut server = Server::new();
+    server.register_simple("double", |(x,): (i32,)| Ok(x * 2));
+    let server = test_util::spawn(server);
+    let mut http = hyper::Client::new();
+    http.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut client = Client::with_http_client(http);
+    assert_eq!(
+        8,
+        client
+            .call::<_, _, i32>(server.url(), "double", (4,))
+            .unwrap()
+    );
+    let stats = client.pool_stats()[&format!("127.0.0.1:{}", server.addr().port())];
+    assert_eq!((0, 1), (stats.handshakes, stats.requests));
+}
@@ -0,0 +1,164 @@
+use super::super::arena::{ValueArena, ValueRef};
+use super::super::stream::ParamReader;
+use super::super::Value;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use xml::reader::ParserConfig;
+
+/// Counts allocations made by the current thread, so tests running alongside don't add to them.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations<T, F: FnOnce() -> T>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn keeps_values_in_the_arena() {
+    let value = xmlrpc_value!({
+        "name": "Bob",
+        "tags": [1, [true, "nested"], (Value::Base64(vec![1, 2, 3]))],
+        "empty": {},
+        "nil": (Value::Nil),
+    });
+    let mut arena = ValueArena::new();
+    let id = arena.insert(&value);
+    let view = arena.get(id);
+    assert_eq!(Some(ValueRef::String("Bob")), view.get("name"));
+    match view.get("tags") {
+        Some(ValueRef::Array(tags)) => {
+            assert_eq!(3, tags.len());
+            assert_eq!(Some(ValueRef::Int(1)), tags.get(0));
+            assert_eq!(Some(ValueRef::Base64(&[1, 2, 3])), tags.get(2));
+        }
+        v => panic!("Expected array, got {:?}", v),
+    }
+    assert_eq!(value, view.to_value());
+    assert_eq!(10, arena.len());
+
+    arena.clear();
+    assert!(arena.is_empty());
+    let id = arena.insert(&Value::Int(3));
+    assert_eq!(ValueRef::Int(3), arena.get(id));
+}
+
+#[test]
+fn reads_params_into_the_arena() {
+    let data = r#"<methodCall><methodName>greet</methodName><params>
+<param><value><string>Alice</string></value></param>
+<param><value><array><data><value><i4>1</i4></value></data></array></value></param>
+</params></methodCall>"#;
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    let mut arena = ValueArena::new();
+    let mut params = Vec::new();
+    while let Some(id) = reader.next_param_in(&mut arena).unwrap() {
+        params.push(id);
+    }
+    assert_eq!(2, params.len());
+    assert_eq!(ValueRef::String("Alice"), arena.get(params[0]));
+    assert_eq!(xmlrpc_value!([1]), arena.get(params[1]).to_value());
+}
+
+#[test]
+fn reads_params_without_building_values() {
+    let mut data = String::from("<methodCall><methodName>save</methodName><params>");
+    for i in 0..100 {
+        data.push_str(&format!(
+            "<param><value><struct>\
+             <member><name>name</name><value>user {}</value></member>\
+             <member><name>tags</name><value><array><data>\
+             <value><int>{}</int></value><value><string>tag</string></value>\
+             </data></array></value></member>\
+             <member><name>flags</name><value><struct>\
+             <member><name>ok</name><value><boolean>1</boolean></value></member>\
+             </struct></value></member>\
+             </struct></value></param>",
+            i, i
+        ));
+    }
+    data.push_str("</params></methodCall>");
+
+    let read_into = |arena: &mut ValueArena| {
+        let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+        let mut params = Vec::with_capacity(100);
+        let count = allocations(|| {
+            while let Some(id) = reader.next_param_in(arena).unwrap() {
+                params.push(id);
+            }
+        });
+        (params, count)
+    };
+    let mut arena = ValueArena::new();
+    read_into(&mut arena);
+    arena.clear();
+    // The arena's buffers are big enough now, so only xml-rs allocates, for every event.
+    let (params, in_arena) = read_into(&mut arena);
+    assert_eq!(100, params.len());
+    assert_eq!(
+        Some(ValueRef::String("user 42")),
+        arena.get(params[42]).get("name")
+    );
+    assert_eq!(
+        xmlrpc_value!([99, "tag"]),
+        arena.get(params[99]).get("tags").unwrap().to_value()
+    );
+
+    let parsing = allocations(|| {
+        let events = ParserConfig::new()
+            .cdata_to_characters(true)
+            .coalesce_characters(false)
+            .ignore_comments(true)
+            .create_reader(data.as_bytes());
+        for event in events {
+            event.unwrap();
+        }
+    });
+    assert!(in_arena <= parsing, "{} > {}", in_arena, parsing);
+
+    // Each param's two structs and array would take at least one allocation as a `Value`.
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    let as_values = allocations(|| while reader.next_param().unwrap().is_some() {});
+    assert!(
+        as_values >= in_arena + 300,
+        "{} < {}",
+        as_values,
+        in_arena + 300
+    );
+}
+
+#[test]
+fn gets_the_last_of_repeated_members() {
+    let data = r#"<methodResponse><params><param><value><struct>
+<member><name>a</name><value>first</value></member>
+<member><name>a</name><value>second</value></member>
+</struct></value></param></params></methodResponse>"#;
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    let mut arena = ValueArena::new();
+    let id = reader.next_param_in(&mut arena).unwrap().unwrap();
+    assert_eq!(Some(ValueRef::String("second")), arena.get(id).get("a"));
+    assert_eq!(xmlrpc_value!({"a": "second"}), arena.get(id).to_value());
+}
@@ -0,0 +1,57 @@
+use hyper;
+use hyper::header::{Authorization, Basic};
+use hyper::status::StatusCode;
+use secret::{constant_time_eq, Secret};
+use test_util;
+use {Client, Server};
+
+fn client_as(username: &'static str, password: &'static str) -> Client {
+    let mut client = Client::new().unwrap();
+    client.set_pre_send_hook(move |_, _, headers| {
+        headers.set(Authorization(Basic {
+            username: username.into(),
+            password: Some(password.into()),
+        }))
+    });
+    client
+}
+
+#[test]
+fn requires_basic_auth_when_set() {
+    let mut server = Server::new();
+    server.register_simple("ping", |()| Ok(true));
+    server.set_basic_auth("admin", "hunter2");
+    let server = test_util::spawn(server);
+
+    assert!(client_as("admin", "hunter2")
+        .call::<_, _, bool>(server.url(), "ping", ())
+        .unwrap());
+    for client in &mut [
+        Client::new().unwrap(),
+        client_as("admin", "hunter3"),
+        client_as("root", "hunter2"),
+    ] {
+        assert!(client.call::<_, _, bool>(server.url(), "ping", ()).is_err());
+    }
+    let response = hyper::Client::new()
+        .post(server.url().as_ref())
+        .body("")
+        .send()
+        .unwrap();
+    assert_eq!(StatusCode::Unauthorized, response.status);
+}
+
+#[test]
+fn keeps_secrets_out_of_debug_output() {
+    let secret = Secret::from("hunter2");
+    assert_eq!("[redacted]", format!("{:?}", secret));
+    assert_eq!("[redacted]", secret.to_string());
+    assert_eq!("hunter2", secret.expose());
+    assert!(secret == "hunter2");
+    assert!(secret != "hunter3");
+    assert_eq!(secret.clone(), Secret::new("hunter2".to_string()));
+
+    assert!(constant_time_eq(b"", b""));
+    assert!(!constant_time_eq(b"abc", b"abd"));
+    assert!(!constant_time_eq(b"abc", b"abcd"));
+}
@@ -0,0 +1,58 @@
+use super::super::value::ToXml;
+use super::super::{parse, Value, WriteOptions};
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+#[test]
+fn reads_big_numbers() {
+    let data = r#"<?xml version="1.0"?>
+<methodResponse xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">
+  <params>
+    <param><value><ex:biginteger>123456789012345678901234567890</ex:biginteger></value></param>
+    <param><value><ex:bigdecimal>-0.000000000000000000012345</ex:bigdecimal></value></param>
+  </params>
+</methodResponse>"#;
+    let big: BigInt = "123456789012345678901234567890".parse().unwrap();
+    let small: BigDecimal = "-0.000000000000000000012345".parse().unwrap();
+    assert_eq!(
+        Ok(vec![Value::BigInt(big), Value::BigDecimal(small)]),
+        parse::response(data.as_bytes()).unwrap()
+    );
+    assert!(parse::xml("<biginteger>1.5</biginteger>".as_bytes()).is_err());
+}
+
+#[test]
+fn writes_big_numbers_as_strings_unless_extended() {
+    let value = Value::from("12345678901234567890.5".parse::<BigDecimal>().unwrap());
+    assert_eq!(
+        "<value><string>12345678901234567890.5</string></value>",
+        value.to_xml()
+    );
+    let options = WriteOptions {
+        apache_extensions: true,
+        ..Default::default()
+    };
+    let data = value.to_xml_with(&options);
+    assert!(data.contains("<ex:bigdecimal>12345678901234567890.5</ex:bigdecimal>"));
+    assert_eq!(value, parse::xml(data.as_bytes()).unwrap());
+}
+
+#[test]
+fn converts_big_numbers() {
+    let big = BigInt::from(5_000_000_000i64);
+    assert_eq!(big, BigInt::try_from(Value::I8(5_000_000_000)).unwrap());
+    assert_eq!(
+        big,
+        BigInt::try_from(Value::String("5000000000".into())).unwrap()
+    );
+    assert_eq!(
+        5_000_000_000i64,
+        i64::deserialize(Value::BigInt(big)).unwrap()
+    );
+    assert_eq!(
+        BigDecimal::from(3),
+        BigDecimal::try_from(Value::Int(3)).unwrap()
+    );
+}
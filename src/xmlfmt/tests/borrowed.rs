@@ -0,0 +1,279 @@
+#[cfg(feature = "testing")]
+use super::super::value::ToXml;
+use super::super::*;
+#[cfg(feature = "testing")]
+use proptest::sample::{select, Index};
+use std::borrow::Cow;
+
+/// Whether `text` was borrowed from within `data`.
+fn borrowed_from(text: Cow<str>, data: &[u8]) -> bool {
+    match text {
+        Cow::Borrowed(text) => data.as_ptr_range().contains(&text.as_ptr()),
+        Cow::Owned(_) => false,
+    }
+}
+
+#[test]
+fn borrows_strings_and_member_names_from_the_document() {
+    let data = br#"<?xml version="1.0"?>
+<methodResponse><params><param><value><struct>
+<member><name>name</name><value><string>Alice</string></value></member>
+<member><name>bio</name><value>Likes <![CDATA[<b>]]>bold</value></member>
+<member><name>tags</name><value><array><data>
+<value>plain</value><value><string>a &amp; b</string></value>
+</data></array></value></member>
+<member><name>when</name><value><dateTime.iso8601>19980717T14:08:55</dateTime.iso8601></value></member>
+<member><name>data</name><value><base64>aGVsbG8=</base64></value></member>
+<member><name>age</name><value><int>42</int></value></member>
+</struct></value></param></params></methodResponse>"#;
+    let params = parse::response_borrowed(data).unwrap().unwrap();
+    let members = match params[0] {
+        BorrowedValue::Struct(ref members) => members,
+        ref v => panic!("Expected struct, got {:?}", v),
+    };
+    assert!(members
+        .iter()
+        .all(|(name, _)| borrowed_from(name.clone(), data)));
+    match *params[0].get("name").unwrap() {
+        BorrowedValue::String(ref v) => {
+            assert_eq!("Alice", v);
+            assert!(borrowed_from(v.clone(), data));
+        }
+        ref v => panic!("Expected string, got {:?}", v),
+    }
+    match *params[0].get("when").unwrap() {
+        BorrowedValue::DateTime(ref v) => assert!(borrowed_from(v.clone(), data)),
+        ref v => panic!("Expected datetime, got {:?}", v),
+    }
+    match *params[0].get("data").unwrap() {
+        BorrowedValue::Base64(ref v) => assert!(borrowed_from(v.clone(), data)),
+        ref v => panic!("Expected base64, got {:?}", v),
+    }
+    // Text split by CDATA or holding entities has to be copied.
+    match *params[0].get("bio").unwrap() {
+        BorrowedValue::String(Cow::Owned(ref v)) => assert_eq!("Likes <b>bold", v),
+        ref v => panic!("Expected an owned string, got {:?}", v),
+    }
+    match *params[0].get("tags").unwrap() {
+        BorrowedValue::Array(ref items) => {
+            assert_eq!(Some("plain"), items[0].as_str());
+            assert_eq!(Some("a & b"), items[1].as_str());
+        }
+        ref v => panic!("Expected array, got {:?}", v),
+    }
+    assert_eq!(Some(&BorrowedValue::Int(42)), params[0].get("age"));
+
+    let lazy = ParseOptions {
+        lazy_base64: true,
+        ..ParseOptions::default()
+    };
+    let expected = parse::response_from_slice_with(data, &lazy)
+        .unwrap()
+        .unwrap();
+    assert_eq!(expected[0], params[0].to_value());
+}
+
+#[test]
+fn borrows_from_calls() {
+    let data = b"<methodCall><methodName>greet</methodName><params>\
+<param><value><string>Bob</string></value></param>\
+<param><value><i8>5</i8></value></param>\
+<param><value><nil/></value></param>\
+</params></methodCall>";
+    let call = parse::call_borrowed(data).unwrap();
+    assert!(borrowed_from(call.name.clone(), data));
+    assert_eq!("greet", call.name);
+    assert_eq!(Some("Bob"), call.params[0].as_str());
+    assert_eq!(BorrowedValue::Other(Value::I8(5)), call.params[1]);
+    assert_eq!(BorrowedValue::Nil, call.params[2]);
+    let call = parse::call_borrowed(b"<methodCall><methodName>ping</methodName></methodCall>");
+    assert!(call.unwrap().params.is_empty());
+}
+
+#[test]
+fn reads_faults_when_borrowing() {
+    let data = br#"<methodResponse><fault><value><struct>
+<member><name>faultCode</name><value><int>4</int></value></member>
+<member><name>faultString</name><value><string>Too many parameters.</string></value></member>
+</struct></value></fault></methodResponse>"#;
+    let fault = parse::response_borrowed(data).unwrap().unwrap_err();
+    assert_eq!(4, fault.code);
+    assert_eq!("Too many parameters.", fault.message);
+}
+
+#[test]
+fn rejects_what_it_cannot_borrow_or_read() {
+    let data = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><methodCall><methodName>caf\xe9</methodName></methodCall>";
+    assert!(parse::call_borrowed(data).is_err());
+    let data = br#"<!DOCTYPE methodCall><methodCall><methodName>a</methodName></methodCall>"#;
+    assert!(parse::call_borrowed(data).is_err());
+    let data = br#"<methodCall><methodName>a</methodName><params><param><value><foo>1</foo></value></param></params></methodCall>"#;
+    assert!(parse::call_borrowed(data).is_err());
+    let data = br#"<methodCall><methodName>&bogus;</methodName></methodCall>"#;
+    assert!(parse::call_borrowed(data).is_err());
+    let data = br#"<methodCall><methodName>a</methodName><params>"#;
+    assert!(parse::call_borrowed(data).is_err());
+    let data = br#"<methodResponse><params><param><value><base64>not base64</base64></value></param></params></methodResponse>"#;
+    assert!(parse::response_borrowed(data).is_err());
+
+    let options = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::default()
+    };
+    let data = br#"<methodResponse><params><param><value><array><data><value><array><data>
+<value><int>1</int></value></data></array></value></data></array></value></param></params></methodResponse>"#;
+    assert!(parse::response_borrowed_with(data, &options).is_err());
+    assert!(parse::response_borrowed(data).is_ok());
+}
+
+#[test]
+fn rejects_markup_that_copying_rejects() {
+    let documents: &[&[u8]] = &[
+        b"<methodCall><methodName>a]]>b</methodName></methodCall>",
+        b"<methodCall><methodName>a\x01</methodName></methodCall>",
+        b"<methodCall><methodName>&#1;</methodName></methodCall>",
+        b"<methodCall><!-- a -- b --><methodName>a</methodName></methodCall>",
+        b"<methodCall><?xml version=\"1.0\"?><methodName>a</methodName></methodCall>",
+        b"<methodCall a=\"1\" a=\"2\"><methodName>a</methodName></methodCall>",
+        b"<methodCall a='<'><methodName>a</methodName></methodCall>",
+        b"<methodCall a=1><methodName>a</methodName></methodCall>",
+        b"<p:methodCall><methodName>a</methodName></p:methodCall>",
+    ];
+    for data in documents {
+        assert!(parse::call_from_slice(data).is_err());
+        assert!(parse::call_borrowed(data).is_err());
+    }
+}
+
+/// Markup that is easy to get wrong, spliced into documents that are otherwise valid.
+#[cfg(feature = "testing")]
+const SPLICES: &[&str] = &[
+    "",
+    "<",
+    ">",
+    "&",
+    "&amp;",
+    "&#1;",
+    "&#x10FFFF;",
+    "&#xFFFE;",
+    "&#65",
+    "\u{1}",
+    "\u{fffe}",
+    "\r",
+    "\t",
+    "]]>",
+    "<![CDATA[x]]>",
+    "<![CDATA[",
+    "<!-- c -->",
+    "<!-- a -- b -->",
+    "<!--",
+    "<?pi x?>",
+    "<?xml version=\"1.0\"?>",
+    "<?XML?>",
+    "<?xml?>",
+    "<!DOCTYPE methodCall>",
+    " ",
+    "\"",
+    "'",
+    "=",
+    "/",
+    " a=\"1\"",
+    " a='<'",
+    " a=\"&bogus;\"",
+    " a=\"&#1;\"",
+    " a",
+    " a=1",
+    " a=\"1\" a=\"2\"",
+    " a=\"1\"b=\"2\"",
+    " xmlns=\"urn:x\"",
+    " xmlns:p=\"urn:x\"",
+    " p:a=\"1\"",
+    "p:",
+    ":",
+    "<value>",
+    "</value>",
+    "<value/>",
+    "<value />",
+    "<value/ >",
+    "<string>",
+    "</string>",
+    "<nil/>",
+    "<data>",
+    "</data>",
+    "<i4>1</i4>",
+    "<int> 1 </int>",
+    "x",
+    "\u{e9}",
+    "<1>",
+    "< value>",
+    "</ value>",
+    "</value >",
+    "\u{feff}",
+    "<p:value>",
+];
+
+#[cfg(feature = "testing")]
+fn splice(document: &str, at: &Index, removed: usize, inserted: &str) -> String {
+    let mut at = at.index(document.len() + 1);
+    while !document.is_char_boundary(at) {
+        at -= 1;
+    }
+    let mut end = (at + removed).min(document.len());
+    while !document.is_char_boundary(end) {
+        end += 1;
+    }
+    format!("{}{}{}", &document[..at], inserted, &document[end..])
+}
+
+#[cfg(feature = "testing")]
+fn parse_options(lenient: bool) -> ParseOptions {
+    ParseOptions {
+        lenient,
+        ..ParseOptions::default()
+    }
+}
+
+// Whatever is read when borrowing must be read the same when copying, though borrowing may
+// reject documents that copying takes.
+#[cfg(feature = "testing")]
+proptest! {
+    #[test]
+    fn borrows_calls_only_when_they_can_be_copied(
+        call: Call,
+        at: Index,
+        removed in 0..4usize,
+        inserted in select(SPLICES),
+        lenient: bool,
+    ) {
+        let document = splice(&call.to_xml(), &at, removed, inserted);
+        let options = parse_options(lenient);
+        if let Ok(borrowed) = parse::call_borrowed_with(document.as_bytes(), &options) {
+            let copied = parse::call_from_slice_with(document.as_bytes(), &options);
+            prop_assert!(copied.is_ok(), "{:?} rejects {:?}", copied, document);
+            let copied = copied.unwrap();
+            prop_assert_eq!(copied.name, borrowed.name);
+            let params = borrowed.params.iter().map(BorrowedValue::to_value).collect::<Vec<_>>();
+            prop_assert_eq!(copied.params, params);
+        }
+    }
+
+    #[test]
+    fn borrows_responses_only_when_they_can_be_copied(
+        response: Response,
+        at: Index,
+        removed in 0..4usize,
+        inserted in select(SPLICES),
+        lenient: bool,
+    ) {
+        let document = splice(&response.to_xml(), &at, removed, inserted);
+        let options = parse_options(lenient);
+        if let Ok(borrowed) = parse::response_borrowed_with(document.as_bytes(), &options) {
+            let copied = parse::response_from_slice_with(document.as_bytes(), &options);
+            prop_assert!(copied.is_ok(), "{:?} rejects {:?}", copied, document);
+            let borrowed = borrowed.map(|params| {
+                params.iter().map(BorrowedValue::to_value).collect::<Vec<_>>()
+            });
+            prop_assert_eq!(copied.unwrap(), borrowed);
+        }
+    }
+}
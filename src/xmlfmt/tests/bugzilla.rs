@@ -0,0 +1,109 @@
+use super::super::{Fault, Value};
+use bugzilla::{BugSearch, Bugzilla, NewBug};
+use test_util;
+use Server;
+
+fn token(params: &[Value]) -> Option<&str> {
+    params[0].get("Bugzilla_token").and_then(Value::as_str)
+}
+
+fn fake_bugzilla() -> Server {
+    let mut server = Server::new();
+    server.register_value("User.login", |params| {
+        assert_eq!(
+            xmlrpc_value!({"login": "dev@example.com", "password": "secret"}),
+            params[0]
+        );
+        Ok(vec![xmlrpc_value!({"id": 7, "token": "7-abc"})])
+    });
+    server.register_value("Bug.get", |params| {
+        assert_eq!(Some(&xmlrpc_value!([1234])), params[0].get("ids"));
+        Ok(vec![xmlrpc_value!({
+            "bugs": [{
+                "id": 1234,
+                "summary": "Crash on start",
+                "status": "NEW",
+                "is_open": true,
+                "cc": ["qa@example.com"],
+                "creation_time": (Value::DateTime("20240102T03:04:05Z".into())),
+                "flags": []
+            }],
+            "faults": []
+        })])
+    });
+    server.register_value("Bug.search", |params| {
+        assert_eq!(Some("7-abc"), token(&params));
+        assert_eq!(
+            xmlrpc_value!({
+                "product": ["Widgets"],
+                "status": ["NEW", "ASSIGNED"],
+                "limit": 2,
+                "include_fields": ["id", "summary"],
+                "Bugzilla_token": "7-abc"
+            }),
+            params[0]
+        );
+        Ok(vec![xmlrpc_value!({
+            "bugs": [{"id": 1, "summary": "One"}, {"id": 2, "summary": "Two"}]
+        })])
+    });
+    server.register_value("Bug.create", |params| match token(&params) {
+        Some("7-abc") => Ok(vec![xmlrpc_value!({"id": 1235})]),
+        _ => Err(Fault::new(
+            410,
+            "You must log in before using this part of Bugzilla.",
+        )),
+    });
+    server
+}
+
+#[test]
+fn reads_bugs() {
+    let server = test_util::spawn(fake_bugzilla());
+    let mut bugzilla = Bugzilla::new(server.url().clone()).unwrap();
+    let bugs = bugzilla.get_bugs(&[1234]).unwrap().unwrap();
+    assert_eq!(1, bugs.len());
+    assert_eq!(1234, bugs[0].id);
+    assert_eq!(Some("Crash on start".to_string()), bugs[0].summary);
+    assert_eq!(Some(true), bugs[0].is_open);
+    assert_eq!(Some(vec!["qa@example.com".to_string()]), bugs[0].cc);
+    assert_eq!(
+        Some("20240102T03:04:05Z".to_string()),
+        bugs[0].creation_time
+    );
+    assert_eq!(None, bugs[0].resolution);
+}
+
+#[test]
+fn authenticates_with_login_tokens() {
+    let server = test_util::spawn(fake_bugzilla());
+    let mut bugzilla = Bugzilla::new(server.url().clone()).unwrap();
+    let bug = NewBug {
+        product: "Widgets".into(),
+        component: "Core".into(),
+        summary: "Crash on exit".into(),
+        version: "1.0".into(),
+        ..NewBug::default()
+    };
+    assert_eq!(
+        Err(Fault::new(
+            410,
+            "You must log in before using this part of Bugzilla."
+        )),
+        bugzilla.create(&bug).unwrap()
+    );
+    assert_eq!(Ok(7), bugzilla.login("dev@example.com", "secret").unwrap());
+    assert_eq!(Ok(1235), bugzilla.create(&bug).unwrap());
+    let search = BugSearch {
+        product: Some(vec!["Widgets".into()]),
+        status: Some(vec!["NEW".into(), "ASSIGNED".into()]),
+        limit: Some(2),
+        include_fields: Some(vec!["id".into(), "summary".into()]),
+        ..BugSearch::default()
+    };
+    let bugs = bugzilla.search(&search).unwrap().unwrap();
+    assert_eq!(
+        vec![1, 2],
+        bugs.iter().map(|bug| bug.id).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,42 @@
+use super::super::{from_value, from_value_with, to_value, Base64, DeserializeOptions, Value};
+
+#[test]
+fn writes_and_reads_wrapped_bytes_as_base64() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Helper {
+        blob: Base64,
+        digest: Base64<[u8; 4]>,
+    }
+
+    let helper = Helper {
+        blob: Base64(vec![1, 2, 3]),
+        digest: Base64([4, 5, 6, 7]),
+    };
+    let value = to_value(&helper).unwrap();
+    assert_eq!(
+        value,
+        xmlrpc_value!({
+            "blob": (Value::Base64(vec![1, 2, 3])),
+            "digest": (Value::Base64(vec![4, 5, 6, 7])),
+        })
+    );
+    assert_eq!(helper, from_value(value).unwrap());
+    assert!(from_value::<Base64<[u8; 4]>>(Value::Base64(vec![1, 2, 3])).is_err());
+}
+
+#[test]
+fn reads_plain_byte_sequences_from_base64() {
+    assert_eq!(
+        vec![1u8, 2, 3],
+        from_value::<Vec<u8>>(Value::Base64(vec![1, 2, 3])).unwrap()
+    );
+    assert_eq!(
+        [1u8, 2],
+        from_value::<[u8; 2]>(Value::Base64(vec![1, 2])).unwrap()
+    );
+    let options = DeserializeOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(from_value_with::<Vec<u8>>(Value::Base64(vec![1, 2, 3]), &options).is_err());
+}
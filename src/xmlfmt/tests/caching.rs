@@ -0,0 +1,113 @@
+use super::raw_http::{reply, serve, xml_reply};
+use Client;
+
+fn fresh(etag: &str, value: &str) -> Vec<u8> {
+    let body = format!(
+        "<?xml version=\"1.0\"?><methodResponse><params><param><value>{}</value></param>\
+         </params></methodResponse>",
+        value
+    );
+    reply(
+        "200 OK",
+        &[("Content-Type", "text/xml"), ("ETag", etag)],
+        body.as_bytes(),
+    )
+}
+
+fn not_modified() -> Vec<u8> {
+    reply("304 Not Modified", &[("ETag", "\"v1\"")], b"")
+}
+
+#[test]
+fn answers_not_modified_from_the_cache() {
+    let (url, handle) = serve(vec![
+        fresh("\"v1\"", "<i4>1</i4>"),
+        not_modified(),
+        fresh("\"v2\"", "<i4>2</i4>"),
+        xml_reply("<i4>3</i4>"),
+    ]);
+    let mut client = Client::new().unwrap();
+    client.cache_method("version");
+    assert_eq!(1, client.call::<_, _, i32>(&url, "version", ()).unwrap());
+    assert_eq!(1, client.call::<_, _, i32>(&url, "version", ()).unwrap());
+    assert_eq!(2, client.call::<_, _, i32>(&url, "version", ()).unwrap());
+    // Calls with other params are cached separately.
+    assert_eq!(
+        3,
+        client.call::<_, _, i32>(&url, "version", (true,)).unwrap()
+    );
+    let requests = handle.join().unwrap();
+    assert_eq!(None, requests[0].header("if-none-match"));
+    assert_eq!(Some("\"v1\""), requests[1].header("if-none-match"));
+    assert_eq!(Some("\"v1\""), requests[2].header("if-none-match"));
+    assert_eq!(None, requests[3].header("if-none-match"));
+}
+
+#[test]
+fn only_caches_chosen_methods() {
+    let (url, handle) = serve(vec![
+        fresh("\"v1\"", "<i4>1</i4>"),
+        fresh("\"v1\"", "<i4>1</i4>"),
+    ]);
+    let mut client = Client::new().unwrap();
+    client.cache_method("version");
+    assert_eq!(1, client.call::<_, _, i32>(&url, "counter", ()).unwrap());
+    assert_eq!(1, client.call::<_, _, i32>(&url, "counter", ()).unwrap());
+    let requests = handle.join().unwrap();
+    assert_eq!(None, requests[1].header("if-none-match"));
+}
+
+#[test]
+fn drops_the_least_recently_used_responses() {
+    let (url, handle) = serve(vec![
+        fresh("\"a\"", "<i4>1</i4>"),
+        fresh("\"b\"", "<i4>2</i4>"),
+        not_modified(),
+        fresh("\"c\"", "<i4>3</i4>"),
+        fresh("\"b2\"", "<i4>2</i4>"),
+        fresh("\"a2\"", "<i4>1</i4>"),
+    ]);
+    let mut client = Client::new().unwrap();
+    client.cache_method("get");
+    client.set_cache_capacity(2);
+    let mut get = |id: i32| client.call::<_, _, i32>(&url, "get", (id,)).unwrap();
+    assert_eq!(1, get(1));
+    assert_eq!(2, get(2));
+    // Using 1 again leaves 2 as the least recently used response, dropped for 3.
+    assert_eq!(1, get(1));
+    assert_eq!(3, get(3));
+    assert_eq!(2, get(2));
+    assert_eq!(1, get(1));
+    let requests = handle.join().unwrap();
+    assert_eq!(Some("\"a\""), requests[2].header("if-none-match"));
+    assert_eq!(None, requests[4].header("if-none-match"));
+    // And adding 2 back dropped 1, used less recently than 3.
+    assert_eq!(None, requests[5].header("if-none-match"));
+}
+
+#[test]
+fn follows_cache_control() {
+    let body = "<?xml version=\"1.0\"?><methodResponse><params><param><value><i4>1</i4>\
+                </value></param></params></methodResponse>";
+    let (url, handle) = serve(vec![
+        reply(
+            "200 OK",
+            &[("ETag", "\"v1\""), ("Cache-Control", "no-store")],
+            body.as_bytes(),
+        ),
+        reply(
+            "200 OK",
+            &[("Cache-Control", "public, max-age=3600")],
+            body.as_bytes(),
+        ),
+    ]);
+    let mut client = Client::new().unwrap();
+    client.cache_method("version");
+    for _ in 0..4 {
+        assert_eq!(1, client.call::<_, _, i32>(&url, "version", ()).unwrap());
+    }
+    let requests = handle.join().unwrap();
+    // Only the first two calls reached the server, and the first response wasn't kept.
+    assert_eq!(2, requests.len());
+    assert_eq!(None, requests[1].header("if-none-match"));
+}
@@ -0,0 +1,60 @@
+use super::super::charset::{decode, from_content_type};
+use super::super::{parse, ParseOptions, Value};
+
+#[test]
+fn reads_charset_from_content_type() {
+    assert_eq!(
+        Some("ISO-8859-1"),
+        from_content_type("text/xml; charset=ISO-8859-1")
+    );
+    assert_eq!(
+        Some("utf-8"),
+        from_content_type("text/xml;foo=bar; Charset=\"utf-8\"")
+    );
+    assert_eq!(None, from_content_type("text/xml"));
+}
+
+#[test]
+fn transcodes_declared_charsets() {
+    let mut data = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><string>".to_vec();
+    data.extend_from_slice(&[b'c', 0xE9, b' ', 0x80]);
+    data.extend_from_slice(b"</string>");
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><string>c\u{E9} \u{80}</string>",
+        decode(data.clone(), None).unwrap()
+    );
+    assert_eq!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><string>c\u{E9} \u{20AC}</string>",
+        decode(data.clone(), Some("windows-1252")).unwrap()
+    );
+    assert_eq!(
+        Value::String("c\u{E9} \u{80}".into()),
+        parse::xml(&data[..]).unwrap()
+    );
+    let options = ParseOptions {
+        charset: Some("cp1252".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        Value::String("c\u{E9} \u{20AC}".into()),
+        parse::xml_with(&data[..], &options).unwrap()
+    );
+}
+
+#[test]
+fn transcodes_undeclared_charsets_from_transport() {
+    let data = b"<?xml version=\"1.0\"?><string>\xE9</string>".to_vec();
+    assert!(decode(data.clone(), None).is_err());
+    assert_eq!(
+        "<?xml version=\"1.0\"?><string>\u{E9}</string>",
+        decode(data, Some("latin1")).unwrap()
+    );
+}
+
+#[test]
+fn rejects_unknown_charsets() {
+    let data = b"<?xml version='1.0' encoding='KOI8-R'?><string>a</string>".to_vec();
+    assert!(decode(data, None).is_err());
+    let data = "\u{FEFF}<?xml version=\"1.0\" encoding=\"utf-8\"?><string>\u{E9}</string>";
+    assert_eq!(data, decode(data.as_bytes().to_vec(), None).unwrap());
+}
@@ -0,0 +1,104 @@
+use super::super::{from_value, to_value, Value};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::convert::TryFrom;
+
+fn naive() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1998, 7, 17)
+        .unwrap()
+        .and_hms_opt(14, 8, 55)
+        .unwrap()
+}
+
+fn offset(hours: i32, minutes: i32) -> FixedOffset {
+    FixedOffset::east_opt(hours * 3600 + minutes * 60).unwrap()
+}
+
+#[test]
+fn converts_datetimes_into_values() {
+    assert_eq!(
+        Value::from(naive()),
+        Value::DateTime("19980717T14:08:55".into())
+    );
+    assert_eq!(
+        Value::from(Utc.from_utc_datetime(&naive())),
+        Value::DateTime("19980717T14:08:55Z".into())
+    );
+    assert_eq!(
+        Value::from(offset(-5, -30).from_local_datetime(&naive()).unwrap()),
+        Value::DateTime("19980717T14:08:55-05:30".into())
+    );
+}
+
+#[test]
+fn converts_values_into_datetimes() {
+    let value = Value::DateTime("1998-07-17T14:08:55+02:00".into());
+    assert_eq!(
+        DateTime::<FixedOffset>::try_from(value.clone()).unwrap(),
+        offset(2, 0).from_local_datetime(&naive()).unwrap()
+    );
+    assert_eq!(
+        DateTime::<Utc>::try_from(value.clone()).unwrap(),
+        Utc.from_utc_datetime(&naive()) - chrono::Duration::hours(2)
+    );
+    assert_eq!(NaiveDateTime::try_from(value).unwrap(), naive());
+    assert_eq!(
+        DateTime::<Utc>::try_from(Value::DateTime("19980717T14:08:55".into())).unwrap(),
+        Utc.from_utc_datetime(&naive())
+    );
+    assert!(NaiveDateTime::try_from(Value::String("19980717T14:08:55".into())).is_err());
+    assert!(NaiveDateTime::try_from(Value::DateTime("yesterday".into())).is_err());
+}
+
+#[test]
+fn serializes_fields_as_datetimes() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Helper {
+        #[serde(with = "::datetime::chrono::fixed_offset")]
+        at: DateTime<FixedOffset>,
+        #[serde(with = "::datetime::chrono::utc")]
+        utc: DateTime<Utc>,
+        #[serde(with = "::datetime::chrono::naive")]
+        local: NaiveDateTime,
+    }
+
+    let helper = Helper {
+        at: offset(2, 0).from_local_datetime(&naive()).unwrap(),
+        utc: Utc.from_utc_datetime(&naive()),
+        local: naive(),
+    };
+    let value = to_value(&helper).unwrap();
+    assert_eq!(
+        value,
+        xmlrpc_value!({
+            "at": (Value::DateTime("19980717T14:08:55+02:00".into())),
+            "utc": (Value::DateTime("19980717T14:08:55Z".into())),
+            "local": (Value::DateTime("19980717T14:08:55".into())),
+        })
+    );
+    assert_eq!(helper, from_value::<Helper>(value).unwrap());
+}
+
+xmlrpc_datetime_string!(mod plain, "%Y-%m-%d %H:%M:%S");
+
+#[test]
+fn serializes_fields_as_formatted_strings() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Helper {
+        #[serde(with = "plain")]
+        utc: DateTime<Utc>,
+        #[serde(with = "plain")]
+        local: NaiveDateTime,
+    }
+
+    let helper = Helper {
+        utc: Utc.from_utc_datetime(&naive()),
+        local: naive(),
+    };
+    let value = to_value(&helper).unwrap();
+    assert_eq!(
+        value,
+        xmlrpc_value!({"utc": "1998-07-17 14:08:55", "local": "1998-07-17 14:08:55"})
+    );
+    assert_eq!(helper, from_value::<Helper>(value).unwrap());
+    assert!(from_value::<Helper>(xmlrpc_value!({"utc": "yesterday", "local": "today"})).is_err());
+}
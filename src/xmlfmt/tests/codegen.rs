@@ -0,0 +1,247 @@
+use super::super::{Signature, Value, ValueType};
+use codegen::{
+    function_name, generate, generate_service, introspect, Method, Service, ServiceMethod,
+    ServiceParam,
+};
+use test_util;
+use {Client, Fault, Server};
+
+// What the tests below expect to be generated, compiled to check that it's valid Rust.
+#[allow(dead_code)]
+mod typed {
+    include!("codegen/typed.rs");
+}
+
+#[allow(dead_code)]
+mod untyped {
+    include!("codegen/untyped.rs");
+}
+
+mod calculator {
+    include!("codegen/calculator.rs");
+}
+
+#[allow(dead_code)]
+mod pinger {
+    include!("codegen/pinger.rs");
+}
+
+#[test]
+fn names_functions_after_methods() {
+    assert_eq!("system_list_methods", function_name("system.listMethods"));
+    assert_eq!("wp_get_posts", function_name("wp.getPosts"));
+    assert_eq!("get_url", function_name("getURL"));
+    assert_eq!("type_", function_name("type"));
+    assert_eq!("method_2fa", function_name("2fa"));
+    assert_eq!("a_b", function_name("a..b/"));
+}
+
+#[test]
+fn generates_typed_functions_for_single_signatures() {
+    let methods = vec![Method {
+        name: "wp.uploadFile".into(),
+        signatures: vec![vec![
+            "struct".into(),
+            "i4".into(),
+            "string".into(),
+            "base64".into(),
+        ]],
+        help: "Uploads a file.".into(),
+    }];
+    assert_eq!(include_str!("codegen/typed.rs"), generate(&methods));
+}
+
+#[test]
+fn generates_untyped_functions_otherwise() {
+    let methods = vec![
+        Method {
+            name: "add".into(),
+            signatures: vec![
+                vec!["int".into(), "int".into(), "int".into()],
+                vec!["double".into(), "double".into(), "double".into()],
+            ],
+            help: String::new(),
+        },
+        Method {
+            name: "add_".into(),
+            ..Method::default()
+        },
+    ];
+    assert_eq!(include_str!("codegen/untyped.rs"), generate(&methods));
+}
+
+#[test]
+fn introspects_servers() {
+    let mut server = Server::new();
+    server.register_value("system.listMethods", |_| {
+        Ok(vec![xmlrpc_value!(["echo", "system.methodHelp"])])
+    });
+    server.register_value("system.methodHelp", |params| {
+        Ok(vec![match params.first() {
+            Some(Value::String(name)) if name == "echo" => "Returns its param.".into(),
+            _ => "".into(),
+        }])
+    });
+    server.register_value("echo", Ok);
+    server.add_signature(
+        "echo",
+        Signature::new(vec![ValueType::String], ValueType::String),
+    );
+    let server = test_util::spawn(server);
+
+    let methods = introspect(&mut Client::new().unwrap(), server.url()).unwrap();
+    assert_eq!(
+        vec![
+            Method {
+                name: "echo".into(),
+                signatures: vec![vec!["string".into(), "string".into()]],
+                help: "Returns its param.".into(),
+            },
+            Method {
+                name: "system.methodHelp".into(),
+                ..Method::default()
+            },
+        ],
+        methods
+    );
+}
+
+#[test]
+fn generates_both_ends_of_services() {
+    let service = Service {
+        name: "Calculator".into(),
+        doc: String::new(),
+        methods: vec![
+            ServiceMethod {
+                name: "math.add".into(),
+                function: None,
+                params: vec![
+                    ServiceParam {
+                        name: "a".into(),
+                        ty: "i32".into(),
+                    },
+                    ServiceParam {
+                        name: "b".into(),
+                        ty: "i32".into(),
+                    },
+                ],
+                result: "i32".into(),
+                doc: "Adds two numbers.".into(),
+            },
+            ServiceMethod {
+                name: "math.convert".into(),
+                function: None,
+                params: vec![
+                    ServiceParam {
+                        name: "value".into(),
+                        ty: "f64".into(),
+                    },
+                    ServiceParam {
+                        name: "type".into(),
+                        ty: "String".into(),
+                    },
+                ],
+                result: "String".into(),
+                doc: String::new(),
+            },
+            ServiceMethod {
+                name: "ping".into(),
+                function: Some("ping_server".into()),
+                params: vec![],
+                result: "()".into(),
+                doc: String::new(),
+            },
+        ],
+    };
+    assert_eq!(
+        include_str!("codegen/calculator.rs"),
+        generate_service(&service)
+    );
+
+    let service = Service {
+        name: "Pinger".into(),
+        doc: "Answers pings.".into(),
+        methods: service.methods[2..].to_vec(),
+    };
+    assert_eq!(
+        include_str!("codegen/pinger.rs"),
+        generate_service(&service)
+    );
+}
+
+struct Arithmetic;
+
+impl calculator::Calculator for Arithmetic {
+    fn math_add(&self, a: i32, b: i32) -> Result<i32, Fault> {
+        Ok(a + b)
+    }
+
+    fn math_convert(&self, value: f64, r#type: String) -> Result<String, Fault> {
+        Ok(format!("{} as {}", value, r#type))
+    }
+
+    fn ping_server(&self) -> Result<(), Fault> {
+        Ok(())
+    }
+}
+
+#[test]
+fn serves_and_calls_generated_services() {
+    let mut server = Server::new();
+    calculator::register(&mut server, Arithmetic);
+    let server = test_util::spawn(server);
+
+    let client = calculator::CalculatorClient::new(server.url().clone()).unwrap();
+    assert_eq!(5, client.math_add(2, 3).unwrap());
+    assert_eq!(
+        "2.5 as i32",
+        client.math_convert(2.5, "i32".into()).unwrap()
+    );
+    client.ping_server().unwrap();
+}
+
+#[cfg(feature = "idl")]
+#[test]
+fn reads_services_from_toml() {
+    let service = Service::from_toml(
+        r#"
+name = "Calculator"
+
+[[methods]]
+name = "math.add"
+params = [{ name = "a", type = "i32" }]
+result = "i32"
+
+[[methods]]
+name = "ping"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        Service {
+            name: "Calculator".into(),
+            doc: String::new(),
+            methods: vec![
+                ServiceMethod {
+                    name: "math.add".into(),
+                    function: None,
+                    params: vec![ServiceParam {
+                        name: "a".into(),
+                        ty: "i32".into(),
+                    }],
+                    result: "i32".into(),
+                    doc: String::new(),
+                },
+                ServiceMethod {
+                    name: "ping".into(),
+                    function: None,
+                    params: vec![],
+                    result: "()".into(),
+                    doc: String::new(),
+                },
+            ],
+        },
+        service
+    );
+    assert!(Service::from_toml("name = \"A\"\nversion = 2").is_err());
+}
@@ -0,0 +1,53 @@
+pub trait Calculator {
+    /// Adds two numbers.
+    fn math_add(&self, a: i32, b: i32) -> Result<i32, xml_rpc::Fault>;
+    fn math_convert(&self, value: f64, r#type: String) -> Result<String, xml_rpc::Fault>;
+    fn ping_server(&self) -> Result<(), xml_rpc::Fault>;
+}
+
+pub fn register<T: Calculator + Send + Sync + 'static>(
+    server: &mut xml_rpc::Server,
+    service: T,
+) {
+    let service = std::sync::Arc::new(service);
+    {
+        let service = std::sync::Arc::clone(&service);
+        server.register_simple("math.add", move |(a, b,): (i32, i32,)| {
+            service.math_add(a, b)
+        });
+    }
+    {
+        let service = std::sync::Arc::clone(&service);
+        server.register_simple("math.convert", move |(value, r#type,): (f64, String,)| {
+            service.math_convert(value, r#type)
+        });
+    }
+    server.register_simple("ping", move |(): ()| {
+        service.ping_server()
+    });
+}
+
+pub struct CalculatorClient {
+    endpoint: xml_rpc::client::Endpoint,
+}
+
+impl CalculatorClient {
+    pub fn new(uri: xml_rpc::Url) -> xml_rpc::error::Result<CalculatorClient> {
+        Ok(CalculatorClient {
+            endpoint: xml_rpc::client::Endpoint::new(uri)?,
+        })
+    }
+
+    /// Adds two numbers.
+    pub fn math_add(&self, a: i32, b: i32) -> Result<i32, xml_rpc::client::CallError> {
+        self.endpoint.call("math.add", (a, b,))
+    }
+
+    pub fn math_convert(&self, value: f64, r#type: String) -> Result<String, xml_rpc::client::CallError> {
+        self.endpoint.call("math.convert", (value, r#type,))
+    }
+
+    pub fn ping_server(&self) -> Result<(), xml_rpc::client::CallError> {
+        self.endpoint.call("ping", ())
+    }
+}
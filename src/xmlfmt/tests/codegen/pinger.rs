@@ -0,0 +1,29 @@
+/// Answers pings.
+pub trait Pinger {
+    fn ping_server(&self) -> Result<(), xml_rpc::Fault>;
+}
+
+pub fn register<T: Pinger + Send + Sync + 'static>(
+    server: &mut xml_rpc::Server,
+    service: T,
+) {
+    server.register_simple("ping", move |(): ()| {
+        service.ping_server()
+    });
+}
+
+pub struct PingerClient {
+    endpoint: xml_rpc::client::Endpoint,
+}
+
+impl PingerClient {
+    pub fn new(uri: xml_rpc::Url) -> xml_rpc::error::Result<PingerClient> {
+        Ok(PingerClient {
+            endpoint: xml_rpc::client::Endpoint::new(uri)?,
+        })
+    }
+
+    pub fn ping_server(&self) -> Result<(), xml_rpc::client::CallError> {
+        self.endpoint.call("ping", ())
+    }
+}
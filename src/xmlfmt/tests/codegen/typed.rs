@@ -0,0 +1,10 @@
+/// Uploads a file.
+pub fn wp_upload_file(
+    client: &mut xml_rpc::Client,
+    uri: &xml_rpc::Url,
+    arg0: i32,
+    arg1: &str,
+    arg2: &[u8],
+) -> Result<std::collections::HashMap<String, xml_rpc::Value>, xml_rpc::client::CallError> {
+    client.call(uri, "wp.uploadFile", (arg0, arg1, xml_rpc::Base64(arg2),))
+}
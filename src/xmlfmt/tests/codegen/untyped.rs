@@ -0,0 +1,19 @@
+/// Signatures:
+///
+/// * `int(int, int)`
+/// * `double(double, double)`
+pub fn add(
+    client: &mut xml_rpc::Client,
+    uri: &xml_rpc::Url,
+    params: xml_rpc::Params,
+) -> xml_rpc::error::Result<xml_rpc::Response> {
+    client.call_value(uri, "add", params)
+}
+
+pub fn add_(
+    client: &mut xml_rpc::Client,
+    uri: &xml_rpc::Url,
+    params: xml_rpc::Params,
+) -> xml_rpc::error::Result<xml_rpc::Response> {
+    client.call_value(uri, "add_", params)
+}
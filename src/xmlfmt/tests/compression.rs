@@ -0,0 +1,110 @@
+use super::raw_http::{reply, serve, xml_reply};
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use std::io::Write;
+use test_util;
+use {Client, Server, Value};
+
+const RESPONSE: &str = "<?xml version=\"1.0\"?><methodResponse><params><param>\
+                        <value><string>compressed</string></value></param></params>\
+                        </methodResponse>";
+
+/// Makes a call answered with `body` sent as `encoding`, returning the request's
+/// `Accept-Encoding` header.
+fn call(client: &mut Client, encoding: &str, body: Vec<u8>) -> Option<String> {
+    let mut headers = vec![("Content-Type", "text/xml")];
+    if !encoding.is_empty() {
+        headers.push(("Content-Encoding", encoding));
+    }
+    let (url, handle) = serve(vec![reply("200 OK", &headers, &body)]);
+    let result: String = client.call(&url, "greet", ()).unwrap();
+    assert_eq!("compressed".to_string(), result);
+    let requests = handle.join().unwrap();
+    requests[0].header("accept-encoding").map(Into::into)
+}
+
+fn gzip() -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(RESPONSE.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn asks_for_compression() {
+    assert_eq!(
+        Some("gzip, deflate".to_string()),
+        call(&mut Client::new().unwrap(), "gzip", gzip())
+    );
+}
+
+#[test]
+fn decompresses_deflate() {
+    let mut client = Client::new().unwrap();
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(RESPONSE.as_bytes()).unwrap();
+    call(&mut client, "deflate", encoder.finish().unwrap());
+    // Some servers leave out the zlib wrapper.
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(RESPONSE.as_bytes()).unwrap();
+    call(&mut client, "deflate", encoder.finish().unwrap());
+    call(&mut client, "x-gzip", gzip());
+}
+
+#[test]
+fn compression_can_be_disabled() {
+    let mut client = Client::new().unwrap();
+    client.set_compression(false);
+    assert_eq!(None, call(&mut client, "", RESPONSE.as_bytes().to_vec()));
+}
+
+#[test]
+fn rejects_unknown_encodings() {
+    let (url, handle) = serve(vec![reply(
+        "200 OK",
+        &[("Content-Encoding", "br")],
+        RESPONSE.as_bytes(),
+    )]);
+    let result = Client::new()
+        .unwrap()
+        .call::<_, _, String>(&url, "greet", ());
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Unsupported Content-Encoding: br"));
+    handle.join().unwrap();
+}
+
+#[test]
+fn servers_take_compressed_calls() {
+    let mut server = Server::new();
+    server.register_value("echo", Ok);
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+    client.set_request_compression(true);
+    let response = client
+        .call_value(server.url(), "echo", vec![Value::from("compressed")])
+        .unwrap();
+    assert_eq!(Ok(vec![Value::from("compressed")]), response);
+}
+
+#[test]
+fn falls_back_to_uncompressed_calls() {
+    let (url, handle) = serve(vec![
+        reply("415 Unsupported Media Type", &[], b""),
+        xml_reply("<string>plain</string>"),
+        xml_reply("<string>plain</string>"),
+    ]);
+    let mut client = Client::new().unwrap();
+    client.set_request_compression(true);
+    for _ in 0..2 {
+        let result: String = client.call(&url, "greet", ()).unwrap();
+        assert_eq!("plain", result);
+    }
+    let requests = handle.join().unwrap();
+    assert_eq!(Some("gzip"), requests[0].header("content-encoding"));
+    // The host is remembered, so only the first call is sent twice.
+    for request in &requests[1..] {
+        assert_eq!(None, request.header("content-encoding"));
+        assert!(request.body.starts_with(b"<?xml"));
+    }
+}
@@ -0,0 +1,162 @@
+use super::raw_http::{reply, serve, xml_reply};
+use hyper;
+use std::io::Read;
+use test_util;
+use {Client, Server};
+
+#[test]
+fn sends_the_chosen_content_type_with_calls() {
+    let (url, handle) = serve(vec![xml_reply("<i4>1</i4>"), xml_reply("<i4>2</i4>")]);
+    let mut client = Client::new().unwrap();
+    assert_eq!(1, client.call::<_, _, i32>(&url, "version", ()).unwrap());
+    client.set_content_type("application/xml; charset=utf-8");
+    assert_eq!(2, client.call::<_, _, i32>(&url, "version", ()).unwrap());
+    let requests = handle.join().unwrap();
+    assert_eq!(Some("text/xml"), requests[0].header("content-type"));
+    assert_eq!(
+        Some("application/xml; charset=utf-8"),
+        requests[1].header("content-type")
+    );
+}
+
+#[test]
+fn takes_responses_of_either_content_type() {
+    let body = "<?xml version=\"1.0\"?><methodResponse><params><param><value>caf\u{e9}</value>\
+                </param></params></methodResponse>";
+    let (url, handle) = serve(vec![
+        reply(
+            "200 OK",
+            &[("Content-Type", "application/xml")],
+            body.as_bytes(),
+        ),
+        reply(
+            "200 OK",
+            &[("Content-Type", "text/xml; charset=ISO-8859-1")],
+            b"<?xml version=\"1.0\"?><methodResponse><params><param><value>caf\xe9</value>\
+              </param></params></methodResponse>",
+        ),
+    ]);
+    let mut client = Client::new().unwrap();
+    for _ in 0..2 {
+        assert_eq!(
+            "caf\u{e9}".to_string(),
+            client.call::<_, _, String>(&url, "name", ()).unwrap()
+        );
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn sends_the_chosen_content_type_with_responses() {
+    let mut server = Server::new();
+    server.register_simple("ping", |()| Ok(true));
+    server.set_content_type("application/xml; charset=utf-8");
+    let server = test_util::spawn(server);
+    let call = "<?xml version=\"1.0\"?><methodCall><methodName>ping</methodName></methodCall>";
+    let mut response = hyper::Client::new()
+        .post(server.url().as_ref())
+        .body(call)
+        .send()
+        .unwrap();
+    assert_eq!(
+        Some(&b"application/xml; charset=utf-8"[..]),
+        response
+            .headers
+            .get_raw("Content-Type")
+            .map(|values| &values[0][..])
+    );
+    let mut body = String::new();
+    response.read_to_string(&mut body).unwrap();
+    assert!(body.contains("<boolean>1</boolean>"));
+}
+
+#[test]
+fn rejects_other_content_types_when_strict() {
+    let mut server = Server::new();
+    server.register_simple("ping", |()| Ok(true));
+    server.set_strict_content_type(true);
+    let server = test_util::spawn(server);
+    let call = "<?xml version=\"1.0\"?><methodCall><methodName>ping</methodName></methodCall>";
+    let status = |content_type: Option<&str>| {
+        let mut headers = hyper::header::Headers::new();
+        if let Some(content_type) = content_type {
+            headers.set_raw("Content-Type", vec![content_type.as_bytes().to_vec()]);
+        }
+        hyper::Client::new()
+            .post(server.url().as_ref())
+            .headers(headers)
+            .body(call)
+            .send()
+            .unwrap()
+            .status
+            .to_u16()
+    };
+    assert_eq!(415, status(None));
+    assert_eq!(415, status(Some("text/plain")));
+    assert_eq!(415, status(Some("application/x-www-form-urlencoded")));
+    assert_eq!(200, status(Some("text/xml")));
+    assert_eq!(200, status(Some("Application/XML; charset=utf-8")));
+    assert_eq!(200, status(Some("application/soap+xml")));
+    let mut client = Client::new().unwrap();
+    assert!(client.call::<_, _, bool>(server.url(), "ping", ()).unwrap());
+}
+
+#[test]
+fn sends_the_headers_strict_clients_expect() {
+    let mut server = Server::new();
+    server.register_simple("ping", |()| Ok(true));
+    server.set_server_name("pinger/1.0");
+    let server = test_util::spawn(server);
+    let call = "<?xml version=\"1.0\"?><methodCall><methodName>ping</methodName></methodCall>";
+    let mut response = hyper::Client::new()
+        .post(server.url().as_ref())
+        .body(call)
+        .send()
+        .unwrap();
+    let header = |name: &str| {
+        response
+            .headers
+            .get_raw(name)
+            .map(|values| String::from_utf8(values[0].clone()).unwrap())
+    };
+    assert_eq!(
+        Some("text/xml; charset=utf-8".to_string()),
+        header("Content-Type")
+    );
+    assert_eq!(Some("pinger/1.0".to_string()), header("Server"));
+    assert!(header("Date").is_some());
+    let length: usize = header("Content-Length").unwrap().parse().unwrap();
+    let mut body = Vec::new();
+    response.read_to_end(&mut body).unwrap();
+    assert_eq!(length, body.len());
+}
+
+#[test]
+fn sends_the_length_of_large_responses() {
+    let mut server = Server::new();
+    server.register_simple("blob", |()| Ok("x".repeat(100_000)));
+    server.set_date_header(|| "Sun, 06 Nov 1994 08:49:37 GMT".into());
+    let server = test_util::spawn(server);
+    let call = "<?xml version=\"1.0\"?><methodCall><methodName>blob</methodName></methodCall>";
+    let mut response = hyper::Client::new()
+        .post(server.url().as_ref())
+        .body(call)
+        .send()
+        .unwrap();
+    let header = |name: &str| {
+        response
+            .headers
+            .get_raw(name)
+            .map(|values| String::from_utf8(values[0].clone()).unwrap())
+    };
+    assert_eq!(None, header("Transfer-Encoding"));
+    assert_eq!(
+        Some("Sun, 06 Nov 1994 08:49:37 GMT".to_string()),
+        header("Date")
+    );
+    let length: usize = header("Content-Length").unwrap().parse().unwrap();
+    let mut body = Vec::new();
+    response.read_to_end(&mut body).unwrap();
+    assert_eq!(length, body.len());
+    assert!(length > 100_000);
+}
@@ -0,0 +1,198 @@
+use super::super::datetime::{self, DateTime};
+use super::super::value::ToXml;
+use super::super::*;
+
+fn naive() -> DateTime {
+    DateTime {
+        year: 1998,
+        month: 7,
+        day: 17,
+        hour: 14,
+        minute: 8,
+        second: 55,
+        nanosecond: 0,
+        offset: None,
+    }
+}
+
+#[test]
+fn parses_spec_datetimes() {
+    assert_eq!(DateTime::parse("19980717T14:08:55").unwrap(), naive());
+    assert_eq!(DateTime::parse(" 19980717T140855\n").unwrap(), naive());
+    assert_eq!(DateTime::parse("1998-07-17T14:08:55").unwrap(), naive());
+}
+
+#[test]
+fn parses_fractions_and_timezones() {
+    assert_eq!(
+        DateTime::parse("19980717T14:08:55.25Z").unwrap(),
+        DateTime {
+            nanosecond: 250_000_000,
+            offset: Some(0),
+            ..naive()
+        }
+    );
+    assert_eq!(
+        DateTime::parse("1998-07-17T14:08:55-05:30").unwrap(),
+        DateTime {
+            offset: Some(-19_800),
+            ..naive()
+        }
+    );
+    assert_eq!(
+        DateTime::parse("19980717T14:08:55+0100").unwrap(),
+        DateTime {
+            offset: Some(3_600),
+            ..naive()
+        }
+    );
+    assert_eq!(
+        DateTime::parse("19980717T14:08:55+01").unwrap(),
+        DateTime {
+            offset: Some(3_600),
+            ..naive()
+        }
+    );
+}
+
+#[test]
+fn parses_comma_separated_fractions() {
+    assert_eq!(
+        DateTime::parse("1998-07-17T14:08:55,1234567Z").unwrap(),
+        DateTime {
+            nanosecond: 123_456_700,
+            offset: Some(0),
+            ..naive()
+        }
+    );
+    assert!(DateTime::parse("19980717T14:08:55,").is_err());
+}
+
+#[test]
+fn writes_datetimes_without_fractions() {
+    let options = WriteOptions {
+        declaration: None,
+        datetime_fractions: false,
+        ..WriteOptions::default()
+    };
+    let write = |v: &str| Value::DateTime(v.into()).to_xml_with(&options);
+    assert_eq!(
+        write("1998-07-17T14:08:55.25+01:00"),
+        "<value><dateTime.iso8601>1998-07-17T14:08:55+01:00</dateTime.iso8601></value>"
+    );
+    assert_eq!(
+        write("19980717T14:08:55,5"),
+        "<value><dateTime.iso8601>19980717T14:08:55</dateTime.iso8601></value>"
+    );
+    assert_eq!(
+        write("1998-07-17T14:08:55"),
+        "<value><dateTime.iso8601>1998-07-17T14:08:55</dateTime.iso8601></value>"
+    );
+}
+
+#[test]
+fn writes_datetimes_in_a_naive_zone() {
+    let options = WriteOptions {
+        declaration: None,
+        ..Profile::dotnet(-5 * 3600).write
+    };
+    let write = |v: &str| Value::DateTime(v.into()).to_xml_with(&options);
+    assert_eq!(
+        write("20000301T02:30:00.5Z"),
+        "<value><dateTime.iso8601>20000229T21:30:00</dateTime.iso8601></value>"
+    );
+    assert_eq!(
+        write("1998-12-31T23:00:00-06:00"),
+        "<value><dateTime.iso8601>1999-01-01T00:00:00</dateTime.iso8601></value>"
+    );
+    assert_eq!(
+        write("19980717T14:08:55"),
+        "<value><dateTime.iso8601>19980717T14:08:55</dateTime.iso8601></value>"
+    );
+
+    let read = DateTime::parse("19980717T14:08:55")
+        .unwrap()
+        .apply_policy(Profile::dotnet(3600).deserialize.naive_datetimes);
+    assert_eq!(read.offset, Some(3600));
+    assert_eq!(read.to_offset(0).hour, 13);
+}
+
+#[test]
+fn rejects_invalid_datetimes() {
+    assert!(DateTime::parse("33").is_err());
+    assert!(DateTime::parse("19980717").is_err());
+    assert!(DateTime::parse("1998-0717T14:08:55").is_err());
+    assert!(DateTime::parse("19980231T14:08:55").is_err());
+    assert!(DateTime::parse("19980717T24:08:55").is_err());
+    assert!(DateTime::parse("19980717T14:08:55+25:00").is_err());
+    assert!(DateTime::parse("19980717T14:08:55 and more").is_err());
+}
+
+#[test]
+fn formats_datetimes() {
+    let value = DateTime {
+        nanosecond: 120_000_000,
+        offset: Some(-19_800),
+        ..naive()
+    };
+    assert_eq!(value.to_string(), "19980717T14:08:55.12-05:30");
+    assert_eq!(
+        value.format(DateTimeFormat::Extended),
+        "1998-07-17T14:08:55.12-05:30"
+    );
+    assert_eq!(naive().to_string(), "19980717T14:08:55");
+}
+
+#[test]
+fn reads_compact_datetime_from_xml() {
+    let data = r#"<?xml version="1.0"?><dateTime.iso8601>19980717T14:08:55</dateTime.iso8601>"#;
+    let data = parse::xml(data.as_bytes()).unwrap();
+    assert_eq!(data, Value::DateTime("19980717T14:08:55".into()));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Helper {
+    #[serde(
+        serialize_with = "datetime::serialize_str",
+        deserialize_with = "datetime::deserialize_string"
+    )]
+    at: String,
+}
+
+#[test]
+fn applies_datetime_options() {
+    let params = vec![Value::Struct(
+        vec![(
+            "at".to_owned(),
+            Value::DateTime("1998-07-17T14:08:55".into()),
+        )]
+        .into_iter()
+        .collect(),
+    )];
+    assert_eq!(
+        from_params::<(Helper,)>(params.clone()).unwrap().0.at,
+        "19980717T14:08:55Z"
+    );
+    let options = DeserializeOptions {
+        naive_datetimes: NaiveDateTimePolicy::AssumeLocal,
+        ..Default::default()
+    };
+    let (helper,): (Helper,) = from_params_with(params, &options).unwrap();
+    assert_eq!(helper.at, "19980717T14:08:55");
+
+    let options = SerializeOptions {
+        datetime_format: DateTimeFormat::Extended,
+        ..Default::default()
+    };
+    assert_eq!(
+        into_params_with(&(helper,), &options).unwrap(),
+        vec![Value::Struct(
+            vec![(
+                "at".to_owned(),
+                Value::DateTime("1998-07-17T14:08:55".into()),
+            )]
+            .into_iter()
+            .collect(),
+        )]
+    );
+}
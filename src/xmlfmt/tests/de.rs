@@ -1,5 +1,7 @@
-use super::super::Value;
-use serde::Deserialize;
+use super::super::{
+    from_value_with, DeserializeOptions, EnumRepresentation, OptionRepresentation, Value,
+};
+use serde::{Deserialize, Serialize};
 use serde_bytes;
 use std::collections::HashMap;
 
@@ -9,6 +11,16 @@ fn reads_bool() {
     assert!(!bool::deserialize(Value::Bool(false)).unwrap());
 }
 
+#[test]
+fn reads_bool_from_int_and_numeric_strings() {
+    assert!(bool::deserialize(Value::Int(1)).unwrap());
+    assert!(!bool::deserialize(Value::Int(0)).unwrap());
+    assert!(bool::deserialize(Value::Int(2)).is_err());
+    assert!(bool::deserialize(Value::String("1".into())).unwrap());
+    assert!(!bool::deserialize(Value::String("0".into())).unwrap());
+    assert!(bool::deserialize(Value::String("2".into())).is_err());
+}
+
 #[test]
 fn reads_integers_as_ints_or_strings_if_too_big() {
     assert_eq!(200u8, u8::deserialize(Value::Int(200)).unwrap());
@@ -334,3 +346,373 @@ fn reads_variant_as_one_member_struct() {
         Helper::deserialize(Value::Struct(members)).unwrap()
     );
 }
+
+#[test]
+fn reads_options_as_bare_values() {
+    let options = DeserializeOptions {
+        options: OptionRepresentation::Omit,
+        ..Default::default()
+    };
+    assert_eq!(
+        Some(vec![33]),
+        from_value_with::<Option<Vec<i32>>>(Value::Array(vec![Value::Int(33)]), &options).unwrap()
+    );
+    assert_eq!(
+        Some(vec![33]),
+        Option::<Vec<i32>>::deserialize(Value::Array(vec![Value::Array(vec![Value::Int(33)])]))
+            .unwrap()
+    );
+}
+
+#[test]
+fn reads_without_coercions_in_strict_mode() {
+    let options = DeserializeOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(from_value_with::<i32>(Value::String("33".into()), &options).is_err());
+    assert!(from_value_with::<bool>(Value::String("true".into()), &options).is_err());
+    assert!(from_value_with::<bool>(Value::Int(1), &options).is_err());
+    assert!(from_value_with::<f64>(Value::Int(33), &options).is_err());
+    assert!(from_value_with::<Option<i32>>(Value::Int(33), &options).is_err());
+    assert_eq!(
+        33,
+        from_value_with::<i32>(Value::Int(33), &options).unwrap()
+    );
+}
+
+#[test]
+fn reads_values_unchanged() {
+    let value = xmlrpc_value!({
+        "int": 33,
+        "list": [true, "foo", 2.5, [], {}],
+        "blob": (Value::Base64(vec![1, 2, 3])),
+        "when": (Value::DateTime("33".into())),
+    });
+    assert_eq!(value, Value::deserialize(value.clone()).unwrap());
+    assert_eq!(
+        value,
+        from_value_with::<Value>(value.clone(), &DeserializeOptions::default()).unwrap()
+    );
+}
+
+#[test]
+fn reads_value_fields_as_catch_all() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Helper {
+        name: String,
+        extra: Value,
+    }
+
+    let value = xmlrpc_value!({
+        "name": "foo",
+        "extra": {"when": (Value::DateTime("19980717T14:08:55".into())), "list": [1]},
+    });
+    assert_eq!(
+        Helper::deserialize(value).unwrap(),
+        Helper {
+            name: "foo".into(),
+            extra: xmlrpc_value!({
+                "when": (Value::DateTime("19980717T14:08:55".into())),
+                "list": [1],
+            }),
+        }
+    );
+}
+
+#[test]
+fn reads_extension_types() {
+    assert_eq!(
+        5_000_000_000i64,
+        i64::deserialize(Value::I8(5_000_000_000)).unwrap()
+    );
+    assert!(i32::deserialize(Value::I8(5_000_000_000)).is_err());
+    assert_eq!(None, Option::<i32>::deserialize(Value::Nil).unwrap());
+    assert_eq!((), <()>::deserialize(Value::Nil).unwrap());
+    let value = xmlrpc_value!({"big": (Value::I8(5_000_000_000)), "none": (Value::Nil)});
+    assert_eq!(value, Value::deserialize(value.clone()).unwrap());
+}
+
+#[test]
+fn reads_enums_in_configured_representation() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Helper {
+        Stop,
+        Move { x: i32 },
+    }
+
+    let options = DeserializeOptions {
+        enums: EnumRepresentation::UnitAsString,
+        ..Default::default()
+    };
+    assert_eq!(
+        Helper::Stop,
+        from_value_with(xmlrpc_value!("Stop"), &options).unwrap()
+    );
+    assert_eq!(
+        Helper::Move { x: 3 },
+        from_value_with(xmlrpc_value!({"Move": {"x": 3}}), &options).unwrap()
+    );
+    assert!(from_value_with::<Helper>(xmlrpc_value!("Stop"), &Default::default()).is_err());
+
+    let options = DeserializeOptions {
+        enums: EnumRepresentation::Internal { tag: "type".into() },
+        ..Default::default()
+    };
+    assert_eq!(
+        Helper::Stop,
+        from_value_with(xmlrpc_value!({"type": "Stop"}), &options).unwrap()
+    );
+    assert_eq!(
+        Helper::Move { x: 3 },
+        from_value_with(xmlrpc_value!({"type": "Move", "x": 3}), &options).unwrap()
+    );
+    assert!(from_value_with::<Helper>(xmlrpc_value!({"x": 3}), &options).is_err());
+}
+
+#[test]
+fn reads_integers_from_integral_doubles_if_enabled() {
+    assert!(from_value_with::<i32>(Value::Double(42.0), &Default::default()).is_err());
+    let options = DeserializeOptions {
+        integral_doubles: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        42,
+        from_value_with::<i32>(Value::Double(42.0), &options).unwrap()
+    );
+    assert_eq!(
+        0u8,
+        from_value_with::<u8>(Value::Double(-0.0), &options).unwrap()
+    );
+    assert_eq!(
+        1u64 << 60,
+        from_value_with::<u64>(Value::Double((1u64 << 60) as f64), &options).unwrap()
+    );
+    assert!(from_value_with::<i32>(Value::Double(42.5), &options).is_err());
+    assert!(from_value_with::<u8>(Value::Double(256.0), &options).is_err());
+    assert!(from_value_with::<u8>(Value::Double(-1.0), &options).is_err());
+    assert!(from_value_with::<i64>(Value::Double(f64::NAN), &options).is_err());
+    assert!(from_value_with::<i64>(Value::Double(f64::INFINITY), &options).is_err());
+}
+
+#[test]
+fn reads_missing_members_as_none_unless_required() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Helper {
+        foo: i32,
+        bar: Option<i32>,
+    }
+
+    let value = xmlrpc_value!({ "foo": 3 });
+    assert_eq!(
+        Helper { foo: 3, bar: None },
+        from_value_with(value.clone(), &Default::default()).unwrap()
+    );
+    let options = DeserializeOptions {
+        require_all_members: true,
+        ..Default::default()
+    };
+    assert!(from_value_with::<Helper>(value, &options).is_err());
+    assert_eq!(
+        Helper {
+            foo: 3,
+            bar: Some(4)
+        },
+        from_value_with(xmlrpc_value!({ "foo": 3, "bar": [4] }), &options).unwrap()
+    );
+}
+
+#[test]
+fn reads_and_writes_xml_directly() {
+    use super::super::{from_xml_reader, to_xml_writer};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: i32,
+        tags: Vec<String>,
+    }
+
+    let point = Point {
+        x: 3,
+        tags: vec!["a".into()],
+    };
+    let mut xml = Vec::new();
+    to_xml_writer(&point, &mut xml).unwrap();
+    assert_eq!(point, from_xml_reader::<Point, _>(&xml[..]).unwrap());
+
+    let xml = "<value><struct><member><name>x</name><value><i4>5</i4></value></member>\
+               <member><name>tags</name><value><array><data/></array></value></member>\
+               </struct></value>";
+    assert_eq!(
+        Point { x: 5, tags: vec![] },
+        from_xml_reader(xml.as_bytes()).unwrap()
+    );
+    assert!(from_xml_reader::<Point, _>("<value><i4>5</i4></value>".as_bytes()).is_err());
+}
+
+#[test]
+fn borrows_strings_from_values() {
+    use super::super::from_value_ref;
+    use std::borrow::Cow;
+
+    #[derive(Debug, Deserialize)]
+    struct Request<'a> {
+        user: &'a str,
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        tags: Vec<&'a str>,
+        note: Option<&'a str>,
+        count: i32,
+    }
+
+    let value = xmlrpc_value!({"user": "alice", "name": "Alice", "tags": ["a", "b"], "count": 3});
+    let request: Request = from_value_ref(&value).unwrap();
+    assert_eq!("alice", request.user);
+    assert!(matches!(request.name, Cow::Borrowed("Alice")));
+    assert_eq!(vec!["a", "b"], request.tags);
+    assert_eq!(None, request.note);
+    assert_eq!(3, request.count);
+    let user = match value.get("user") {
+        Some(Value::String(user)) => user.as_ptr(),
+        _ => unreachable!(),
+    };
+    assert_eq!(user, request.user.as_ptr());
+
+    assert!(from_value_ref::<Request>(&xmlrpc_value!({"user": 3})).is_err());
+}
+
+#[test]
+fn borrows_tuple_structs_and_enums() {
+    use super::super::{from_value_ref, from_value_ref_with};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Pair<'a>(&'a str, &'a str);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Shape<'a> {
+        Empty,
+        Named(&'a str),
+        Pair(&'a str, &'a str),
+        Labelled { label: &'a str },
+    }
+
+    let value = xmlrpc_value!(["a", "b"]);
+    assert_eq!(Pair("a", "b"), from_value_ref(&value).unwrap());
+
+    let value = xmlrpc_value!({"Named": "circle"});
+    assert_eq!(Shape::Named("circle"), from_value_ref(&value).unwrap());
+    let value = xmlrpc_value!({"Pair": ["x", "y"]});
+    assert_eq!(Shape::Pair("x", "y"), from_value_ref(&value).unwrap());
+    let value = xmlrpc_value!({"Labelled": {"label": "box"}});
+    assert_eq!(
+        Shape::Labelled { label: "box" },
+        from_value_ref(&value).unwrap()
+    );
+    let value = xmlrpc_value!({"Empty": {}});
+    assert_eq!(Shape::Empty, from_value_ref(&value).unwrap());
+    assert!(from_value_ref::<Shape>(&xmlrpc_value!({"Named": "a", "Empty": {}})).is_err());
+
+    let options = DeserializeOptions {
+        enums: EnumRepresentation::UnitAsString,
+        ..Default::default()
+    };
+    let value = xmlrpc_value!("Empty");
+    assert_eq!(Shape::Empty, from_value_ref_with(&value, &options).unwrap());
+
+    let options = DeserializeOptions {
+        enums: EnumRepresentation::Internal { tag: "kind".into() },
+        ..Default::default()
+    };
+    let value = xmlrpc_value!({"kind": "Labelled", "label": "box"});
+    let shape: Shape = from_value_ref_with(&value, &options).unwrap();
+    assert_eq!(Shape::Labelled { label: "box" }, shape);
+    let label = match value.get("label") {
+        Some(Value::String(label)) => label.as_ptr(),
+        _ => unreachable!(),
+    };
+    match shape {
+        Shape::Labelled { label: borrowed } => assert_eq!(label, borrowed.as_ptr()),
+        _ => unreachable!(),
+    }
+    let value = xmlrpc_value!({"kind": "Empty"});
+    assert_eq!(Shape::Empty, from_value_ref_with(&value, &options).unwrap());
+}
+
+#[test]
+fn skips_ignored_members_when_borrowing() {
+    use super::super::from_value_ref;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Summary<'a> {
+        id: &'a str,
+    }
+
+    // Ignored members aren't looked at, so even base64 that wouldn't decode is let through.
+    let value = xmlrpc_value!({
+        "id": "a1",
+        "payload": [(Value::Base64Text("not base64".into())), {"deep": [1, 2, 3]}],
+    });
+    assert_eq!(Summary { id: "a1" }, from_value_ref(&value).unwrap());
+}
+
+#[test]
+fn borrows_options_written_as_arrays() {
+    use super::super::from_value_ref;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Note<'a> {
+        text: Option<&'a str>,
+    }
+
+    let value = xmlrpc_value!({"text": ["hi"]});
+    assert_eq!(Note { text: Some("hi") }, from_value_ref(&value).unwrap());
+    let value = xmlrpc_value!({"text": []});
+    assert_eq!(Note { text: None }, from_value_ref(&value).unwrap());
+    assert!(from_value_ref::<Note>(&xmlrpc_value!({"text": ["a", "b"]})).is_err());
+}
+
+#[test]
+fn reads_php_arrays_either_way() {
+    #[derive(Debug, Default, Deserialize, PartialEq)]
+    struct Post {
+        #[serde(default)]
+        title: String,
+        tags: Vec<String>,
+    }
+
+    let options = DeserializeOptions {
+        php_arrays: true,
+        ..Default::default()
+    };
+    let keyed = xmlrpc_value!({"1": "b", "0": "a", "2": "c"});
+    assert!(from_value_with::<Vec<String>>(keyed.clone(), &Default::default()).is_err());
+    assert_eq!(
+        vec!["a", "b", "c"],
+        from_value_with::<Vec<String>>(keyed, &options).unwrap()
+    );
+    let gapped = xmlrpc_value!({"0": "a", "2": "c"});
+    assert!(from_value_with::<Vec<String>>(gapped, &options).is_err());
+    let padded = xmlrpc_value!({"0": "a", "01": "b"});
+    assert!(from_value_with::<Vec<String>>(padded, &options).is_err());
+
+    let listed = xmlrpc_value!(["a", "b"]);
+    let map = from_value_with::<HashMap<String, String>>(listed, &options).unwrap();
+    assert_eq!(Some(&"b".to_string()), map.get("1"));
+
+    let post = xmlrpc_value!({"tags": {}});
+    assert_eq!(
+        Post::default(),
+        from_value_with::<Post>(post, &options).unwrap()
+    );
+    let post = xmlrpc_value!({"title": "Hi", "tags": {"0": "rust"}});
+    assert_eq!(
+        vec!["rust"],
+        from_value_with::<Post>(post, &options).unwrap().tags
+    );
+    let empty = xmlrpc_value!({"tags": [], "title": "Hi"});
+    assert!(from_value_with::<Post>(empty, &options)
+        .unwrap()
+        .tags
+        .is_empty());
+}
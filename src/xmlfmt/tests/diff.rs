@@ -0,0 +1,75 @@
+use super::super::{diff, Difference, Value, ValueType};
+
+#[test]
+fn lists_differences_by_path() {
+    let left = xmlrpc_value!({
+        "name": "widget",
+        "sizes": [1, 2, 3],
+        "owner": {"id": 7, "odd key": true},
+        "gone": "here"
+    });
+    let right = xmlrpc_value!({
+        "name": "gadget",
+        "sizes": [1, "2"],
+        "owner": {"id": 7, "odd key": true, "extra": 1.5}
+    });
+    let diff = diff(&left, &right);
+    assert_eq!(
+        diff.differences,
+        vec![
+            Difference::Missing {
+                path: "$.gone".into(),
+                left: Value::String("here".into()),
+            },
+            Difference::Changed {
+                path: "$.name".into(),
+                left: Value::String("widget".into()),
+                right: Value::String("gadget".into()),
+            },
+            Difference::Added {
+                path: "$.owner.extra".into(),
+                right: Value::Double(1.5),
+            },
+            Difference::TypeMismatch {
+                path: "$.sizes[1]".into(),
+                left: ValueType::Int,
+                right: ValueType::String,
+            },
+            Difference::Missing {
+                path: "$.sizes[2]".into(),
+                left: Value::Int(3),
+            },
+        ]
+    );
+    assert_eq!(
+        "$.gone: only on the left, \"here\"\n\
+         $.name: \"widget\" on the left, \"gadget\" on the right\n\
+         $.owner.extra: only on the right, 1.5\n\
+         $.sizes[1]: int on the left, string on the right\n\
+         $.sizes[2]: only on the left, 3",
+        diff.to_string()
+    );
+}
+
+#[test]
+fn treats_equal_values_as_equal() {
+    let value = xmlrpc_value!({"a": [1, {"b": "c"}], "odd key": nil_value()});
+    assert!(diff(&value, &value.clone()).is_empty());
+    assert!(diff(
+        &Value::Base64(b"hello".to_vec()),
+        &Value::Base64Text("aGVsbG8=".into())
+    )
+    .is_empty());
+    assert_value_eq!(value, value.clone());
+}
+
+#[test]
+#[should_panic(expected = "values differ: in the response\n$[\"odd key\"]: only on the left, nil")]
+fn panics_with_the_differences() {
+    let left = xmlrpc_value!({"odd key": nil_value()});
+    assert_value_eq!(left, xmlrpc_value!({}), "in the {}", "response");
+}
+
+fn nil_value() -> Value {
+    Value::Nil
+}
@@ -0,0 +1,44 @@
+use super::super::value::ToXml;
+use super::super::{parse, Call, Encoder, Response, Value, WriteOptions};
+
+#[test]
+fn encodes_messages_back_to_back() {
+    let mut encoder = Encoder::new();
+    let call = Call {
+        name: "echo".into(),
+        params: vec![Value::Base64((0..100).collect()), Value::Int(4)],
+    };
+    assert_eq!(call.to_xml(), encoder.encode_str(&call));
+    let response: Response = Ok(vec![Value::String("done".into())]);
+    let data = encoder.encode(&response).to_vec();
+    assert_eq!(response, parse::response(&data[..]).unwrap());
+    let value = Value::Bool(true);
+    assert_eq!(value.to_xml(), encoder.encode_str(&value));
+    assert_eq!(value.to_xml().into_bytes(), encoder.into_buffer());
+}
+
+#[test]
+fn encodes_with_options() {
+    let options = WriteOptions {
+        declaration: None,
+        base64_line_length: Some(76),
+        ..Default::default()
+    };
+    let mut encoder = Encoder::with_options(options.clone());
+    let value = Value::Base64((0..=255).cycle().take(2000).collect());
+    let data = encoder.encode_str(&value);
+    assert_eq!(value.to_xml_with(&options), data);
+    assert!(data.split('\n').all(|line| line.len() <= 76 + 15));
+    assert_eq!(value, parse::xml(data.as_bytes()).unwrap());
+}
+
+#[test]
+fn reserves_the_write_buffer_size() {
+    let options = WriteOptions {
+        write_buffer_size: 4096,
+        ..Default::default()
+    };
+    let mut encoder = Encoder::with_options(options);
+    encoder.encode(&Value::Int(1));
+    assert!(encoder.into_buffer().capacity() >= 4096);
+}
@@ -0,0 +1,56 @@
+use client::Endpoint;
+use runtime::{Blocking, Runtime};
+use test_util::{self, TestServer};
+use {Fault, Server, Value};
+
+fn calculator() -> TestServer {
+    let mut server = Server::new();
+    server.register_simple("add", |(a, b): (i32, i32)| Ok(a + b));
+    server.register_value("fail", |_| Err(Fault::new(3, "Failed")));
+    test_util::spawn(server)
+}
+
+// Written once for both runtimes.
+fn add<R: Runtime>(endpoint: &Endpoint<R>, a: i32, b: i32) -> R::Output<::client::CallResult<i32>> {
+    endpoint.call("add", (a, b))
+}
+
+#[test]
+fn makes_blocking_calls() {
+    let server = calculator();
+    let endpoint = Endpoint::<Blocking>::new(server.url().clone()).unwrap();
+    assert_eq!(5, add(&endpoint, 2, 3).unwrap());
+    assert_eq!(
+        Err(Fault::new(3, "Failed")),
+        endpoint.call_value("fail", vec![]).unwrap()
+    );
+    assert_eq!(
+        Ok(vec![Value::Int(7)]),
+        endpoint
+            .clone()
+            .call_value("add", vec![Value::Int(3), Value::Int(4)])
+            .unwrap()
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn makes_async_calls() {
+    use futures_lite::future::block_on;
+    use runtime::Async;
+
+    let server = calculator();
+    let endpoint = Endpoint::<Async>::new(server.url().clone()).unwrap();
+    let sums = (0..4).map(|i| add(&endpoint, i, 1)).collect::<Vec<_>>();
+    let sums = sums
+        .into_iter()
+        .map(|sum| block_on(sum).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![1, 2, 3, 4], sums);
+    assert_eq!(
+        Some(&Fault::new(3, "Failed")),
+        block_on(endpoint.call::<_, _, i32>("fail", ()))
+            .unwrap_err()
+            .fault()
+    );
+}
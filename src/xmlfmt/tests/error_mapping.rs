@@ -0,0 +1,142 @@
+use super::super::Fault;
+use std::error::Error;
+use std::fmt;
+use test_util;
+use {CallResultExt, Client, Server};
+
+#[derive(Debug)]
+enum AccountError {
+    NotFound(String),
+    Database(String),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AccountError::NotFound(ref name) => write!(f, "No account named {}", name),
+            AccountError::Database(ref detail) => write!(f, "Database failed: {}", detail),
+        }
+    }
+}
+
+impl Error for AccountError {}
+
+fn map_account_errors(err: &(dyn Error + 'static)) -> Fault {
+    match err.downcast_ref::<AccountError>() {
+        Some(&AccountError::NotFound(_)) => Fault::new(404, err.to_string()),
+        _ => Fault::new(500, "Internal error"),
+    }
+}
+
+fn balance((name,): (String,)) -> Result<i32, AccountError> {
+    match name.as_str() {
+        "alice" => Ok(10),
+        "bob" => Err(AccountError::Database("connection reset".into())),
+        _ => Err(AccountError::NotFound(name)),
+    }
+}
+
+fn call(server: &test_util::TestServer, name: &str) -> Result<i32, Fault> {
+    Client::new()
+        .unwrap()
+        .call(server.url(), "balance", (name,))
+        .nested()
+        .unwrap()
+}
+
+#[test]
+fn maps_handler_errors_into_faults() {
+    let mut server = Server::new();
+    server.register_fallible("balance", balance);
+    server.map_errors(map_account_errors);
+    let server = test_util::spawn(server);
+
+    assert_eq!(Ok(10), call(&server, "alice"));
+    assert_eq!(
+        Err(Fault::new(404, "No account named carol")),
+        call(&server, "carol")
+    );
+    assert_eq!(Err(Fault::new(500, "Internal error")), call(&server, "bob"));
+}
+
+#[test]
+fn hides_handler_errors_by_default() {
+    let mut server = Server::new();
+    server.register_fallible("balance", balance);
+    server.register_fallible("fail", |(): ()| Err::<i32, _>("Bad request"));
+    let server = test_util::spawn(server);
+
+    assert_eq!(Err(Fault::new(500, "Internal error")), call(&server, "bob"));
+    let response: Result<i32, Fault> = Client::new()
+        .unwrap()
+        .call(server.url(), "fail", ())
+        .nested()
+        .unwrap();
+    assert_eq!(Err(Fault::new(500, "Internal error")), response);
+    let response: Result<i32, Fault> = Client::new()
+        .unwrap()
+        .call(server.url(), "balance", (1, 2))
+        .nested()
+        .unwrap();
+    assert_eq!(Err(Fault::new(400, "Failed to decode request")), response);
+}
+
+#[test]
+fn maps_errors_reading_params_of_simple_handlers() {
+    let mut server = Server::new();
+    server.register_simple("double", |(x,): (i32,)| Ok(x * 2));
+    server.map_errors(|err| Fault::new(-32602, format!("Bad params: {}", err)));
+    let server = test_util::spawn(server);
+
+    let response: Result<i32, Fault> = Client::new()
+        .unwrap()
+        .call(server.url(), "double", ("two",))
+        .nested()
+        .unwrap();
+    let fault = response.unwrap_err();
+    assert_eq!(-32602, fault.code());
+    assert!(fault.message().starts_with("Bad params: "), "{}", fault);
+}
+
+fn withdraw((amount,): (i32,)) -> Result<i32, Box<dyn Error + Send + Sync>> {
+    if amount > 10 {
+        Err(Fault::new(409, "Insufficient funds"))?;
+    }
+    let remaining: i32 = "10".parse()?;
+    Ok(remaining - amount)
+}
+
+#[test]
+fn passes_faults_through_as_errors() {
+    let mut server = Server::new();
+    server.register_fallible("withdraw", withdraw);
+    server.map_errors(map_account_errors);
+    let server = test_util::spawn(server);
+
+    let mut client = Client::new().unwrap();
+    let mut call = |amount: i32| -> Result<i32, Fault> {
+        client
+            .call(server.url(), "withdraw", (amount,))
+            .nested()
+            .unwrap()
+    };
+    assert_eq!(Ok(6), call(4));
+    assert_eq!(Err(Fault::new(409, "Insufficient funds")), call(11));
+
+    let fault: Box<dyn Error> = Box::new(Fault::invalid_params("Expected a name"));
+    assert_eq!("<fault -32602: \"Expected a name\">", fault.to_string());
+}
+
+#[test]
+fn sorts_faults_by_interop_code_range() {
+    let fault = Fault::invalid_params("Bad");
+    assert_eq!(-32602, fault.code());
+    assert_eq!("Bad", fault.message());
+    assert!(fault.is_server_error() && !fault.is_parse_error());
+    assert!(Fault::new(-32700, "").is_parse_error());
+    assert!(Fault::replayed_call("").is_server_error());
+    assert!(Fault::new(-32500, "").is_application_error());
+    assert!(Fault::system_error("").is_system_error());
+    assert!(Fault::transport_error("").is_transport_error());
+    assert!(!Fault::new(404, "").is_server_error());
+}
@@ -0,0 +1,97 @@
+use super::super::Fault;
+use error::{Error, ErrorKind, ResultExt};
+use std::io;
+use test_util::StubServer;
+use {CallError, CallResultExt, Client, Url};
+
+fn chained(err: io::Error) -> Error {
+    Err::<(), _>(err)
+        .chain_err(|| "Failed to read the HTTP response.")
+        .unwrap_err()
+}
+
+#[test]
+fn tells_timeouts_apart() {
+    let err = Error::from(ErrorKind::ConcurrencyLimitTimeout(2));
+    assert!(err.is_timeout());
+    assert!(err.is_transport());
+
+    let err = chained(io::Error::new(io::ErrorKind::TimedOut, "Timed out"));
+    assert!(err.is_timeout());
+    assert!(err.is_transport());
+    assert!(!err.is_fault());
+}
+
+#[test]
+fn reports_failed_connections_as_transport_errors() {
+    let uri = Url::parse("http://127.0.0.1:1/").unwrap();
+    let err = Client::new()
+        .unwrap()
+        .call_value(&uri, "echo", vec![])
+        .unwrap_err();
+    assert!(err.is_transport());
+    assert!(!err.is_timeout());
+    assert!(err.fault().is_none());
+}
+
+#[test]
+fn carries_faults() {
+    let err = Error::from(Fault::new(4, "Too many parameters"));
+    assert!(err.is_fault());
+    assert_eq!(err.fault(), Some(&Fault::new(4, "Too many parameters")));
+    assert!(!err.is_transport());
+    assert!(!err.is_timeout());
+}
+
+#[test]
+fn leaves_decoding_errors_out() {
+    let err = Error::from(super::super::from_value::<i32>("a".into()).unwrap_err());
+    assert!(!err.is_transport());
+    assert!(!err.is_timeout());
+    assert!(!err.is_fault());
+}
+
+#[test]
+fn sorts_call_errors() {
+    let stub = StubServer::new(vec![
+        ("name", Ok(vec!["Bob".into()])),
+        ("fail", Err(Fault::new(4, "Too many parameters"))),
+    ]);
+    let mut client = Client::new().unwrap();
+    assert_eq!(
+        "Bob",
+        client.call::<_, _, String>(stub.url(), "name", ()).unwrap()
+    );
+    match client.call::<_, _, String>(stub.url(), "fail", ()) {
+        Err(CallError::Fault(fault)) => assert_eq!(4, fault.code),
+        v => panic!("Expected fault, got {:?}", v),
+    }
+    match client.call::<_, _, i32>(stub.url(), "name", ()) {
+        Err(CallError::Decode(_)) => {}
+        v => panic!("Expected decoding error, got {:?}", v),
+    }
+    let uri = Url::parse("http://127.0.0.1:1/").unwrap();
+    match client.call::<_, _, i32>(&uri, "name", ()) {
+        Err(CallError::Transport(_)) => {}
+        v => panic!("Expected transport error, got {:?}", v),
+    }
+}
+
+#[test]
+fn nests_call_results() {
+    let stub = StubServer::new(vec![("fail", Err(Fault::new(4, "Too many parameters")))]);
+    let mut client = Client::new().unwrap();
+    assert_eq!(
+        Err(Fault::new(4, "Too many parameters")),
+        client
+            .call::<_, _, i32>(stub.url(), "fail", ())
+            .nested()
+            .unwrap()
+    );
+    let uri = Url::parse("http://127.0.0.1:1/").unwrap();
+    let err = client
+        .call::<_, _, i32>(&uri, "fail", ())
+        .nested()
+        .unwrap_err();
+    assert!(err.is_transport());
+}
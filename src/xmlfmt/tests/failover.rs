@@ -0,0 +1,53 @@
+use super::super::Fault;
+use super::raw_http::refused;
+use test_util::{self, TestServer};
+use {Client, Server};
+
+fn endpoint(name: &'static str) -> TestServer {
+    let mut server = Server::new();
+    server.register_simple("whoami", move |()| Ok(name));
+    server.register_simple("fail", move |()| Err::<(), _>(Fault::new(1, name)));
+    test_util::spawn(server)
+}
+
+#[test]
+fn falls_back_on_transport_errors() {
+    let backup = endpoint("backup");
+    let primary = refused();
+    let mut client = Client::new().unwrap();
+    client.set_fallbacks(primary.clone(), vec![refused(), backup.url().clone()]);
+    assert_eq!(None, client.last_endpoint());
+    assert_eq!(
+        "backup".to_string(),
+        client.call::<_, _, String>(&primary, "whoami", ()).unwrap()
+    );
+    assert_eq!(Some(backup.url()), client.last_endpoint());
+}
+
+#[test]
+fn returns_faults_without_falling_back() {
+    let primary = endpoint("primary");
+    let backup = endpoint("backup");
+    let mut client = Client::new().unwrap();
+    client.set_fallbacks(primary.url().clone(), vec![backup.url().clone()]);
+    assert_eq!(
+        Some(&Fault::new(1, "primary")),
+        client
+            .call::<_, _, ()>(primary.url(), "fail", ())
+            .unwrap_err()
+            .fault()
+    );
+    assert_eq!(Some(primary.url()), client.last_endpoint());
+}
+
+#[test]
+fn fails_when_every_endpoint_fails() {
+    let primary = refused();
+    let mut client = Client::new().unwrap();
+    client.set_fallbacks(primary.clone(), vec![refused()]);
+    assert!(client.call::<_, _, String>(&primary, "whoami", ()).is_err());
+    // Other URLs keep no fallbacks.
+    assert!(client
+        .call::<_, _, String>(&refused(), "whoami", ())
+        .is_err());
+}
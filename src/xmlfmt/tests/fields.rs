@@ -0,0 +1,46 @@
+use super::super::{from_value, to_value, Value};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Upload {
+    #[serde(with = "::as_base64")]
+    data: Vec<u8>,
+    #[serde(with = "::as_datetime")]
+    at: String,
+    #[serde(with = "::as_i64_string")]
+    size: i64,
+}
+
+#[test]
+fn pins_field_representations() {
+    let upload = Upload {
+        data: vec![1, 2, 3],
+        at: "19980717T14:08:55Z".into(),
+        size: 1 << 40,
+    };
+    let value = to_value(&upload).unwrap();
+    assert_eq!(
+        xmlrpc_value!({
+            "data": (Value::Base64(vec![1, 2, 3])),
+            "at": (Value::DateTime("19980717T14:08:55Z".into())),
+            "size": "1099511627776",
+        }),
+        value
+    );
+    assert_eq!(upload, from_value(value).unwrap());
+}
+
+#[test]
+fn reads_i64_strings_from_integers_too() {
+    let value = xmlrpc_value!({
+        "data": (Value::Base64(vec![])),
+        "at": (Value::DateTime("19980717T14:08:55Z".into())),
+        "size": (Value::I8(-5)),
+    });
+    assert_eq!(-5, from_value::<Upload>(value).unwrap().size);
+    let value = xmlrpc_value!({
+        "data": (Value::Base64(vec![])),
+        "at": (Value::DateTime("19980717T14:08:55Z".into())),
+        "size": "five",
+    });
+    assert!(from_value::<Upload>(value).is_err());
+}
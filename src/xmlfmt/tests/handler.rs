@@ -0,0 +1,101 @@
+use super::super::{Call, Fault, Params, Response, Value};
+use server::{Ctx, Handler, XmlRpcService};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use test_util;
+use {Client, Server};
+
+#[derive(Default)]
+struct Counter {
+    count: AtomicI32,
+}
+
+impl Handler for Counter {
+    fn call(&self, method: &str, params: Params) -> Response {
+        match method {
+            "add" => {
+                let by = params.first().and_then(|v| match *v {
+                    Value::Int(by) => Some(by),
+                    _ => None,
+                });
+                let by = by.ok_or_else(|| Fault::invalid_params("Expected an int"))?;
+                Ok(vec![Value::Int(
+                    self.count.fetch_add(by, Ordering::SeqCst) + by,
+                )])
+            }
+            "get" => Ok(vec![Value::Int(self.count.load(Ordering::SeqCst))]),
+            _ => Err(Fault::new(404, format!("No method {}", method))),
+        }
+    }
+}
+
+#[test]
+fn dispatches_prefixed_methods_to_services() {
+    let mut server = Server::new();
+    server.register_service("counter.", Arc::new(Counter::default()));
+    server.register_service("counter.nested.", Arc::new(Counter::default()));
+    server.register_value("counter.get", |_| Ok(vec![Value::from("direct")]));
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+    let mut call = |name: &str, params: Params| {
+        client
+            .call_value(server.url(), name, params)
+            .unwrap()
+            .map_err(|fault| fault.code)
+    };
+
+    assert_eq!(
+        Ok(vec![Value::Int(2)]),
+        call("counter.add", vec![Value::Int(2)])
+    );
+    assert_eq!(
+        Ok(vec![Value::Int(5)]),
+        call("counter.add", vec![Value::Int(3)])
+    );
+    assert_eq!(
+        Ok(vec![Value::Int(1)]),
+        call("counter.nested.add", vec![Value::Int(1)])
+    );
+    assert_eq!(Ok(vec![Value::from("direct")]), call("counter.get", vec![]));
+    assert_eq!(Err(404), call("counter.reset", vec![]));
+    assert_eq!(Err(404), call("other.add", vec![Value::Int(1)]));
+}
+
+struct Greeter;
+
+impl XmlRpcService for Greeter {
+    fn dispatch(&self, call: Call, ctx: &Ctx) -> Response {
+        match call.name.as_str() {
+            "greet" => Ok(vec![Value::from(format!(
+                "Hello from {}",
+                ctx.header("x-caller").unwrap_or("nobody")
+            ))]),
+            _ => Err(Fault::new(404, format!("No method {}", call.name))),
+        }
+    }
+}
+
+#[test]
+fn dispatches_unregistered_methods_to_the_service() {
+    let mut inner = Server::new();
+    inner.register_value("inner", |_| Ok(vec![Value::from("inner")]));
+    let mut server = Server::new();
+    server.register_value("direct", |_| Ok(vec![Value::from("direct")]));
+    server.set_service(move |call: Call, ctx: &Ctx| match call.name.as_str() {
+        "inner" => inner.dispatch(call, ctx),
+        _ => Greeter.dispatch(call, ctx),
+    });
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+    client.set_pre_send_hook(|_, _, headers| headers.set_raw("X-Caller", vec![b"tests".to_vec()]));
+    let mut call = |name: &str| {
+        client
+            .call_value(server.url(), name, Vec::new())
+            .unwrap()
+            .map_err(|fault| fault.code)
+    };
+    assert_eq!(Ok(vec![Value::from("direct")]), call("direct"));
+    assert_eq!(Ok(vec![Value::from("inner")]), call("inner"));
+    assert_eq!(Ok(vec![Value::from("Hello from tests")]), call("greet"));
+    assert_eq!(Err(404), call("missing"));
+}
@@ -0,0 +1,73 @@
+use super::raw_http::refused;
+use std::thread;
+use std::time::{Duration, Instant};
+use test_util::{self, TestServer};
+use {Client, Server};
+
+fn replica(name: &'static str, wait: Duration) -> TestServer {
+    let mut server = Server::new();
+    server.register_simple("whoami", move |()| {
+        thread::sleep(wait);
+        Ok(name)
+    });
+    test_util::spawn(server)
+}
+
+#[test]
+fn races_a_second_replica_after_the_delay() {
+    let slow = replica("slow", Duration::from_secs(2));
+    let fast = replica("fast", Duration::from_millis(0));
+    let uris = [slow.url().clone(), fast.url().clone()];
+    let start = Instant::now();
+    let mut client = Client::new().unwrap();
+    let result: String = client
+        .call_hedged(&uris, Duration::from_millis(50), "whoami", ())
+        .unwrap();
+    assert_eq!("fast".to_string(), result);
+    assert_eq!(Some(fast.url()), client.last_endpoint());
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn keeps_the_first_replica_when_it_answers_in_time() {
+    let first = replica("first", Duration::from_millis(0));
+    let second = replica("second", Duration::from_millis(0));
+    let uris = [first.url().clone(), second.url().clone()];
+    let result: String = Client::new()
+        .unwrap()
+        .call_hedged(&uris, Duration::from_secs(5), "whoami", ())
+        .unwrap();
+    assert_eq!("first".to_string(), result);
+}
+
+#[test]
+fn hedges_at_once_when_a_call_fails() {
+    let backup = replica("backup", Duration::from_millis(0));
+    let uris = [refused(), backup.url().clone()];
+    let start = Instant::now();
+    let result: String = Client::new()
+        .unwrap()
+        .call_hedged(&uris, Duration::from_secs(5), "whoami", ())
+        .unwrap();
+    assert_eq!("backup".to_string(), result);
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn fails_when_every_replica_fails() {
+    let uris = [refused(), refused()];
+    let result = Client::new().unwrap().call_hedged::<_, _, String>(
+        &uris,
+        Duration::from_millis(10),
+        "whoami",
+        (),
+    );
+    assert!(result.is_err());
+    let result = Client::new().unwrap().call_hedged::<_, _, String>(
+        &[],
+        Duration::from_millis(10),
+        "whoami",
+        (),
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,57 @@
+use super::super::error::ErrorKind;
+use super::super::parse;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn corpus(target: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/corpus")
+        .join(target)
+}
+
+fn read(target: &str, name: &str) -> Vec<u8> {
+    fs::read(corpus(target).join(name)).unwrap()
+}
+
+#[test]
+fn parses_whole_fuzz_corpus_without_panicking() {
+    for target in &["parse_call", "parse_response", "parse_value"] {
+        for entry in fs::read_dir(corpus(target)).unwrap() {
+            let data = fs::read(entry.unwrap().path()).unwrap();
+            let _ = parse::call(&data[..]);
+            let _ = parse::response(&data[..]);
+            let _ = parse::xml(&data[..]);
+        }
+    }
+}
+
+#[test]
+fn rejects_entity_tricks() {
+    for name in &[
+        "billion_laughs.xml",
+        "external_entity.xml",
+        "parameter_entity.xml",
+    ] {
+        match parse::call(&read("parse_call", name)[..]) {
+            Err(err) => match *err.kind() {
+                ErrorKind::DoctypeNotAllowed(_) => {}
+                ref kind => panic!("{}: {:?}", name, kind),
+            },
+            Ok(call) => panic!("{}: {:?}", name, call),
+        }
+    }
+}
+
+#[test]
+fn limits_nesting() {
+    for name in &["deep_arrays.xml", "deep_structs.xml"] {
+        let err = parse::call(&read("parse_call", name)[..]).unwrap_err();
+        assert!(
+            err.iter()
+                .any(|e| e.to_string().contains("Parsing limit exceeded")),
+            "{}: {}",
+            name,
+            err
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Calls between this crate and Python's `xmlrpc` modules, which need `python3` on the path.
+//!
+//! Run with `cargo test interop -- --ignored`.
+
+use super::super::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use test_util;
+use {Client, Server};
+
+static PYTHON_SERVER: &str = r#"
+import sys
+from xmlrpc.server import SimpleXMLRPCServer
+server = SimpleXMLRPCServer(("127.0.0.1", 0), logRequests=False, allow_none=True, use_builtin_types=True)
+server.register_function(lambda *params: list(params), "echo")
+server.register_function(lambda v: type(v).__name__, "describe")
+print(server.server_address[1], flush=True)
+server.serve_forever()
+"#;
+
+static PYTHON_CLIENT: &str = r#"
+import sys, datetime
+from xmlrpc.client import ServerProxy
+proxy = ServerProxy(sys.argv[1], allow_none=True, use_builtin_types=True)
+values = [
+    3, -2**31, True, False, "", "text <&> ünïcödé", 2.5, -0.125,
+    datetime.datetime(1998, 7, 17, 14, 8, 55), b"\x00\x01\xff" * 40,
+    [], [1, "two", [3.0]], {}, {"a": {"b": [None, 1]}}, None,
+]
+for value in values:
+    echoed = proxy.echo(value)
+    assert echoed == value, "sent %r, got %r" % (value, echoed)
+"#;
+
+/// Kills the Python server once the test is done with it.
+struct Python(Child);
+
+impl Drop for Python {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn python_server() -> (Python, String) {
+    let mut child = Command::new("python3")
+        .args(["-c", PYTHON_SERVER])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start python3");
+    let mut port = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut port)
+        .unwrap();
+    (Python(child), format!("http://127.0.0.1:{}/", port.trim()))
+}
+
+fn call(uri: &str, method: &str, params: Vec<Value>) -> Value {
+    let mut client = Client::new().unwrap();
+    let mut response = client
+        .call_value(&uri.parse().unwrap(), method, params)
+        .unwrap()
+        .unwrap();
+    assert_eq!(1, response.len());
+    response.pop().unwrap()
+}
+
+#[test]
+#[ignore]
+fn python_reads_every_value_type() {
+    let (_python, uri) = python_server();
+    let cases = vec![
+        (Value::Int(-3), "int"),
+        (Value::I8(1 << 40), "int"),
+        (Value::Bool(true), "bool"),
+        (Value::String("a <b> & c".into()), "str"),
+        (Value::Double(2.5), "float"),
+        (Value::DateTime("19980717T14:08:55".into()), "datetime"),
+        (Value::Base64(vec![0, 1, 255]), "bytes"),
+        (xmlrpc_value!([1, "two"]), "list"),
+        (xmlrpc_value!({"a": 1}), "dict"),
+        (Value::Nil, "NoneType"),
+    ];
+    for (value, python_type) in cases {
+        assert_eq!(
+            Value::String(python_type.into()),
+            call(&uri, "describe", vec![value.clone()]),
+            "{}",
+            value
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn python_echoes_values_back() {
+    let (_python, uri) = python_server();
+    let values = vec![
+        Value::Int(i32::MIN),
+        Value::Bool(false),
+        Value::String("ünïcödé\n\ttext".into()),
+        Value::Double(-0.125),
+        Value::DateTime("19980717T14:08:55".into()),
+        Value::Base64((0..=255).collect()),
+        xmlrpc_value!([[], {}, [1, [2.5, "three"]], {"a": {"b": [true]}}]),
+        Value::Nil,
+    ];
+    for value in values {
+        assert_eq!(
+            Value::Array(vec![value.clone()]),
+            call(&uri, "echo", vec![value])
+        );
+    }
+    // Python writes every integer it can as `int`.
+    assert_eq!(xmlrpc_value!([5]), call(&uri, "echo", vec![Value::I8(5)]));
+}
+
+#[test]
+#[ignore]
+fn python_calls_this_server() {
+    let mut server = Server::new();
+    server.register_value("echo", Ok);
+    let server = test_util::spawn(server);
+
+    let mut child = Command::new("python3")
+        .args(["-", server.url().as_str()])
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("Failed to start python3");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(PYTHON_CLIENT.as_bytes())
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+}
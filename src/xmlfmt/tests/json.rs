@@ -0,0 +1,79 @@
+use super::super::Value;
+use serde_json;
+use std::convert::TryFrom;
+
+#[test]
+fn round_trips_through_json() {
+    let value = xmlrpc_value!({
+        "int": 33,
+        "list": [true, "foo", 2.5, [], {}],
+        "blob": (Value::Base64(vec![1, 2, 3])),
+        "when": (Value::DateTime("19980717T14:08:55".into())),
+    });
+    let json = serde_json::Value::try_from(value.clone()).unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "int": 33,
+            "list": [true, "foo", 2.5, [], {}],
+            "blob": {"$base64": "AQID"},
+            "when": {"$dateTime.iso8601": "19980717T14:08:55"},
+        }),
+        json
+    );
+    assert_eq!(value, Value::try_from(json).unwrap());
+}
+
+#[test]
+fn converts_json_numbers() {
+    assert_eq!(
+        xmlrpc_value!([1, "5000000000", "18446744073709551615", 1.5]),
+        Value::try_from(serde_json::json!([1, 5_000_000_000i64, u64::MAX, 1.5])).unwrap()
+    );
+}
+
+#[test]
+fn converts_null_and_i8() {
+    assert_eq!(
+        Value::Nil,
+        Value::try_from(serde_json::Value::Null).unwrap()
+    );
+    assert_eq!(
+        serde_json::json!([null, 5_000_000_000i64]),
+        serde_json::Value::try_from(xmlrpc_value!([(Value::Nil), (Value::I8(5_000_000_000))]))
+            .unwrap()
+    );
+}
+
+#[test]
+fn rejects_unrepresentable_values() {
+    assert!(Value::try_from(serde_json::json!({"$base64": "!!"})).is_err());
+    assert!(serde_json::Value::try_from(Value::Double(f64::NAN)).is_err());
+    assert_eq!(
+        xmlrpc_value!({"$base64": "AQID", "other": 1}),
+        Value::try_from(serde_json::json!({"$base64": "AQID", "other": 1})).unwrap()
+    );
+}
+
+#[test]
+fn escapes_members_named_like_tags() {
+    let value = xmlrpc_value!({"$base64": "AQID"});
+    let json = serde_json::Value::try_from(value.clone()).unwrap();
+    assert_eq!(serde_json::json!({"$$base64": "AQID"}), json);
+    assert_eq!(value, Value::try_from(json).unwrap());
+
+    let value = xmlrpc_value!({
+        "$dateTime.iso8601": "tomorrow",
+        "$$cost": 1,
+        "$other": 2,
+    });
+    let json = serde_json::Value::try_from(value.clone()).unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "$$dateTime.iso8601": "tomorrow",
+            "$$$cost": 1,
+            "$other": 2,
+        }),
+        json
+    );
+    assert_eq!(value, Value::try_from(json).unwrap());
+}
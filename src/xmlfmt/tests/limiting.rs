@@ -0,0 +1,120 @@
+use error::ErrorKind;
+use std::thread;
+use std::time::{Duration, Instant};
+use test_util::{self, TestServer};
+use {Client, ConcurrencyLimit, Server};
+
+fn slow_server() -> TestServer {
+    let mut server = Server::new();
+    server.register_simple("nap", |()| {
+        thread::sleep(Duration::from_millis(200));
+        Ok(true)
+    });
+    test_util::spawn(server)
+}
+
+fn nap(server: &TestServer, limit: &ConcurrencyLimit) -> ::error::Result<()> {
+    let mut client = Client::new().unwrap();
+    client.set_concurrency_limit(limit.clone());
+    let result: bool = client.call(server.url(), "nap", ())?;
+    assert!(result);
+    Ok(())
+}
+
+#[test]
+fn runs_calls_over_the_limit_in_turn() {
+    let server = slow_server();
+    let limit = ConcurrencyLimit::new(1, None);
+    let start = Instant::now();
+    thread::scope(|scope| {
+        let calls = (0..2)
+            .map(|_| scope.spawn(|| nap(&server, &limit)))
+            .collect::<Vec<_>>();
+        for call in calls {
+            call.join().unwrap().unwrap();
+        }
+    });
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    assert_eq!(0, limit.running());
+}
+
+#[test]
+fn a_limit_of_zero_still_runs_calls() {
+    let server = slow_server();
+    let limit = ConcurrencyLimit::new(0, None);
+    nap(&server, &limit).unwrap();
+    assert_eq!(0, limit.running());
+}
+
+#[test]
+fn gives_up_waiting_after_the_timeout() {
+    let server = slow_server();
+    let limit = ConcurrencyLimit::new(1, Some(Duration::from_millis(50)));
+    thread::scope(|scope| {
+        let first = scope.spawn(|| nap(&server, &limit));
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(1, limit.running());
+        match nap(&server, &limit).unwrap_err().0 {
+            ErrorKind::ConcurrencyLimitTimeout(1) => {}
+            err => panic!("Unexpected error: {}", err),
+        }
+        first.join().unwrap().unwrap();
+    });
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn waits_for_room_without_blocking() {
+    use futures_lite::future::{block_on, poll_once};
+
+    let limit = ConcurrencyLimit::new(1, None);
+    let held = limit.acquire().unwrap();
+    let mut waiting = limit.acquire_async();
+    assert!(block_on(poll_once(&mut waiting)).is_none());
+    drop(held);
+    let permit = block_on(waiting).unwrap();
+    assert_eq!(1, limit.running());
+    drop(permit);
+    assert_eq!(0, limit.running());
+
+    let limit = ConcurrencyLimit::new(1, Some(Duration::from_millis(50)));
+    let _held = limit.acquire().unwrap();
+    match block_on(limit.acquire_async()).err().unwrap().0 {
+        ErrorKind::ConcurrencyLimitTimeout(1) => {}
+        err => panic!("Unexpected error: {}", err),
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_endpoints_wait_for_their_turn() {
+    use client::{CallError, Endpoint};
+    use futures_lite::future::{block_on, poll_once};
+    use runtime::Async;
+
+    let server = slow_server();
+    let limit = ConcurrencyLimit::new(1, None);
+    let mut client = Client::new().unwrap();
+    client.set_concurrency_limit(limit.clone());
+    let endpoint = Endpoint::<Async>::with_client(client, server.url().clone());
+    let held = limit.acquire().unwrap();
+    let mut call = endpoint.call::<_, _, bool>("nap", ());
+    assert!(block_on(poll_once(&mut call)).is_none());
+    assert_eq!(1, limit.running());
+    drop(held);
+    assert!(block_on(call).unwrap());
+    assert_eq!(0, limit.running());
+
+    let limit = ConcurrencyLimit::new(1, Some(Duration::from_millis(50)));
+    let mut client = Client::new().unwrap();
+    client.set_concurrency_limit(limit.clone());
+    let endpoint = Endpoint::<Async>::with_client(client, server.url().clone());
+    let _held = limit.acquire().unwrap();
+    match block_on(endpoint.call::<_, _, bool>("nap", ())) {
+        Err(CallError::Transport(ref err)) => match *err.kind() {
+            ErrorKind::ConcurrencyLimitTimeout(1) => {}
+            ref err => panic!("Unexpected error: {}", err),
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,59 @@
+use super::raw_http::refused;
+use log::{self, Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+use test_util;
+use {Client, Server};
+
+lazy_static! {
+    static ref RECORDS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+}
+
+struct Recorder;
+
+impl Log for Recorder {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Debug
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS
+                .lock()
+                .unwrap()
+                .push((record.target().into(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn logged(target: &str, message: &str) -> bool {
+    RECORDS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(t, m)| t == target && m.contains(message))
+}
+
+#[test]
+fn logs_dispatch_and_failed_calls() {
+    let _ = log::set_logger(&Recorder);
+    log::set_max_level(LevelFilter::Debug);
+
+    let mut server = Server::new();
+    server.register_simple("logged_ping", |()| Ok(true));
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+    client
+        .call::<_, _, bool>(server.url(), "logged_ping", ())
+        .unwrap();
+    assert!(logged("xml_rpc::server", "Dispatching logged_ping"));
+    assert!(logged("xml_rpc::client", "Opened a connection"));
+
+    let uri = refused();
+    assert!(client.call::<_, _, bool>(&uri, "logged_ping", ()).is_err());
+    assert!(logged(
+        "xml_rpc::client",
+        &format!("Call to {} failed", uri)
+    ));
+}
@@ -0,0 +1,56 @@
+use super::super::Value;
+use std::collections::HashMap;
+
+#[test]
+fn builds_scalars() {
+    assert_eq!(xmlrpc_value!(33), Value::Int(33));
+    assert_eq!(xmlrpc_value!(-33), Value::Int(-33));
+    assert_eq!(xmlrpc_value!(true), Value::Bool(true));
+    assert_eq!(xmlrpc_value!(2.5), Value::Double(2.5));
+    assert_eq!(xmlrpc_value!("foo"), Value::String("foo".into()));
+    let name = String::from("bar");
+    assert_eq!(xmlrpc_value!(name.clone()), Value::String(name));
+}
+
+#[test]
+fn builds_arrays() {
+    assert_eq!(xmlrpc_value!([]), Value::Array(vec![]));
+    assert_eq!(
+        xmlrpc_value!([1, true, "bar",]),
+        Value::Array(vec![
+            Value::Int(1),
+            Value::Bool(true),
+            Value::String("bar".into()),
+        ])
+    );
+    assert_eq!(
+        xmlrpc_value!([[1 + 1], {}, []]),
+        Value::Array(vec![
+            Value::Array(vec![Value::Int(2)]),
+            Value::Struct(HashMap::new()),
+            Value::Array(vec![]),
+        ])
+    );
+}
+
+#[test]
+fn builds_structs() {
+    let mut inner = HashMap::new();
+    inner.insert("qux".to_owned(), Value::Double(2.5));
+    let mut members = HashMap::new();
+    members.insert(
+        "foo".to_owned(),
+        Value::Array(vec![
+            Value::Int(1),
+            Value::Bool(true),
+            Value::String("bar".into()),
+        ]),
+    );
+    members.insert("baz".to_owned(), Value::Struct(inner));
+    members.insert("quux".to_owned(), Value::Int(7));
+    let key = "quux";
+    assert_eq!(
+        xmlrpc_value!({"foo": [1, true, "bar"], "baz": {"qux": 2.5}, key: 3 + 4}),
+        Value::Struct(members)
+    );
+}
@@ -0,0 +1,193 @@
+use super::super::{Fault, Value};
+use metaweblog::{register_metaweblog, BlogInfo, Credentials, MetaWeblogApi, Post};
+use std::sync::Mutex;
+use test_util;
+use {Client, DeserializeOptions, OptionRepresentation, SerializeOptions, Server};
+
+#[derive(Default)]
+struct Blog {
+    posts: Mutex<Vec<Post>>,
+}
+
+fn check(credentials: &Credentials) -> Result<(), Fault> {
+    if credentials.username == "admin" && credentials.password == "secret" {
+        Ok(())
+    } else {
+        Err(Fault::new(403, "Bad login"))
+    }
+}
+
+impl MetaWeblogApi for Blog {
+    fn new_post(
+        &self,
+        blog_id: String,
+        credentials: Credentials,
+        mut post: Post,
+        _publish: bool,
+    ) -> Result<String, Fault> {
+        check(&credentials)?;
+        assert_eq!("1", blog_id);
+        let mut posts = self.posts.lock().unwrap();
+        let id = (posts.len() + 1).to_string();
+        post.postid = Some(id.clone());
+        posts.push(post);
+        Ok(id)
+    }
+
+    fn edit_post(
+        &self,
+        post_id: String,
+        credentials: Credentials,
+        post: Post,
+        _publish: bool,
+    ) -> Result<bool, Fault> {
+        check(&credentials)?;
+        let mut posts = self.posts.lock().unwrap();
+        let stored = posts
+            .iter_mut()
+            .find(|stored| stored.postid.as_ref() == Some(&post_id))
+            .ok_or_else(|| Fault::new(404, "No such post"))?;
+        if post.title.is_some() {
+            stored.title = post.title;
+        }
+        Ok(true)
+    }
+
+    fn get_post(&self, post_id: String, credentials: Credentials) -> Result<Post, Fault> {
+        check(&credentials)?;
+        self.posts
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|post| post.postid.as_ref() == Some(&post_id))
+            .cloned()
+            .ok_or_else(|| Fault::new(404, "No such post"))
+    }
+
+    fn get_recent_posts(
+        &self,
+        _blog_id: String,
+        credentials: Credentials,
+        count: i32,
+    ) -> Result<Vec<Post>, Fault> {
+        check(&credentials)?;
+        let posts = self.posts.lock().unwrap();
+        Ok(posts.iter().rev().take(count as usize).cloned().collect())
+    }
+
+    fn get_users_blogs(&self, credentials: Credentials) -> Result<Vec<BlogInfo>, Fault> {
+        check(&credentials)?;
+        Ok(vec![BlogInfo {
+            blogid: "1".into(),
+            url: "http://blog.example.com/".into(),
+            blog_name: "Example".into(),
+            is_admin: None,
+        }])
+    }
+}
+
+fn client() -> Client {
+    let mut client = Client::new().unwrap();
+    client.set_serialize_options(SerializeOptions {
+        options: OptionRepresentation::Omit,
+        ..SerializeOptions::default()
+    });
+    client.set_deserialize_options(DeserializeOptions {
+        options: OptionRepresentation::Omit,
+        ..DeserializeOptions::default()
+    });
+    client
+}
+
+#[test]
+fn serves_posts() {
+    let mut server = Server::new();
+    register_metaweblog(&mut server, Blog::default());
+    let server = test_util::spawn(server);
+    let mut client = client();
+    let post = Post {
+        title: Some("Hello".into()),
+        description: Some("First post".into()),
+        date_created: Some("20240102T03:04:05Z".into()),
+        ..Post::default()
+    };
+    // Blog IDs sent as ints are passed on as strings.
+    let id: String = client
+        .call(
+            server.url(),
+            "metaWeblog.newPost",
+            (1, "admin", "secret", &post, true),
+        )
+        .unwrap();
+    assert_eq!("1", id);
+    let edit = Post {
+        title: Some("Hello again".into()),
+        ..Post::default()
+    };
+    assert!(client
+        .call::<_, _, bool>(
+            server.url(),
+            "metaWeblog.editPost",
+            ("1", "admin", "secret", &edit, true),
+        )
+        .unwrap());
+    let stored: Post = client
+        .call(server.url(), "metaWeblog.getPost", ("1", "admin", "secret"))
+        .unwrap();
+    assert_eq!(Some("Hello again".to_string()), stored.title);
+    assert_eq!(Some("First post".to_string()), stored.description);
+    assert_eq!(Some("20240102T03:04:05Z".to_string()), stored.date_created);
+    assert_eq!(None, stored.categories);
+    let recent: Vec<Post> = client
+        .call(
+            server.url(),
+            "metaWeblog.getRecentPosts",
+            ("1", "admin", "secret", 10),
+        )
+        .unwrap();
+    assert_eq!(vec![stored], recent);
+    let blogs: Vec<BlogInfo> = client
+        .call(
+            server.url(),
+            "blogger.getUsersBlogs",
+            ("appkey", "admin", "secret"),
+        )
+        .unwrap();
+    assert_eq!("Example", blogs[0].blog_name);
+}
+
+#[test]
+fn replies_with_faults() {
+    let mut server = Server::new();
+    register_metaweblog(&mut server, Blog::default());
+    let server = test_util::spawn(server);
+    let mut client = client();
+    assert_eq!(
+        Some(&Fault::new(403, "Bad login")),
+        client
+            .call::<_, _, Post>(server.url(), "metaWeblog.getPost", ("1", "admin", "guess"))
+            .unwrap_err()
+            .fault()
+    );
+    assert_eq!(
+        Some(&Fault::new(
+            404,
+            "metaWeblog.getCategories is not supported"
+        )),
+        client
+            .call::<_, _, Value>(
+                server.url(),
+                "metaWeblog.getCategories",
+                ("1", "admin", "secret"),
+            )
+            .unwrap_err()
+            .fault()
+    );
+    let fault = client
+        .call::<_, _, Value>(server.url(), "metaWeblog.getPost", ("1",))
+        .unwrap_err()
+        .fault()
+        .cloned()
+        .unwrap();
+    assert_eq!(Fault::invalid_params("").code, fault.code);
+}
@@ -0,0 +1,95 @@
+use server::Saturation;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use test_util::{self, MetricsRecorder, TestServer};
+use xmlfmt::value::SYSTEM_ERROR;
+use {CallResultExt, Client, Fault, Server};
+
+/// Serves `rebuild`, which says it started on the returned channel, waits to read `done` and
+/// returns how many times it ran.
+fn rebuild_server(
+    saturation: Saturation,
+    recorder: &MetricsRecorder,
+    done: &Arc<RwLock<()>>,
+) -> (TestServer, Receiver<i32>) {
+    let (started, starts) = mpsc::channel();
+    let runs = AtomicUsize::new(0);
+    let done = Arc::clone(done);
+    let mut server = Server::new();
+    server.register_simple("rebuild", move |(_,): (String,)| {
+        let run = runs.fetch_add(1, Ordering::SeqCst) as i32 + 1;
+        started.send(run).unwrap();
+        drop(done.read());
+        Ok(run)
+    });
+    server.limit_concurrency("rebuild", 1, saturation);
+    server.set_metrics_sink(recorder.clone());
+    (test_util::spawn(server), starts)
+}
+
+fn rebuild(server: &TestServer, index: &str) -> Result<i32, Fault> {
+    let mut client = Client::new().unwrap();
+    client
+        .call(server.url(), "rebuild", (index.to_string(),))
+        .nested()
+        .unwrap()
+}
+
+/// Calls `rebuild` from a thread per index, the first one running and each of the others
+/// waiting on the limit before the next one starts, and lets them finish once all are waiting.
+/// Gives the results and the number of runs.
+fn rebuild_concurrently(
+    saturation: Saturation,
+    indexes: &[&str],
+) -> (Vec<Result<i32, Fault>>, i32) {
+    let recorder = MetricsRecorder::default();
+    let done = Arc::new(RwLock::new(()));
+    let running = done.write().unwrap();
+    let (server, starts) = rebuild_server(saturation, &recorder, &done);
+    let results = thread::scope(|scope| {
+        let server = &server;
+        let mut calls = vec![scope.spawn(move || rebuild(server, indexes[0]))];
+        assert_eq!(1, starts.recv().unwrap());
+        for (waiting, index) in indexes[1..].iter().enumerate() {
+            calls.push(scope.spawn(move || rebuild(server, index)));
+            recorder.wait_for("requests_queued", waiting as f64 + 1.0);
+        }
+        drop(running);
+        calls.into_iter().map(|call| call.join().unwrap()).collect()
+    });
+    (results, 1 + starts.try_iter().count() as i32)
+}
+
+#[test]
+fn queues_calls_over_the_limit() {
+    let (results, runs) = rebuild_concurrently(Saturation::Queue, &["users", "users"]);
+    assert_eq!(vec![Ok(1), Ok(2)], results);
+    assert_eq!(2, runs);
+}
+
+#[test]
+fn rejects_calls_over_the_limit() {
+    let recorder = MetricsRecorder::default();
+    let done = Arc::new(RwLock::new(()));
+    let running = done.write().unwrap();
+    let (server, starts) = rebuild_server(Saturation::Reject, &recorder, &done);
+    thread::scope(|scope| {
+        let first = scope.spawn(|| rebuild(&server, "users"));
+        assert_eq!(1, starts.recv().unwrap());
+        let second = rebuild(&server, "users");
+        assert_eq!(SYSTEM_ERROR, second.unwrap_err().code);
+        drop(running);
+        assert_eq!(Ok(1), first.join().unwrap());
+    });
+    assert_eq!(None, starts.try_iter().next());
+}
+
+#[test]
+fn coalesces_calls_with_the_same_params() {
+    let (results, runs) =
+        rebuild_concurrently(Saturation::Coalesce, &["users", "users", "posts", "users"]);
+    assert_eq!(vec![Ok(1), Ok(1), Ok(2), Ok(1)], results);
+    assert_eq!(2, runs);
+}
@@ -0,0 +1,77 @@
+use server::Saturation;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use test_util::{self, MetricsRecorder, TestServer};
+use xmlfmt::value::SYSTEM_ERROR;
+use {CallResultExt, Client, Fault, Server};
+
+/// Serves `nap`, which says it started on the returned channel and then waits to read `awake`.
+fn napping_server(
+    saturation: Saturation,
+    recorder: &MetricsRecorder,
+    awake: &Arc<RwLock<()>>,
+) -> (TestServer, Receiver<()>) {
+    let (napping, naps) = mpsc::channel();
+    let awake = Arc::clone(awake);
+    let mut server = Server::new();
+    server.register_simple("nap", move |()| {
+        napping.send(()).unwrap();
+        drop(awake.read());
+        Ok(true)
+    });
+    server.limit_concurrency("nap", 1, saturation);
+    server.set_workers(4);
+    server.set_metrics_sink(recorder.clone());
+    (test_util::spawn(server), naps)
+}
+
+fn nap(server: &TestServer) {
+    let mut client = Client::new().unwrap();
+    let result: Result<bool, Fault> = client.call(server.url(), "nap", ()).nested().unwrap();
+    match result {
+        Ok(napped) => assert!(napped),
+        Err(fault) => assert_eq!(SYSTEM_ERROR, fault.code(), "{}", fault),
+    }
+}
+
+/// Calls `nap` a second time while the first call naps, and lets them finish once `metric` is
+/// reported as 1.
+fn nap_twice(saturation: Saturation, metric: &str) -> MetricsRecorder {
+    let recorder = MetricsRecorder::default();
+    let awake = Arc::new(RwLock::new(()));
+    let asleep = awake.write().unwrap();
+    let (server, naps) = napping_server(saturation, &recorder, &awake);
+    thread::scope(|scope| {
+        let first = scope.spawn(|| nap(&server));
+        naps.recv().unwrap();
+        let second = scope.spawn(|| nap(&server));
+        recorder.wait_for(metric, 1.0);
+        drop(asleep);
+        first.join().unwrap();
+        second.join().unwrap();
+    });
+    drop(server);
+    recorder
+}
+
+#[test]
+fn reports_requests_in_flight_and_shed() {
+    let recorder = nap_twice(Saturation::Reject, "requests_shed");
+    let in_flight = recorder.values("requests_in_flight");
+    assert_eq!(vec![1.0, 2.0, 1.0, 0.0], in_flight);
+    assert_eq!(
+        vec![0.25, 0.5, 0.25, 0.0],
+        recorder.values("worker_utilization")
+    );
+    assert_eq!(vec![1.0], recorder.values("requests_shed"));
+    assert!(recorder.values("requests_queued").is_empty());
+}
+
+#[test]
+fn reports_queued_requests() {
+    let recorder = nap_twice(Saturation::Queue, "requests_queued");
+    assert_eq!(vec![1.0, 0.0], recorder.values("requests_queued"));
+    assert!(recorder.values("requests_shed").is_empty());
+    assert_eq!(Some(&0.0), recorder.values("requests_in_flight").last());
+}
@@ -1,3 +1,78 @@
+mod agent;
+mod arena;
+mod auth;
+#[cfg(feature = "bignum")]
+mod bignum;
+mod borrowed;
+#[cfg(feature = "bugzilla")]
+mod bugzilla;
+mod bytes;
+mod caching;
+mod charset;
+#[cfg(feature = "chrono")]
+mod chrono;
+mod codegen;
+mod compression;
+mod content_type;
+mod datetime;
 mod de;
+mod diff;
+mod encoder;
+mod endpoint;
+mod error_mapping;
+mod errors;
+mod failover;
+mod fields;
+mod handler;
+mod hedging;
+mod hostile;
+mod interop;
+#[cfg(feature = "serde_json")]
+mod json;
+mod limiting;
+#[cfg(feature = "log")]
+mod logging;
+mod macros;
+#[cfg(feature = "metaweblog")]
+mod metaweblog;
+mod method_limits;
+mod metrics;
+mod multicall;
+#[cfg(feature = "oauth2")]
+mod oauth2;
+mod params;
 mod parsevalue;
+mod pooling;
+mod probes;
+mod proxy;
+mod query;
+mod raw_http;
+mod replay;
+mod request_id;
+#[cfg(unix)]
+mod reuse_port;
+#[cfg(feature = "ros")]
+mod ros;
+#[cfg(feature = "testing")]
+mod roundtrip;
+mod routes;
 mod ser;
+#[cfg(feature = "macros")]
+mod service;
+mod shutdown;
+#[cfg(unix)]
+mod signals;
+mod signature;
+mod signing;
+mod stream;
+mod streaming;
+#[cfg(feature = "supervisor")]
+mod supervisor;
+mod test_util;
+#[cfg(feature = "time")]
+mod time;
+mod trace;
+mod value;
+mod visit;
+#[cfg(feature = "wordpress")]
+mod wordpress;
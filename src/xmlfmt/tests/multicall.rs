@@ -0,0 +1,71 @@
+use super::super::{Call, Fault, MulticallResponse, Value};
+use test_util;
+use {Client, Server};
+
+fn call(name: &str, params: Vec<Value>) -> Call {
+    Call {
+        name: name.into(),
+        params,
+    }
+}
+
+#[test]
+fn makes_several_calls_in_one_request() {
+    let mut server = Server::new();
+    server.register_simple("add", |(a, b): (i32, i32)| Ok(a + b));
+    server.register_value("fail", |_| Err(Fault::new(3, "Failed")));
+    server.register_value("nothing", |_| Ok(vec![]));
+    server.protect_from_replays("transfer");
+    let server = test_util::spawn(server);
+
+    let calls = vec![
+        call("add", vec![Value::Int(2), Value::Int(3)]),
+        call("fail", vec![]),
+        call("nothing", vec![]),
+        call("system.multicall", vec![Value::Array(vec![])]),
+        call("transfer", vec![]),
+        call("missing", vec![]),
+    ];
+    let response = Client::new()
+        .unwrap()
+        .multicall(server.url(), calls)
+        .unwrap()
+        .unwrap();
+    assert_eq!(6, response.len());
+    assert_eq!(Ok(5), response.deserialize::<i32>(0).unwrap());
+    assert_eq!(
+        Err(Fault::new(3, "Failed")),
+        response.deserialize::<i32>(1).unwrap()
+    );
+    assert_eq!(Some(&Ok(Value::Array(vec![]))), response.get(2));
+    assert_eq!(400, response.get(3).unwrap().clone().unwrap_err().code);
+    assert_eq!(400, response.get(4).unwrap().clone().unwrap_err().code);
+    assert_eq!(404, response.get(5).unwrap().clone().unwrap_err().code);
+    assert_eq!("missing", response.entries[5].0);
+    assert!(response.deserialize::<i32>(6).is_err());
+}
+
+#[test]
+fn reads_back_what_it_writes() {
+    let response = MulticallResponse {
+        entries: vec![
+            ("a".into(), Ok(Value::Int(1))),
+            (
+                "b".into(),
+                Err(Fault::new(2, "Two").with_detail(xmlrpc_value!({"x": 1}))),
+            ),
+        ],
+    };
+    let calls = vec![call("a", vec![]), call("b", vec![])];
+    let value = response.clone().into_value();
+    assert_eq!(
+        xmlrpc_value!([[1], {"faultCode": 2, "faultString": "Two", "x": 1}]),
+        value
+    );
+    assert_eq!(
+        response,
+        MulticallResponse::from_value(&calls, value.clone()).unwrap()
+    );
+    assert!(MulticallResponse::from_value(&calls[..1], value).is_err());
+    assert!(MulticallResponse::from_value(&calls[..1], xmlrpc_value!([[1, 2]])).is_err());
+}
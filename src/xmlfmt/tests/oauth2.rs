@@ -0,0 +1,97 @@
+use super::raw_http::{reply, serve, xml_reply};
+use oauth2::ClientCredentials;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use {Client, TokenSource};
+
+fn token_reply(token: &str) -> Vec<u8> {
+    let body = format!(
+        r#"{{"access_token":"{}","token_type":"Bearer","expires_in":3600}}"#,
+        token
+    );
+    reply(
+        "200 OK",
+        &[("Content-Type", "application/json")],
+        body.as_bytes(),
+    )
+}
+
+#[test]
+fn fetches_tokens_once() {
+    let (token_url, tokens) = serve(vec![token_reply("T1")]);
+    let (url, calls) = serve(vec![xml_reply("<i4>1</i4>"), xml_reply("<i4>2</i4>")]);
+    let mut credentials = ClientCredentials::new(token_url, "app", "s3cret");
+    credentials.set_scopes(vec!["read".into(), "write:all".into()]);
+    let mut client = Client::new().unwrap();
+    client.set_token_source(credentials);
+    assert_eq!(1, client.call::<_, _, i32>(&url, "count", ()).unwrap());
+    assert_eq!(2, client.call::<_, _, i32>(&url, "count", ()).unwrap());
+    let tokens = tokens.join().unwrap();
+    assert_eq!(
+        b"grant_type=client_credentials&scope=read+write%3Aall".to_vec(),
+        tokens[0].body
+    );
+    assert_eq!(
+        Some("Basic YXBwOnMzY3JldA=="),
+        tokens[0].header("authorization")
+    );
+    for call in calls.join().unwrap() {
+        assert_eq!(Some("Bearer T1"), call.header("authorization"));
+    }
+}
+
+#[test]
+fn refreshes_rejected_tokens() {
+    let (token_url, tokens) = serve(vec![token_reply("T1"), token_reply("T2")]);
+    let (url, calls) = serve(vec![
+        reply("401 Unauthorized", &[], b""),
+        xml_reply("<i4>1</i4>"),
+    ]);
+    let mut client = Client::new().unwrap();
+    client.set_token_source(ClientCredentials::new(token_url, "app", "s3cret"));
+    assert_eq!(1, client.call::<_, _, i32>(&url, "count", ()).unwrap());
+    assert_eq!(2, tokens.join().unwrap().len());
+    let calls = calls.join().unwrap();
+    assert_eq!(Some("Bearer T1"), calls[0].header("authorization"));
+    assert_eq!(Some("Bearer T2"), calls[1].header("authorization"));
+}
+
+#[test]
+fn reports_token_endpoint_errors() {
+    let (token_url, tokens) = serve(vec![reply(
+        "400 Bad Request",
+        &[],
+        br#"{"error":"invalid_client"}"#,
+    )]);
+    let credentials = ClientCredentials::new(token_url, "app", "wrong");
+    let err = credentials.token().unwrap_err().to_string();
+    assert!(err.contains("400 Bad Request"), "{}", err);
+    assert!(err.contains("invalid_client"), "{}", err);
+    tokens.join().unwrap();
+}
+
+struct Counted(Arc<AtomicUsize>);
+
+impl TokenSource for Counted {
+    fn token(&self) -> ::error::Result<String> {
+        Ok(format!("token{}", self.0.load(Ordering::SeqCst)))
+    }
+
+    fn invalidate(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn retries_rejected_calls_only_once() {
+    let (url, calls) = serve(vec![
+        reply("401 Unauthorized", &[], b""),
+        reply("401 Unauthorized", &[], b""),
+    ]);
+    let invalidated = Arc::new(AtomicUsize::new(0));
+    let mut client = Client::new().unwrap();
+    client.set_token_source(Counted(Arc::clone(&invalidated)));
+    assert!(client.call::<_, _, i32>(&url, "count", ()).is_err());
+    assert_eq!(1, invalidated.load(Ordering::SeqCst));
+    assert_eq!(2, calls.join().unwrap().len());
+}
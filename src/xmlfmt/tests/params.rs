@@ -0,0 +1,96 @@
+use super::super::value::INVALID_PARAMS;
+use super::super::{from_params, into_params, ParamsExt, ParamsReader, Value};
+use test_util;
+use {Client, Server};
+
+#[test]
+fn checks_params_by_index() {
+    let params = [Value::Int(3), Value::String("foo".into())];
+    assert!(params.expect_len(2).is_ok());
+    assert_eq!(INVALID_PARAMS, params.expect_len(3).unwrap_err().code);
+    assert_eq!(3, params.take::<i32>(0).unwrap());
+    assert_eq!("foo", params.take::<String>(1).unwrap());
+    let fault = params.take::<i32>(1).unwrap_err();
+    assert_eq!(INVALID_PARAMS, fault.code);
+    assert!(fault.message.starts_with("Invalid parameter 1"));
+    assert_eq!(
+        "Missing parameter 2",
+        params.take::<i32>(2).unwrap_err().message
+    );
+}
+
+#[test]
+fn reads_params_in_order() {
+    let mut params = ParamsReader::new(vec![Value::Int(3), Value::Bool(true)]);
+    assert_eq!(3, params.next::<i32>().unwrap());
+    assert!(params.optional::<bool>().unwrap().unwrap());
+    assert_eq!(None, params.optional::<bool>().unwrap());
+    assert_eq!(
+        "Missing parameter 2",
+        params.next::<i32>().unwrap_err().message
+    );
+    assert!(params.finish().is_ok());
+
+    let mut params = ParamsReader::new(vec![Value::Int(3), Value::Int(4)]);
+    assert_eq!(3, params.next::<i32>().unwrap());
+    assert_eq!(
+        "Expected 1 parameters, got 2",
+        params.finish().unwrap_err().message
+    );
+}
+
+#[test]
+fn converts_params_into_tuples() {
+    let params = vec![
+        Value::Array(vec![Value::Int(1), Value::Int(2)]),
+        Value::Int(3),
+    ];
+    let (list, count): (Vec<i32>, i32) = from_params(params).unwrap();
+    assert_eq!((vec![1, 2], 3), (list, count));
+    let (list,): (Vec<i32>,) = from_params(vec![Value::Array(vec![Value::Int(1)])]).unwrap();
+    assert_eq!(vec![1], list);
+    assert!(from_params::<()>(vec![]).is_ok());
+    assert!(from_params::<()>(vec![Value::Struct(Default::default())]).is_ok());
+}
+
+#[test]
+fn fails_tuples_of_wrong_arity_or_type() {
+    assert!(from_params::<(i32,)>(vec![]).is_err());
+    assert!(from_params::<(i32,)>(vec![Value::Int(1), Value::Int(2)]).is_err());
+    assert!(from_params::<()>(vec![Value::Int(1)]).is_err());
+    let err = from_params::<(i32, i32)>(vec![Value::Int(1), Value::Bool(true)]).unwrap_err();
+    assert!(err.iter().any(|e| e.to_string() == "Invalid parameter 1"));
+}
+
+#[test]
+fn writes_tuples_as_params() {
+    assert_eq!(
+        vec![
+            Value::String("/node".into()),
+            Value::Array(vec![Value::Int(1), Value::Int(2)]),
+        ],
+        into_params(&("/node", vec![1, 2])).unwrap()
+    );
+    assert_eq!(
+        vec![Value::Array(vec![Value::Int(1)])],
+        into_params(&(vec![1],)).unwrap()
+    );
+    assert!(into_params(&()).unwrap().is_empty());
+}
+
+#[test]
+fn answers_with_a_single_param() {
+    let mut server = Server::new();
+    server.register_simple("pair", |()| Ok((1, "two")));
+    server.register_simple("list", |()| Ok(vec![1, 2]));
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+    assert_eq!(
+        Ok(vec![xmlrpc_value!([1, "two"])]),
+        client.call_value(server.url(), "pair", vec![]).unwrap()
+    );
+    assert_eq!(
+        Ok(vec![xmlrpc_value!([1, 2])]),
+        client.call_value(server.url(), "list", vec![]).unwrap()
+    );
+}
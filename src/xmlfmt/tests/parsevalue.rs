@@ -187,6 +187,7 @@ fn reads_fault() {
         Err(Fault {
             code: 4,
             message: "Too many parameters.".into(),
+            detail: None,
         })
     );
 }
@@ -227,6 +228,48 @@ fn reads_call() {
     );
 }
 
+#[test]
+fn reads_calls_from_slices_and_chunks() {
+    let data = "<?xml version=\"1.0\"?><methodCall><methodName>add</methodName>\
+                <params><param><value><i4>1</i4></value></param></params></methodCall>";
+    let call = parse::call_from_slice(data.as_bytes()).expect(BAD_DATA);
+    assert_eq!(call.name, "add");
+    assert_eq!(call.params, vec![Value::Int(1)]);
+
+    let latin1 = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><methodCall>\
+                   <methodName>caf\xE9</methodName></methodCall>";
+    let name = parse::call_from_slice(latin1).expect(BAD_DATA).name;
+    assert_eq!(name, "caf\u{e9}");
+
+    let mut feed = parse::Feed::new();
+    for chunk in data.as_bytes().chunks(1) {
+        feed.feed(chunk).expect(BAD_DATA);
+    }
+    assert_eq!(feed.finish_call().expect(BAD_DATA), call);
+
+    let mut feed = parse::Feed::new();
+    feed.feed(b"<?xml version=\"1.0\"?><methodCall><methodName>add</methodName><params>")
+        .expect(BAD_DATA);
+    let error = feed
+        .feed(
+            b"<param><value><string>first</string></value></param>\
+              <param><value><i4>second</i4></value></param>",
+        )
+        .unwrap_err();
+    assert!(error.to_string().contains("integer"), "{}", error);
+
+    let mut feed = parse::Feed::new();
+    feed.feed(b"  ").expect(BAD_DATA);
+    assert!(feed.feed(b"{\"method\": \"add\"}").is_err());
+
+    let mut feed = parse::Feed::with_options(ParseOptions {
+        max_size: 16,
+        ..Default::default()
+    });
+    feed.feed(&data.as_bytes()[..16]).expect(BAD_DATA);
+    assert!(feed.feed(b"<").is_err());
+}
+
 #[test]
 fn reads_array_structure_xml_value() {
     let data = r#"<?xml version="1.0"?>
@@ -305,9 +348,50 @@ fn writes_fault() {
     ser_and_de_response_value(Err(Fault {
         code: 4,
         message: "Too many parameters.".into(),
+        detail: None,
     }));
 }
 
+#[test]
+fn writes_fault_detail() {
+    use super::super::value::ToXml;
+    ser_and_de_response_value(Err(Fault::new(4, "Too many parameters.")
+        .with_detail(xmlrpc_value!({"limit": 3, "given": [1, 2, 3, 4]}))));
+    ser_and_de_response_value(Err(Fault::new(1, "Bad").with_detail(5)));
+    ser_and_de_response_value(Err(Fault::new(1, "Bad").with_detail(("detail", 5))));
+    ser_and_de_response_value(Err(Fault::new(1, "Bad").with_detail(xmlrpc_value!({}))));
+
+    let fault = Fault::new(1, "Bad").with_detail(xmlrpc_value!({"faultCode": 2, "x": 3}));
+    assert_eq!(Some(xmlrpc_value!({"x": 3})), fault.detail);
+    // Set directly, the detail's own faultCode and faultString are left out.
+    let response: Response = Err(Fault {
+        detail: Some(xmlrpc_value!({"faultString": "Other", "x": 3})),
+        ..Fault::new(1, "Bad")
+    });
+    let xml = response.to_xml();
+    assert_eq!(1, xml.matches("faultString").count());
+    assert_eq!(Err(fault), parse::response(xml.as_bytes()).unwrap());
+}
+
+#[test]
+fn reads_fault_detail() {
+    use super::super::value::ToXml;
+    let data = r#"<?xml version="1.0"?>
+<methodResponse><fault><value><struct>
+  <member><name>faultString</name><value>Not found</value></member>
+  <member><name>resource</name><value>/users/7</value></member>
+  <member><name>faultCode</name><value><int>404</int></value></member>
+</struct></value></fault></methodResponse>"#;
+    let fault = Fault::new(404, "Not found").with_detail(("resource", "/users/7"));
+    assert_eq!(Err(fault), parse::response(data.as_bytes()).unwrap());
+
+    let response: Response = Err(Fault::new(1, "Bad").with_detail(5));
+    assert_eq!(
+        Err(Fault::new(1, "Bad").with_detail(5)),
+        parse::response(response.to_xml().as_bytes()).unwrap()
+    );
+}
+
 #[test]
 fn writes_call() {
     let mut fields = HashMap::<String, Value>::new();
@@ -331,3 +415,822 @@ fn reads_and_writes_empty_call() {
 fn reads_and_writes_empty_response() {
     ser_and_de_response_value(Ok(vec![]))
 }
+
+fn nested_arrays(depth: usize) -> String {
+    format!(
+        r#"<?xml version="1.0"?><methodCall><methodName>deep</methodName><params><param>{}<value><int>1</int></value>{}</param></params></methodCall>"#,
+        "<value><array><data>".repeat(depth),
+        "</data></array></value>".repeat(depth),
+    )
+}
+
+fn assert_limit_exceeded<T: std::fmt::Debug>(result: error::Result<T>) {
+    match result {
+        Err(error::Error(error::ErrorKind::LimitExceeded(_), _)) => {}
+        v => panic!("expected limit error, got {:?}", v),
+    }
+}
+
+#[test]
+fn limits_nesting_depth() {
+    let options = ParseOptions {
+        max_depth: 4,
+        ..Default::default()
+    };
+    let data = nested_arrays(3);
+    assert!(parse::call_with(data.as_bytes(), &options).is_ok());
+    let data = nested_arrays(4);
+    assert_limit_exceeded(parse::call_with(data.as_bytes(), &options));
+    let data = nested_arrays(10_000);
+    assert!(parse::call(data.as_bytes()).is_err());
+}
+
+#[test]
+fn limits_sizes_and_counts() {
+    let data = r#"<?xml version="1.0"?><array><data><value><struct><member><name>a</name><value>foo</value></member><member><name>b</name><value><base64>AQID</base64></value></member></struct></value></data></array>"#;
+    assert!(parse::xml_with(data.as_bytes(), &ParseOptions::default()).is_ok());
+    let limited = |options: ParseOptions| parse::xml_with(data.as_bytes(), &options);
+    assert_limit_exceeded(limited(ParseOptions {
+        max_size: data.len() - 1,
+        ..Default::default()
+    }));
+    assert!(limited(ParseOptions {
+        max_values: 3,
+        ..Default::default()
+    })
+    .is_ok());
+    assert_limit_exceeded(limited(ParseOptions {
+        max_values: 2,
+        ..Default::default()
+    }));
+    // Only `<value>` elements count, so wrapping the root in one counts it too.
+    let wrapped = format!(
+        "<value>{}</value>",
+        data.trim_start_matches(r#"<?xml version="1.0"?>"#)
+    );
+    let options = ParseOptions {
+        max_values: 3,
+        ..Default::default()
+    };
+    assert_limit_exceeded(parse::xml_with(wrapped.as_bytes(), &options));
+    let options = ParseOptions {
+        max_values: 4,
+        ..Default::default()
+    };
+    assert!(parse::xml_with(wrapped.as_bytes(), &options).is_ok());
+    assert_limit_exceeded(limited(ParseOptions {
+        max_members: 1,
+        ..Default::default()
+    }));
+    assert!(limited(ParseOptions {
+        max_string_length: 4,
+        ..Default::default()
+    })
+    .is_ok());
+    assert_limit_exceeded(limited(ParseOptions {
+        max_string_length: 3,
+        ..Default::default()
+    }));
+}
+
+#[test]
+fn writes_into_any_sink() {
+    use super::super::value::ToXml;
+    let value = xmlrpc_value!([1, {"a&b": "<c>"}]);
+    let mut data = Vec::new();
+    value.write_xml(&mut data).unwrap();
+    assert_eq!(
+        "<value><array><data><value><i4>1</i4></value><value><struct><member><name>a&amp;b</name><value><string>&lt;c&gt;</string></value></member></struct></value></data></array></value>",
+        String::from_utf8(data).unwrap()
+    );
+}
+
+#[test]
+fn escapes_fault_messages() {
+    ser_and_de_response_value(Err(Fault::new(4, "a < b && c")));
+}
+
+#[test]
+fn writes_configured_declaration() {
+    use super::super::value::ToXml;
+    let call = Call {
+        name: "foo".into(),
+        params: vec![],
+    };
+    let expected_body = "<methodCall><methodName>foo</methodName><params></params></methodCall>";
+    assert_eq!(
+        format!(r#"<?xml version="1.0"?>{}"#, expected_body),
+        call.to_xml()
+    );
+    let options = WriteOptions {
+        declaration: Some(XmlDeclaration {
+            encoding: true,
+            standalone: Some(true),
+        }),
+        ..Default::default()
+    };
+    assert_eq!(
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>{}"#,
+            expected_body
+        ),
+        call.to_xml_with(&options)
+    );
+    let options = WriteOptions {
+        declaration: None,
+        ..Default::default()
+    };
+    assert_eq!(expected_body, call.to_xml_with(&options));
+    assert_eq!(call, parse::call(expected_body.as_bytes()).unwrap());
+}
+
+#[test]
+fn rejects_doctypes_by_default() {
+    let internal =
+        r#"<?xml version="1.0"?><!DOCTYPE string [<!ENTITY a "x&amp;y">]><string>&a;</string>"#;
+    let external = r#"<?xml version="1.0"?><!DOCTYPE string [<!ENTITY a SYSTEM "file:///etc/passwd">]><string>&a;</string>"#;
+    let parameter =
+        r#"<?xml version="1.0"?><!DOCTYPE string [<!ENTITY % a "x">]><string>a</string>"#;
+    let is_doctype_error = |result: error::Result<Value>| {
+        matches!(
+            result,
+            Err(error::Error(error::ErrorKind::DoctypeNotAllowed(_), _))
+        )
+    };
+    assert!(is_doctype_error(parse::xml(internal.as_bytes())));
+    assert!(is_doctype_error(parse::xml(external.as_bytes())));
+
+    let options = ParseOptions {
+        doctype: DoctypePolicy::AllowInternalEntities,
+        ..Default::default()
+    };
+    assert_eq!(
+        Value::String("x&y".into()),
+        parse::xml_with(internal.as_bytes(), &options).unwrap()
+    );
+    assert!(is_doctype_error(parse::xml_with(
+        external.as_bytes(),
+        &options
+    )));
+    assert!(is_doctype_error(parse::xml_with(
+        parameter.as_bytes(),
+        &options
+    )));
+}
+
+#[test]
+fn reads_nonconforming_documents_leniently() {
+    let options = ParseOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let data = r#"<?xml version="1.0"?>
+<methodCall>
+    <methodName>foo</methodName>
+    <extra>ignored</extra>
+    <params>
+        <param>
+            <!-- a comment -->
+            <value> <color>red</color> </value>
+            <note>ignored</note>
+        </param>
+        <param><value><array><value><i4>1</i4></value><value>2</value></array></value></param>
+        <param><value><struct>
+            <member><name>a</name><junk/><value><boolean>1</boolean></value></member>
+        </struct></value></param>
+    </params>
+</methodCall>"#;
+    assert!(parse::call(data.as_bytes()).is_err());
+    assert_eq!(
+        Call {
+            name: "foo".into(),
+            params: vec![
+                Value::String("red".into()),
+                xmlrpc_value!([1, "2"]),
+                xmlrpc_value!({"a": true}),
+            ],
+        },
+        parse::call_with(data.as_bytes(), &options).unwrap()
+    );
+
+    let data = r#"<?xml version="1.0"?><methodResponse></methodResponse>"#;
+    assert_eq!(
+        Ok(vec![]),
+        parse::response_with(data.as_bytes(), &options).unwrap()
+    );
+}
+
+#[test]
+fn reports_what_lenient_parsing_let_through() {
+    use super::super::stream::WarningKind;
+    let options = ParseOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let data = r#"<?xml version="1.0"?>
+<methodCall>
+    <methodName>foo</methodName>
+    <extra>ignored</extra>
+    <params>
+        <param><value><color>red</color></value></param>
+        <param><value><array><value><i4>1</i4></value></array></value></param>
+        <param><value><struct>
+            <member><name>a</name><junk/><value><i4>1</i4></value></member>
+        </struct></value></param>
+    </params>
+</methodCall>"#;
+    let (call, warnings) = parse::call_with_warnings(data.as_bytes(), &options).unwrap();
+    assert_eq!(3, call.params.len());
+    assert_eq!(
+        vec![
+            WarningKind::SkippedElement {
+                parent: "methodCall".into(),
+                tag: "extra".into(),
+            },
+            WarningKind::UnknownType("color".into()),
+            WarningKind::MissingData,
+            WarningKind::SkippedElement {
+                parent: "member".into(),
+                tag: "junk".into(),
+            },
+        ],
+        warnings.into_iter().map(|w| w.kind).collect::<Vec<_>>()
+    );
+
+    let data = r#"<?xml version="1.0"?><methodResponse>oops</methodResponse>"#;
+    let (response, warnings) = parse::response_with_warnings(data.as_bytes(), &options).unwrap();
+    assert_eq!(Ok(vec![]), response);
+    assert_eq!(2, warnings.len());
+    assert_eq!(
+        "Expected <params> or <fault> inside <methodResponse> at line 1, column 42",
+        warnings[1].to_string()
+    );
+
+    let data = r#"<?xml version="1.0"?><methodResponse><params/></methodResponse>"#;
+    let (_, warnings) = parse::response_with_warnings(data.as_bytes(), &options).unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn completes_truncated_datetimes_leniently() {
+    use super::super::stream::WarningKind;
+
+    let data = r#"<?xml version="1.0"?><methodResponse><params>
+        <param><value><dateTime.iso8601>1998-07-17</dateTime.iso8601></value></param>
+        <param><value><dateTime.iso8601>14:08:55</dateTime.iso8601></value></param>
+    </params></methodResponse>"#;
+    assert_eq!(
+        Ok(vec![
+            Value::DateTime("1998-07-17".into()),
+            Value::DateTime("14:08:55".into()),
+        ]),
+        parse::response(data.as_bytes()).unwrap()
+    );
+
+    let options = ParseOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    let (response, warnings) = parse::response_with_warnings(data.as_bytes(), &options).unwrap();
+    assert_eq!(
+        Ok(vec![
+            Value::DateTime("1998-07-17T00:00:00".into()),
+            Value::DateTime("19700101T14:08:55".into()),
+        ]),
+        response
+    );
+    assert_eq!(
+        vec![
+            WarningKind::TruncatedDateTime("1998-07-17".into()),
+            WarningKind::TruncatedDateTime("14:08:55".into()),
+        ],
+        warnings.into_iter().map(|w| w.kind).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn reads_only_spec_formats_strictly() {
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let strictly = |data: &str| parse::xml_with(data.as_bytes(), &options);
+    assert_eq!(Value::Int(-33), strictly("<int>-33</int>").unwrap());
+    assert_eq!(
+        Value::Double(1.5),
+        strictly("<double>+1.5</double>").unwrap()
+    );
+    assert_eq!(Value::Double(0.5), strictly("<double>.5</double>").unwrap());
+    assert_eq!(
+        Value::DateTime("19980717T14:08:55".into()),
+        strictly("<dateTime.iso8601>19980717T14:08:55</dateTime.iso8601>").unwrap()
+    );
+    for data in &[
+        "<double>NaN</double>",
+        "<double>inf</double>",
+        "<double>1e5</double>",
+        "<int>5000000000</int>",
+        "<int> 5</int>",
+        "<boolean>2</boolean>",
+        "<boolean>true</boolean>",
+        "<dateTime.iso8601>1998-07-17T14:08:55</dateTime.iso8601>",
+        "<color>red</color>",
+    ] {
+        assert!(strictly(data).is_err(), "accepted {}", data);
+    }
+    assert!(parse::xml("<double>NaN</double>".as_bytes()).is_ok());
+
+    let data = r#"<?xml version="1.0"?><methodCall><methodName>grüß</methodName></methodCall>"#;
+    assert!(parse::call(data.as_bytes()).is_ok());
+    assert!(parse::call_with(data.as_bytes(), &options).is_err());
+}
+
+#[test]
+fn reads_text_split_across_nodes() {
+    let data = r#"<?xml version="1.0"?>
+<struct>
+    <member>
+        <name>a&amp;b<![CDATA[&c]]></name>
+        <value><string>a&amp;b&amp;c<!-- comment --> <![CDATA[<value>d</value>]]> &#101;</string></value>
+    </member>
+    <member>
+        <name>untagged</name>
+        <value>x&lt;<![CDATA[y]]>&gt;z</value>
+    </member>
+</struct>"#;
+    assert_eq!(
+        xmlrpc_value!({
+            "a&b&c": "a&b&c <value>d</value> e",
+            "untagged": "x<y>z",
+        }),
+        parse::xml(data.as_bytes()).unwrap()
+    );
+}
+
+#[test]
+fn reads_and_writes_strings_with_entities() {
+    ser_and_de(Value::String("a&b&c <d> ]]> \"e\"".into()));
+    ser_and_de(xmlrpc_value!({"a&b": "&", "<c>": [""]}));
+}
+
+#[test]
+fn writes_doubles_in_plain_decimal() {
+    use super::super::value::ToXml;
+    let double = |v: f64, precision: Option<usize>| {
+        let options = WriteOptions {
+            double_precision: precision,
+            ..Default::default()
+        };
+        Value::Double(v).to_xml_with(&options)
+    };
+    assert_eq!(
+        "<value><double>0.0000001</double></value>",
+        double(1e-7, None)
+    );
+    assert_eq!(
+        "<value><double>100000000000000000000</double></value>",
+        double(1e20, None)
+    );
+    assert_eq!(
+        "<value><double>0.333</double></value>",
+        double(1.0 / 3.0, Some(3))
+    );
+    assert_eq!("<value><double>2.5</double></value>", double(2.5, Some(3)));
+    assert_eq!("<value><double>3</double></value>", double(3.0, Some(3)));
+    assert_eq!("<value><double>0</double></value>", double(-1e-7, Some(3)));
+    assert_eq!(
+        "<value><double>-12</double></value>",
+        double(-12.3, Some(0))
+    );
+}
+
+#[test]
+fn reads_wrapped_and_url_safe_base64() {
+    let data = "<base64>\n  AAECAwQF\r\n  BgcICQ==\n</base64>";
+    assert_eq!(
+        Value::Base64((0..10).collect()),
+        parse::xml(data.as_bytes()).unwrap()
+    );
+
+    let data = "<base64>-_8</base64>";
+    assert!(parse::xml(data.as_bytes()).is_err());
+    let options = ParseOptions {
+        base64_url_safe: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        Value::Base64(vec![0xFB, 0xFF]),
+        parse::xml_with(data.as_bytes(), &options).unwrap()
+    );
+    let data = "<base64>+/8=</base64>";
+    assert_eq!(
+        Value::Base64(vec![0xFB, 0xFF]),
+        parse::xml_with(data.as_bytes(), &options).unwrap()
+    );
+}
+
+#[test]
+fn keeps_lazy_base64_encoded() {
+    use super::super::value::ToXml;
+    let options = ParseOptions {
+        lazy_base64: true,
+        ..Default::default()
+    };
+    let data = "<base64>\n  AAECAwQF\n  BgcICQ==\n</base64>";
+    let value = parse::xml_with(data.as_bytes(), &options).unwrap();
+    assert_eq!(Value::Base64Text("AAECAwQFBgcICQ==".into()), value);
+    assert_eq!(
+        "<value><base64>AAECAwQFBgcICQ==</base64></value>",
+        value.to_xml()
+    );
+    assert_eq!(
+        from_value::<Vec<u8>>(value.clone()).unwrap(),
+        (0..10).collect::<Vec<u8>>()
+    );
+    assert_eq!(value.into_bytes().unwrap(), (0..10).collect::<Vec<u8>>());
+
+    for bad in &["AAE", "AA=E", "AAE!", "AB==", "AAF="] {
+        let data = format!("<base64>{}</base64>", bad);
+        assert!(
+            parse::xml_with(data.as_bytes(), &options).is_err(),
+            "{}",
+            bad
+        );
+    }
+}
+
+#[test]
+fn compares_base64_the_same_with_or_without_lazy_base64() {
+    let lazy = ParseOptions {
+        lazy_base64: true,
+        ..Default::default()
+    };
+    let data = "<array><data><value><base64>aGVsbG8=</base64></value></data></array>";
+    let eager = parse::xml(data.as_bytes()).unwrap();
+    let kept = parse::xml_with(data.as_bytes(), &lazy).unwrap();
+    assert_eq!(eager, kept);
+    assert_eq!(kept, eager);
+    assert_eq!(Value::Base64(b"hello".to_vec()), kept[0]);
+    assert_ne!(Value::Base64(b"world".to_vec()), kept[0]);
+    assert_ne!(Value::String("aGVsbG8=".into()), kept[0]);
+}
+
+#[test]
+fn writes_wrapped_base64() {
+    use super::super::value::ToXml;
+    let value = Value::Base64((0..10).collect());
+    let options = WriteOptions {
+        base64_line_length: Some(8),
+        ..Default::default()
+    };
+    assert_eq!(
+        "<value><base64>AAECAwQF\nBgcICQ==</base64></value>",
+        value.to_xml_with(&options)
+    );
+    assert_eq!(
+        "<value><base64>AAECAwQFBgcICQ==</base64></value>",
+        value.to_xml()
+    );
+    ser_and_de(value);
+}
+
+#[test]
+fn round_trips_long_blobs_and_escaped_strings() {
+    use super::super::value::ToXml;
+    let blob = Value::Base64((0..5000).map(|i| (i * 7) as u8).collect());
+    assert_eq!(blob, parse::xml(blob.to_xml().as_bytes()).unwrap());
+    let text: String = (0..500)
+        .map(|i| ["a<b", "&", "c>", "plain text"][i % 4])
+        .collect();
+    let xml = Value::String(text.clone()).to_xml();
+    let content = xml
+        .trim_start_matches("<value><string>")
+        .trim_end_matches("</string></value>");
+    assert!(!content.contains(['<', '>']));
+    assert_eq!(Value::String(text), parse::xml(xml.as_bytes()).unwrap());
+}
+
+#[test]
+fn reads_apache_extensions() {
+    let data = r#"<?xml version="1.0"?>
+<methodResponse xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">
+  <params>
+    <param><value><ex:i8>5000000000</ex:i8></value></param>
+    <param><value><ex:nil/></value></param>
+    <param><value><ex:dateTime>2017-01-01T10:00:00.000+0100</ex:dateTime></value></param>
+    <param><value><ex:serializable>rO0ABQ==</ex:serializable></value></param>
+    <param><value><i8>-7</i8></value></param>
+    <param><value><nil/></value></param>
+  </params>
+</methodResponse>"#;
+    assert_eq!(
+        Ok(vec![
+            Value::I8(5_000_000_000),
+            Value::Nil,
+            Value::DateTime("2017-01-01T10:00:00.000+0100".into()),
+            Value::Base64(vec![0xAC, 0xED, 0x00, 0x05]),
+            Value::I8(-7),
+            Value::Nil,
+        ]),
+        parse::response(data.as_bytes()).expect(BAD_DATA)
+    );
+
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(parse::xml_with("<nil/>".as_bytes(), &options).is_err());
+    assert!(parse::xml("<nil>0</nil>".as_bytes()).is_err());
+}
+
+#[test]
+fn writes_apache_extensions() {
+    use super::super::value::ToXml;
+    let value = Value::Array(vec![Value::I8(5_000_000_000), Value::Nil]);
+    assert_eq!(
+        "<value><array><data><value><i8>5000000000</i8></value>\
+         <value><nil/></value></data></array></value>",
+        value.to_xml()
+    );
+    let options = WriteOptions {
+        apache_extensions: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        "<value xmlns:ex=\"http://ws.apache.org/xmlrpc/namespaces/extensions\">\
+         <array><data><value><ex:i8>5000000000</ex:i8></value>\
+         <value><ex:nil/></value></data></array></value>",
+        value.to_xml_with(&options)
+    );
+    let response: Response = Ok(vec![value.clone()]);
+    assert_eq!(
+        Ok(vec![value.clone()]),
+        parse::response(response.to_xml_with(&options).as_bytes()).expect(BAD_DATA)
+    );
+    ser_and_de(value);
+}
+
+#[test]
+fn reads_word_booleans() {
+    use super::super::value::ToXml;
+    for &(data, expected) in &[
+        ("<boolean>true</boolean>", true),
+        ("<boolean> TRUE </boolean>", true),
+        ("<boolean>False</boolean>", false),
+        ("<boolean>false</boolean>", false),
+    ] {
+        assert_eq!(Value::Bool(expected), parse::xml(data.as_bytes()).unwrap());
+    }
+    assert!(parse::xml("<boolean>yes</boolean>".as_bytes()).is_err());
+    assert_eq!(
+        "<value><boolean>1</boolean></value>",
+        Value::Bool(true).to_xml()
+    );
+}
+
+#[test]
+fn reads_nonconforming_faults() {
+    let fault = |content: &str| {
+        format!(
+            "<methodResponse><fault><value>{}</value></fault></methodResponse>",
+            content
+        )
+    };
+    let data = fault(
+        "<struct><member><name>faultCode</name><value><string>42</string></value></member>\
+         <member><name>faultString</name><value>Oops</value></member></struct>",
+    );
+    assert_eq!(
+        Err(Fault::new(42, "Oops")),
+        parse::response(data.as_bytes()).unwrap()
+    );
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(parse::response_with(data.as_bytes(), &options).is_err());
+
+    let data = fault("<struct><member><name>error</name><value>Broken</value></member></struct>");
+    assert_eq!(
+        Err(Fault {
+            code: 0,
+            message: r#"{"error": "Broken"}"#.into(),
+            detail: Some(xmlrpc_value!({"error": "Broken"})),
+        }),
+        parse::response(data.as_bytes()).unwrap()
+    );
+    let data = fault("<string>Broken</string>");
+    assert_eq!(
+        Err(Fault {
+            code: 0,
+            message: r#""Broken""#.into(),
+            detail: Some(Value::String("Broken".into())),
+        }),
+        parse::response(data.as_bytes()).unwrap()
+    );
+}
+
+#[test]
+fn keeps_malformed_faults_in_strict_errors() {
+    use super::super::value::ToXml;
+
+    let data = "<methodResponse><fault><value><struct><member><name>error</name>\
+                <value>Broken</value></member></struct></value></fault></methodResponse>";
+    let options = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    match parse::response_with(data.as_bytes(), &options)
+        .unwrap_err()
+        .0
+    {
+        error::ErrorKind::MalformedFault(value, xml) => {
+            assert_eq!(xmlrpc_value!({"error": "Broken"}), value);
+            assert_eq!(data, xml);
+        }
+        err => panic!("unexpected error {}", err),
+    }
+    match stream::ParamReader::with_options(data.as_bytes(), options).err() {
+        Some(error::Error(error::ErrorKind::MalformedFault(value, xml), _)) => {
+            assert_eq!(value.to_xml(), xml);
+        }
+        err => panic!("unexpected result {:?}", err),
+    }
+}
+
+#[test]
+fn validates_method_names() {
+    let call = |name: &str| format!("<methodCall><methodName>{}</methodName></methodCall>", name);
+    assert!(parse::call(call("system.listMethods").as_bytes()).is_ok());
+    assert!(parse::call(call("ns:grüß/x").as_bytes()).is_ok());
+    for name in &["a&lt;b&gt;", "two words", "tab&#9;", "quo&quot;te"] {
+        assert!(
+            parse::call(call(name).as_bytes()).is_err(),
+            "accepted {}",
+            name
+        );
+    }
+    let options = ParseOptions {
+        method_names: MethodNamePolicy::Any,
+        ..Default::default()
+    };
+    assert_eq!(
+        "two words",
+        parse::call_with(call("two words").as_bytes(), &options)
+            .unwrap()
+            .name
+    );
+
+    assert!(Call::new("echo", vec![]).is_ok());
+    assert!(Call::new("echo</methodName>", vec![]).is_err());
+}
+
+#[test]
+fn writes_canonical_xml() {
+    use super::super::value::ToXml;
+    let value = xmlrpc_value!({
+        "zeta": (-0.0),
+        "alpha": (Value::DateTime("1998-07-17T14:08:55+02:00".into())),
+        "mid": (Value::Base64(vec![0; 60])),
+    });
+    let options = WriteOptions {
+        base64_line_length: Some(76),
+        canonical: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        format!(
+            "<value><struct>\
+             <member><name>alpha</name><value><dateTime.iso8601>19980717T14:08:55+02:00</dateTime.iso8601></value></member>\
+             <member><name>mid</name><value><base64>{}</base64></value></member>\
+             <member><name>zeta</name><value><double>0</double></value></member>\
+             </struct></value>",
+            "A".repeat(80)
+        ),
+        value.to_xml_with(&options)
+    );
+}
+
+#[test]
+fn writes_members_sorted() {
+    use super::super::value::ToXml;
+    let value = xmlrpc_value!({"b": 1.5, "c": {"z": 1, "y": 2}, "a": 0});
+    let options = WriteOptions {
+        declaration: None,
+        sort_members: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        "<value><struct>\
+         <member><name>a</name><value><i4>0</i4></value></member>\
+         <member><name>b</name><value><double>1.5</double></value></member>\
+         <member><name>c</name><value><struct>\
+         <member><name>y</name><value><i4>2</i4></value></member>\
+         <member><name>z</name><value><i4>1</i4></value></member>\
+         </struct></value></member>\
+         </struct></value>",
+        value.to_xml_with(&options)
+    );
+}
+
+#[test]
+fn omits_empty_params() {
+    use super::super::value::ToXml;
+    let options = WriteOptions {
+        declaration: None,
+        omit_empty_params: true,
+        ..Default::default()
+    };
+    let call = Call {
+        name: "ping".into(),
+        params: vec![],
+    };
+    let response: Response = Ok(vec![]);
+    assert_eq!(
+        "<methodCall><methodName>ping</methodName></methodCall>",
+        call.to_xml_with(&options)
+    );
+    assert_eq!(
+        "<methodResponse></methodResponse>",
+        response.to_xml_with(&options)
+    );
+    assert_eq!(
+        "<methodResponse><params><param><value><i4>1</i4></value></param></params></methodResponse>",
+        Ok::<_, Fault>(vec![Value::Int(1)]).to_xml_with(&options)
+    );
+
+    assert_eq!(
+        call,
+        parse::call(call.to_xml_with(&options).as_bytes()).unwrap()
+    );
+    let data = response.to_xml_with(&options);
+    assert_eq!(response, parse::response(data.as_bytes()).unwrap());
+    let strict = ParseOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(parse::response_with(data.as_bytes(), &strict).is_err());
+}
+
+#[test]
+fn writes_the_chosen_int_tag() {
+    use super::super::value::ToXml;
+    let options = WriteOptions {
+        int_tag: IntTag::Int,
+        ..Default::default()
+    };
+    assert_eq!("<value><i4>3</i4></value>", Value::Int(3).to_xml());
+    assert_eq!(
+        "<value><int>3</int></value>",
+        Value::Int(3).to_xml_with(&options)
+    );
+}
+
+#[test]
+fn writes_untyped_strings() {
+    use super::super::value::ToXml;
+    let options = WriteOptions {
+        untyped_strings: true,
+        ..Default::default()
+    };
+    let value = xmlrpc_value!([" a < b ", ""]);
+    let xml = value.to_xml_with(&options);
+    assert_eq!(
+        "<value><array><data><value> a &lt; b </value><value></value></data></array></value>",
+        xml
+    );
+    assert_eq!(value, parse::xml(xml.as_bytes()).unwrap());
+}
+
+#[test]
+fn writes_empty_strings_as_chosen() {
+    use super::super::value::ToXml;
+    let write = |empty_strings, untyped_strings| {
+        let options = WriteOptions {
+            empty_strings,
+            untyped_strings,
+            ..Default::default()
+        };
+        let xml = Value::from("").to_xml_with(&options);
+        assert_eq!(Value::from(""), parse::xml(xml.as_bytes()).unwrap());
+        xml
+    };
+    assert_eq!(
+        "<value><string></string></value>",
+        write(EmptyStrings::Pair, false)
+    );
+    assert_eq!(
+        "<value><string/></value>",
+        write(EmptyStrings::SelfClosing, false)
+    );
+    assert_eq!("<value/>", write(EmptyStrings::BareValue, false));
+    assert_eq!("<value></value>", write(EmptyStrings::Pair, true));
+    assert_eq!("<value/>", write(EmptyStrings::SelfClosing, true));
+    assert_eq!(
+        "<value><string>a</string></value>",
+        Value::from("a").to_xml_with(&WriteOptions {
+            empty_strings: EmptyStrings::BareValue,
+            ..Default::default()
+        })
+    );
+}
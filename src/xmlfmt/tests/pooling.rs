@@ -0,0 +1,35 @@
+use test_util;
+use {Client, PoolStats, Server};
+
+#[test]
+fn counts_connections_kept_alive_between_calls() {
+    let mut server = Server::new();
+    server.register_simple("double", |(x,): (i32,)| Ok(x * 2));
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+    for x in 0..3 {
+        assert_eq!(
+            x * 2,
+            client
+                .call::<_, _, i32>(server.url(), "double", (x,))
+                .unwrap()
+        );
+    }
+    let stats = client.pool_stats();
+    let stats = stats[&format!("127.0.0.1:{}", server.addr().port())];
+    assert_eq!(
+        PoolStats {
+            open: 1,
+            handshakes: 1,
+            requests: 3,
+        },
+        stats
+    );
+    assert!((stats.reuse_rate() - 2.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn counts_nothing_before_the_first_call() {
+    assert!(Client::new().unwrap().pool_stats().is_empty());
+    assert_eq!(0.0, PoolStats::default().reuse_rate());
+}
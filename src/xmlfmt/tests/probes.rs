@@ -0,0 +1,122 @@
+use hyper;
+use hyper::method::Method;
+use hyper::status::StatusCode;
+use test_util::{self, TestServer};
+use Server;
+
+fn request(
+    server: &TestServer,
+    method: Method,
+    headers: &[(&str, &str)],
+) -> hyper::client::Response {
+    let mut request_headers = hyper::header::Headers::new();
+    for &(name, value) in headers {
+        request_headers.set_raw(name.to_owned(), vec![value.as_bytes().to_vec()]);
+    }
+    hyper::Client::new()
+        .request(method, server.url().as_ref())
+        .headers(request_headers)
+        .send()
+        .unwrap()
+}
+
+fn header<'a>(response: &'a hyper::client::Response, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .get_raw(name)
+        .map(|values| ::std::str::from_utf8(&values[0]).unwrap())
+}
+
+#[test]
+fn answers_options_and_head() {
+    let mut server = Server::new();
+    server.set_basic_auth("admin", "secret");
+    let server = test_util::spawn(server);
+
+    let response = request(&server, Method::Options, &[]);
+    assert_eq!(StatusCode::NoContent, response.status);
+    assert_eq!(Some("POST, OPTIONS"), header(&response, "Allow"));
+
+    let response = request(&server, Method::Head, &[]);
+    assert_eq!(StatusCode::Ok, response.status);
+    assert_eq!(
+        Some("text/xml; charset=utf-8"),
+        header(&response, "Content-Type")
+    );
+}
+
+#[test]
+fn rejects_probes_when_told_to() {
+    let mut server = Server::new();
+    server.set_answer_probes(false);
+    let server = test_util::spawn(server);
+    let response = request(&server, Method::Options, &[]);
+    assert_eq!(StatusCode::BadRequest, response.status);
+}
+
+#[test]
+fn answers_cors_preflights_from_allowed_origins() {
+    let mut server = Server::new();
+    server.allow_cors_origin("https://example.com");
+    let server = test_util::spawn(server);
+    let preflight = |origin| {
+        request(
+            &server,
+            Method::Options,
+            &[
+                ("Origin", origin),
+                ("Access-Control-Request-Method", "POST"),
+                (
+                    "Access-Control-Request-Headers",
+                    "content-type, x-request-id",
+                ),
+            ],
+        )
+    };
+
+    let response = preflight("https://example.com");
+    assert_eq!(StatusCode::NoContent, response.status);
+    assert_eq!(
+        Some("https://example.com"),
+        header(&response, "Access-Control-Allow-Origin")
+    );
+    assert_eq!(
+        Some("true"),
+        header(&response, "Access-Control-Allow-Credentials")
+    );
+    assert_eq!(
+        Some("content-type, x-request-id"),
+        header(&response, "Access-Control-Allow-Headers")
+    );
+    assert_eq!(
+        Some("POST, OPTIONS"),
+        header(&response, "Access-Control-Allow-Methods")
+    );
+
+    let response = preflight("https://evil.example.com");
+    assert_eq!(StatusCode::NoContent, response.status);
+    assert_eq!(None, header(&response, "Access-Control-Allow-Origin"));
+    assert_eq!(None, header(&response, "Access-Control-Allow-Methods"));
+}
+
+#[test]
+fn allows_any_origin_without_credentials() {
+    let mut server = Server::new();
+    server.allow_cors_origin("*");
+    let server = test_util::spawn(server);
+
+    let response = request(
+        &server,
+        Method::Options,
+        &[
+            ("Origin", "https://example.com"),
+            ("Access-Control-Request-Method", "POST"),
+        ],
+    );
+    assert_eq!(Some("*"), header(&response, "Access-Control-Allow-Origin"));
+    assert_eq!(None, header(&response, "Access-Control-Allow-Credentials"));
+    assert_eq!(
+        Some("POST, OPTIONS"),
+        header(&response, "Access-Control-Allow-Methods")
+    );
+}
@@ -0,0 +1,108 @@
+use super::raw_http::refused;
+use proxy::Proxy;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+use test_util;
+use {Call, Client, Fault, Server, Value};
+
+#[test]
+fn forwards_and_rewrites_calls() {
+    let mut upstream = Server::new();
+    upstream.register_simple("add", |(a, b): (i32, i32)| Ok(a + b));
+    let upstream = test_util::spawn(upstream);
+
+    let mut proxy = Proxy::new(upstream.url().clone()).unwrap();
+    proxy.rewrite_calls(|call| {
+        if call.name == "sum" {
+            call.name = "add".into();
+        }
+    });
+    proxy.rewrite_responses(|call, response| {
+        if call.name == "add" {
+            if let Ok(ref mut params) = *response {
+                params.push(Value::String("checked".into()));
+            }
+        }
+    });
+    let exchanges = Arc::new(Mutex::new(Vec::new()));
+    {
+        let exchanges = Arc::clone(&exchanges);
+        proxy.on_exchange(move |call, response| {
+            exchanges
+                .lock()
+                .unwrap()
+                .push((call.name.clone(), response.clone()));
+        });
+    }
+    let mut server = Server::new();
+    proxy.serve(&mut server);
+    let server = test_util::spawn(server);
+
+    let mut client = Client::new().unwrap();
+    let sum = client
+        .call_value(server.url(), "sum", vec![Value::Int(2), Value::Int(3)])
+        .unwrap();
+    assert_eq!(
+        Ok(vec![Value::Int(5), Value::String("checked".into())]),
+        sum
+    );
+    let missing = client.call_value(server.url(), "missing", vec![]).unwrap();
+    assert_eq!(404, missing.unwrap_err().code);
+
+    let exchanges = exchanges.lock().unwrap();
+    assert_eq!(2, exchanges.len());
+    assert_eq!(("add".to_string(), sum), exchanges[0]);
+    assert_eq!("missing", exchanges[1].0);
+}
+
+#[test]
+fn reports_unreachable_upstreams_as_faults() {
+    let proxy = Proxy::new(refused()).unwrap();
+    let response = proxy.forward(Call {
+        name: "add".into(),
+        params: vec![],
+    });
+    assert_eq!(Fault::transport_error("").code, response.unwrap_err().code);
+}
+
+#[test]
+fn forwards_calls_at_the_same_time() {
+    let arrived = Arc::new((Mutex::new(0), Condvar::new()));
+    let mut upstream = Server::new();
+    {
+        let arrived = Arc::clone(&arrived);
+        // Answers whether the other call arrived while this one waited for it.
+        upstream.register_simple("meet", move |()| {
+            let (ref count, ref changed) = *arrived;
+            let mut count = count.lock().unwrap();
+            *count += 1;
+            changed.notify_all();
+            let (count, _) = changed
+                .wait_timeout_while(count, Duration::from_secs(10), |count| *count < 2)
+                .unwrap();
+            Ok(*count == 2)
+        });
+    }
+    upstream.set_workers(2);
+    let upstream = test_util::spawn(upstream);
+
+    let proxy = Proxy::new(upstream.url().clone()).unwrap();
+    let met = thread::scope(|scope| {
+        let calls = (0..2)
+            .map(|_| {
+                scope.spawn(|| {
+                    proxy.forward(Call {
+                        name: "meet".into(),
+                        params: vec![],
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        calls
+            .into_iter()
+            .map(|call| call.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(vec![Ok(vec![Value::Bool(true)]); 2], met);
+}
@@ -0,0 +1,41 @@
+use super::super::{Query, Value};
+
+#[test]
+fn selects_values_by_path() {
+    let value = xmlrpc_value!([
+        "status",
+        {
+            "web": {"port": 80, "hosts": ["a", "b"]},
+            "odd key": {"port": 443}
+        }
+    ]);
+    let select = |query: &str| value.select(query).unwrap();
+    assert_eq!(vec![&Value::from("status")], select("[0]"));
+    assert_eq!(vec![&Value::Int(80)], select("$[1].web.port"));
+    assert_eq!(vec![&Value::Int(443)], select("[1][\"odd key\"].port"));
+    assert_eq!(vec![&Value::from("b")], select("[1].web.hosts[1]"));
+    assert_eq!(2, select("[1].web.hosts[*]").len());
+    assert!(select("[1].web.missing").is_empty());
+    assert!(select("[0].port").is_empty());
+
+    let mut ports = select("..port");
+    ports.sort_by_key(|port| port.as_i64());
+    assert_eq!(vec![&Value::Int(80), &Value::Int(443)], ports);
+    assert_eq!(2, select("[1].*.port").len());
+    assert_eq!(vec![&value], select("$"));
+
+    let query: Query = "..hosts[0]".parse().unwrap();
+    assert_eq!(vec![&Value::from("a")], query.select(&value));
+}
+
+#[test]
+fn rejects_invalid_queries() {
+    for query in &["$port", "[1", "[x]", "a..", "[\"open]", "a.[\"b\"x]"] {
+        let err = Query::parse(query).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Invalid value query"),
+            "{}",
+            query
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! A bare HTTP server, for checking what the client sends and how it takes replies `Server`
+//! can't make.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use Url;
+
+pub struct Request {
+    /// Header lines with lowercased names, like `content-type: text/xml`.
+    pub headers: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let prefix = format!("{}:", name.to_lowercase());
+        self.headers
+            .iter()
+            .find(|header| header.starts_with(&prefix))
+            .map(|header| header[prefix.len()..].trim())
+    }
+}
+
+/// A whole response, closing the connection after it.
+pub fn reply(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut reply = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        reply.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    reply.push_str("\r\n");
+    let mut reply = reply.into_bytes();
+    reply.extend_from_slice(body);
+    reply
+}
+
+pub fn xml_reply(value: &str) -> Vec<u8> {
+    let body = format!(
+        "<?xml version=\"1.0\"?><methodResponse><params><param><value>{}</value></param>\
+         </params></methodResponse>",
+        value
+    );
+    reply("200 OK", &[("Content-Type", "text/xml")], body.as_bytes())
+}
+
+/// A URL nothing listens on, so calls to it fail to connect.
+pub fn refused() -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}/", listener.local_addr().unwrap())
+        .parse()
+        .unwrap()
+}
+
+/// Answers a connection with each of the replies in turn, returning the requests.
+pub fn serve(replies: Vec<Vec<u8>>) -> (Url, thread::JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let handle = thread::spawn(move || {
+        let mut requests = Vec::new();
+        for reply in replies {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut headers = Vec::new();
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim();
+                if line.is_empty() {
+                    break;
+                }
+                let (name, value) = line.split_at(line.find(':').unwrap());
+                headers.push(format!("{}{}", name.to_lowercase(), value));
+            }
+            let mut request = Request {
+                headers,
+                body: Vec::new(),
+            };
+            let length = request
+                .header("content-length")
+                .map_or(0, |length| length.parse().unwrap());
+            request.body.resize(length, 0);
+            reader.read_exact(&mut request.body).unwrap();
+            reader.get_mut().write_all(&reply).unwrap();
+            requests.push(request);
+        }
+        requests
+    });
+    (url, handle)
+}
@@ -0,0 +1,76 @@
+use super::super::value::{INVALID_PARAMS, REPLAYED_CALL};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use test_util::{self, TestServer};
+use {Client, Server, Value};
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn transfer_server(transfers: &Arc<AtomicUsize>) -> TestServer {
+    let mut server = Server::new();
+    let count = Arc::clone(transfers);
+    server.register_value("transfer", move |_| {
+        count.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![Value::Bool(true)])
+    });
+    server.register_simple("read", |()| Ok(true));
+    server.protect_from_replays("transfer");
+    test_util::spawn(server)
+}
+
+fn fault_code(client: &mut Client, server: &TestServer, params: Vec<Value>) -> Option<i32> {
+    client
+        .call_value(server.url(), "transfer", params)
+        .unwrap()
+        .err()
+        .map(|fault| fault.code)
+}
+
+#[test]
+fn rejects_replayed_nonces_in_members() {
+    let transfers = Arc::new(AtomicUsize::new(0));
+    let server = transfer_server(&transfers);
+    let mut client = Client::new().unwrap();
+    let params = vec![xmlrpc_value!({"nonce": "n-1", "timestamp": (now() as i32)})];
+
+    assert_eq!(None, fault_code(&mut client, &server, params.clone()));
+    assert_eq!(
+        Some(REPLAYED_CALL),
+        fault_code(&mut client, &server, params)
+    );
+    let stale = vec![xmlrpc_value!({"nonce": "n-2", "timestamp": (now() as i32 - 3600)})];
+    assert_eq!(Some(REPLAYED_CALL), fault_code(&mut client, &server, stale));
+    let unsigned = vec![xmlrpc_value!({"amount": 5})];
+    assert_eq!(
+        Some(INVALID_PARAMS),
+        fault_code(&mut client, &server, unsigned)
+    );
+    assert_eq!(1, transfers.load(Ordering::SeqCst));
+
+    // Other methods aren't protected.
+    assert!(client.call::<_, _, bool>(server.url(), "read", ()).unwrap());
+    assert!(client.call::<_, _, bool>(server.url(), "read", ()).unwrap());
+}
+
+#[test]
+fn reads_nonces_from_headers() {
+    let transfers = Arc::new(AtomicUsize::new(0));
+    let server = transfer_server(&transfers);
+    let mut client = Client::new().unwrap();
+    client.set_pre_send_hook(|_, _, headers| {
+        headers.set_raw("X-Nonce", vec![b"header-nonce".to_vec()]);
+        headers.set_raw("X-Timestamp", vec![now().to_string().into_bytes()]);
+    });
+    assert_eq!(None, fault_code(&mut client, &server, Vec::new()));
+    assert_eq!(
+        Some(REPLAYED_CALL),
+        fault_code(&mut client, &server, Vec::new())
+    );
+    assert_eq!(1, transfers.load(Ordering::SeqCst));
+}
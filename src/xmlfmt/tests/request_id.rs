@@ -0,0 +1,69 @@
+use hyper;
+use hyper::header::Headers;
+use server;
+use std::io::Read;
+use test_util::{self, TestServer};
+use Server;
+
+fn whoami_server() -> TestServer {
+    let mut server = Server::new();
+    server.register_simple("whoami", |()| Ok(server::request_id().unwrap_or_default()));
+    test_util::spawn(server)
+}
+
+/// Returns the ID the handler saw and the one sent back.
+fn call(server: &TestServer, headers: &[(&str, &str)]) -> (String, String) {
+    let mut raw = Headers::new();
+    for &(name, value) in headers {
+        raw.set_raw(name.to_owned(), vec![value.as_bytes().to_vec()]);
+    }
+    let call = "<?xml version=\"1.0\"?><methodCall><methodName>whoami</methodName></methodCall>";
+    let mut response = hyper::Client::new()
+        .post(server.url().as_ref())
+        .headers(raw)
+        .body(call)
+        .send()
+        .unwrap();
+    let echoed =
+        String::from_utf8(response.headers.get_raw("X-Request-Id").unwrap()[0].clone()).unwrap();
+    let mut body = String::new();
+    response.read_to_string(&mut body).unwrap();
+    let seen = body
+        .split("<string>")
+        .nth(1)
+        .and_then(|rest| rest.split("</string>").next())
+        .unwrap_or_default()
+        .to_owned();
+    (seen, echoed)
+}
+
+#[test]
+fn passes_on_the_given_request_id() {
+    let server = whoami_server();
+    let (seen, echoed) = call(&server, &[("X-Request-Id", "abc-123")]);
+    assert_eq!("abc-123", seen);
+    assert_eq!("abc-123", echoed);
+}
+
+#[test]
+fn takes_the_trace_id_of_a_traceparent() {
+    let server = whoami_server();
+    let traceparent = "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01";
+    let (seen, echoed) = call(&server, &[("traceparent", traceparent)]);
+    assert_eq!("4bf92f3577b34da6a3ce929d0e0e4736", seen);
+    assert_eq!(seen, echoed);
+    let zeros = "00-00000000000000000000000000000000-00f067aa0ba902b7-01";
+    let (seen, _) = call(&server, &[("traceparent", zeros)]);
+    assert_ne!("00000000000000000000000000000000", seen);
+}
+
+#[test]
+fn makes_up_missing_request_ids() {
+    let server = whoami_server();
+    let (first, echoed) = call(&server, &[]);
+    assert_eq!(first, echoed);
+    assert_eq!(32, first.len());
+    let (second, _) = call(&server, &[("X-Request-Id", "")]);
+    assert_ne!(first, second);
+    assert_eq!(None, server::request_id());
+}
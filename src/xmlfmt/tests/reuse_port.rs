@@ -0,0 +1,30 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use {Client, Server};
+
+fn echo_server() -> Server {
+    let mut server = Server::new();
+    server.register_simple("echo", |(x,): (i32,)| Ok(x));
+    server
+}
+
+#[test]
+fn shares_a_port_between_servers() {
+    let any_port = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+    let first = echo_server().bind_reuse_port(&any_port).unwrap();
+    let addr = first.local_addr();
+    let second = echo_server().bind_reuse_port(&addr).unwrap();
+    assert_eq!(addr, second.local_addr());
+    assert!(echo_server().bind(&addr).is_err());
+
+    let url = format!("http://{}/", addr).parse().unwrap();
+    let caller = ::std::thread::spawn(move || {
+        let mut client = Client::new().unwrap();
+        client.call::<_, _, i32>(&url, "echo", (7,)).unwrap()
+    });
+    while !caller.is_finished() {
+        first.poll_timeout(Duration::from_millis(10));
+        second.poll_timeout(Duration::from_millis(10));
+    }
+    assert_eq!(7, caller.join().unwrap());
+}
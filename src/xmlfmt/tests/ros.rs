@@ -0,0 +1,186 @@
+use super::super::value::ToXml;
+use super::super::{Call, Fault, Profile, Value};
+use ros::{self, Master, Slave, SlaveApi, SystemState, Topics};
+use std::sync::Mutex;
+use test_util;
+use Server;
+
+fn fake_master() -> Server {
+    let mut server = Server::new();
+    server.register_value("lookupNode", |params| {
+        assert_eq!(Some("/caller"), params[0].as_str());
+        Ok(vec![match params[1].as_str() {
+            Some("/talker") => xmlrpc_value!([1, "node api", "http://talker:1234/"]),
+            _ => xmlrpc_value!([-1, "unknown node [/nobody]", ""]),
+        }])
+    });
+    server.register_value("getSystemState", |_| {
+        Ok(vec![xmlrpc_value!([
+            1,
+            "current system state",
+            [
+                [["/chatter", ["/talker"]]],
+                [["/chatter", ["/listener"]]],
+                []
+            ]
+        ])])
+    });
+    server.register_value("setParam", |params| {
+        assert_eq!(
+            xmlrpc_value!(["/caller", "/rate", 10.5]),
+            Value::Array(params)
+        );
+        Ok(vec![xmlrpc_value!([1, "parameter /rate set", 0])])
+    });
+    server
+}
+
+#[test]
+fn calls_the_master() {
+    let server = test_util::spawn(fake_master());
+    let mut master = Master::new(server.url().clone(), "/caller").unwrap();
+    assert_eq!(
+        Ok("http://talker:1234/".to_string()),
+        master.lookup_node("/talker").unwrap()
+    );
+    assert_eq!(
+        Err(Fault::new(ros::ERROR, "unknown node [/nobody]")),
+        master.lookup_node("/nobody").unwrap()
+    );
+    assert_eq!(
+        Ok(SystemState {
+            publishers: vec![("/chatter".into(), vec!["/talker".into()])],
+            subscribers: vec![("/chatter".into(), vec!["/listener".into()])],
+            services: vec![],
+        }),
+        master.get_system_state().unwrap()
+    );
+    assert_eq!(Ok(()), master.set_param("/rate", &10.5).unwrap());
+}
+
+#[derive(Default)]
+struct Node {
+    publishers: Mutex<Vec<String>>,
+}
+
+impl SlaveApi for Node {
+    fn get_master_uri(&self, _: String) -> Result<String, String> {
+        Ok("http://master:11311/".into())
+    }
+
+    fn shutdown(&self, _: String, message: String) -> Result<(), String> {
+        Err(format!("Refusing to shut down: {}", message))
+    }
+
+    fn get_subscriptions(&self, _: String) -> Result<Topics, String> {
+        Ok(vec![("/chatter".into(), "std_msgs/String".into())])
+    }
+
+    fn get_publications(&self, _: String) -> Result<Topics, String> {
+        Ok(vec![])
+    }
+
+    fn param_update(&self, _: String, _: String, _: Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn publisher_update(
+        &self,
+        _: String,
+        _: String,
+        publishers: Vec<String>,
+    ) -> Result<(), String> {
+        *self.publishers.lock().unwrap() = publishers;
+        Ok(())
+    }
+
+    fn request_topic(
+        &self,
+        _: String,
+        topic: String,
+        protocols: Vec<Vec<Value>>,
+    ) -> Result<Vec<Value>, String> {
+        match protocols.first().and_then(|p| p.first()) {
+            Some(Value::String(protocol)) if protocol == "TCPROS" => {
+                Ok(vec!["TCPROS".into(), topic.into(), Value::Int(1234)])
+            }
+            _ => Err("No supported protocol".into()),
+        }
+    }
+}
+
+#[test]
+fn serves_the_slave_api() {
+    let mut server = Server::new();
+    server.set_profile(Profile::ros());
+    ros::register_slave(&mut server, Node::default());
+    let server = test_util::spawn(server);
+    let mut slave = Slave::new(server.url().clone(), "/caller").unwrap();
+    assert_eq!(
+        Ok("http://master:11311/".to_string()),
+        slave.get_master_uri().unwrap()
+    );
+    assert_eq!(Ok(std::process::id() as i32), slave.get_pid().unwrap());
+    assert_eq!(
+        Err(Fault::new(ros::FAILURE, "Refusing to shut down: now")),
+        slave.shutdown("now").unwrap()
+    );
+    assert_eq!(
+        Ok(vec![(
+            "/chatter".to_string(),
+            "std_msgs/String".to_string()
+        )]),
+        slave.get_subscriptions().unwrap()
+    );
+    assert_eq!(
+        Ok(()),
+        slave
+            .publisher_update("/chatter", &["http://talker:1234/".into()])
+            .unwrap()
+    );
+    assert_eq!(
+        Ok(vec!["TCPROS".into(), "/chatter".into(), Value::Int(1234)]),
+        slave
+            .request_topic("/chatter", &[vec!["TCPROS".into()]])
+            .unwrap()
+    );
+    assert_eq!(
+        ros::FAILURE,
+        slave
+            .request_topic("/chatter", &[vec!["UDPROS".into()]])
+            .unwrap()
+            .unwrap_err()
+            .code
+    );
+    assert_eq!(
+        ros::FAILURE,
+        slave.get_bus_stats().unwrap().unwrap_err().code
+    );
+}
+
+#[test]
+fn replies_to_bad_params_with_errors() {
+    let mut server = Server::new();
+    ros::register_slave(&mut server, Node::default());
+    let server = test_util::spawn(server);
+    let response = ::Client::new()
+        .unwrap()
+        .call_value(server.url(), "getPid", vec![])
+        .unwrap()
+        .unwrap();
+    assert_eq!(Some(ros::ERROR as i64), response[0][0].as_i64());
+}
+
+#[test]
+fn writes_what_xmlrpcpp_reads() {
+    let profile = Profile::ros();
+    let call = Call {
+        name: "setParam".into(),
+        params: vec![Value::from("/caller"), Value::from(""), Value::Int(7)],
+    };
+    assert!(call.to_xml_with(&profile.write).contains(
+        "<params><param><value>/caller</value></param><param><value></value></param>\
+         <param><value><i4>7</i4></value></param></params>"
+    ));
+    assert!(super::super::to_value_with(&(1i64 << 40), &profile.serialize).is_err());
+}
@@ -0,0 +1,30 @@
+use super::super::value::ToXml;
+use super::super::{parse, Call, Response, Value, WriteOptions};
+
+// Big numbers are only written as themselves with the extensions.
+fn options() -> WriteOptions {
+    WriteOptions {
+        apache_extensions: true,
+        ..Default::default()
+    }
+}
+
+proptest! {
+    #[test]
+    fn values_read_back_the_same(value: Value) {
+        let xml = value.to_xml_with(&options());
+        prop_assert_eq!(value, parse::xml(xml.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn calls_read_back_the_same(call: Call) {
+        let xml = call.to_xml_with(&options());
+        prop_assert_eq!(call, parse::call(xml.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn responses_read_back_the_same(response: Response) {
+        let xml = response.to_xml_with(&options());
+        prop_assert_eq!(response, parse::response(xml.as_bytes()).unwrap());
+    }
+}
@@ -0,0 +1,44 @@
+use super::super::{Fault, Params, Response, Value};
+use test_util;
+use {Client, Server};
+
+fn add(params: Params) -> Response {
+    match *params.as_slice() {
+        [Value::Int(a), Value::Int(b)] => Ok(vec![Value::Int(a + b)]),
+        _ => Err(Fault::invalid_params("Expected two ints")),
+    }
+}
+
+fn echo(params: Params) -> Response {
+    Ok(params)
+}
+
+xmlrpc_routes! {
+    struct Methods {
+        ADD = "math.add" => add,
+        ECHO = "echo" => echo,
+    }
+}
+
+#[test]
+fn dispatches_declared_methods() {
+    assert_eq!("math.add", Methods::ADD);
+    assert_eq!(&["math.add", "echo"], Methods::ALL);
+    assert_eq!(
+        Some(Ok(vec![Value::Int(5)])),
+        Methods::dispatch(Methods::ADD, vec![Value::Int(2), Value::Int(3)])
+    );
+    assert_eq!(None, Methods::dispatch("math.sub", vec![]));
+}
+
+#[test]
+fn registers_declared_methods() {
+    let mut server = Server::new();
+    Methods::register(&mut server);
+    let server = test_util::spawn(server);
+    let response = Client::new()
+        .unwrap()
+        .call_value(server.url(), Methods::ECHO, vec![Value::Int(3)])
+        .unwrap();
+    assert_eq!(Ok(vec![Value::Int(3)]), response);
+}
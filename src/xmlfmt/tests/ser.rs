@@ -1,59 +1,79 @@
 use super::super::ser::Serializer;
-use super::super::Value;
+use super::super::{
+    into_params, EnumRepresentation, IntegerPolicy, NonFinitePolicy, OptionRepresentation,
+    SerializeOptions, Value,
+};
 use serde::Serialize;
 use serde_bytes::Bytes;
 use std::collections::HashMap;
 
 #[test]
 fn writes_bool() {
-    assert_eq!(true.serialize(Serializer {}).unwrap(), Value::Bool(true));
-    assert_eq!(false.serialize(Serializer {}).unwrap(), Value::Bool(false));
+    assert_eq!(
+        true.serialize(Serializer::default()).unwrap(),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        false.serialize(Serializer::default()).unwrap(),
+        Value::Bool(false)
+    );
 }
 
 #[test]
 fn writes_integers_as_ints_or_strings_if_too_big() {
-    assert_eq!(200u8.serialize(Serializer {}).unwrap(), Value::Int(200));
     assert_eq!(
-        42_000u16.serialize(Serializer {}).unwrap(),
+        200u8.serialize(Serializer::default()).unwrap(),
+        Value::Int(200)
+    );
+    assert_eq!(
+        42_000u16.serialize(Serializer::default()).unwrap(),
         Value::Int(42_000)
     );
     assert_eq!(
-        4_200_000_000u32.serialize(Serializer {}).unwrap(),
+        4_200_000_000u32.serialize(Serializer::default()).unwrap(),
         Value::String("4200000000".into())
     );
     assert_eq!(
         10_000_000_000_000_000_000u64
-            .serialize(Serializer {})
+            .serialize(Serializer::default())
             .unwrap(),
         Value::String("10000000000000000000".into())
     );
-    assert_eq!((-42_i8).serialize(Serializer {}).unwrap(), Value::Int(-42));
     assert_eq!(
-        (-26_000_i16).serialize(Serializer {}).unwrap(),
+        (-42_i8).serialize(Serializer::default()).unwrap(),
+        Value::Int(-42)
+    );
+    assert_eq!(
+        (-26_000_i16).serialize(Serializer::default()).unwrap(),
         Value::Int(-26_000)
     );
     assert_eq!(
-        (-2_000_000_000_i32).serialize(Serializer {}).unwrap(),
+        (-2_000_000_000_i32)
+            .serialize(Serializer::default())
+            .unwrap(),
         Value::Int(-2000000000)
     );
     assert_eq!(
         (-8_000_000_000_000_000_000_i64)
-            .serialize(Serializer {})
+            .serialize(Serializer::default())
             .unwrap(),
         Value::String("-8000000000000000000".into())
     );
-    assert_eq!(42i8.serialize(Serializer {}).unwrap(), Value::Int(42));
     assert_eq!(
-        26_000i16.serialize(Serializer {}).unwrap(),
+        42i8.serialize(Serializer::default()).unwrap(),
+        Value::Int(42)
+    );
+    assert_eq!(
+        26_000i16.serialize(Serializer::default()).unwrap(),
         Value::Int(26_000)
     );
     assert_eq!(
-        2_000_000_000i32.serialize(Serializer {}).unwrap(),
+        2_000_000_000i32.serialize(Serializer::default()).unwrap(),
         Value::Int(2000000000)
     );
     assert_eq!(
         8_000_000_000_000_000_000i64
-            .serialize(Serializer {})
+            .serialize(Serializer::default())
             .unwrap(),
         Value::String("8000000000000000000".into())
     );
@@ -62,11 +82,11 @@ fn writes_integers_as_ints_or_strings_if_too_big() {
 #[test]
 fn writes_floats() {
     assert_eq!(
-        3.25f32.serialize(Serializer {}).unwrap(),
+        3.25f32.serialize(Serializer::default()).unwrap(),
         Value::Double(3.25f64)
     );
     assert_eq!(
-        3.25f64.serialize(Serializer {}).unwrap(),
+        3.25f64.serialize(Serializer::default()).unwrap(),
         Value::Double(3.25f64)
     );
 }
@@ -74,11 +94,11 @@ fn writes_floats() {
 #[test]
 fn writes_chars_as_strings() {
     assert_eq!(
-        'A'.serialize(Serializer {}).unwrap(),
+        'A'.serialize(Serializer::default()).unwrap(),
         Value::String("A".into())
     );
     assert_eq!(
-        ' '.serialize(Serializer {}).unwrap(),
+        ' '.serialize(Serializer::default()).unwrap(),
         Value::String(" ".into())
     );
 }
@@ -86,12 +106,12 @@ fn writes_chars_as_strings() {
 #[test]
 fn writes_strings() {
     assert_eq!(
-        "static string".serialize(Serializer {}).unwrap(),
+        "static string".serialize(Serializer::default()).unwrap(),
         Value::String("static string".into())
     );
     assert_eq!(
         String::from("string object")
-            .serialize(Serializer {})
+            .serialize(Serializer::default())
             .unwrap(),
         Value::String("string object".into())
     );
@@ -100,7 +120,9 @@ fn writes_strings() {
 #[test]
 fn writes_bytes_as_base64() {
     assert_eq!(
-        Bytes::new(b"0123").serialize(Serializer {}).unwrap(),
+        Bytes::new(b"0123")
+            .serialize(Serializer::default())
+            .unwrap(),
         Value::Base64(vec![48, 49, 50, 51])
     );
 }
@@ -109,15 +131,15 @@ fn writes_bytes_as_base64() {
 fn writes_options_as_one_elem_or_empty_array() {
     let none: Option<i32> = None;
     assert_eq!(
-        none.serialize(Serializer {}).unwrap(),
+        none.serialize(Serializer::default()).unwrap(),
         Value::Array(Vec::new())
     );
     assert_eq!(
-        Some(33i32).serialize(Serializer {}).unwrap(),
+        Some(33i32).serialize(Serializer::default()).unwrap(),
         Value::Array(vec![Value::Int(33)])
     );
     assert_eq!(
-        Some("txt").serialize(Serializer {}).unwrap(),
+        Some("txt").serialize(Serializer::default()).unwrap(),
         Value::Array(vec![Value::String("txt".into())])
     );
 }
@@ -125,7 +147,7 @@ fn writes_options_as_one_elem_or_empty_array() {
 #[test]
 fn writes_units_as_empty_struct() {
     assert_eq!(
-        ().serialize(Serializer {}).unwrap(),
+        ().serialize(Serializer::default()).unwrap(),
         Value::Struct(HashMap::new())
     );
 
@@ -133,7 +155,7 @@ fn writes_units_as_empty_struct() {
     struct Helper;
 
     assert_eq!(
-        Helper.serialize(Serializer {}).unwrap(),
+        Helper.serialize(Serializer::default()).unwrap(),
         Value::Struct(HashMap::new())
     );
 }
@@ -146,11 +168,13 @@ fn writes_newtype_struct_as_its_content() {
     struct HelperString(String);
 
     assert_eq!(
-        HelperInt(33).serialize(Serializer {}).unwrap(),
+        HelperInt(33).serialize(Serializer::default()).unwrap(),
         Value::Int(33)
     );
     assert_eq!(
-        HelperString("txt".into()).serialize(Serializer {}).unwrap(),
+        HelperString("txt".into())
+            .serialize(Serializer::default())
+            .unwrap(),
         Value::String("txt".into())
     );
 }
@@ -158,7 +182,9 @@ fn writes_newtype_struct_as_its_content() {
 #[test]
 fn writes_vector_as_array() {
     assert_eq!(
-        vec![33, 15, 44, 12].serialize(Serializer {}).unwrap(),
+        vec![33, 15, 44, 12]
+            .serialize(Serializer::default())
+            .unwrap(),
         Value::Array(vec![
             Value::Int(33),
             Value::Int(15),
@@ -167,7 +193,9 @@ fn writes_vector_as_array() {
         ])
     );
     assert_eq!(
-        vec!['a', 'b', 'c', 'd'].serialize(Serializer {}).unwrap(),
+        vec!['a', 'b', 'c', 'd']
+            .serialize(Serializer::default())
+            .unwrap(),
         Value::Array(vec![
             Value::String("a".into()),
             Value::String("b".into()),
@@ -181,7 +209,7 @@ fn writes_vector_as_array() {
 fn writes_tuple_as_array() {
     assert_eq!(
         (4, 1_000_000_000_000i64, "hello", true)
-            .serialize(Serializer {})
+            .serialize(Serializer::default())
             .unwrap(),
         Value::Array(vec![
             Value::Int(4),
@@ -196,7 +224,7 @@ fn writes_tuple_as_array() {
 
     assert_eq!(
         Helper(4, 1_000_000_000_000u64, "hello".into(), true)
-            .serialize(Serializer {})
+            .serialize(Serializer::default())
             .unwrap(),
         Value::Array(vec![
             Value::Int(4),
@@ -230,7 +258,7 @@ fn writes_struct_as_struct() {
             baz: "hello".into(),
             qux: true,
         }
-        .serialize(Serializer {})
+        .serialize(Serializer::default())
         .unwrap(),
         Value::Struct(members)
     );
@@ -255,7 +283,7 @@ fn writes_map_as_struct() {
     );
 
     assert_eq!(
-        data.serialize(Serializer {}).unwrap(),
+        data.serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 }
@@ -279,7 +307,7 @@ fn map_accepts_string_keys() {
     );
 
     assert_eq!(
-        data.serialize(Serializer {}).unwrap(),
+        data.serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 }
@@ -303,7 +331,7 @@ fn map_accepts_integer_keys() {
     );
 
     assert_eq!(
-        data.serialize(Serializer {}).unwrap(),
+        data.serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 }
@@ -327,7 +355,7 @@ fn map_accepts_char_keys() {
     );
 
     assert_eq!(
-        data.serialize(Serializer {}).unwrap(),
+        data.serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 }
@@ -346,7 +374,7 @@ fn map_accepts_boolean_keys() {
     members.insert("false".into(), Value::Array(vec![]));
 
     assert_eq!(
-        data.serialize(Serializer {}).unwrap(),
+        data.serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 }
@@ -357,7 +385,7 @@ fn rejects_maps_with_unsupported_keys() {
     data.insert(Some(4), vec![44i8, 12]);
     data.insert(Some(3), vec![]);
     data.insert(Some(2), vec![-3, 44, 28]);
-    data.serialize(Serializer {}).unwrap_err();
+    data.serialize(Serializer::default()).unwrap_err();
 }
 
 #[test]
@@ -373,14 +401,14 @@ fn writes_variant_as_one_member_struct() {
     let mut members = HashMap::new();
     members.insert("Foo".into(), Value::Struct(HashMap::new()));
     assert_eq!(
-        Helper::Foo.serialize(Serializer {}).unwrap(),
+        Helper::Foo.serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 
     let mut members = HashMap::new();
     members.insert("Bar".into(), Value::Int(44));
     assert_eq!(
-        Helper::Bar(44).serialize(Serializer {}).unwrap(),
+        Helper::Bar(44).serialize(Serializer::default()).unwrap(),
         Value::Struct(members)
     );
 
@@ -390,7 +418,9 @@ fn writes_variant_as_one_member_struct() {
         Value::Array(vec![Value::Bool(false), Value::String("tsk".into())]),
     );
     assert_eq!(
-        Helper::Baz(false, "tsk").serialize(Serializer {}).unwrap(),
+        Helper::Baz(false, "tsk")
+            .serialize(Serializer::default())
+            .unwrap(),
         Value::Struct(members)
     );
 
@@ -412,8 +442,206 @@ fn writes_variant_as_one_member_struct() {
             alpha: -4,
             beta: vec![true, false, true],
         }
-        .serialize(Serializer {})
+        .serialize(Serializer::default())
+        .unwrap(),
+        Value::Struct(members)
+    );
+}
+
+#[test]
+fn writes_wide_integers_according_to_policy() {
+    let options = SerializeOptions {
+        integers: IntegerPolicy::IntWhenFits,
+        ..Default::default()
+    };
+    assert_eq!(
+        42i64.serialize(Serializer::new(&options)).unwrap(),
+        Value::Int(42)
+    );
+    assert_eq!(
+        4_200_000_000u32
+            .serialize(Serializer::new(&options))
+            .unwrap(),
+        Value::String("4200000000".into())
+    );
+
+    let options = SerializeOptions {
+        integers: IntegerPolicy::ErrorWhenWide,
+        ..Default::default()
+    };
+    assert_eq!(
+        42u64.serialize(Serializer::new(&options)).unwrap(),
+        Value::Int(42)
+    );
+    assert!(4_200_000_000u32
+        .serialize(Serializer::new(&options))
+        .is_err());
+}
+
+#[test]
+fn writes_options_as_bare_values_or_omitted_members() {
+    #[derive(Serialize)]
+    struct Helper {
+        foo: Option<i32>,
+        bar: Option<i32>,
+    }
+
+    let options = SerializeOptions {
+        options: OptionRepresentation::Omit,
+        ..Default::default()
+    };
+    let mut members = HashMap::new();
+    members.insert("foo".into(), Value::Int(33));
+    assert_eq!(
+        Helper {
+            foo: Some(33),
+            bar: None,
+        }
+        .serialize(Serializer::new(&options))
         .unwrap(),
         Value::Struct(members)
     );
+    assert!(vec![None::<i32>]
+        .serialize(Serializer::new(&options))
+        .is_err());
+}
+
+#[test]
+fn writes_only_exact_data_in_strict_mode() {
+    let options = SerializeOptions {
+        strict: true,
+        ..Default::default()
+    };
+    assert!(f64::NAN.serialize(Serializer::new(&options)).is_err());
+
+    let mut map = HashMap::new();
+    map.insert(4, "four");
+    assert!(map.serialize(Serializer::new(&options)).is_err());
+    assert!(map.serialize(Serializer::default()).is_ok());
+}
+
+#[test]
+fn writes_values_unchanged() {
+    let value = xmlrpc_value!({
+        "int": 33,
+        "list": [true, "foo", 2.5, [], {}],
+        "blob": (Value::Base64(vec![1, 2, 3])),
+        "when": (Value::DateTime("33".into())),
+    });
+    assert_eq!(value, value.serialize(Serializer::default()).unwrap());
+    assert_eq!(vec![value.clone()], into_params(&(value.clone(),)).unwrap());
+    assert_eq!(
+        vec![xmlrpc_value!([1, 2])],
+        into_params(&(xmlrpc_value!([1, 2]),)).unwrap()
+    );
+}
+
+#[test]
+fn writes_value_fields_as_catch_all() {
+    #[derive(Serialize)]
+    struct Helper {
+        name: String,
+        extra: Value,
+    }
+
+    assert_eq!(
+        Helper {
+            name: "foo".into(),
+            extra: xmlrpc_value!({"when": (Value::DateTime("yesterday".into()))}),
+        }
+        .serialize(Serializer::default())
+        .unwrap(),
+        xmlrpc_value!({
+            "name": "foo",
+            "extra": {"when": (Value::DateTime("yesterday".into()))},
+        })
+    );
+}
+
+#[test]
+fn writes_non_finite_doubles_per_policy() {
+    let with_policy = |non_finite: NonFinitePolicy| SerializeOptions {
+        non_finite,
+        ..Default::default()
+    };
+    let options = with_policy(NonFinitePolicy::Verbatim);
+    assert_eq!(
+        Value::Double(f64::INFINITY),
+        f64::INFINITY.serialize(Serializer::new(&options)).unwrap()
+    );
+    let options = with_policy(NonFinitePolicy::Error);
+    assert!(f64::NAN.serialize(Serializer::new(&options)).is_err());
+    assert_eq!(
+        Value::Double(1.5),
+        1.5f64.serialize(Serializer::new(&options)).unwrap()
+    );
+    let options = with_policy(NonFinitePolicy::Substitute(-1.0));
+    assert_eq!(
+        Value::Array(vec![Value::Double(-1.0), Value::Double(-1.0)]),
+        vec![f64::NAN, f64::NEG_INFINITY]
+            .serialize(Serializer::new(&options))
+            .unwrap()
+    );
+    let options = SerializeOptions {
+        strict: true,
+        ..options
+    };
+    assert!(f64::NAN.serialize(Serializer::new(&options)).is_err());
+}
+
+#[test]
+fn writes_extension_types_when_asked() {
+    let options = SerializeOptions {
+        integers: IntegerPolicy::WideAsI8,
+        options: OptionRepresentation::Nil,
+        ..Default::default()
+    };
+    assert_eq!(
+        Value::Array(vec![Value::Int(3), Value::I8(5_000_000_000), Value::Nil]),
+        (3i64, 5_000_000_000i64, None::<i32>)
+            .serialize(Serializer::new(&options))
+            .unwrap()
+    );
+    assert!(u64::MAX.serialize(Serializer::new(&options)).is_err());
+    let value = xmlrpc_value!([(Value::I8(-5_000_000_000)), (Value::Nil)]);
+    assert_eq!(value, value.serialize(Serializer::default()).unwrap());
+}
+
+#[test]
+fn writes_enums_in_configured_representation() {
+    #[derive(Serialize)]
+    enum Helper {
+        Stop,
+        Move { x: i32 },
+        Wrap(i32),
+    }
+
+    let options = SerializeOptions {
+        enums: EnumRepresentation::UnitAsString,
+        ..Default::default()
+    };
+    let serializer = Serializer::new(&options);
+    assert_eq!(
+        xmlrpc_value!("Stop"),
+        Helper::Stop.serialize(serializer).unwrap()
+    );
+    assert_eq!(
+        xmlrpc_value!({"Move": {"x": 3}}),
+        Helper::Move { x: 3 }.serialize(serializer).unwrap()
+    );
+
+    let options = SerializeOptions {
+        enums: EnumRepresentation::Internal { tag: "type".into() },
+        ..Default::default()
+    };
+    let serializer = Serializer::new(&options);
+    assert_eq!(
+        xmlrpc_value!({"type": "Stop"}),
+        Helper::Stop.serialize(serializer).unwrap()
+    );
+    assert_eq!(
+        xmlrpc_value!({"type": "Move", "x": 3}),
+        Helper::Move { x: 3 }.serialize(serializer).unwrap()
+    );
+    assert!(Helper::Wrap(3).serialize(serializer).is_err());
 }
@@ -0,0 +1,76 @@
+use super::super::Fault;
+use test_util::{self, TestServer};
+use xmlrpc_service;
+use Server;
+
+#[xmlrpc_service]
+trait Calculator {
+    #[xmlrpc(name = "math.add")]
+    fn add(&self, a: i32, b: i32) -> Result<i32, Fault>;
+    fn divide(&self, a: f64, b: f64) -> Result<f64, Fault>;
+    fn version(&self) -> Result<String, Fault>;
+}
+
+struct Implementation;
+
+impl Calculator for Implementation {
+    fn add(&self, a: i32, b: i32) -> Result<i32, Fault> {
+        Ok(a + b)
+    }
+
+    fn divide(&self, a: f64, b: f64) -> Result<f64, Fault> {
+        if b == 0.0 {
+            return Err(Fault::new(1, "Division by zero"));
+        }
+        Ok(a / b)
+    }
+
+    fn version(&self) -> Result<String, Fault> {
+        Ok("1.0".into())
+    }
+}
+
+fn serve() -> TestServer {
+    let mut server = Server::new();
+    register_calculator(&mut server, Implementation);
+    test_util::spawn(server)
+}
+
+#[test]
+fn calls_services_through_their_traits() {
+    let server = serve();
+    let client = CalculatorClient::new(server.url().clone()).unwrap();
+    assert_eq!(Ok(5), client.add(2, 3));
+    assert_eq!(Ok(2.5), client.divide(5.0, 2.0));
+    assert_eq!(
+        Err(Fault::new(1, "Division by zero")),
+        client.divide(5.0, 0.0)
+    );
+    assert_eq!(Ok("1.0".into()), client.version());
+}
+
+#[test]
+fn serves_renamed_methods() {
+    let server = serve();
+    let uri = server.url();
+    let mut client = ::Client::new().unwrap();
+    assert_eq!(
+        5,
+        client.call::<_, _, i32>(uri, "math.add", (2, 3)).unwrap()
+    );
+    assert_eq!(
+        404,
+        client
+            .call::<_, _, i32>(uri, "add", (2, 3))
+            .unwrap_err()
+            .fault()
+            .unwrap()
+            .code
+    );
+}
+
+#[test]
+fn reports_failed_calls_as_transport_errors() {
+    let client = CalculatorClient::new("http://127.0.0.1:1/".parse().unwrap()).unwrap();
+    assert_eq!(-32300, client.version().unwrap_err().code);
+}
@@ -0,0 +1,39 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::thread;
+use std::time::{Duration, Instant};
+use {Client, Server, Url};
+
+fn nap(url: &Url, millis: u64) -> thread::JoinHandle<bool> {
+    let url = url.clone();
+    thread::spawn(move || {
+        let mut client = Client::new().unwrap();
+        client
+            .call::<_, _, bool>(&url, "nap", (millis as i32,))
+            .is_ok()
+    })
+}
+
+#[test]
+fn drains_in_flight_requests_up_to_the_deadline() {
+    let mut server = Server::new();
+    server.register_simple("nap", |(millis,): (i32,)| {
+        thread::sleep(Duration::from_millis(millis as u64));
+        Ok(true)
+    });
+    let server = server
+        .bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+        .unwrap();
+    let url: Url = format!("http://{}/", server.local_addr()).parse().unwrap();
+    let short = nap(&url, 200);
+    let long = nap(&url, 5000);
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_millis(100) {
+        server.poll_timeout(Duration::from_millis(10));
+    }
+
+    assert_eq!(1, server.shutdown(Duration::from_millis(500)));
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(short.join().unwrap());
+    assert!(!nap(&url, 0).join().unwrap());
+    drop(long);
+}
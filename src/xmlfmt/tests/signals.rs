@@ -0,0 +1,41 @@
+use libc;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use Server;
+
+fn run_in_background(stop_on_signals: bool) -> mpsc::Receiver<()> {
+    let server = Server::new()
+        .bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+        .unwrap();
+    let handle = server.stop_handle();
+    if stop_on_signals {
+        handle.stop_on_signals().unwrap();
+    }
+    let (done, stopped) = mpsc::channel();
+    thread::spawn(move || {
+        server.run();
+        done.send(()).unwrap();
+    });
+    stopped
+}
+
+#[test]
+fn stops_servers_on_sigterm_when_asked_to() {
+    let ignoring = run_in_background(false);
+    let stopping = run_in_background(true);
+    assert_eq!(0, unsafe { libc::raise(libc::SIGTERM) });
+
+    assert!(stopping.recv_timeout(Duration::from_secs(2)).is_ok());
+    assert!(ignoring.recv_timeout(Duration::from_millis(300)).is_err());
+}
+
+#[test]
+fn stops_servers_through_their_handle() {
+    let server = Server::new()
+        .bind(&SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+        .unwrap();
+    server.stop_handle().stop();
+    assert_eq!(0, server.run_and_drain(Duration::from_secs(1)));
+}
@@ -0,0 +1,68 @@
+use super::super::signature::{check_any, coerce_any};
+use super::super::value::INVALID_PARAMS;
+use super::super::{Params, Signature, Value, ValueType};
+
+#[test]
+fn checks_params_against_signature() {
+    let signature = Signature::new(vec![ValueType::String, ValueType::Int], ValueType::Boolean);
+    assert!(signature
+        .check(&[Value::String("foo".into()), Value::Int(3)])
+        .is_ok());
+    let fault = signature.check(&[Value::String("foo".into())]).unwrap_err();
+    assert_eq!(INVALID_PARAMS, fault.code);
+    assert_eq!("Expected 2 parameters, got 1", fault.message);
+    let fault = signature
+        .check(&[Value::String("foo".into()), Value::Double(3.0)])
+        .unwrap_err();
+    assert_eq!("Expected int for parameter 1, got double", fault.message);
+}
+
+#[test]
+fn passes_params_fitting_any_signature() {
+    let signatures = [
+        Signature::new(vec![ValueType::Int], ValueType::Int),
+        Signature::new(vec![ValueType::Double], ValueType::Double),
+    ];
+    assert!(check_any(&signatures, &[Value::Int(3)]).is_ok());
+    assert!(check_any(&signatures, &[Value::Double(3.0)]).is_ok());
+    assert!(check_any(&signatures, &[Value::Bool(true)]).is_err());
+    assert!(check_any(&[], &[Value::Bool(true)]).is_ok());
+}
+
+#[test]
+fn lists_result_type_first() {
+    let signature = Signature::new(
+        vec![ValueType::DateTime, ValueType::Array],
+        ValueType::Struct,
+    );
+    assert_eq!(
+        Value::from(signature),
+        xmlrpc_value!(["struct", "dateTime.iso8601", "array"])
+    );
+}
+
+fn params(value: Value) -> Params {
+    match value {
+        Value::Array(params) => params,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn coerces_params_to_fit_signatures() {
+    let signatures = [Signature::new(
+        vec![ValueType::Int, ValueType::Boolean, ValueType::Array],
+        ValueType::Int,
+    )];
+    assert_eq!(
+        Ok(params(xmlrpc_value!([3, true, ["a"]]))),
+        coerce_any(&signatures, params(xmlrpc_value!([" 3", 1, "a"])))
+    );
+    assert_eq!(
+        Ok(params(xmlrpc_value!([3, false, [1]]))),
+        coerce_any(&signatures, params(xmlrpc_value!([3, "false", [1]])))
+    );
+    let fault = coerce_any(&signatures, params(xmlrpc_value!(["x", 1, []]))).unwrap_err();
+    assert_eq!("Expected int for parameter 0, got string", fault.message);
+    assert!(coerce_any(&signatures, params(xmlrpc_value!([3, 2, []]))).is_err());
+}
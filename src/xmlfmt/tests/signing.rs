@@ -0,0 +1,46 @@
+use super::raw_http::{refused, serve, xml_reply};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::sync::{Arc, Mutex};
+use Client;
+
+fn signature(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(b"secret");
+    hasher.write(body);
+    format!("{:016x}", hasher.finish())
+}
+
+#[test]
+fn signs_the_exact_body() {
+    let (url, handle) = serve(vec![xml_reply("<i4>1</i4>")]);
+    let mut client = Client::new().unwrap();
+    client.set_pre_send_hook(|_, body, headers| {
+        headers.set_raw("X-Signature", vec![signature(body).into_bytes()]);
+    });
+    assert_eq!(
+        1,
+        client
+            .call::<_, _, i32>(&url, "sum", ("a", 1, vec![2.5]))
+            .unwrap()
+    );
+    let requests = handle.join().unwrap();
+    assert_eq!(
+        Some(signature(&requests[0].body).as_str()),
+        requests[0].header("x-signature")
+    );
+}
+
+#[test]
+fn runs_for_every_attempt() {
+    let (backup, handle) = serve(vec![xml_reply("<i4>1</i4>")]);
+    let primary = refused();
+    let attempts = Arc::new(Mutex::new(Vec::new()));
+    let mut client = Client::new().unwrap();
+    let seen = Arc::clone(&attempts);
+    client.set_pre_send_hook(move |uri, _, _| seen.lock().unwrap().push(uri.clone()));
+    client.set_fallbacks(primary.clone(), vec![backup.clone()]);
+    assert_eq!(1, client.call::<_, _, i32>(&primary, "sum", ()).unwrap());
+    handle.join().unwrap();
+    assert_eq!(vec![primary, backup], *attempts.lock().unwrap());
+}
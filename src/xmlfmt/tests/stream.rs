@@ -0,0 +1,205 @@
+use super::super::stream::{Header, ParamReader};
+use super::super::{Fault, ParseOptions, Value};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::cell::Cell;
+use std::io::{self, Read, Write};
+
+static CALL: &str = r#"<?xml version="1.0"?>
+<methodCall>
+  <methodName>upload</methodName>
+  <params>
+    <param><value><string>file.bin</string></value></param>
+    <param><value><struct>
+      <member><name>size</name><value><i4>5</i4></value></member>
+      <member><name>data</name><value><base64>AQIDBAU=</base64></value></member>
+    </struct></value></param>
+    <param><value>  untagged <!-- comment --> text</value></param>
+  </params>
+</methodCall>"#;
+
+#[test]
+fn reads_params_one_at_a_time() {
+    let mut reader = ParamReader::new(CALL.as_bytes()).unwrap();
+    assert_eq!(&Header::Call("upload".into()), reader.header());
+    assert_eq!(
+        Some(Value::String("file.bin".into())),
+        reader.next_param().unwrap()
+    );
+    assert_eq!(
+        Some(xmlrpc_value!({"size": 5, "data": (Value::Base64(vec![1, 2, 3, 4, 5]))})),
+        reader.next_param().unwrap()
+    );
+    assert_eq!(
+        Some(Value::String("  untagged  text".into())),
+        reader.next_param().unwrap()
+    );
+    assert_eq!(None, reader.next_param().unwrap());
+    assert_eq!(None, reader.next_param().unwrap());
+}
+
+#[test]
+fn writes_base64_into_sink() {
+    let mut reader = ParamReader::new(CALL.as_bytes()).unwrap();
+    let mut sink = Vec::new();
+    reader.next_param_to(&mut sink).unwrap();
+    assert!(sink.is_empty());
+    assert_eq!(
+        Some(xmlrpc_value!({"size": 5, "data": (Value::Base64(vec![]))})),
+        reader.next_param_to(&mut sink).unwrap()
+    );
+    assert_eq!(vec![1, 2, 3, 4, 5], sink);
+}
+
+/// Counts the bytes read through it.
+struct Counting<'a, R> {
+    inner: R,
+    read: &'a Cell<usize>,
+}
+
+impl<'a, R: Read> Read for Counting<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.read.set(self.read.get() + count);
+        Ok(count)
+    }
+}
+
+/// Records how much of the document was read by the time of each write.
+struct Recording<'a> {
+    read: &'a Cell<usize>,
+    writes: Vec<(usize, usize)>,
+    data: Vec<u8>,
+}
+
+impl<'a> Write for Recording<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes.push((self.read.get(), buf.len()));
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn decodes_base64_into_sink_as_it_arrives() {
+    let blob = (0..4 << 20).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let text = STANDARD.encode(&blob);
+    // Pieces not lined up with base64's blocks of 4 characters, as a writer streaming the data
+    // in CDATA sections might send them.
+    let pieces = text
+        .as_bytes()
+        .chunks(10_001)
+        .map(|piece| format!("<![CDATA[{}]]>", ::std::str::from_utf8(piece).unwrap()))
+        .collect::<String>();
+    let data = format!(
+        "<methodCall><methodName>upload</methodName><params><param><value><base64>{}\
+         </base64></value></param></params></methodCall>",
+        pieces
+    );
+    let read = Cell::new(0);
+    let source = Counting {
+        inner: data.as_bytes(),
+        read: &read,
+    };
+    let mut reader = ParamReader::new(source).unwrap();
+    let mut sink = Recording {
+        read: &read,
+        writes: Vec::new(),
+        data: Vec::new(),
+    };
+    assert_eq!(
+        Some(Value::Base64(vec![])),
+        reader.next_param_to(&mut sink).unwrap()
+    );
+    assert!(blob == sink.data);
+    assert!(sink.writes.len() > 1000);
+    // Writes come while the document is still being read, at most a block of data at a time.
+    let (read_at_first_write, _) = sink.writes[0];
+    assert!(read_at_first_write < 64 << 10);
+    assert!(sink.writes.iter().all(|&(_, len)| len <= 3072));
+}
+
+#[test]
+fn reads_responses_and_faults() {
+    let data = r#"<?xml version="1.0"?><methodResponse><params><param><value><array><data><value><boolean>1</boolean></value><value><double>2.5</double></value></data></array></value></param></params></methodResponse>"#;
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    assert_eq!(&Header::Response, reader.header());
+    assert_eq!(
+        vec![xmlrpc_value!([true, 2.5])],
+        reader.collect_params().unwrap()
+    );
+
+    let data = r#"<?xml version="1.0"?><methodResponse><fault><value><struct><member><name>faultCode</name><value><int>4</int></value></member><member><name>faultString</name><value><string>Too many parameters.</string></value></member></struct></value></fault></methodResponse>"#;
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    assert_eq!(
+        &Header::Fault(Fault::new(4, "Too many parameters.")),
+        reader.header()
+    );
+    assert_eq!(None, reader.next_param().unwrap());
+}
+
+#[test]
+fn applies_parse_limits() {
+    let options = ParseOptions {
+        max_values: 2,
+        ..Default::default()
+    };
+    let mut reader = ParamReader::with_options(CALL.as_bytes(), options).unwrap();
+    assert!(reader.next_param().is_ok());
+    assert!(reader.next_param().is_err());
+
+    let options = ParseOptions {
+        max_size: 100,
+        ..Default::default()
+    };
+    assert!(ParamReader::with_options(CALL.as_bytes(), options)
+        .and_then(|mut reader| reader.collect_params())
+        .is_err());
+}
+
+#[test]
+fn reads_through_any_buffer_size() {
+    let expected = ParamReader::new(CALL.as_bytes())
+        .and_then(|mut reader| reader.collect_params())
+        .unwrap();
+    for &read_buffer_size in &[0, 1, 7, 1 << 20] {
+        let options = ParseOptions {
+            read_buffer_size,
+            ..Default::default()
+        };
+        let mut reader = ParamReader::with_options(CALL.as_bytes(), options).unwrap();
+        assert_eq!(expected, reader.collect_params().unwrap());
+    }
+}
+
+#[test]
+fn rejects_malformed_documents() {
+    let data = r#"<?xml version="1.0"?><methodCall><methodName>foo</methodName><params><param><value><foo>1</foo></value></param></params></methodCall>"#;
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    assert!(reader.next_param().is_err());
+    let data = r#"<?xml version="1.0"?><params></params>"#;
+    assert!(ParamReader::new(data.as_bytes()).is_err());
+}
+
+#[test]
+fn rejects_doctypes() {
+    let data = r#"<?xml version="1.0"?><!DOCTYPE methodCall><methodCall><methodName>foo</methodName></methodCall>"#;
+    assert!(ParamReader::new(data.as_bytes()).is_err());
+}
+
+#[test]
+fn reports_error_positions() {
+    let data = "<?xml version=\"1.0\"?>\n<methodResponse>\n<params>\n<param><value><foo>1</foo></value></param>\n</params>\n</methodResponse>";
+    let mut reader = ParamReader::new(data.as_bytes()).unwrap();
+    let err = reader.next_param().unwrap_err();
+    assert_eq!(
+        "Issue while decoding data structure: Unknown value type <foo> at line 4, column 15",
+        err.to_string()
+    );
+    let data = "<?xml version=\"1.0\"?>\n<methodResponse>\n<struct/>\n</methodResponse>";
+    let err = ParamReader::new(data.as_bytes()).err().unwrap();
+    assert!(err.to_string().contains("at line 3, column 1"), "{}", err);
+}
@@ -0,0 +1,96 @@
+use super::super::value::ToXml;
+use super::super::{Call, Fault, Response, ResponseStream, StreamedParam, Value, WriteOptions};
+use std::io::{self, Read};
+use test_util;
+use {Client, Server};
+
+fn read_all(mut stream: ResponseStream) -> String {
+    let mut xml = String::new();
+    // Tiny reads, to cross every boundary between encoded parts.
+    let mut buf = [0u8; 7];
+    loop {
+        let read = stream.read(&mut buf).unwrap();
+        if read == 0 {
+            return xml;
+        }
+        xml.push_str(std::str::from_utf8(&buf[..read]).unwrap());
+    }
+}
+
+#[test]
+fn streams_the_same_xml_as_a_buffered_response() {
+    let blob: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    for line_length in [None, Some(76)] {
+        let options = WriteOptions {
+            base64_line_length: line_length,
+            ..WriteOptions::default()
+        };
+        let response: Response = Ok(vec![
+            Value::Int(3),
+            Value::Base64(blob.clone()),
+            Value::String("done".into()),
+        ]);
+        let stream = ResponseStream::new(
+            vec![
+                Value::Int(3).into(),
+                StreamedParam::Base64(Box::new(io::Cursor::new(blob.clone()))),
+                Value::String("done".into()).into(),
+            ],
+            options.clone(),
+        );
+        assert_eq!(response.to_xml_with(&options), read_all(stream));
+    }
+}
+
+#[test]
+fn serves_streamed_results() {
+    let mut server = Server::new();
+    server.register_streaming("blob", |params| match params.first() {
+        Some(&Value::Int(size)) if size >= 0 => Ok(vec![StreamedParam::Base64(Box::new(
+            io::repeat(7).take(size as u64),
+        ))]),
+        _ => Err(Fault::invalid_params("Expected a size")),
+    });
+    let server = test_util::spawn(server);
+    let mut client = Client::new().unwrap();
+
+    let response = client
+        .call_value(server.url(), "blob", vec![Value::Int(100_000)])
+        .unwrap();
+    assert_eq!(Ok(vec![Value::Base64(vec![7; 100_000])]), response);
+    let response = client
+        .call_value(server.url(), "blob", vec![Value::Int(-1)])
+        .unwrap();
+    assert_eq!("Expected a size", response.unwrap_err().message);
+}
+
+#[test]
+fn refuses_streamed_methods_in_multicalls() {
+    let mut server = Server::new();
+    server.register_streaming("blob", |_| Ok(vec![StreamedParam::Value(Value::Int(1))]));
+    server.register_simple("ping", |()| Ok(true));
+    let server = test_util::spawn(server);
+    let calls = vec![
+        Call {
+            name: "blob".into(),
+            params: vec![],
+        },
+        Call {
+            name: "ping".into(),
+            params: vec![],
+        },
+    ];
+    let response = Client::new()
+        .unwrap()
+        .multicall(server.url(), calls)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Some(&Err(Fault::new(
+            400,
+            "Method can't be called in a multicall"
+        ))),
+        response.get(0)
+    );
+    assert_eq!(Some(&Ok(Value::Bool(true))), response.get(1));
+}
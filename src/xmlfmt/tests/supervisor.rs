@@ -0,0 +1,121 @@
+use super::super::{Fault, Value};
+use supervisor::{faults, ProcessStatus, Supervisor, Tail};
+use test_util;
+use Server;
+
+fn fake_supervisor() -> Server {
+    let mut server = Server::new();
+    server.register_value("supervisor.getProcessInfo", |params| {
+        match params[0].as_str() {
+            Some("web") => Ok(vec![xmlrpc_value!({
+                "name": "web",
+                "group": "web",
+                "description": "pid 42, uptime 0:01:00",
+                "start": 1700000000,
+                "stop": 0,
+                "now": 1700000060,
+                "state": 20,
+                "statename": "RUNNING",
+                "spawnerr": "",
+                "exitstatus": 0,
+                "logfile": "/var/log/web.log",
+                "stdout_logfile": "/var/log/web.log",
+                "stderr_logfile": "",
+                "pid": 42
+            })]),
+            _ => Err(Fault::new(faults::BAD_NAME, "BAD_NAME: nope")),
+        }
+    });
+    server.register_value("supervisor.stopAllProcesses", |params| {
+        assert_eq!(vec![Value::Bool(true)], params);
+        Ok(vec![xmlrpc_value!([{
+            "name": "web",
+            "group": "web",
+            "status": 80,
+            "description": "OK"
+        }])])
+    });
+    server.register_value("supervisor.tailProcessStdoutLog", |params| {
+        assert_eq!(xmlrpc_value!(["web", 0, 5]), Value::Array(params));
+        Ok(vec![xmlrpc_value!(["ready", 120, true])])
+    });
+    server
+}
+
+#[test]
+fn calls_supervisor() {
+    let server = test_util::spawn(fake_supervisor());
+    let mut supervisor = Supervisor::new(server.url().clone()).unwrap();
+    let info = supervisor.get_process_info("web").unwrap().unwrap();
+    assert_eq!("RUNNING", info.statename);
+    assert_eq!(1700000060, info.now);
+    assert_eq!(42, info.pid);
+    assert_eq!(
+        Err(Fault::new(faults::BAD_NAME, "BAD_NAME: nope")),
+        supervisor.get_process_info("nope").unwrap()
+    );
+    assert_eq!(
+        Ok(vec![ProcessStatus {
+            name: "web".into(),
+            group: "web".into(),
+            status: faults::SUCCESS,
+            description: "OK".into(),
+        }]),
+        supervisor.stop_all_processes(true).unwrap()
+    );
+    assert_eq!(
+        Ok(Tail {
+            bytes: "ready".into(),
+            offset: 120,
+            overflow: true,
+        }),
+        supervisor.tail_process_stdout_log("web", 0, 5).unwrap()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn calls_over_unix_sockets() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    let path = std::env::temp_dir().join(format!("xml-rpc-supervisor-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if line.to_lowercase().starts_with("content-length:") {
+                length = line[15..].trim().parse().unwrap();
+            }
+        }
+        let mut request = vec![0; length];
+        reader.read_exact(&mut request).unwrap();
+        let body = "<?xml version=\"1.0\"?><methodResponse><params><param>\
+                    <value><string>3.0</string></value></param></params></methodResponse>";
+        write!(
+            reader.get_mut(),
+            "HTTP/1.0 200 OK\r\nContent-Type: text/xml\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        (request_line, String::from_utf8(request).unwrap())
+    });
+    let mut supervisor = Supervisor::unix_socket(path.clone()).unwrap();
+    assert_eq!(Ok("3.0".to_string()), supervisor.get_api_version().unwrap());
+    let (request_line, request) = handle.join().unwrap();
+    assert_eq!("POST /RPC2 HTTP/1.1", request_line.trim());
+    assert!(request.contains("<methodName>supervisor.getAPIVersion</methodName>"));
+    std::fs::remove_file(&path).unwrap();
+}
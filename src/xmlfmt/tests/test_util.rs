@@ -0,0 +1,65 @@
+use super::super::Value;
+use test_util;
+use {Client, Fault, Server};
+
+#[test]
+fn serves_until_dropped() {
+    let mut server = Server::new();
+    server.register_value("echo", Ok);
+    let server = test_util::spawn(server);
+    let url = server.url().clone();
+    assert_eq!(
+        Ok(vec![Value::Int(3)]),
+        Client::new()
+            .unwrap()
+            .call_value(&url, "echo", vec![Value::Int(3)])
+            .unwrap()
+    );
+    assert_eq!(server.addr().port(), url.port().unwrap());
+    drop(server);
+    assert!(Client::new()
+        .unwrap()
+        .call_value(&url, "echo", vec![Value::Int(3)])
+        .is_err());
+}
+
+#[test]
+fn stubs_answer_with_canned_responses_and_record_calls() {
+    let stub = test_util::StubServer::new(vec![
+        ("version", Ok(vec![Value::from("1.0")])),
+        ("upload", Err(Fault::new(403, "Read only"))),
+    ]);
+    let mut client = Client::new().unwrap();
+    let version = client.call_value(stub.url(), "version", vec![]).unwrap();
+    assert_eq!(Ok(vec![Value::from("1.0")]), version);
+    for size in 1..3 {
+        let upload = client
+            .call_value(stub.url(), "upload", vec![Value::Int(size)])
+            .unwrap();
+        assert_eq!(403, upload.unwrap_err().code);
+    }
+
+    assert_eq!(1, stub.count("version"));
+    assert_eq!(
+        vec![vec![Value::Int(1)], vec![Value::Int(2)]],
+        stub.calls_to("upload")
+    );
+    assert_eq!(
+        vec!["version", "upload", "upload"],
+        stub.calls()
+            .iter()
+            .map(|call| call.method.as_str())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[should_panic(expected = "without a response")]
+fn stubs_fail_on_unmatched_calls() {
+    let stub = test_util::StubServer::new(Vec::<(&str, _)>::new());
+    let response = Client::new()
+        .unwrap()
+        .call_value(stub.url(), "missing", vec![])
+        .unwrap();
+    assert_eq!(404, response.unwrap_err().code);
+}
@@ -0,0 +1,141 @@
+use super::super::{from_params, into_params, Value};
+use std::convert::TryFrom;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+fn naive() -> PrimitiveDateTime {
+    PrimitiveDateTime::new(
+        Date::from_calendar_date(1998, Month::July, 17).unwrap(),
+        Time::from_hms(14, 8, 55).unwrap(),
+    )
+}
+
+#[test]
+fn converts_datetimes_into_values() {
+    assert_eq!(
+        Value::from(naive()),
+        Value::DateTime("19980717T14:08:55".into())
+    );
+    assert_eq!(
+        Value::from(naive().assume_utc()),
+        Value::DateTime("19980717T14:08:55Z".into())
+    );
+    assert_eq!(
+        Value::from(naive().assume_offset(UtcOffset::from_hms(-5, -30, 0).unwrap())),
+        Value::DateTime("19980717T14:08:55-05:30".into())
+    );
+}
+
+#[test]
+fn converts_values_into_datetimes() {
+    let value = Value::DateTime("1998-07-17T14:08:55+02:00".into());
+    assert_eq!(
+        OffsetDateTime::try_from(Value::DateTime("19980717T14:08:55+0200".into())).unwrap(),
+        naive().assume_offset(UtcOffset::from_hms(2, 0, 0).unwrap())
+    );
+    assert_eq!(
+        OffsetDateTime::try_from(value.clone()).unwrap(),
+        naive().assume_offset(UtcOffset::from_hms(2, 0, 0).unwrap())
+    );
+    assert_eq!(PrimitiveDateTime::try_from(value).unwrap(), naive());
+    assert_eq!(
+        OffsetDateTime::try_from(Value::DateTime("19980717T14:08:55".into())).unwrap(),
+        naive().assume_utc()
+    );
+    assert!(OffsetDateTime::try_from(Value::String("19980717T14:08:55".into())).is_err());
+    assert!(OffsetDateTime::try_from(Value::DateTime("yesterday".into())).is_err());
+}
+
+#[test]
+fn serializes_fields_as_datetimes() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Helper {
+        #[serde(with = "::datetime::time::offset")]
+        at: OffsetDateTime,
+        #[serde(with = "::datetime::time::primitive")]
+        local: PrimitiveDateTime,
+    }
+
+    let helper = Helper {
+        at: naive().assume_utc(),
+        local: naive(),
+    };
+    let params = into_params(&(&helper,)).unwrap();
+    match params[0] {
+        Value::Struct(ref members) => {
+            assert_eq!(members["at"], Value::DateTime("19980717T14:08:55Z".into()));
+            assert_eq!(
+                members["local"],
+                Value::DateTime("19980717T14:08:55".into())
+            );
+        }
+        ref v => panic!("Expected struct, got {:?}", v),
+    }
+    assert_eq!(helper, from_params::<(Helper,)>(params).unwrap().0);
+}
+
+xmlrpc_datetime_string!(mod plain, "[day].[month].[year] [hour]:[minute]");
+
+#[test]
+fn serializes_fields_as_formatted_strings() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Helper {
+        #[serde(with = "plain")]
+        local: PrimitiveDateTime,
+    }
+
+    let helper = Helper {
+        local: naive().replace_second(0).unwrap(),
+    };
+    let params = into_params(&(&helper,)).unwrap();
+    match params[0] {
+        Value::Struct(ref members) => {
+            assert_eq!(members["local"], Value::String("17.07.1998 14:08".into()));
+        }
+        ref v => panic!("Expected struct, got {:?}", v),
+    }
+    assert_eq!(helper, from_params::<(Helper,)>(params).unwrap().0);
+}
+
+/// Run by `assumes_the_local_offset_of_each_date`, in a process of its own with `TZ` set.
+#[test]
+#[ignore]
+fn assumes_the_local_offset_in_a_child_process() {
+    use super::super::datetime::DateTime;
+    use super::super::options::NaiveDateTimePolicy;
+    if std::env::var_os("XML_RPC_TEST_LOCAL_OFFSET").is_none() {
+        return;
+    }
+    let offset = |v: &str| {
+        DateTime::parse(v)
+            .unwrap()
+            .to_offset_date_time(NaiveDateTimePolicy::AssumeLocal)
+            .unwrap()
+            .offset()
+    };
+    assert_eq!(
+        UtcOffset::from_hms(1, 0, 0).unwrap(),
+        offset("20240115T12:00:00")
+    );
+    assert_eq!(
+        UtcOffset::from_hms(2, 0, 0).unwrap(),
+        offset("20240715T12:00:00")
+    );
+}
+
+#[test]
+fn assumes_the_local_offset_of_each_date() {
+    // Central European time, with summer time from March to October.
+    let output = std::process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "xmlfmt::tests::time::assumes_the_local_offset_in_a_child_process",
+            "--ignored",
+        ])
+        .env("TZ", "CET-1CEST,M3.5.0,M10.5.0/3")
+        .env("XML_RPC_TEST_LOCAL_OFFSET", "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+}
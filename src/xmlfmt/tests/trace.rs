@@ -0,0 +1,89 @@
+use hyper;
+use hyper::header::Headers;
+use std::io::Read;
+use test_util;
+use trace::TraceContext;
+use {Client, Server};
+
+/// The trace ID, parent ID and span ID seen by the handler.
+fn seen() -> Vec<String> {
+    TraceContext::current()
+        .map(|trace| vec![trace.trace_id, trace.parent_id, trace.span_id])
+        .unwrap_or_default()
+}
+
+#[test]
+fn passes_trace_context_on_to_nested_calls() {
+    let mut inner = Server::new();
+    inner.register_simple("seen", |()| {
+        let state = TraceContext::current().and_then(|trace| trace.state);
+        Ok((seen(), state.unwrap_or_default()))
+    });
+    let inner = test_util::spawn(inner);
+    let inner_url = inner.url().clone();
+
+    let mut outer = Server::new();
+    outer.register_simple("relay", move |()| {
+        let mut client = Client::new().unwrap();
+        let nested = client
+            .call::<_, _, (Vec<String>, String)>(&inner_url, "seen", ())
+            .unwrap();
+        Ok((seen(), nested))
+    });
+    let outer = test_util::spawn(outer);
+
+    let mut headers = Headers::new();
+    headers.set_raw(
+        "traceparent",
+        vec![b"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_vec()],
+    );
+    headers.set_raw("tracestate", vec![b"vendor=abc".to_vec()]);
+    let call = "<?xml version=\"1.0\"?><methodCall><methodName>relay</methodName></methodCall>";
+    let mut body = String::new();
+    hyper::Client::new()
+        .post(outer.url().as_ref())
+        .headers(headers)
+        .body(call)
+        .send()
+        .unwrap()
+        .read_to_string(&mut body)
+        .unwrap();
+    let mut response = ::xmlfmt::parse::response(body.as_bytes()).unwrap().unwrap();
+    let (outer_seen, (inner_seen, state)): (Vec<String>, (Vec<String>, String)) =
+        ::xmlfmt::from_value(response.remove(0)).unwrap();
+
+    assert_eq!("4bf92f3577b34da6a3ce929d0e0e4736", outer_seen[0]);
+    assert_eq!("00f067aa0ba902b7", outer_seen[1]);
+    assert_eq!(outer_seen[0], inner_seen[0]);
+    assert_eq!(outer_seen[2], inner_seen[1]);
+    assert_ne!(outer_seen[2], inner_seen[2]);
+    assert_eq!("vendor=abc", state);
+}
+
+#[test]
+fn calls_without_trace_context_send_none() {
+    let mut server = Server::new();
+    server.register_simple("seen", |()| Ok(seen()));
+    let server = test_util::spawn(server);
+    let seen = Client::new()
+        .unwrap()
+        .call::<_, _, Vec<String>>(server.url(), "seen", ())
+        .unwrap();
+    assert!(seen.is_empty());
+}
+
+#[test]
+fn reads_only_valid_traceparents() {
+    let parse = |header| TraceContext::from_headers(header, None);
+    let trace = parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").unwrap();
+    assert_eq!("4bf92f3577b34da6a3ce929d0e0e4736", trace.trace_id);
+    assert_eq!(1, trace.flags);
+    assert!(trace
+        .traceparent()
+        .starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+    assert!(parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+    assert!(parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01").is_none());
+    assert!(parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_none());
+    assert!(parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+    assert!(parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra").is_some());
+}
@@ -0,0 +1,240 @@
+use super::super::datetime::DateTime;
+use super::super::{Call, Fault, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+#[test]
+fn converts_matching_values() {
+    assert_eq!(33, i32::try_from(Value::Int(33)).unwrap());
+    assert_eq!(33, i64::try_from(Value::Int(33)).unwrap());
+    assert_eq!(
+        -5_000_000_000,
+        i64::try_from(Value::String("-5000000000".into())).unwrap()
+    );
+    assert_eq!(33, u32::try_from(Value::Int(33)).unwrap());
+    assert_eq!(
+        10_000_000_000,
+        u64::try_from(Value::String("10000000000".into())).unwrap()
+    );
+    assert!(bool::try_from(Value::Bool(true)).unwrap());
+    assert_eq!(2.5, f64::try_from(Value::Double(2.5)).unwrap());
+    assert_eq!(3.0, f64::try_from(Value::Int(3)).unwrap());
+    assert_eq!(
+        "foo",
+        String::try_from(Value::String("foo".into())).unwrap()
+    );
+    assert_eq!(
+        vec![1, 2],
+        Vec::<u8>::try_from(Value::Base64(vec![1, 2])).unwrap()
+    );
+    assert_eq!(
+        vec![Value::Int(1)],
+        Vec::<Value>::try_from(Value::Array(vec![Value::Int(1)])).unwrap()
+    );
+    let mut members = HashMap::new();
+    members.insert("foo".to_owned(), Value::Int(1));
+    assert_eq!(
+        members.clone(),
+        HashMap::<String, Value>::try_from(Value::Struct(members)).unwrap()
+    );
+}
+
+#[test]
+fn describes_mismatched_values() {
+    assert_eq!(
+        "Issue while decoding data structure: Expected i4, got string \"33\"",
+        i32::try_from(Value::String("33".into()))
+            .unwrap_err()
+            .to_string()
+    );
+    assert_eq!(
+        "Issue while decoding data structure: Expected u32, got integer `-1`",
+        u32::try_from(Value::Int(-1)).unwrap_err().to_string()
+    );
+    assert_eq!(
+        "Issue while decoding data structure: Expected i8, got string \"foo\"",
+        i64::try_from(Value::String("foo".into()))
+            .unwrap_err()
+            .to_string()
+    );
+    assert!(String::try_from(Value::Int(3)).is_err());
+    assert!(Vec::<u8>::try_from(Value::String("AQI=".into())).is_err());
+    assert!(Vec::<Value>::try_from(Value::Struct(HashMap::new())).is_err());
+}
+
+#[test]
+fn converts_from_plain_types() {
+    assert_eq!(Value::Int(-3), Value::from(-3i8));
+    assert_eq!(Value::Int(60_000), Value::from(60_000u16));
+    assert_eq!(Value::Base64(vec![1, 2]), Value::from(&[1u8, 2][..]));
+    assert_eq!(Value::Base64(vec![1, 2]), Value::from(vec![1u8, 2]));
+    assert_eq!(Value::Array(vec![]), Value::from(None::<i32>));
+    assert_eq!(Value::Array(vec![Value::Int(3)]), Value::from(Some(3)));
+    assert_eq!(xmlrpc_value!({"port": 80}), Value::from(("port", 80)));
+    assert_eq!(
+        Value::DateTime("19980717T14:08:55".into()),
+        Value::from(DateTime::parse("1998-07-17T14:08:55").unwrap())
+    );
+    let params: Vec<Value> = vec![
+        "name".into(),
+        7u8.into(),
+        (&b"raw"[..]).into(),
+        Some("x").into(),
+    ];
+    assert_eq!(4, params.len());
+}
+
+#[test]
+fn coerces_through_accessors() {
+    assert_eq!(Some(33), Value::Int(33).as_i64());
+    assert_eq!(Some(5_000_000_000), Value::I8(5_000_000_000).as_i64());
+    assert_eq!(
+        Some(-5_000_000_000),
+        Value::String("-5000000000".into()).as_i64()
+    );
+    assert_eq!(None, Value::String("five".into()).as_i64());
+    assert_eq!(None, Value::Double(3.0).as_i64());
+    assert_eq!(Some(3.0), Value::Int(3).as_f64());
+    assert_eq!(Some(2.5), Value::String("2.5".into()).as_f64());
+    assert_eq!(Some("foo"), Value::String("foo".into()).as_str());
+    assert_eq!(None, Value::Int(3).as_str());
+    assert_eq!(Some(true), Value::Bool(true).as_bool());
+    assert_eq!(Some(true), Value::String("1".into()).as_bool());
+    assert_eq!(Some(false), Value::String("false".into()).as_bool());
+    assert_eq!(None, Value::Int(1).as_bool());
+}
+
+#[test]
+fn looks_up_nested_values() {
+    let value = xmlrpc_value!({
+        "config": {"ports": [80, 443], "a/b": {"~": true}},
+        "name": "foo",
+    });
+    assert_eq!(Some(&Value::String("foo".into())), value.get("name"));
+    assert_eq!(None, value.get("missing"));
+    assert_eq!(None, value.get_index(0));
+    assert_eq!(
+        Some(&Value::Int(443)),
+        value["config"]["ports"].get_index(1)
+    );
+    assert_eq!(None, value["config"]["ports"].get_index(2));
+    assert_eq!(Value::Int(80), value["config"]["ports"][0]);
+    assert_eq!(Some(&value), value.pointer(""));
+    assert_eq!(Some(&Value::Int(80)), value.pointer("/config/ports/0"));
+    assert_eq!(Some(&Value::Bool(true)), value.pointer("/config/a~1b/~0"));
+    assert_eq!(None, value.pointer("/config/ports/foo"));
+    assert_eq!(None, value.pointer("/name/0"));
+    assert_eq!(None, value.pointer("config"));
+}
+
+#[test]
+fn indexes_missing_values_as_nil() {
+    let value = xmlrpc_value!({"name": "foo", "ports": [80]});
+    assert_eq!(&Value::Nil, &value["missing"]);
+    assert_eq!(&Value::Nil, &value["ports"][1]);
+    assert_eq!(&Value::Nil, &value["name"][0]);
+    assert_eq!(&Value::Nil, &value["name"]["first"]);
+    assert_eq!(&Value::Nil, &value["missing"]["deeper"][3]);
+}
+
+#[test]
+fn displays_compactly() {
+    let value = xmlrpc_value!({
+        "name": "fo\"o",
+        "ports": [80, 2.0, false],
+        "blob": (Value::Base64(vec![1, 2, 3])),
+        "when": (Value::DateTime("19980717T14:08:55".into())),
+        "empty": {},
+    });
+    assert_eq!(
+        "{\"blob\": <base64 AQID>, \"empty\": {}, \"name\": \"fo\\\"o\", \
+         \"ports\": [80, 2.0, false], \"when\": <dateTime.iso8601 19980717T14:08:55>}",
+        value.to_string()
+    );
+    let call = Call {
+        name: "add".into(),
+        params: vec![Value::Int(1), Value::Array(vec![])],
+    };
+    assert_eq!("add(1, [])", call.to_string());
+    assert_eq!(
+        "<fault 4: \"Too many\">",
+        Fault::new(4, "Too many").to_string()
+    );
+}
+
+#[test]
+fn converts_messages_with_serde() {
+    use super::super::{Response, ResponseExt};
+
+    let call = Call::from_serialize("move", &(3, "left")).unwrap();
+    assert_eq!(vec![Value::Int(3), Value::from("left")], call.params);
+    assert_eq!((3, "left".to_string()), call.into_deserialize().unwrap());
+    assert!(Call::from_serialize("bad name", &(1,)).is_err());
+
+    let response = Response::from_serialize(&vec![1, 2]).unwrap();
+    assert_eq!(Ok(vec![xmlrpc_value!([1, 2])]), response);
+    assert_eq!(
+        Ok(vec![1, 2]),
+        response.into_deserialize::<Vec<i32>>().unwrap()
+    );
+    let fault: Response = Err(Fault::new(4, "Too many"));
+    assert_eq!(
+        Err(Fault::new(4, "Too many")),
+        fault.into_deserialize::<i32>().unwrap()
+    );
+}
+
+struct Broken;
+
+impl ::serde::Serialize for Broken {
+    fn serialize<S: ::serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(::serde::ser::Error::custom("Broken"))
+    }
+}
+
+#[test]
+fn builds_checked_messages() {
+    use super::super::{MethodNamePolicy, Response, ResponseExt};
+
+    let call = Call::builder("move")
+        .arg(3)
+        .serialize_arg(&vec!["left", "up"])
+        .build()
+        .unwrap();
+    assert_eq!(
+        vec![Value::Int(3), xmlrpc_value!(["left", "up"])],
+        call.params
+    );
+    assert!(Call::builder("bad name").arg(1).build().is_err());
+    assert!(Call::builder("a/b")
+        .method_names(MethodNamePolicy::Spec)
+        .build()
+        .is_ok());
+    assert!(Call::builder("broken")
+        .serialize_arg(&Broken)
+        .arg(1)
+        .build()
+        .is_err());
+
+    assert_eq!(
+        Ok(vec![Value::Int(1), Value::from("two")]),
+        Response::builder().param(1).param("two").build().unwrap()
+    );
+    let fault = Fault::new(4, "Too many");
+    assert_eq!(
+        Err(fault.clone()),
+        Response::builder().fault(fault.clone()).build().unwrap()
+    );
+    assert!(Response::builder()
+        .param(1)
+        .fault(fault.clone())
+        .build()
+        .is_err());
+    assert!(Response::builder()
+        .fault(Fault {
+            detail: Some(xmlrpc_value!({"faultCode": 5})),
+            ..fault
+        })
+        .build()
+        .is_err());
+}
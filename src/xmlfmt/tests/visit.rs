@@ -0,0 +1,46 @@
+use super::super::Value;
+
+#[test]
+fn walks_every_value_with_its_path() {
+    let value = xmlrpc_value!({"hosts": ["a", "b"], "odd key": {"port": 80}});
+    let mut count = 0;
+    value.walk(&mut |_| count += 1);
+    assert_eq!(6, count);
+
+    let mut paths = Vec::new();
+    value.walk_with_paths(&mut |path, value| {
+        if let Value::String(ref v) = *value {
+            paths.push(format!("{} = {}", path, v));
+        }
+    });
+    paths.sort();
+    assert_eq!(vec!["$.hosts[0] = a", "$.hosts[1] = b"], paths);
+}
+
+#[test]
+fn maps_nested_values_first() {
+    let value = xmlrpc_value!({"user": "admin", "password": "secret", "tries": [1, 2]});
+    let redacted = value.clone().map(|value| match value {
+        Value::Struct(mut members) => {
+            if members.contains_key("password") {
+                members.insert("password".into(), "***".into());
+            }
+            Value::Struct(members)
+        }
+        Value::Int(v) => Value::Int(v * 10),
+        value => value,
+    });
+    assert_eq!(
+        xmlrpc_value!({"user": "admin", "password": "***", "tries": [10, 20]}),
+        redacted
+    );
+
+    let rewritten = value.map_with_paths(|path, value| match path {
+        "$.user" | "$.tries[1]" => Value::Nil,
+        _ => value,
+    });
+    assert_eq!(
+        xmlrpc_value!({"user": (Value::Nil), "password": "secret", "tries": [1, (Value::Nil)]}),
+        rewritten
+    );
+}
@@ -0,0 +1,119 @@
+use super::super::{Fault, Value};
+use metaweblog;
+use test_util;
+use wordpress::{Post, PostFilter, WordPress};
+use Server;
+
+fn check_login(params: &[Value]) -> Result<(), Fault> {
+    match (
+        params.get(1).and_then(Value::as_str),
+        params.get(2).and_then(Value::as_str),
+    ) {
+        (Some("admin"), Some("secret")) => Ok(()),
+        _ => Err(Fault::new(403, "Incorrect username or password.")),
+    }
+}
+
+fn fake_wordpress() -> Server {
+    let mut server = Server::new();
+    server.register_value("wp.getPosts", |params| {
+        check_login(&params)?;
+        assert_eq!(Value::Int(1), params[0]);
+        assert_eq!(xmlrpc_value!({"post_type": "page", "number": 5}), params[3]);
+        Ok(vec![xmlrpc_value!([{
+            "post_id": "7",
+            "post_title": "About",
+            "post_date": (Value::DateTime("20240102T03:04:05".into())),
+            "post_date_gmt": (Value::DateTime("20240102T01:04:05".into())),
+            "sticky": false,
+            "terms": [{
+                "term_id": "3",
+                "name": "Rust",
+                "slug": "rust",
+                "taxonomy": "post_tag",
+                "count": 4
+            }]
+        }])])
+    });
+    server.register_value("wp.newPost", |params| {
+        check_login(&params)?;
+        assert_eq!(
+            xmlrpc_value!({
+                "post_title": "Hello",
+                "post_status": "draft",
+                "post_date": (Value::DateTime("20240301T09:30:00".into())),
+                "terms_names": { "post_tag": ["rust"] }
+            }),
+            params[3]
+        );
+        Ok(vec![Value::String("42".into())])
+    });
+    server.register_value("metaWeblog.editPost", |params| {
+        check_login(&params)?;
+        assert_eq!(Some("42"), params[0].as_str());
+        assert_eq!(xmlrpc_value!({ "title": "Hello again" }), params[3]);
+        assert_eq!(Value::Bool(true), params[4]);
+        Ok(vec![Value::Bool(true)])
+    });
+    server
+}
+
+#[test]
+fn reads_posts() {
+    let server = test_util::spawn(fake_wordpress());
+    let mut wordpress = WordPress::new(server.url().clone(), "admin", "secret").unwrap();
+    let filter = PostFilter {
+        post_type: Some("page".into()),
+        number: Some(5),
+        ..PostFilter::default()
+    };
+    let posts = wordpress.get_posts(&filter).unwrap().unwrap();
+    assert_eq!(1, posts.len());
+    assert_eq!(Some("7".to_string()), posts[0].post_id);
+    assert_eq!(Some("About".to_string()), posts[0].post_title);
+    assert_eq!(Some("20240102T03:04:05".to_string()), posts[0].post_date);
+    assert_eq!(
+        Some("20240102T01:04:05Z".to_string()),
+        posts[0].post_date_gmt
+    );
+    assert_eq!(Some(false), posts[0].sticky);
+    let terms = posts[0].terms.as_ref().unwrap();
+    assert_eq!("rust", terms[0].slug);
+    assert_eq!(None, posts[0].post_content);
+}
+
+#[test]
+fn writes_posts() {
+    let server = test_util::spawn(fake_wordpress());
+    let mut wordpress = WordPress::new(server.url().clone(), "admin", "secret").unwrap();
+    let mut post = Post {
+        post_title: Some("Hello".into()),
+        post_status: Some("draft".into()),
+        post_date: Some("2024-03-01T09:30:00".into()),
+        ..Post::default()
+    };
+    post.terms_names = Some(
+        vec![("post_tag".to_string(), vec!["rust".to_string()])]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(Ok("42".to_string()), wordpress.new_post(&post).unwrap());
+    let post = metaweblog::Post {
+        title: Some("Hello again".into()),
+        ..metaweblog::Post::default()
+    };
+    assert_eq!(
+        Ok(true),
+        wordpress.edit_metaweblog_post("42", &post, true).unwrap()
+    );
+}
+
+#[test]
+fn reports_bad_logins() {
+    let server = test_util::spawn(fake_wordpress());
+    let mut wordpress = WordPress::new(server.url().clone(), "admin", "guess").unwrap();
+    assert_eq!(
+        Err(Fault::new(403, "Incorrect username or password.")),
+        wordpress.new_post(&Post::default()).unwrap()
+    );
+}
@@ -1,44 +1,480 @@
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use serde::de::Unexpected;
+#[cfg(feature = "simd")]
+use base64_simd;
+#[cfg(feature = "bignum")]
+use bigdecimal::BigDecimal;
+#[cfg(feature = "simd")]
+use memchr;
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
+use serde::de::{DeserializeOwned, Unexpected};
+use serde::Serialize;
 use std;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use xml::escape::escape_str_pcdata;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::ops::Index;
+use xmlfmt::datetime::DateTime;
+use xmlfmt::error::{Error, ErrorKind, Result};
+use xmlfmt::options::{DateTimeFormat, EmptyStrings, IntTag, MethodNamePolicy, WriteOptions};
+use xmlfmt::{from_params, from_value, into_params, to_value, FromParams, IntoParams};
 
-#[derive(Clone, Debug, PartialEq)]
+/// More variants may be added for other extensions, or appear with features like `bignum`, so
+/// matches on values need a catch-all arm.
+///
+/// Values are equal when they hold the same data, so `Base64` equals `Base64Text` encoding the
+/// same bytes, whether `ParseOptions::lazy_base64` was on or not.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum Value {
     Int(i32),
+    /// A 64-bit integer, from the `i8` extension.
+    I8(i64),
     Bool(bool),
+    /// A plain `String`, as xml-rs hands out each piece of text as one. Parsing moves it in
+    /// as is, where a compact string type would copy it and free the original, so the
+    /// allocations would be the same. See `arena` and `parse::call_borrowed` for fewer.
     String(String),
     Double(f64),
     DateTime(String),
     Base64(Vec<u8>),
+    /// Base64 still encoded, as read with `ParseOptions::lazy_base64`, and written back as is.
+    Base64Text(String),
     Array(Vec<Value>),
     Struct(HashMap<String, Value>),
+    /// The `nil` extension.
+    Nil,
+    /// Apache's `ex:biginteger`.
+    #[cfg(feature = "bignum")]
+    BigInt(BigInt),
+    /// Apache's `ex:bigdecimal`.
+    #[cfg(feature = "bignum")]
+    BigDecimal(BigDecimal),
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::I8(a), Value::I8(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Double(a), Value::Double(b)) => a == b,
+            (Value::DateTime(a), Value::DateTime(b)) => a == b,
+            (Value::Base64(a), Value::Base64(b)) => a == b,
+            // Lazy base64 is checked to be canonical, so equal text means equal data.
+            (Value::Base64Text(a), Value::Base64Text(b)) => a == b,
+            (Value::Base64(bytes), Value::Base64Text(text))
+            | (Value::Base64Text(text), Value::Base64(bytes)) => {
+                decode_base64(text).is_ok_and(|decoded| decoded == *bytes)
+            }
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Struct(a), Value::Struct(b)) => a == b,
+            (Value::Nil, Value::Nil) => true,
+            #[cfg(feature = "bignum")]
+            (Value::BigInt(a), Value::BigInt(b)) => a == b,
+            #[cfg(feature = "bignum")]
+            (Value::BigDecimal(a), Value::BigDecimal(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 impl Value {
-    pub fn unexpected(&self) -> Unexpected {
+    pub fn unexpected(&self) -> Unexpected<'_> {
         match *self {
             Value::Int(v) => Unexpected::Signed(i64::from(v)),
+            Value::I8(v) => Unexpected::Signed(v),
             Value::Bool(v) => Unexpected::Bool(v),
             Value::String(ref v) => Unexpected::Str(v),
             Value::Double(v) => Unexpected::Float(v),
             Value::DateTime(_) => Unexpected::Other("dateTime.iso8601"),
             Value::Base64(ref v) => Unexpected::Bytes(v),
+            Value::Base64Text(_) => Unexpected::Other("base64"),
             Value::Array(_) => Unexpected::Seq,
             Value::Struct(_) => Unexpected::Map,
+            Value::Nil => Unexpected::Unit,
+            #[cfg(feature = "bignum")]
+            Value::BigInt(_) => Unexpected::Other("biginteger"),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(_) => Unexpected::Other("bigdecimal"),
+        }
+    }
+
+    pub fn get(&self, member: &str) -> Option<&Value> {
+        match *self {
+            Value::Struct(ref v) => v.get(member),
+            _ => None,
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&Value> {
+        match *self {
+            Value::Array(ref v) => v.get(index),
+            _ => None,
+        }
+    }
+
+    /// Reads an integer from any integer type, or from a string holding one.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(v) => Some(i64::from(v)),
+            Value::I8(v) => Some(v),
+            Value::String(ref v) => v.trim().parse().ok(),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(ref v) => i64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Reads a number from a double, any integer type, or a string holding one.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Double(v) => Some(v),
+            Value::String(ref v) => v.trim().parse().ok(),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Takes the data out of a base64 value, decoding it if it's still encoded.
+    pub fn into_bytes(self) -> Result<Vec<u8>> {
+        Vec::try_from(self)
+    }
+
+    /// Reads a boolean, also from the strings `true`, `false`, `1` and `0`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(v) => Some(v),
+            Value::String(ref v) => match v.trim() {
+                "true" | "1" => Some(true),
+                "false" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
         }
     }
+
+    /// Looks up a nested value by a JSON Pointer like `/config/ports/0`.
+    ///
+    /// Segments index into arrays when they are numbers, and into structs otherwise.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+        pointer[1..]
+            .split('/')
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .try_fold(self, |value, segment| match *value {
+                Value::Array(ref v) => segment.parse::<usize>().ok().and_then(|i| v.get(i)),
+                Value::Struct(ref v) => v.get(&segment),
+                _ => None,
+            })
+    }
+}
+
+/// What indexing gives for missing members and items, like `serde_json` does.
+static NIL: Value = Value::Nil;
+
+/// Nil if the value is not a struct, or has no such member.
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, member: &str) -> &Value {
+        self.get(member).unwrap_or(&NIL)
+    }
+}
+
+/// Nil if the value is not an array, or the index is out of bounds.
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.get_index(index).unwrap_or(&NIL)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Value {
+        Value::Int(v)
+    }
+}
+
+macro_rules! from_small_integer {
+    ($($t:ty)*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(v: $t) -> Value {
+                    Value::Int(i32::from(v))
+                }
+            }
+        )*
+    };
+}
+
+from_small_integer!(i8 i16 u8 u16);
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::Bool(v)
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Value {
+        Value::String(v)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(v: &'a str) -> Value {
+        Value::String(v.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Value {
+        Value::Double(v)
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl From<BigInt> for Value {
+    fn from(v: BigInt) -> Value {
+        Value::BigInt(v)
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl From<BigDecimal> for Value {
+    fn from(v: BigDecimal) -> Value {
+        Value::BigDecimal(v)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(v: Vec<u8>) -> Value {
+        Value::Base64(v)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Value {
+    fn from(v: &'a [u8]) -> Value {
+        Value::Base64(v.into())
+    }
+}
+
+/// Follows the default `OptionRepresentation`, so `None` is an empty array, and `Some(v)` holds
+/// just `v`.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Value {
+        Value::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Makes a struct with the single member.
+impl<K: Into<String>, V: Into<Value>> From<(K, V)> for Value {
+    fn from((key, value): (K, V)) -> Value {
+        let mut members = HashMap::new();
+        members.insert(key.into(), value.into());
+        Value::Struct(members)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(v: Vec<Value>) -> Value {
+        Value::Array(v)
+    }
+}
+
+impl From<HashMap<String, Value>> for Value {
+    fn from(v: HashMap<String, Value>) -> Value {
+        Value::Struct(v)
+    }
+}
+
+fn mismatch<T>(expected: &str, v: &Value) -> Result<T> {
+    bail!(ErrorKind::Decoding(format!(
+        "Expected {}, got {}",
+        expected,
+        v.unexpected()
+    )))
+}
+
+macro_rules! try_from_value {
+    ($t:ty, $expected:expr, $($pattern:pat => $result:expr),+) => {
+        impl TryFrom<Value> for $t {
+            type Error = Error;
+
+            fn try_from(v: Value) -> Result<$t> {
+                match v {
+                    $($pattern => $result,)+
+                    v => mismatch($expected, &v),
+                }
+            }
+        }
+    };
+}
+
+fn wide_integer<T: std::str::FromStr>(v: &str, expected: &str) -> Result<T> {
+    v.parse().map_err(|_| {
+        ErrorKind::Decoding(format!("Expected {}, got {}", expected, Unexpected::Str(v))).into()
+    })
+}
+
+fn unsigned<T: TryFrom<i64>>(v: i64, expected: &str) -> Result<T> {
+    T::try_from(v).map_err(|_| {
+        ErrorKind::Decoding(format!(
+            "Expected {}, got {}",
+            expected,
+            Unexpected::Signed(v)
+        ))
+        .into()
+    })
+}
+
+try_from_value!(i32, "i4", Value::Int(v) => Ok(v));
+try_from_value!(i64, "i8",
+    Value::Int(v) => Ok(i64::from(v)),
+    Value::I8(v) => Ok(v),
+    Value::String(ref v) => wide_integer(v, "i8"));
+try_from_value!(u32, "u32",
+    Value::Int(v) => unsigned(i64::from(v), "u32"),
+    Value::I8(v) => unsigned(v, "u32"),
+    Value::String(ref v) => wide_integer(v, "u32"));
+try_from_value!(u64, "u64",
+    Value::Int(v) => unsigned(i64::from(v), "u64"),
+    Value::I8(v) => unsigned(v, "u64"),
+    Value::String(ref v) => wide_integer(v, "u64"));
+try_from_value!(bool, "boolean", Value::Bool(v) => Ok(v));
+try_from_value!(f64, "double",
+    Value::Double(v) => Ok(v),
+    Value::Int(v) => Ok(f64::from(v)));
+try_from_value!(String, "string", Value::String(v) => Ok(v));
+try_from_value!(Vec<u8>, "base64",
+    Value::Base64(v) => Ok(v),
+    Value::Base64Text(ref v) => decode_base64(v));
+try_from_value!(Vec<Value>, "array", Value::Array(v) => Ok(v));
+try_from_value!(HashMap<String, Value>, "struct", Value::Struct(v) => Ok(v));
+#[cfg(feature = "bignum")]
+try_from_value!(BigInt, "biginteger",
+    Value::BigInt(v) => Ok(v),
+    Value::Int(v) => Ok(v.into()),
+    Value::I8(v) => Ok(v.into()),
+    Value::String(ref v) => wide_integer(v, "biginteger"));
+#[cfg(feature = "bignum")]
+try_from_value!(BigDecimal, "bigdecimal",
+    Value::BigDecimal(v) => Ok(v),
+    Value::BigInt(v) => Ok(v.into()),
+    Value::Int(v) => Ok(v.into()),
+    Value::I8(v) => Ok(v.into()),
+    Value::String(ref v) => wide_integer(v, "bigdecimal"));
+
+/// Renders a compact, non-XML form meant for logs, like `{"ports": [80, 443]}`.
+///
+/// Struct members are sorted by name, so output is stable.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::I8(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::String(ref v) => write!(f, "{:?}", v),
+            Value::Double(v) => write!(f, "{:?}", v),
+            Value::DateTime(ref v) => write!(f, "<dateTime.iso8601 {}>", v),
+            Value::Base64(ref v) => write!(f, "<base64 {}>", STANDARD.encode(v)),
+            Value::Base64Text(ref v) => write!(f, "<base64 {}>", v),
+            Value::Array(ref v) => {
+                f.write_str("[")?;
+                write_separated(f, v.iter().map(|item| item as &dyn fmt::Display))?;
+                f.write_str("]")
+            }
+            Value::Struct(ref v) => {
+                let mut members = v.iter().collect::<Vec<_>>();
+                members.sort_by(|a, b| a.0.cmp(b.0));
+                let members = members
+                    .into_iter()
+                    .map(|(key, value)| Member { key, value })
+                    .collect::<Vec<_>>();
+                f.write_str("{")?;
+                write_separated(f, members.iter().map(|m| m as &dyn fmt::Display))?;
+                f.write_str("}")
+            }
+            Value::Nil => f.write_str("nil"),
+            #[cfg(feature = "bignum")]
+            Value::BigInt(ref v) => write!(f, "{}", v),
+            #[cfg(feature = "bignum")]
+            Value::BigDecimal(ref v) => write!(f, "{}", v),
+        }
+    }
+}
+
+struct Member<'a> {
+    key: &'a str,
+    value: &'a Value,
+}
+
+impl<'a> fmt::Display for Member<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}: {}", self.key, self.value)
+    }
+}
+
+fn write_separated<'a, I>(f: &mut fmt::Formatter, items: I) -> fmt::Result
+where
+    I: Iterator<Item = &'a dyn fmt::Display>,
+{
+    for (index, item) in items.enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
 }
 
 pub type Params = Vec<Value>;
 
+// Codes of the interoperability spec for fault codes.
+pub const PARSE_ERROR: i32 = -32700;
+pub const UNSUPPORTED_ENCODING: i32 = -32701;
+pub const INVALID_CHARACTER: i32 = -32702;
+pub const INVALID_XML_RPC: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+pub const APPLICATION_ERROR: i32 = -32500;
+pub const SYSTEM_ERROR: i32 = -32400;
+pub const TRANSPORT_ERROR: i32 = -32300;
+/// In the spec's range for implementation-defined server errors.
+pub const REPLAYED_CALL: i32 = -32001;
+
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 pub struct Fault {
     #[serde(rename = "faultCode")]
     pub code: i32,
     #[serde(rename = "faultString")]
     pub message: String,
+    /// Machine-readable context, sent as extra members of the fault struct.
+    ///
+    /// Members of a struct are written next to `faultCode` and `faultString`, leaving out
+    /// members by those names, and read back the same way. Any other value, or a struct with
+    /// only a `detail` member, is written as a `detail` member, and a fault with nothing but
+    /// that besides its code and message is read back with its value. Faults without a usable
+    /// code or message are parsed with their whole value here.
+    #[serde(skip)]
+    pub detail: Option<Value>,
 }
 
 impl Fault {
@@ -49,91 +485,879 @@ impl Fault {
         Fault {
             code,
             message: message.into(),
+            detail: None,
         }
     }
+
+    /// A fault with the interoperability spec's -32602 code, for invalid method parameters.
+    pub fn invalid_params<T: Into<String>>(message: T) -> Fault {
+        Fault::new(INVALID_PARAMS, message)
+    }
+
+    /// A fault with the interoperability spec's -32300 code, for calls that failed to go through.
+    pub fn transport_error<T: Into<String>>(message: T) -> Fault {
+        Fault::new(TRANSPORT_ERROR, message)
+    }
+
+    /// A fault with the interoperability spec's -32400 code, for calls the server can't take on.
+    pub fn system_error<T: Into<String>>(message: T) -> Fault {
+        Fault::new(SYSTEM_ERROR, message)
+    }
+
+    /// A fault with the -32001 code, for calls rejected by `Server::protect_from_replays`.
+    pub fn replayed_call<T: Into<String>>(message: T) -> Fault {
+        Fault::new(REPLAYED_CALL, message)
+    }
+
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The request couldn't be parsed, with a code from -32799 to -32700.
+    pub fn is_parse_error(&self) -> bool {
+        (-32799..=-32700).contains(&self.code)
+    }
+
+    /// The request was parsed but not valid, like a call to a missing method or with invalid
+    /// params, with a code from -32699 to -32600, or from -32099 to -32000 for errors particular
+    /// to the server.
+    pub fn is_server_error(&self) -> bool {
+        (-32699..=-32600).contains(&self.code) || (-32099..=-32000).contains(&self.code)
+    }
+
+    /// With a code from -32599 to -32500.
+    pub fn is_application_error(&self) -> bool {
+        (-32599..=-32500).contains(&self.code)
+    }
+
+    /// With a code from -32499 to -32400.
+    pub fn is_system_error(&self) -> bool {
+        (-32499..=-32400).contains(&self.code)
+    }
+
+    /// With a code from -32399 to -32300.
+    pub fn is_transport_error(&self) -> bool {
+        (-32399..=-32300).contains(&self.code)
+    }
+
+    /// Sets the detail, leaving out any `faultCode` and `faultString` members it has.
+    pub fn with_detail<T: Into<Value>>(self, detail: T) -> Fault {
+        let detail = match detail.into() {
+            Value::Struct(mut members) => {
+                for name in &FAULT_MEMBERS {
+                    members.remove(*name);
+                }
+                Value::Struct(members)
+            }
+            detail => detail,
+        };
+        Fault {
+            detail: Some(detail),
+            ..self
+        }
+    }
+}
+
+/// The members of a fault struct that its detail can't have.
+const FAULT_MEMBERS: [&str; 2] = ["faultCode", "faultString"];
+
+/// Whether a struct detail is written as members of the fault struct, rather than inside a
+/// `detail` member, which would be read back as the whole detail if it was the only one.
+fn spreads_detail(members: &HashMap<String, Value>) -> bool {
+    members
+        .keys()
+        .any(|name| name != "detail" && !FAULT_MEMBERS.contains(&name.as_str()))
+}
+
+/// The members of a struct detail to write next to the fault's own.
+fn detail_members(members: &HashMap<String, Value>) -> Cow<'_, HashMap<String, Value>> {
+    if FAULT_MEMBERS.iter().any(|name| members.contains_key(*name)) {
+        let mut members = members.clone();
+        for name in &FAULT_MEMBERS {
+            members.remove(*name);
+        }
+        Cow::Owned(members)
+    } else {
+        Cow::Borrowed(members)
+    }
+}
+
+/// Reads a fault struct, keeping members besides `faultCode` and `faultString` as the detail.
+impl TryFrom<Value> for Fault {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Fault> {
+        let mut members = HashMap::<String, Value>::try_from(v)?;
+        // Some servers send the code as a string.
+        let code = match members.remove("faultCode") {
+            Some(v) => match v.as_i64().map(i32::try_from) {
+                Some(Ok(code)) => code,
+                _ => return mismatch("i4 faultCode", &v),
+            },
+            None => bail!(ErrorKind::Decoding("Fault has no faultCode".into())),
+        };
+        let message = match members.remove("faultString") {
+            Some(v) => String::try_from(v)?,
+            None => bail!(ErrorKind::Decoding("Fault has no faultString".into())),
+        };
+        let detail = if members.is_empty() {
+            None
+        } else if members.len() == 1 && members.contains_key("detail") {
+            members.remove("detail")
+        } else {
+            Some(Value::Struct(members))
+        };
+        Ok(Fault {
+            code,
+            message,
+            detail,
+        })
+    }
+}
+
+/// Lets faults be returned with `?` from functions returning other errors, like
+/// `Box<dyn Error>`, which `Server::register_fallible` turns back into the same fault.
+impl std::error::Error for Fault {}
+
+/// Writes a fault struct, the way a fault response holds it.
+impl From<Fault> for Value {
+    fn from(fault: Fault) -> Value {
+        let mut members = match fault.detail {
+            Some(Value::Struct(members)) if spreads_detail(&members) => members,
+            Some(detail) => vec![("detail".to_owned(), detail)].into_iter().collect(),
+            None => HashMap::new(),
+        };
+        members.insert("faultCode".into(), Value::Int(fault.code));
+        members.insert("faultString".into(), Value::String(fault.message));
+        Value::Struct(members)
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<fault {}: {:?}", self.code, self.message)?;
+        if let Some(ref detail) = self.detail {
+            write!(f, " {}", detail)?;
+        }
+        f.write_str(">")
+    }
 }
 
 pub type Response = std::result::Result<Params, Fault>;
 
+/// Conveniences for `Response`, which is a plain `Result`.
+pub trait ResponseExt: Sized {
+    /// Builds a response like `Response::builder().param(5).build()`, with the trait in scope.
+    fn builder() -> ResponseBuilder;
+
+    /// A successful response with `v` as its single param.
+    fn from_serialize<T: Serialize>(v: &T) -> Result<Self>;
+
+    /// Converts the result like `Client::call`, passing faults through.
+    fn into_deserialize<T: DeserializeOwned>(self) -> Result<std::result::Result<T, Fault>>;
+}
+
+impl ResponseExt for Response {
+    fn builder() -> ResponseBuilder {
+        ResponseBuilder::default()
+    }
+
+    fn from_serialize<T: Serialize>(v: &T) -> Result<Response> {
+        Ok(Ok(vec![to_value(v)?]))
+    }
+
+    fn into_deserialize<T: DeserializeOwned>(self) -> Result<std::result::Result<T, Fault>> {
+        match self {
+            Ok(mut params) => {
+                let value = if params.len() == 1 {
+                    params.pop().unwrap()
+                } else {
+                    Value::Array(params)
+                };
+                from_value(value).map(Ok)
+            }
+            Err(fault) => Ok(Err(fault)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Call {
     pub name: String,
     pub params: Params,
 }
 
+impl Call {
+    /// Makes a call, refusing names that aren't `MethodNamePolicy::Printable`.
+    pub fn new<T: Into<String>>(name: T, params: Params) -> Result<Call> {
+        let name = name.into();
+        check_method_name(&name, MethodNamePolicy::default())?;
+        Ok(Call { name, params })
+    }
+
+    /// Makes a call with params converted like `into_params`.
+    pub fn from_serialize<K: Into<String>, T: IntoParams>(name: K, params: &T) -> Result<Call> {
+        Call::new(name, into_params(params)?)
+    }
+
+    /// Converts the params like `from_params`.
+    pub fn into_deserialize<T: FromParams>(self) -> Result<T> {
+        from_params(self.params)
+    }
+
+    /// Builds a call like `Call::builder("math.add").arg(5).arg(3).build()`.
+    pub fn builder<T: Into<String>>(name: T) -> CallBuilder {
+        CallBuilder {
+            name: name.into(),
+            method_names: MethodNamePolicy::default(),
+            params: Ok(Vec::new()),
+        }
+    }
+}
+
+/// Collects the params of a call, failing on its name or the first param that doesn't convert
+/// once built.
+#[derive(Debug)]
+pub struct CallBuilder {
+    name: String,
+    method_names: MethodNamePolicy,
+    params: Result<Params>,
+}
+
+impl CallBuilder {
+    pub fn arg<T: Into<Value>>(mut self, v: T) -> CallBuilder {
+        if let Ok(ref mut params) = self.params {
+            params.push(v.into());
+        }
+        self
+    }
+
+    /// Adds a param converted like `to_value`.
+    pub fn serialize_arg<T: Serialize>(mut self, v: &T) -> CallBuilder {
+        self.params = self
+            .params
+            .and_then(|mut params| to_value(v).map(|v| params.push(v)).map(|_| params));
+        self
+    }
+
+    /// Checks the name against `policy`, instead of `MethodNamePolicy::Printable`.
+    pub fn method_names(mut self, policy: MethodNamePolicy) -> CallBuilder {
+        self.method_names = policy;
+        self
+    }
+
+    pub fn build(self) -> Result<Call> {
+        check_method_name(&self.name, self.method_names)?;
+        Ok(Call {
+            name: self.name,
+            params: self.params?,
+        })
+    }
+}
+
+/// Collects the params of a response, or its fault, failing on a response with both, on a fault
+/// detail holding `faultCode` or `faultString` of its own, or on the first param that doesn't
+/// convert once built.
+#[derive(Debug)]
+pub struct ResponseBuilder {
+    params: Result<Params>,
+    fault: Option<Fault>,
+}
+
+impl Default for ResponseBuilder {
+    fn default() -> Self {
+        ResponseBuilder {
+            params: Ok(Vec::new()),
+            fault: None,
+        }
+    }
+}
+
+impl ResponseBuilder {
+    pub fn param<T: Into<Value>>(mut self, v: T) -> ResponseBuilder {
+        if let Ok(ref mut params) = self.params {
+            params.push(v.into());
+        }
+        self
+    }
+
+    /// Adds a param converted like `to_value`.
+    pub fn serialize_param<T: Serialize>(mut self, v: &T) -> ResponseBuilder {
+        self.params = self
+            .params
+            .and_then(|mut params| to_value(v).map(|v| params.push(v)).map(|_| params));
+        self
+    }
+
+    pub fn fault(mut self, fault: Fault) -> ResponseBuilder {
+        self.fault = Some(fault);
+        self
+    }
+
+    pub fn build(self) -> Result<Response> {
+        let params = self.params?;
+        let fault = match self.fault {
+            Some(fault) => fault,
+            None => return Ok(Ok(params)),
+        };
+        if !params.is_empty() {
+            bail!(ErrorKind::UnsupportedData(
+                "A response can't have both params and a fault".into()
+            ));
+        }
+        if let Some(Value::Struct(ref members)) = fault.detail {
+            if members.contains_key("faultCode") || members.contains_key("faultString") {
+                bail!(ErrorKind::UnsupportedData(
+                    "Fault detail can't have its own faultCode or faultString".into()
+                ));
+            }
+        }
+        Ok(Err(fault))
+    }
+}
+
+pub fn check_method_name(name: &str, policy: MethodNamePolicy) -> Result<()> {
+    let valid = match policy {
+        MethodNamePolicy::Printable => !name
+            .chars()
+            .any(|c| c.is_control() || c.is_whitespace() || "<>&\"'".contains(c)),
+        MethodNamePolicy::Spec => {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_.:/".contains(c))
+        }
+        MethodNamePolicy::Any => true,
+    };
+    if !valid {
+        bail!(ErrorKind::UnsupportedData(format!(
+            "Method name {:?} is not allowed",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Renders like a function call, `name(param, ...)`.
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        write_separated(f, self.params.iter().map(|p| p as &dyn fmt::Display))?;
+        f.write_str(")")
+    }
+}
+
 pub trait ToXml {
-    fn to_xml(&self) -> String;
+    fn write_xml_with<W: Write>(&self, w: &mut W, options: &WriteOptions) -> io::Result<()>;
+
+    fn write_xml<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_xml_with(w, &WriteOptions::default())
+    }
+
+    fn to_xml_with(&self, options: &WriteOptions) -> String {
+        let mut buffer = Vec::with_capacity(options.write_buffer_size);
+        self.write_xml_with(&mut buffer, options)
+            .expect("Writing into a Vec can't fail");
+        String::from_utf8(buffer).expect("Generated XML is always valid UTF-8")
+    }
+
+    fn to_xml(&self) -> String {
+        self.to_xml_with(&WriteOptions::default())
+    }
+}
+
+fn write_declaration<W: Write>(w: &mut W, options: &WriteOptions) -> io::Result<()> {
+    let declaration = match options.declaration {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    w.write_all(br#"<?xml version="1.0""#)?;
+    if declaration.encoding {
+        w.write_all(br#" encoding="UTF-8""#)?;
+    }
+    match declaration.standalone {
+        Some(true) => w.write_all(br#" standalone="yes""#)?,
+        Some(false) => w.write_all(br#" standalone="no""#)?,
+        None => {}
+    }
+    w.write_all(b"?>")
+}
+
+fn write_params<W: Write>(w: &mut W, params: &[Value], options: &WriteOptions) -> io::Result<()> {
+    if params.is_empty() && options.omit_empty_params {
+        return Ok(());
+    }
+    w.write_all(b"<params>")?;
+    for param in params {
+        w.write_all(b"<param>")?;
+        write_value(w, param, options, false)?;
+        w.write_all(b"</param>")?;
+    }
+    w.write_all(b"</params>")
+}
+
+fn write_members<W: Write>(
+    w: &mut W,
+    members: &HashMap<String, Value>,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    if options.canonical || options.sort_members {
+        let mut members: Vec<_> = members.iter().collect();
+        members.sort_unstable_by_key(|&(key, _)| key);
+        for (key, value) in members {
+            write_member(w, key, value, options)?;
+        }
+    } else {
+        for (key, value) in members {
+            write_member(w, key, value, options)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_member<W: Write>(
+    w: &mut W,
+    key: &str,
+    value: &Value,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    w.write_all(b"<member><name>")?;
+    write_escaped(w, key)?;
+    w.write_all(b"</name>")?;
+    write_value(w, value, options, false)?;
+    w.write_all(b"</member>")
+}
+
+/// Writes element content, copying runs of text that need no escaping in one go.
+fn write_escaped<W: Write>(w: &mut W, text: &str) -> io::Result<()> {
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    for index in escaped_bytes(bytes) {
+        let entity: &[u8] = match bytes[index] {
+            b'<' => b"&lt;",
+            b'>' => b"&gt;",
+            _ => b"&amp;",
+        };
+        w.write_all(&bytes[start..index])?;
+        w.write_all(entity)?;
+        start = index + 1;
+    }
+    w.write_all(&bytes[start..])
+}
+
+/// Positions of the bytes `write_escaped` replaces.
+#[cfg(not(feature = "simd"))]
+fn escaped_bytes(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|&(_, &byte)| byte == b'<' || byte == b'>' || byte == b'&')
+        .map(|(index, _)| index)
+}
+
+#[cfg(feature = "simd")]
+fn escaped_bytes(bytes: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    memchr::memchr3_iter(b'<', b'>', b'&', bytes)
+}
+
+fn base64_line_length(options: &WriteOptions) -> usize {
+    match options.base64_line_length {
+        Some(length) if !options.canonical => length,
+        _ => 0,
+    }
+}
+
+/// Encodes through a stack buffer, breaking lines every `line_length` characters unless it's 0.
+///
+/// `column` carries over between calls encoding parts of the same data, which have to be
+/// multiples of 3 bytes long, except for the last one.
+fn write_base64<W: Write>(
+    w: &mut W,
+    data: &[u8],
+    line_length: usize,
+    column: &mut usize,
+) -> io::Result<()> {
+    let mut buffer = [0u8; 1024];
+    // Chunks are a multiple of 3 bytes, so padding only ever ends the last one.
+    for chunk in data.chunks(768) {
+        let length = encode_base64(chunk, &mut buffer);
+        let mut encoded = &buffer[..length];
+        while line_length > 0 && *column + encoded.len() > line_length {
+            let (line, rest) = encoded.split_at(line_length - *column);
+            w.write_all(line)?;
+            w.write_all(b"\n")?;
+            *column = 0;
+            encoded = rest;
+        }
+        w.write_all(encoded)?;
+        *column += encoded.len();
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "simd"))]
+fn encode_base64(data: &[u8], buffer: &mut [u8]) -> usize {
+    STANDARD
+        .encode_slice(data, buffer)
+        .expect("Buffer fits a whole encoded chunk")
+}
+
+#[cfg(feature = "simd")]
+fn encode_base64(data: &[u8], buffer: &mut [u8]) -> usize {
+    base64_simd::STANDARD
+        .encode(data, base64_simd::Out::from_slice(buffer))
+        .len()
+}
+
+#[cfg(not(feature = "simd"))]
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(text)
+        .map_err(|err| ErrorKind::Decoding(format!("Failed to parse base64: {}", err)).into())
+}
+
+#[cfg(feature = "simd")]
+fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    base64_simd::STANDARD
+        .decode_to_vec(text)
+        .map_err(|err| ErrorKind::Decoding(format!("Failed to parse base64: {}", err)).into())
+}
+
+/// A param of a streamed response.
+pub enum StreamedParam {
+    Value(Value),
+    /// Base64 data, read and encoded a chunk at a time.
+    Base64(Box<dyn Read + Send>),
+}
+
+impl From<Value> for StreamedParam {
+    fn from(value: Value) -> StreamedParam {
+        StreamedParam::Value(value)
+    }
+}
+
+/// Reads as the XML of a response with the given params, encoding each of them only once
+/// everything before it has been read.
+pub struct ResponseStream {
+    params: Box<dyn Iterator<Item = StreamedParam> + Send>,
+    options: WriteOptions,
+    base64: Option<(Box<dyn Read + Send>, usize)>,
+    started: bool,
+    finished: bool,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl ResponseStream {
+    pub fn new<I>(params: I, options: WriteOptions) -> ResponseStream
+    where
+        I: IntoIterator<Item = StreamedParam>,
+        I::IntoIter: Send + 'static,
+    {
+        ResponseStream {
+            params: Box::new(params.into_iter()),
+            options,
+            base64: None,
+            started: false,
+            finished: false,
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Encodes the next part into the buffer, returning false once everything is encoded.
+    fn fill(&mut self) -> io::Result<bool> {
+        self.buffer.clear();
+        self.position = 0;
+        let w = &mut self.buffer;
+        if !self.started {
+            self.started = true;
+            write_declaration(w, &self.options)?;
+            w.write_all(b"<methodResponse")?;
+            write_namespace(w, &self.options)?;
+            w.write_all(b"><params>")?;
+            return Ok(true);
+        }
+        if let Some((mut reader, mut column)) = self.base64.take() {
+            // A multiple of 3 bytes, so that only the last chunk gets padded.
+            let mut chunk = [0u8; 3 << 10];
+            let mut length = 0;
+            while length < chunk.len() {
+                match reader.read(&mut chunk[length..]) {
+                    Ok(0) => break,
+                    Ok(read) => length += read,
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            write_base64(
+                w,
+                &chunk[..length],
+                base64_line_length(&self.options),
+                &mut column,
+            )?;
+            if length < chunk.len() {
+                w.write_all(b"</base64></value></param>")?;
+            } else {
+                self.base64 = Some((reader, column));
+            }
+            return Ok(true);
+        }
+        if self.finished {
+            return Ok(false);
+        }
+        match self.params.next() {
+            Some(StreamedParam::Value(ref value)) => {
+                w.write_all(b"<param>")?;
+                write_value(w, value, &self.options, false)?;
+                w.write_all(b"</param>")?;
+            }
+            Some(StreamedParam::Base64(reader)) => {
+                w.write_all(b"<param><value><base64>")?;
+                self.base64 = Some((reader, 0));
+            }
+            None => {
+                self.finished = true;
+                w.write_all(b"</params></methodResponse>")?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl Read for ResponseStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.buffer.len() {
+            if !self.fill()? {
+                return Ok(0);
+            }
+        }
+        let remaining = &self.buffer[self.position..];
+        let length = remaining.len().min(buf.len());
+        buf[..length].copy_from_slice(&remaining[..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// Namespace of the Apache XML-RPC extension types, like `ex:i8` and `ex:nil`.
+const EXTENSIONS_NAMESPACE: &str = "http://ws.apache.org/xmlrpc/namespaces/extensions";
+
+fn write_namespace<W: Write>(w: &mut W, options: &WriteOptions) -> io::Result<()> {
+    if options.apache_extensions {
+        write!(w, r#" xmlns:ex="{}""#, EXTENSIONS_NAMESPACE)?;
+    }
+    Ok(())
+}
+
+fn extension_prefix(options: &WriteOptions) -> &'static str {
+    if options.apache_extensions {
+        "ex:"
+    } else {
+        ""
+    }
+}
+
+/// Big numbers are only typed with Apache's extensions, and are strings otherwise.
+#[cfg(feature = "bignum")]
+fn write_big_number<W: Write, T: fmt::Display>(
+    w: &mut W,
+    tag: &str,
+    v: &T,
+    options: &WriteOptions,
+) -> io::Result<()> {
+    if options.apache_extensions {
+        write!(w, "<ex:{0}>{1}</ex:{0}>", tag, v)
+    } else {
+        write!(w, "<string>{}</string>", v)
+    }
+}
+
+fn format_double(v: f64, options: &WriteOptions) -> String {
+    let v = if options.canonical && v == 0.0 {
+        0.0
+    } else {
+        v
+    };
+    match options.double_precision {
+        Some(precision) => {
+            let text = format!("{:.*}", precision, v);
+            if text.contains('.') {
+                let text = text.trim_end_matches('0').trim_end_matches('.');
+                match text {
+                    "-0" => "0".into(),
+                    _ => text.into(),
+                }
+            } else {
+                text
+            }
+        }
+        None => v.to_string(),
+    }
 }
 
 impl ToXml for Call {
-    fn to_xml(&self) -> String {
-        use std::fmt::Write;
-        format!(
-            include_str!("templates/call.xml"),
-            name = self.name,
-            params = self.params.iter().fold(String::new(), |mut output, param| {
-                let _ = write!(output, "<param>{}</param>", param.to_xml());
-                output
-            }),
-        )
+    fn write_xml_with<W: Write>(&self, w: &mut W, options: &WriteOptions) -> io::Result<()> {
+        write_declaration(w, options)?;
+        w.write_all(b"<methodCall")?;
+        write_namespace(w, options)?;
+        w.write_all(b"><methodName>")?;
+        write_escaped(w, &self.name)?;
+        w.write_all(b"</methodName>")?;
+        write_params(w, &self.params, options)?;
+        w.write_all(b"</methodCall>")
     }
 }
 
 impl ToXml for Response {
-    fn to_xml(&self) -> String {
-        use std::fmt::Write;
+    fn write_xml_with<W: Write>(&self, w: &mut W, options: &WriteOptions) -> io::Result<()> {
+        write_declaration(w, options)?;
+        w.write_all(b"<methodResponse")?;
+        write_namespace(w, options)?;
+        w.write_all(b">")?;
         match *self {
-            Ok(ref params) => format!(
-                include_str!("templates/response_success.xml"),
-                params = params.iter().fold(String::new(), |mut output, param| {
-                    let _ = write!(output, "<param>{}</param>", param.to_xml());
-                    output
-                })
-            ),
-            Err(Fault { code, ref message }) => format!(
-                include_str!("templates/response_fault.xml"),
-                code = code,
-                message = message
-            ),
+            Ok(ref params) => write_params(w, params, options)?,
+            Err(ref fault) => {
+                write!(
+                    w,
+                    "<fault><value><struct>\
+                     <member><name>faultCode</name><value><int>{}</int></value></member>\
+                     <member><name>faultString</name><value><string>",
+                    fault.code
+                )?;
+                write_escaped(w, &fault.message)?;
+                w.write_all(b"</string></value></member>")?;
+                match fault.detail {
+                    Some(Value::Struct(ref members)) if spreads_detail(members) => {
+                        write_members(w, &detail_members(members), options)?
+                    }
+                    Some(ref detail) => {
+                        w.write_all(b"<member><name>detail</name>")?;
+                        write_value(w, detail, options, false)?;
+                        w.write_all(b"</member>")?;
+                    }
+                    None => {}
+                }
+                w.write_all(b"</struct></value></fault>")?
+            }
         }
+        w.write_all(b"</methodResponse>")
     }
 }
 
 impl ToXml for Value {
-    fn to_xml(&self) -> String {
-        match *self {
-            Value::Int(v) => format!("<value><i4>{}</i4></value>", v),
-            Value::Bool(v) => format!(
-                "<value><boolean>{}</boolean></value>",
-                if v { 1 } else { 0 }
-            ),
-            Value::String(ref v) => {
-                format!("<value><string>{}</string></value>", escape_str_pcdata(v))
-            }
-            Value::Double(v) => format!("<value><double>{}</double></value>", v),
-            Value::DateTime(ref v) => {
-                format!("<value><dateTime.iso8601>{}</dateTime.iso8601></value>", v)
-            }
-            Value::Base64(ref v) => {
-                format!("<value><base64>{}</base64></value>", STANDARD.encode(v))
-            }
-            Value::Array(ref v) => format!(
-                "<value><array><data>{}</data></array></value>",
-                v.iter().map(Value::to_xml).collect::<String>()
-            ),
-            Value::Struct(ref v) => format!(
-                "<value><struct>{}</struct></value>",
-                v.iter().fold(String::new(), |mut output, (key, value)| {
-                    use std::fmt::Write;
-                    let _ = write!(
-                        output,
-                        "<member><name>{}</name>{}</member>",
-                        key,
-                        value.to_xml()
-                    );
-                    output
-                })
-            ),
+    fn write_xml_with<W: Write>(&self, w: &mut W, options: &WriteOptions) -> io::Result<()> {
+        write_value(w, self, options, true)
+    }
+}
+
+/// Writes a `<value>`, which declares the extension namespace if it's the `root` element.
+fn write_value<W: Write>(
+    w: &mut W,
+    value: &Value,
+    options: &WriteOptions,
+    root: bool,
+) -> io::Result<()> {
+    w.write_all(b"<value")?;
+    if root {
+        write_namespace(w, options)?;
+    }
+    if let Value::String(ref v) = *value {
+        let self_closing = match options.empty_strings {
+            EmptyStrings::Pair => false,
+            EmptyStrings::SelfClosing => options.untyped_strings,
+            EmptyStrings::BareValue => true,
+        };
+        if v.is_empty() && self_closing {
+            return w.write_all(b"/>");
+        }
+    }
+    w.write_all(b">")?;
+    let prefix = extension_prefix(options);
+    match *value {
+        Value::Int(v) => match options.int_tag {
+            IntTag::I4 => write!(w, "<i4>{}</i4>", v)?,
+            IntTag::Int => write!(w, "<int>{}</int>", v)?,
+        },
+        Value::I8(v) => write!(w, "<{0}i8>{1}</{0}i8>", prefix, v)?,
+        Value::Bool(v) => write!(w, "<boolean>{}</boolean>", if v { 1 } else { 0 })?,
+        Value::String(ref v) if options.untyped_strings => write_escaped(w, v)?,
+        Value::String(ref v) if v.is_empty() && options.empty_strings != EmptyStrings::Pair => {
+            w.write_all(b"<string/>")?
+        }
+        Value::String(ref v) => {
+            w.write_all(b"<string>")?;
+            write_escaped(w, v)?;
+            w.write_all(b"</string>")?
+        }
+        Value::Double(v) => write!(w, "<double>{}</double>", format_double(v, options))?,
+        Value::DateTime(ref v) => {
+            w.write_all(b"<dateTime.iso8601>")?;
+            match DateTime::parse(v) {
+                Ok(mut parsed)
+                    if options.canonical
+                        || !options.datetime_fractions && parsed.nanosecond != 0
+                        || options.naive_datetime_offset.is_some() && !parsed.is_naive() =>
+                {
+                    if !options.datetime_fractions {
+                        parsed.nanosecond = 0;
+                    }
+                    if let Some(offset) = options.naive_datetime_offset {
+                        parsed = parsed.to_offset(offset);
+                        parsed.offset = None;
+                    }
+                    let format = if options.canonical {
+                        DateTimeFormat::Compact
+                    } else {
+                        DateTimeFormat::of(v)
+                    };
+                    w.write_all(parsed.format(format).as_bytes())?
+                }
+                _ => write_escaped(w, v)?,
+            }
+            w.write_all(b"</dateTime.iso8601>")?
+        }
+        Value::Base64(ref v) => {
+            w.write_all(b"<base64>")?;
+            write_base64(w, v, base64_line_length(options), &mut 0)?;
+            w.write_all(b"</base64>")?
+        }
+        Value::Base64Text(ref v) => {
+            w.write_all(b"<base64>")?;
+            match base64_line_length(options) {
+                0 => w.write_all(v.as_bytes())?,
+                length => {
+                    for (index, line) in v.as_bytes().chunks(length).enumerate() {
+                        if index > 0 {
+                            w.write_all(b"\n")?;
+                        }
+                        w.write_all(line)?;
+                    }
+                }
+            }
+            w.write_all(b"</base64>")?
+        }
+        Value::Array(ref v) => {
+            w.write_all(b"<array><data>")?;
+            for item in v {
+                write_value(w, item, options, false)?;
+            }
+            w.write_all(b"</data></array>")?
+        }
+        Value::Struct(ref v) => {
+            w.write_all(b"<struct>")?;
+            write_members(w, v, options)?;
+            w.write_all(b"</struct>")?
         }
+        Value::Nil => write!(w, "<{}nil/>", prefix)?,
+        #[cfg(feature = "bignum")]
+        Value::BigInt(ref v) => write_big_number(w, "biginteger", v, options)?,
+        #[cfg(feature = "bignum")]
+        Value::BigDecimal(ref v) => write_big_number(w, "bigdecimal", v, options)?,
     }
+    w.write_all(b"</value>")
 }
@@ -0,0 +1,125 @@
+//! Going through every value in a tree, to look at or rewrite them in one place, like to redact
+//! fields or collect statistics over any payload.
+//!
+//! Paths are written like `$.items[2].name`, with `$` standing for the value walked.
+
+use xmlfmt::Value;
+
+impl Value {
+    /// Calls `f` with the value and every value nested in it, each before those nested in it.
+    pub fn walk<F>(&self, f: &mut F)
+    where
+        F: FnMut(&Value),
+    {
+        f(self);
+        match *self {
+            Value::Array(ref items) => items.iter().for_each(|item| item.walk(f)),
+            Value::Struct(ref members) => members.values().for_each(|member| member.walk(f)),
+            _ => {}
+        }
+    }
+
+    /// Like `walk`, also giving the path of each value.
+    pub fn walk_with_paths<F>(&self, f: &mut F)
+    where
+        F: FnMut(&str, &Value),
+    {
+        self.walk_from("$", f)
+    }
+
+    /// Replaces every value with what `f` returns for it, starting with the most nested ones, so
+    /// arrays and structs are given to `f` with their items and members already replaced.
+    pub fn map<F>(self, f: F) -> Value
+    where
+        F: Fn(Value) -> Value,
+    {
+        self.map_by(&f)
+    }
+
+    /// Like `map`, also giving the path of each value.
+    pub fn map_with_paths<F>(self, f: F) -> Value
+    where
+        F: Fn(&str, Value) -> Value,
+    {
+        self.map_from("$".into(), &f)
+    }
+
+    fn walk_from<F>(&self, path: &str, f: &mut F)
+    where
+        F: FnMut(&str, &Value),
+    {
+        f(path, self);
+        match *self {
+            Value::Array(ref items) => {
+                for (index, item) in items.iter().enumerate() {
+                    item.walk_from(&index_path(path, index), f);
+                }
+            }
+            Value::Struct(ref members) => {
+                for (key, member) in members {
+                    member.walk_from(&member_path(path, key), f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn map_by<F>(self, f: &F) -> Value
+    where
+        F: Fn(Value) -> Value,
+    {
+        let value = match self {
+            Value::Array(items) => Value::Array(items.into_iter().map(|v| v.map_by(f)).collect()),
+            Value::Struct(members) => Value::Struct(
+                members
+                    .into_iter()
+                    .map(|(key, member)| (key, member.map_by(f)))
+                    .collect(),
+            ),
+            value => value,
+        };
+        f(value)
+    }
+
+    fn map_from<F>(self, path: String, f: &F) -> Value
+    where
+        F: Fn(&str, Value) -> Value,
+    {
+        let value = match self {
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| item.map_from(index_path(&path, index), f))
+                    .collect(),
+            ),
+            Value::Struct(members) => Value::Struct(
+                members
+                    .into_iter()
+                    .map(|(key, member)| {
+                        let member = member.map_from(member_path(&path, &key), f);
+                        (key, member)
+                    })
+                    .collect(),
+            ),
+            value => value,
+        };
+        f(&path, value)
+    }
+}
+
+pub fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+/// Members with names that aren't identifiers are quoted, like `$["odd key"]`.
+pub fn member_path(path: &str, key: &str) -> String {
+    let plain = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c == '_' || c.is_ascii_alphanumeric());
+    if plain {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}[{:?}]", path, key)
+    }
+}
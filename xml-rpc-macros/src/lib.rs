@@ -0,0 +1,199 @@
+//! Procedural macros for the `xml-rpc` crate, re-exported by it with its `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Error, FnArg, Ident, ItemTrait, LitStr, ReturnType, TraitItem, Type};
+
+/// Turns a trait into an XML-RPC service, generating next to it:
+///
+/// * `register_<trait>`, serving an implementation of the trait on an `xml_rpc::Server`
+/// * `<Trait>Client`, implementing the trait by calling a server over `xml_rpc::Client`
+///
+/// Methods take `&self` and owned params, and return `Result<T, xml_rpc::Fault>`. They are
+/// called by their own names, unless renamed with `#[xmlrpc(name = "math.add")]`. The client
+/// reports calls that fail to go through as transport error faults.
+#[proc_macro_attribute]
+pub fn xmlrpc_service(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(
+            TokenStream2::from(attr).span(),
+            "xmlrpc_service takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let mut item = parse_macro_input!(item as ItemTrait);
+    expand(&mut item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Method {
+    ident: Ident,
+    name: String,
+    args: Vec<Ident>,
+    types: Vec<Type>,
+    output: ReturnType,
+}
+
+fn expand(item: &mut ItemTrait) -> syn::Result<TokenStream2> {
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(
+            item.generics.span(),
+            "xmlrpc_service traits can't be generic",
+        ));
+    }
+    let mut methods = Vec::new();
+    for trait_item in &mut item.items {
+        let method = match trait_item {
+            TraitItem::Fn(method) => method,
+            other => {
+                return Err(Error::new(
+                    other.span(),
+                    "xmlrpc_service traits can only have methods",
+                ))
+            }
+        };
+        let mut name = method.sig.ident.to_string();
+        let mut renames = Vec::new();
+        method.attrs.retain(|attr| {
+            if attr.path().is_ident("xmlrpc") {
+                renames.push(attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        name = meta.value()?.parse::<LitStr>()?.value();
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `name = \"...\"`"))
+                    }
+                }));
+                false
+            } else {
+                true
+            }
+        });
+        renames.into_iter().collect::<syn::Result<()>>()?;
+
+        let sig = &method.sig;
+        if !sig.generics.params.is_empty() || sig.asyncness.is_some() {
+            return Err(Error::new(
+                sig.span(),
+                "xmlrpc_service methods can't be generic or async",
+            ));
+        }
+        match sig.receiver() {
+            Some(receiver) if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            _ => {
+                return Err(Error::new(
+                    sig.span(),
+                    "xmlrpc_service methods must take `&self`",
+                ))
+            }
+        }
+        let types = sig
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                FnArg::Typed(arg) => Some((*arg.ty).clone()),
+                FnArg::Receiver(_) => None,
+            })
+            .collect::<Vec<_>>();
+        methods.push(Method {
+            ident: sig.ident.clone(),
+            name,
+            args: (0..types.len())
+                .map(|i| format_ident!("arg{}", i))
+                .collect(),
+            types,
+            output: sig.output.clone(),
+        });
+    }
+
+    let vis = &item.vis;
+    let service = &item.ident;
+    let register = format_ident!("register_{}", snake_case(&service.to_string()));
+    let client = format_ident!("{}Client", service);
+
+    let registrations = methods.iter().map(|method| {
+        let Method {
+            ident,
+            name,
+            args,
+            types,
+            ..
+        } = method;
+        quote! {
+            {
+                let service = ::std::sync::Arc::clone(&service);
+                server.register_simple(#name, move |(#(#args,)*): (#(#types,)*)| {
+                    service.#ident(#(#args),*)
+                });
+            }
+        }
+    });
+    let calls = methods.iter().map(|method| {
+        let Method {
+            ident,
+            name,
+            args,
+            types,
+            output,
+        } = method;
+        quote! {
+            fn #ident(&self, #(#args: #types),*) #output {
+                let mut client = self.client.lock().unwrap_or_else(|err| err.into_inner());
+                match client.call(&self.uri, #name, (#(#args,)*)) {
+                    Ok(response) => Ok(response),
+                    Err(::xml_rpc::client::CallError::Fault(fault)) => Err(fault),
+                    Err(err) => Err(::xml_rpc::Fault::transport_error(err.to_string())),
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #item
+
+        #vis fn #register<T>(server: &mut ::xml_rpc::Server, service: T)
+        where
+            T: #service + Send + Sync + 'static,
+        {
+            let service = ::std::sync::Arc::new(service);
+            #(#registrations)*
+        }
+
+        #vis struct #client {
+            client: ::std::sync::Mutex<::xml_rpc::Client>,
+            uri: ::xml_rpc::Url,
+        }
+
+        impl #client {
+            #vis fn new(uri: ::xml_rpc::Url) -> ::xml_rpc::error::Result<#client> {
+                Ok(#client::with_client(::xml_rpc::Client::new()?, uri))
+            }
+
+            #vis fn with_client(client: ::xml_rpc::Client, uri: ::xml_rpc::Url) -> #client {
+                #client {
+                    client: ::std::sync::Mutex::new(client),
+                    uri,
+                }
+            }
+        }
+
+        impl #service for #client {
+            #(#calls)*
+        }
+    })
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}